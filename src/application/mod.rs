@@ -1,2 +1,3 @@
+pub mod parallel;
 pub mod ports;
 pub mod use_cases;
@@ -0,0 +1,139 @@
+use futures_util::stream::{self, StreamExt};
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Applies `f` to every item using at most `limit` scoped worker threads.
+/// Results come back in the same order as `items`.
+pub fn map_bounded<T, R, F>(items: &[T], limit: NonZeroUsize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let workers = limit.get().min(items.len());
+
+    let mut results: Vec<(usize, R)> = std::thread::scope(|s| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(i) else {
+                            return done;
+                        };
+                        done.push((i, f(item)));
+                    }
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    });
+
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
+/// Awaits `f`'s future for every item, at most `limit` at a time, all on
+/// the calling task. Results come back in the same order as `items`.
+pub async fn join_bounded<'a, T, R, F, Fut>(items: &'a [T], limit: NonZeroUsize, f: F) -> Vec<R>
+where
+    F: FnMut(&'a T) -> Fut,
+    Fut: Future<Output = R>,
+{
+    stream::iter(items)
+        .map(f)
+        .buffered(limit.get())
+        .collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn limit(n: usize) -> NonZeroUsize {
+        NonZeroUsize::new(n).unwrap()
+    }
+
+    #[test]
+    fn preserves_input_order() {
+        let items: Vec<u64> = (0..20).collect();
+
+        let doubled = map_bounded(&items, limit(4), |n| {
+            std::thread::sleep(Duration::from_millis(20 - n));
+            n * 2
+        });
+
+        assert_eq!(doubled, (0..20).map(|n| n * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn never_exceeds_the_limit() {
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let items = vec![(); 16];
+
+        map_bounded(&items, limit(3), |_| {
+            let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(5));
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+        });
+
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+    }
+
+    #[test]
+    fn handles_empty_input() {
+        let items: Vec<u8> = Vec::new();
+
+        assert!(map_bounded(&items, limit(4), |n| *n).is_empty());
+    }
+
+    #[tokio::test]
+    async fn joins_in_input_order() {
+        let items: Vec<u64> = (0..20).collect();
+
+        let doubled = join_bounded(&items, limit(4), |n| async move {
+            tokio::time::sleep(Duration::from_millis(20 - n)).await;
+            n * 2
+        })
+        .await;
+
+        assert_eq!(doubled, (0..20).map(|n| n * 2).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn never_awaits_more_than_the_limit() {
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let items = vec![(); 16];
+
+        join_bounded(&items, limit(3), |_| async {
+            let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+        })
+        .await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn joins_empty_input() {
+        let items: Vec<u8> = Vec::new();
+
+        assert!(join_bounded(&items, limit(4), |n| async move { *n })
+            .await
+            .is_empty());
+    }
+}
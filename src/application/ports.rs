@@ -1,13 +1,28 @@
-use std::collections::HashSet;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::time::Duration;
 
-use crate::domain::channel::Channel;
-use crate::domain::video::{Video, VideoId};
+use crate::domain::channel::{Channel, ChannelId, ChannelRef};
+use crate::domain::download::Download;
+use crate::domain::job::PlaybackJob;
+use crate::domain::journal::JournalEntry;
+use crate::domain::metadata::VideoMetadata;
+use crate::domain::sponsor::SkipSegment;
+use crate::domain::video::{DigestCursor, Video, VideoId, WatchProgress};
 
 #[derive(Debug)]
 pub enum FetchError {
     Network(String),
     HttpError(u16),
+    /// HTTP 429, with the server's `Retry-After` delay when it sent one.
+    RateLimited(Option<Duration>),
     Parse(String),
+    /// yt-dlp is missing, or ran and failed; trying again won't help.
+    YtDlp(String),
 }
 
 impl std::fmt::Display for FetchError {
@@ -15,7 +30,14 @@ impl std::fmt::Display for FetchError {
         match self {
             FetchError::Network(msg) => write!(f, "network error: {msg}"),
             FetchError::HttpError(status) => write!(f, "HTTP {status} from YouTube"),
+            FetchError::RateLimited(Some(delay)) => write!(
+                f,
+                "rate limited by YouTube (retry after {}s)",
+                delay.as_secs()
+            ),
+            FetchError::RateLimited(None) => write!(f, "rate limited by YouTube"),
             FetchError::Parse(msg) => write!(f, "parse error: {msg}"),
+            FetchError::YtDlp(msg) => write!(f, "{msg}"),
         }
     }
 }
@@ -54,12 +76,62 @@ impl std::fmt::Display for PlayError {
 
 impl std::error::Error for PlayError {}
 
+/// The future an async port returns. The fetch pipeline polls them all on
+/// one thread, so they need not be `Send`.
+pub type LocalFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
 pub trait FeedFetcher: Send + Sync {
-    fn fetch(&self, channel: &Channel) -> Result<Vec<Video>, FetchError>;
+    fn fetch<'a>(&'a self, channel: &'a Channel)
+        -> LocalFuture<'a, Result<Vec<Video>, FetchError>>;
+}
+
+/// What a channel's feed holds right now.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedSummary {
+    pub title: Option<String>,
+    pub entries: usize,
+}
+
+/// Looks at a channel's feed as it is now, bypassing every cache.
+pub trait FeedProbe: Send + Sync {
+    fn probe<'a>(
+        &'a self,
+        channel: &'a Channel,
+    ) -> LocalFuture<'a, Result<FeedSummary, FetchError>>;
+}
+
+/// The uploads a channel had when it was last fetched, read back without
+/// the network; `None` when it hasn't been fetched yet.
+pub trait UploadCatalog {
+    fn known_uploads(&self, channel: &Channel) -> Option<Vec<Video>>;
+}
+
+/// Announces a video outside blepo, e.g. on a phone.
+pub trait Notifier {
+    fn notify(&self, video: &Video) -> Result<(), FetchError>;
+}
+
+/// Videos already announced; `None` until notifications first run.
+pub trait NotifiedStore {
+    fn load_notified(&self) -> Result<Option<HashSet<VideoId>>, StoreError>;
+    fn save_notified(&self, video_ids: &HashSet<VideoId>) -> Result<(), StoreError>;
+}
+
+/// Where `blepo digest` left off; `None` before the first digest.
+pub trait DigestStore {
+    fn load_digest(&self) -> Result<Option<DigestCursor>, StoreError>;
+    fn save_digest(&self, cursor: &DigestCursor) -> Result<(), StoreError>;
+}
+
+pub trait ChannelResolver {
+    fn resolve(&self, reference: &ChannelRef) -> Result<ChannelId, FetchError>;
 }
 
 pub trait VideoStore {
     fn load_watched(&self) -> Result<HashSet<VideoId>, StoreError>;
+    /// When watched videos were first marked watched, for those marked since
+    /// blepo started keeping track.
+    fn load_watched_at(&self) -> Result<HashMap<VideoId, DateTime<Utc>>, StoreError>;
     fn mark_watched(&self, video_id: &VideoId) -> Result<(), StoreError>;
     fn mark_watched_batch(&self, video_ids: &[&VideoId]) -> Result<(), StoreError> {
         for id in video_ids {
@@ -67,12 +139,129 @@ pub trait VideoStore {
         }
         Ok(())
     }
+    /// Videos started but not watched; marking one watched drops its entry.
+    fn load_progress(&self) -> Result<HashMap<VideoId, WatchProgress>, StoreError>;
+    fn save_progress(&self, video_id: &VideoId, progress: WatchProgress) -> Result<(), StoreError>;
+}
+
+pub trait ActionJournal {
+    fn undo(&self, count: NonZeroUsize) -> Result<Vec<JournalEntry>, StoreError>;
 }
 
 pub trait VideoPlayer {
-    fn play(&self, url: &str) -> Result<(), PlayError>;
+    /// Starts playback and returns right away, with the process ID of the
+    /// player left running, if any.
+    fn play(&self, url: &str) -> Result<Option<u32>, PlayError>;
+    /// Plays until the player is closed; fails if the player reports an error.
+    fn play_attached(&self, url: &str) -> Result<(), PlayError>;
+    /// Like `play_attached`, also returning the furthest position reached,
+    /// in percent.
+    fn play_tracked(&self, url: &str) -> Result<f64, PlayError>;
+    /// Plays `urls` as one playlist until the player is closed, calling
+    /// `finished` with the index of each entry as playback moves past it.
+    fn play_playlist(
+        &self,
+        urls: &[&str],
+        finished: &mut dyn FnMut(usize),
+    ) -> Result<(), PlayError>;
 }
 
 pub trait ShortsChecker: Send + Sync {
-    fn is_short(&self, video_id: &VideoId) -> bool;
+    /// `Err` means the answer is unknown (e.g. network failure), as opposed
+    /// to a definitive `Ok(false)`.
+    fn is_short<'a>(&'a self, video_id: &'a VideoId) -> LocalFuture<'a, Result<bool, FetchError>>;
+}
+
+pub trait MetadataFetcher: Send + Sync {
+    fn fetch_metadata(&self, video_id: &VideoId) -> Result<VideoMetadata, FetchError>;
+}
+
+/// The channel that uploaded a video.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Uploader {
+    pub id: ChannelId,
+    pub name: String,
+}
+
+pub trait UploaderLookup {
+    fn uploader(&self, video_id: &VideoId) -> Result<Uploader, FetchError>;
+}
+
+/// The channels in the config, which blepo can add to.
+pub trait ChannelList {
+    fn contains(&self, id: &ChannelId) -> bool;
+    fn add_channel(&self, uploader: &Uploader) -> Result<(), StoreError>;
+}
+
+/// Opens a URL outside blepo, e.g. in the default browser.
+pub trait UrlOpener {
+    fn open_url(&self, url: &str) -> Result<(), PlayError>;
+}
+
+/// Saves videos to disk for watching offline.
+pub trait Downloader: Send + Sync {
+    /// Downloads the video, returning the file it was saved as. The
+    /// uploading channel, when known, may choose where it goes.
+    fn download(
+        &self,
+        video_id: &VideoId,
+        channel_id: Option<&ChannelId>,
+    ) -> Result<PathBuf, FetchError>;
+}
+
+/// The download queue, in the order videos were queued, with the videos
+/// already saved.
+pub trait DownloadStore: Send + Sync {
+    fn load_downloads(&self) -> Result<Vec<Download>, StoreError>;
+    fn save_downloads(&self, downloads: &[Download]) -> Result<(), StoreError>;
+    /// Deletes a saved video and the files saved next to it, such as its
+    /// subtitles and `.nfo`. Files already gone are not an error.
+    fn delete_files(&self, path: &Path) -> Result<(), StoreError>;
+}
+
+/// The numbered videos last shown, so commands can refer to them by number.
+pub trait ListingStore {
+    fn save_listing(&self, videos: &[Video]) -> Result<(), StoreError>;
+    fn load_listing(&self) -> Result<Vec<Video>, StoreError>;
+}
+
+/// Videos put aside to watch later, in play order.
+pub trait QueueStore {
+    fn load_queue(&self) -> Result<Vec<Video>, StoreError>;
+    fn save_queue(&self, videos: &[Video]) -> Result<(), StoreError>;
+}
+
+/// Players launched in the background, oldest first.
+/// Titles learned from feeds for channels configured without a name.
+pub trait ChannelNameStore {
+    fn load_channel_names(&self) -> Result<HashMap<ChannelId, String>, StoreError>;
+    fn save_channel_names(&self, names: &HashMap<ChannelId, String>) -> Result<(), StoreError>;
+}
+
+/// The first day each channel snoozed with `blepo snooze` is listed again.
+pub trait SnoozeStore {
+    fn load_snoozes(&self) -> Result<HashMap<ChannelId, NaiveDate>, StoreError>;
+    fn save_snoozes(&self, snoozes: &HashMap<ChannelId, NaiveDate>) -> Result<(), StoreError>;
+}
+
+pub trait JobStore {
+    fn load_jobs(&self) -> Result<Vec<PlaybackJob>, StoreError>;
+    fn save_jobs(&self, jobs: &[PlaybackJob]) -> Result<(), StoreError>;
+}
+
+pub trait ProcessControl {
+    fn is_running(&self, pid: u32) -> bool;
+    /// What tells the process apart from a later one given the same ID,
+    /// such as its start time and command; `None` once it has exited.
+    fn identity(&self, pid: u32) -> Option<String>;
+    fn terminate(&self, pid: u32) -> Result<(), PlayError>;
+}
+
+pub trait SkipSegmentFetcher {
+    fn fetch_segments(&self, video_id: &VideoId) -> Result<Vec<SkipSegment>, FetchError>;
+}
+
+pub trait MetadataStore {
+    fn load_metadata(&self, video_id: &VideoId) -> Result<Option<VideoMetadata>, StoreError>;
+    fn save_metadata(&self, entries: &[(VideoId, VideoMetadata)]) -> Result<(), StoreError>;
 }
@@ -1,16 +1,51 @@
-use chrono::{Duration, Utc};
-
-use crate::domain::channel::Channel;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, info_span, warn, Instrument};
+
+use crate::domain::channel::{Channel, ChannelId};
+use crate::domain::download::{self, Download, DownloadState};
+use crate::domain::filter::{
+    filter_by_duration, filter_by_title, DurationRange, MutedWords, TitleFilter,
+};
+use crate::domain::job::PlaybackJob;
+use crate::domain::metadata::VideoMetadata;
+use crate::domain::playback::ChannelPlayer;
+use crate::domain::queue;
+use crate::domain::sponsor::SkipSegment;
 use crate::domain::video::{
-    filter_by_date_range, filter_unwatched, sort_newest_first, FetchWindowDays, Video, VideoId,
+    catch_up_order, dedupe_by_id, filter_by_date_range, filter_unwatched,
+    mark_scheduled_as_upcoming, move_in_progress_to_front, move_live_to_end, sort_newest_first,
+    DigestCursor, FetchWindow, LiveHandling, Video, VideoId, VideoKind, VideoNumber, WatchProgress,
+    WatchThreshold,
+};
+
+use super::parallel::{join_bounded, map_bounded};
+use super::ports::{
+    ActionJournal, ChannelList, ChannelNameStore, DigestStore, DownloadStore, Downloader,
+    FeedFetcher, FeedProbe, FeedSummary, FetchError, JobStore, ListingStore, MetadataFetcher,
+    MetadataStore, NotifiedStore, Notifier, PlayError, ProcessControl, QueueStore, ShortsChecker,
+    SkipSegmentFetcher, SnoozeStore, StoreError, UploadCatalog, Uploader, UploaderLookup,
+    UrlOpener, VideoPlayer, VideoStore,
 };
 
-use super::ports::{FeedFetcher, PlayError, ShortsChecker, StoreError, VideoPlayer, VideoStore};
+/// Target of progress messages such as "Updating videos list...". Unlike
+/// other `info` events they show at the default level; callers leave them
+/// out with `--quiet`.
+pub const PROGRESS_LOG_TARGET: &str = "blepo::progress";
 
 #[derive(Debug)]
 pub enum AppError {
     Store(StoreError),
     Play(PlayError),
+    Fetch(FetchError),
+    /// `blepo stop <n>` named a job `blepo jobs` doesn't list.
+    NoSuchJob(VideoNumber),
 }
 
 impl std::fmt::Display for AppError {
@@ -18,6 +53,8 @@ impl std::fmt::Display for AppError {
         match self {
             AppError::Store(e) => write!(f, "{e}"),
             AppError::Play(e) => write!(f, "{e}"),
+            AppError::Fetch(e) => write!(f, "{e}"),
+            AppError::NoSuchJob(n) => write!(f, "no job #{n}; see `blepo jobs`"),
         }
     }
 }
@@ -36,348 +73,3027 @@ impl From<PlayError> for AppError {
     }
 }
 
-pub fn fetch_videos(
+impl From<FetchError> for AppError {
+    fn from(e: FetchError) -> Self {
+        AppError::Fetch(e)
+    }
+}
+
+/// Something wrong with a configured channel, found by `check_channels`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ChannelProblem {
+    /// HTTP 404: the channel was deleted, or its ID is wrong.
+    NotFound,
+    Empty,
+    /// The feed's title no longer matches the configured name.
+    Renamed(String),
+    Unreachable(String),
+}
+
+impl std::fmt::Display for ChannelProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChannelProblem::NotFound => write!(
+                f,
+                "no feed (HTTP 404); the channel is gone or its ID is wrong"
+            ),
+            ChannelProblem::Empty => write!(f, "the feed has no videos"),
+            ChannelProblem::Renamed(title) => write!(f, "YouTube now calls it \"{title}\""),
+            ChannelProblem::Unreachable(e) => write!(f, "cannot check: {e}"),
+        }
+    }
+}
+
+/// How often a channel uploads and how much of it gets watched, going by the
+/// uploads it had when last fetched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelStats {
+    pub uploads: usize,
+    pub watched: usize,
+    pub per_week: f64,
+    pub last_upload: DateTime<Utc>,
+}
+
+impl ChannelStats {
+    pub fn watched_share(&self) -> f64 {
+        self.watched as f64 / self.uploads as f64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FetchOptions {
+    pub fetch_window: FetchWindow,
+    pub fetch_concurrency: NonZeroUsize,
+    pub shorts_concurrency: NonZeroUsize,
+    /// Keep Shorts in the list, tagged as such, instead of dropping them.
+    pub include_shorts: bool,
+    pub title_filter: TitleFilter,
+    pub muted_words: MutedWords,
+    pub duration_range: DurationRange,
+    pub live_handling: LiveHandling,
+    /// List muted videos anyway, to audit what `muted_words` hides.
+    pub show_muted: bool,
+    /// Report nothing on stderr but failures, e.g. for status bars.
+    pub quiet: bool,
+}
+
+/// What `fetch_videos` found, the channels it could not fetch, and how long
+/// it took.
+#[derive(Debug)]
+pub struct FetchedVideos {
+    pub videos: Vec<Video>,
+    pub report: FetchReport,
+    pub timing: FetchTiming,
+}
+
+/// How long each phase of a fetch took, to find the channels that slow every
+/// run down.
+#[derive(Debug, Default)]
+pub struct FetchTiming {
+    /// Slowest first.
+    pub channels: Vec<ChannelTiming>,
+    /// All channels, fetched `fetch_concurrency` at a time.
+    pub fetching: Duration,
+    pub shorts_checked: usize,
+    pub shorts_checking: Duration,
+}
+
+#[derive(Debug)]
+pub struct ChannelTiming {
+    pub channel_name: String,
+    pub elapsed: Duration,
+    /// The videos fetched, or `None` if the channel failed.
+    pub videos: Option<usize>,
+}
+
+/// The channels a fetch left out, in config order, for the caller to report
+/// once the run is over rather than as they fail.
+#[derive(Debug, Default)]
+pub struct FetchReport {
+    pub failures: Vec<ChannelFailure>,
+}
+
+impl FetchReport {
+    pub fn is_empty(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.failures.len()
+    }
+}
+
+/// A channel that failed to fetch and was left out, for the caller to report.
+#[derive(Debug)]
+pub struct ChannelFailure {
+    pub channel_id: ChannelId,
+    pub channel_name: String,
+    pub error: FetchError,
+}
+
+impl ChannelFailure {
+    /// What the user might do about it.
+    pub fn suggestion(&self) -> &'static str {
+        match &self.error {
+            FetchError::Network(_) => "check the connection and the proxy setting",
+            FetchError::HttpError(404) => "check the channel ID in the config",
+            FetchError::HttpError(status) if *status >= 500 => {
+                "YouTube is having trouble; try again later"
+            }
+            FetchError::HttpError(_) => "try again later",
+            FetchError::RateLimited(_) => "raise request_interval_ms or try again later",
+            FetchError::Parse(_) => "try again later; report it if it keeps happening",
+            FetchError::YtDlp(_) => "check that yt-dlp is installed and up to date",
+        }
+    }
+}
+
+/// Progress is reported every this many Shorts checks.
+const SHORTS_PROGRESS_BATCH: usize = 25;
+
+pub async fn fetch_videos(
     channels: &[Channel],
     fetcher: &dyn FeedFetcher,
     store: &dyn VideoStore,
     shorts_checker: &dyn ShortsChecker,
-    fetch_window_days: FetchWindowDays,
-) -> Result<Vec<Video>, AppError> {
+    options: FetchOptions,
+) -> Result<FetchedVideos, AppError> {
     let now = Utc::now();
-    let cutoff = now - Duration::days(fetch_window_days.as_i64());
+    let cutoff = now - options.fetch_window.as_duration();
+
+    if !options.quiet {
+        info!(target: PROGRESS_LOG_TARGET, "Updating videos list...");
+    }
 
-    eprintln!("Updating videos list...");
+    let started = Instant::now();
+    let results = join_bounded(channels, options.fetch_concurrency, |channel| {
+        async move {
+            let started = Instant::now();
+            let result = fetcher.fetch(channel).await;
+            match &result {
+                Ok(videos) => debug!(videos = videos.len(), "fetched"),
+                Err(error) => debug!(%error, "fetch failed"),
+            }
+            (result, started.elapsed())
+        }
+        .instrument(info_span!("fetch", channel = %channel.name))
+    })
+    .await;
+    let mut timing = FetchTiming {
+        fetching: started.elapsed(),
+        ..FetchTiming::default()
+    };
 
-    let mut all_videos: Vec<Video> = std::thread::scope(|s| {
-        let handles: Vec<_> = channels
-            .iter()
-            .map(|channel| s.spawn(move || (channel, fetcher.fetch(channel))))
-            .collect();
+    let mut all_videos = Vec::new();
+    let mut report = FetchReport::default();
+    for (channel, (result, elapsed)) in channels.iter().zip(results) {
+        timing.channels.push(ChannelTiming {
+            channel_name: channel.name.clone(),
+            elapsed,
+            videos: result.as_ref().ok().map(Vec::len),
+        });
+        match result {
+            Ok(mut fetched) => {
+                mark_scheduled_as_upcoming(&mut fetched, now);
+                let (live, regular): (Vec<Video>, Vec<Video>) = fetched
+                    .into_iter()
+                    .partition(|v| v.kind.is_live_or_upcoming());
+                let live = match options.live_handling {
+                    LiveHandling::Hide => Vec::new(),
+                    LiveHandling::Section | LiveHandling::Include => live,
+                };
+                all_videos.extend(
+                    filter_by_date_range(&regular, cutoff, now)
+                        .into_iter()
+                        .chain(&live)
+                        .filter(|v| channel.title_filter.allows(&v.title))
+                        .filter(|v| channel.duration_range.allows(v.duration))
+                        .map(|v| Video {
+                            tags: channel.tags.clone(),
+                            ..v.clone()
+                        }),
+                );
+            }
+            Err(error) => report.failures.push(ChannelFailure {
+                channel_id: channel.id.clone(),
+                channel_name: channel.name.clone(),
+                error,
+            }),
+        }
+    }
+
+    let titled: Vec<Video> = filter_by_title(&all_videos, &options.title_filter)
+        .into_iter()
+        .cloned()
+        .collect();
+    let mut all_videos: Vec<Video> = dedupe_by_id(
+        filter_by_duration(&titled, &options.duration_range)
+            .into_iter()
+            .cloned()
+            .collect(),
+    );
+    if !options.show_muted {
+        let before = all_videos.len();
+        all_videos.retain(|v| !options.muted_words.mutes(&v.title));
+        let muted = before - all_videos.len();
+        if muted > 0 && !options.quiet {
+            info!(target: PROGRESS_LOG_TARGET, "Muted {muted} videos (--show-muted to list them)");
+        }
+    }
+    sort_newest_first(&mut all_videos);
+    move_in_progress_to_front(&mut all_videos, &store.load_progress()?);
+    if options.live_handling == LiveHandling::Section {
+        move_live_to_end(&mut all_videos);
+    }
+
+    let watched = store.load_watched()?;
+    let unwatched: Vec<Video> = filter_unwatched(&all_videos, &watched)
+        .into_iter()
+        .cloned()
+        .collect();
+
+    timing.channels.sort_by_key(|c| Reverse(c.elapsed));
+
+    let total = unwatched.len();
+    let started = Instant::now();
+    let checked = &AtomicUsize::new(0);
+    let quiet = options.quiet;
+    let is_short = join_bounded(&unwatched, options.shorts_concurrency, |video| async move {
+        let short = !video.kind.is_live_or_upcoming()
+            && shorts_checker.is_short(&video.id).await.unwrap_or(false);
+        let done = checked.fetch_add(1, Ordering::Relaxed) + 1;
+        if !quiet
+            && total > SHORTS_PROGRESS_BATCH
+            && (done.is_multiple_of(SHORTS_PROGRESS_BATCH) || done == total)
+        {
+            info!(target: PROGRESS_LOG_TARGET, "Checked {done}/{total} videos for Shorts");
+        }
+        short
+    })
+    .await;
+    timing.shorts_checked = total;
+    timing.shorts_checking = started.elapsed();
+
+    let videos: Vec<Video> = unwatched
+        .into_iter()
+        .zip(is_short)
+        .filter_map(|(v, short)| match (short, options.include_shorts) {
+            (false, _) => Some(v),
+            (true, true) => Some(Video {
+                kind: VideoKind::Short,
+                ..v
+            }),
+            (true, false) => None,
+        })
+        .collect();
+
+    Ok(FetchedVideos {
+        videos,
+        report,
+        timing,
+    })
+}
 
-        let mut videos = Vec::new();
-        for handle in handles {
-            let (channel, result) = handle.join().unwrap();
-            match result {
-                Ok(fetched) => {
-                    videos.extend(
-                        filter_by_date_range(&fetched, cutoff, now)
-                            .into_iter()
-                            .cloned(),
-                    );
+/// Probes every channel's feed, `concurrency` at a time, and reports the
+/// channels that look dead, empty or renamed, in config order. Names are
+/// compared ignoring case and surrounding whitespace; unnamed channels
+/// can't be renamed.
+pub async fn check_channels<'a>(
+    channels: &'a [Channel],
+    probe: &dyn FeedProbe,
+    concurrency: NonZeroUsize,
+) -> Vec<(&'a Channel, ChannelProblem)> {
+    let results = join_bounded(channels, concurrency, |channel| probe.probe(channel)).await;
+    channels
+        .iter()
+        .zip(results)
+        .filter_map(|(channel, result)| {
+            let problem = match result {
+                Err(FetchError::HttpError(404)) => ChannelProblem::NotFound,
+                Err(e) => ChannelProblem::Unreachable(e.to_string()),
+                Ok(summary) if summary.entries == 0 => ChannelProblem::Empty,
+                Ok(FeedSummary {
+                    title: Some(title), ..
+                }) if !channel.name_from_feed
+                    && !title.trim().eq_ignore_ascii_case(channel.name.trim()) =>
+                {
+                    ChannelProblem::Renamed(title)
                 }
-                Err(e) => {
-                    eprintln!("Warning: failed to fetch {}: {e}", channel.name);
+                Ok(_) => return None,
+            };
+            Some((channel, problem))
+        })
+        .collect()
+}
+
+/// Upload statistics for every channel, the least watched first; channels
+/// with no known uploads come last, as `None`. Scheduled videos don't count.
+/// The upload rate spans from the oldest known upload to `now`, and at least
+/// a week, so one fresh upload doesn't read as dozens a week.
+pub fn channel_stats<'a>(
+    channels: &'a [Channel],
+    catalog: &dyn UploadCatalog,
+    store: &dyn VideoStore,
+    now: DateTime<Utc>,
+) -> Result<Vec<(&'a Channel, Option<ChannelStats>)>, AppError> {
+    let watched = store.load_watched()?;
+    let mut stats: Vec<(&Channel, Option<ChannelStats>)> = channels
+        .iter()
+        .map(|channel| {
+            let uploads: Vec<Video> = catalog
+                .known_uploads(channel)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|v| v.published <= now && v.kind != VideoKind::Upcoming)
+                .collect();
+            let oldest = uploads.iter().map(|v| v.published).min();
+            let last_upload = uploads.iter().map(|v| v.published).max();
+            let stats = oldest.zip(last_upload).map(|(oldest, last_upload)| {
+                let weeks = ((now - oldest).num_seconds() as f64 / WEEK_SECONDS).max(1.0);
+                ChannelStats {
+                    uploads: uploads.len(),
+                    watched: uploads.iter().filter(|v| watched.contains(&v.id)).count(),
+                    per_week: uploads.len() as f64 / weeks,
+                    last_upload,
                 }
+            });
+            (channel, stats)
+        })
+        .collect();
+    stats.sort_by(|(_, a), (_, b)| match (a, b) {
+        (Some(a), Some(b)) => a.watched_share().total_cmp(&b.watched_share()),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    });
+    Ok(stats)
+}
+
+const WEEK_SECONDS: f64 = 7.0 * 24.0 * 60.0 * 60.0;
+
+/// What `notify_new_videos` did.
+#[derive(Debug, PartialEq, Eq)]
+pub struct NotifyOutcome {
+    /// Nothing was announced before: the videos were only remembered.
+    pub first_run: bool,
+    pub sent: usize,
+    pub failed: usize,
+}
+
+/// Announces the videos not announced before, oldest first. The first run
+/// only remembers what is there, so it doesn't announce a whole backlog.
+/// A failed announcement is reported and tried again next time. Videos no
+/// longer fetched are forgotten, keeping the store small.
+pub fn notify_new_videos(
+    videos: &[Video],
+    notifier: &dyn Notifier,
+    store: &dyn NotifiedStore,
+) -> Result<NotifyOutcome, AppError> {
+    let known = store.load_notified()?;
+    let first_run = known.is_none();
+    let known = known.unwrap_or_default();
+    let mut outcome = NotifyOutcome {
+        first_run,
+        sent: 0,
+        failed: 0,
+    };
+    let mut notified = HashSet::new();
+    for video in videos.iter().rev() {
+        if first_run || known.contains(&video.id) {
+            notified.insert(video.id.clone());
+            continue;
+        }
+        match notifier.notify(video) {
+            Ok(()) => {
+                notified.insert(video.id.clone());
+                outcome.sent += 1;
+            }
+            Err(e) => {
+                warn!("cannot announce {}: {e}", video.title);
+                outcome.failed += 1;
             }
         }
-        videos
+    }
+    store.save_notified(&notified)?;
+    Ok(outcome)
+}
+
+/// How long a digest remembers a video that is no longer fetched, so one that
+/// drops out of a fetch for a while isn't new again when it comes back.
+const DIGEST_MEMORY_DAYS: i64 = 30;
+
+/// What `digest` found: the videos not seen by any earlier digest, and when
+/// the last one ran (`None` the first time, when every video is new).
+#[derive(Debug)]
+pub struct Digest {
+    pub since: Option<DateTime<Utc>>,
+    pub videos: Vec<Video>,
+}
+
+/// The videos no earlier digest has seen, in listing order, whatever their
+/// publish date, and moves the cursor to `now`.
+pub fn digest(
+    videos: &[Video],
+    store: &dyn DigestStore,
+    now: DateTime<Utc>,
+) -> Result<Digest, AppError> {
+    let cursor = store.load_digest()?;
+    let since = cursor.as_ref().map(|c| c.last_run);
+    let mut first_seen = cursor.map(|c| c.first_seen).unwrap_or_default();
+    let new: Vec<Video> = videos
+        .iter()
+        .filter(|v| !first_seen.contains_key(&v.id))
+        .cloned()
+        .collect();
+    let fetched: HashSet<&VideoId> = videos.iter().map(|v| &v.id).collect();
+    first_seen.retain(|id, seen| {
+        fetched.contains(id) || now - *seen < chrono::Duration::days(DIGEST_MEMORY_DAYS)
     });
+    for video in &new {
+        first_seen.insert(video.id.clone(), now);
+    }
+    store.save_digest(&DigestCursor {
+        last_run: now,
+        first_seen,
+    })?;
+    Ok(Digest { since, videos: new })
+}
 
-    sort_newest_first(&mut all_videos);
+/// Remembers the titles feeds gave channels configured without a name, so
+/// they are known before the next fetch and follow renames.
+pub fn learn_channel_names(
+    channels: &[Channel],
+    videos: &[Video],
+    names: &dyn ChannelNameStore,
+) -> Result<(), AppError> {
+    let learned: Vec<(&Channel, &Video)> = channels
+        .iter()
+        .filter(|c| c.name_from_feed)
+        .filter_map(|c| {
+            videos
+                .iter()
+                .find(|v| v.channel_id == c.id)
+                .filter(|v| v.channel_name != c.name)
+                .map(|v| (c, v))
+        })
+        .collect();
+    if learned.is_empty() {
+        return Ok(());
+    }
+    let mut known = names.load_channel_names()?;
+    for (channel, video) in learned {
+        known.insert(channel.id.clone(), video.channel_name.clone());
+    }
+    Ok(names.save_channel_names(&known)?)
+}
+
+/// Hides `channel` from listings before `until`, or lists it again when
+/// `None`. Snoozes that have run out are dropped on the way.
+pub fn snooze_channel(
+    channel: &Channel,
+    until: Option<NaiveDate>,
+    today: NaiveDate,
+    snoozes: &dyn SnoozeStore,
+) -> Result<(), AppError> {
+    let mut snoozed = snoozes.load_snoozes()?;
+    snoozed.retain(|_, &mut day| today < day);
+    match until {
+        Some(day) => snoozed.insert(channel.id.clone(), day),
+        None => snoozed.remove(&channel.id),
+    };
+    Ok(snoozes.save_snoozes(&snoozed)?)
+}
+
+/// Adds snoozes from `blepo snooze` to those in the config; the later day
+/// wins.
+pub fn apply_snoozes(channels: &mut [Channel], snoozes: &dyn SnoozeStore) -> Result<(), AppError> {
+    let snoozed = snoozes.load_snoozes()?;
+    for channel in channels {
+        if let Some(&day) = snoozed.get(&channel.id) {
+            channel.snoozed_until = channel.snoozed_until.max(Some(day));
+        }
+    }
+    Ok(())
+}
+
+/// Fetches details for videos that have none stored yet. A failed video is
+/// reported and skipped; it is tried again next time. Progress goes to stderr
+/// unless `quiet`.
+pub fn enrich(
+    videos: &[Video],
+    fetcher: &dyn MetadataFetcher,
+    store: &dyn MetadataStore,
+    concurrency: NonZeroUsize,
+    quiet: bool,
+) -> Result<(), AppError> {
+    let mut missing = Vec::new();
+    for video in videos {
+        if store.load_metadata(&video.id)?.is_none() {
+            missing.push(video);
+        }
+    }
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    if !quiet {
+        info!(target: PROGRESS_LOG_TARGET, "Enriching {} videos...", missing.len());
+    }
+    let results = map_bounded(&missing, concurrency, |video| {
+        fetcher.fetch_metadata(&video.id)
+    });
 
+    let mut enriched = Vec::new();
+    for (video, result) in missing.into_iter().zip(results) {
+        match result {
+            Ok(metadata) => enriched.push((video.id.clone(), metadata)),
+            Err(e) => warn!("cannot enrich {}: {e}", video.title),
+        }
+    }
+    store.save_metadata(&enriched)?;
+    Ok(())
+}
+
+pub fn video_info(
+    video_id: &VideoId,
+    store: &dyn MetadataStore,
+) -> Result<Option<VideoMetadata>, AppError> {
+    Ok(store.load_metadata(video_id)?)
+}
+
+/// Sponsor segments to skip while playing. A failed lookup is reported and
+/// the video plays in full.
+pub fn skip_segments(video: &Video, fetcher: &dyn SkipSegmentFetcher) -> Vec<SkipSegment> {
+    match fetcher.fetch_segments(&video.id) {
+        Ok(segments) => {
+            if !segments.is_empty() {
+                println!("Skipping {} sponsor segment(s).", segments.len());
+            }
+            segments
+        }
+        Err(e) => {
+            warn!("cannot look up sponsor segments: {e}");
+            Vec::new()
+        }
+    }
+}
+
+/// Launches the player in the background, remembering it for `blepo jobs`.
+pub fn mark_and_play(
+    video: &Video,
+    store: &dyn VideoStore,
+    player: &dyn VideoPlayer,
+    jobs: &dyn JobStore,
+    processes: &dyn ProcessControl,
+) -> Result<(), AppError> {
+    println!("Playing: {} [{}]", video.title, video.channel_name);
+    let pid = player.play(&video.url)?;
+    store.mark_watched(&video.id)?;
+    if let Some(pid) = pid {
+        let mut all = jobs.load_jobs()?;
+        all.push(PlaybackJob {
+            pid,
+            identity: processes.identity(pid),
+            video: video.clone(),
+            started: Utc::now(),
+        });
+        jobs.save_jobs(&all)?;
+    }
+    Ok(())
+}
+
+/// Background players still running; those that exited are forgotten. A
+/// process counts only while its identity matches the one recorded at
+/// launch, so a reused process ID is never taken for the player.
+pub fn running_jobs(
+    jobs: &dyn JobStore,
+    processes: &dyn ProcessControl,
+) -> Result<Vec<PlaybackJob>, AppError> {
+    let mut all = jobs.load_jobs()?;
+    let before = all.len();
+    all.retain(|job| job.identity.is_some() && processes.identity(job.pid) == job.identity);
+    if all.len() != before {
+        jobs.save_jobs(&all)?;
+    }
+    Ok(all)
+}
+
+/// Stops job `number` as listed by `blepo jobs`, or every job, once
+/// `running_jobs` has checked it is still the player launched. Jobs that
+/// could not be stopped stay recorded, and `jobs.json` is saved before the
+/// last such failure is returned.
+pub fn stop_jobs(
+    number: Option<VideoNumber>,
+    jobs: &dyn JobStore,
+    processes: &dyn ProcessControl,
+) -> Result<(), AppError> {
+    let mut running = running_jobs(jobs, processes)?;
+    let stopping = match number {
+        Some(n) if n.to_index() < running.len() => vec![running.remove(n.to_index())],
+        Some(n) => return Err(AppError::NoSuchJob(n)),
+        None => std::mem::take(&mut running),
+    };
+    if stopping.is_empty() {
+        println!("Nothing is playing.");
+        return Ok(());
+    }
+    let mut stopped = Ok(());
+    for job in stopping {
+        match processes.terminate(job.pid) {
+            Ok(()) => println!("Stopped: {}", job.video.title),
+            Err(e) => {
+                stopped = Err(e);
+                running.push(job);
+            }
+        }
+    }
+    jobs.save_jobs(&running)?;
+    Ok(stopped?)
+}
+
+/// Plays `videos` back to back as one playlist, marking each watched once
+/// playback moves past it; videos left unplayed stay listed.
+pub fn play_playlist(
+    videos: &[&Video],
+    store: &dyn VideoStore,
+    player: &dyn VideoPlayer,
+) -> Result<(), AppError> {
+    println!("Playing {} videos:", videos.len());
+    for video in videos {
+        println!("  {} [{}]", video.title, video.channel_name);
+    }
+    let urls: Vec<&str> = videos.iter().map(|v| v.url.as_str()).collect();
+    let mut marked = Ok(());
+    let played = player.play_playlist(&urls, &mut |i| {
+        let Some(video) = videos.get(i) else { return };
+        match store.mark_watched(&video.id) {
+            Ok(()) => println!("Marked as watched: {}", video.title),
+            Err(e) => marked = Err(e),
+        }
+    });
+    marked?;
+    Ok(played?)
+}
+
+pub fn enqueue(video: &Video, queue_store: &dyn QueueStore) -> Result<(), AppError> {
+    let mut queued = queue_store.load_queue()?;
+    if queue::enqueue(&mut queued, video.clone()) {
+        queue_store.save_queue(&queued)?;
+        println!("Queued: {} ({} in queue)", video.title, queued.len());
+    } else {
+        println!("Already queued: {}", video.title);
+    }
+    Ok(())
+}
+
+/// The queue without videos watched since they were queued, which are
+/// dropped from it for good.
+pub fn pending_queue(
+    queue_store: &dyn QueueStore,
+    store: &dyn VideoStore,
+) -> Result<Vec<Video>, AppError> {
+    let queued = queue_store.load_queue()?;
     let watched = store.load_watched()?;
-    let unwatched: Vec<Video> = filter_unwatched(&all_videos, &watched)
+    let pending: Vec<Video> = filter_unwatched(&queued, &watched)
         .into_iter()
         .cloned()
         .collect();
+    if pending.len() != queued.len() {
+        queue_store.save_queue(&pending)?;
+    }
+    Ok(pending)
+}
+
+pub fn remove_from_queue(
+    number: VideoNumber,
+    queue_store: &dyn QueueStore,
+    store: &dyn VideoStore,
+) -> Result<(), AppError> {
+    let mut pending = pending_queue(queue_store, store)?;
+    if number.to_index() >= pending.len() {
+        println!("No video #{number} in the queue.");
+        return Ok(());
+    }
+    let removed = pending.remove(number.to_index());
+    queue_store.save_queue(&pending)?;
+    println!("Removed from queue: {}", removed.title);
+    Ok(())
+}
+
+pub fn move_in_queue(
+    from: VideoNumber,
+    to: VideoNumber,
+    queue_store: &dyn QueueStore,
+    store: &dyn VideoStore,
+) -> Result<(), AppError> {
+    let mut pending = pending_queue(queue_store, store)?;
+    match queue::move_entry(&mut pending, from, to) {
+        Some(()) => queue_store.save_queue(&pending)?,
+        None => println!("The queue has {} videos.", pending.len()),
+    }
+    Ok(())
+}
+
+/// Plays the whole queue as one playlist; videos marked watched along the
+/// way leave the queue.
+pub fn play_queue(
+    queue_store: &dyn QueueStore,
+    store: &dyn VideoStore,
+    player: &dyn VideoPlayer,
+) -> Result<(), AppError> {
+    let pending = pending_queue(queue_store, store)?;
+    if pending.is_empty() {
+        println!("The queue is empty.");
+        return Ok(());
+    }
+    let played = play_playlist(&pending.iter().collect::<Vec<_>>(), store, player);
+    pending_queue(queue_store, store)?;
+    played
+}
+
+/// Plays every video, oldest first, as one playlist.
+pub fn play_all(
+    videos: &[Video],
+    store: &dyn VideoStore,
+    player: &dyn VideoPlayer,
+) -> Result<(), AppError> {
+    let order = catch_up_order(videos);
+    if order.is_empty() {
+        println!("No unwatched videos.");
+        return Ok(());
+    }
+    play_playlist(&order, store, player)
+}
+
+/// Plays in the foreground and marks the video watched once the player exits
+/// cleanly; a failed playback leaves it unwatched.
+pub fn play_attached(
+    video: &Video,
+    store: &dyn VideoStore,
+    player: &dyn VideoPlayer,
+) -> Result<(), AppError> {
+    println!("Playing: {} [{}]", video.title, video.channel_name);
+    player.play_attached(&video.url)?;
+    store.mark_watched(&video.id)?;
+    println!("Marked as watched.");
+    Ok(())
+}
+
+/// Plays in the foreground and marks the video watched only if playback got
+/// past `threshold`, so closing a wrongly picked video keeps it listed.
+pub fn play_and_mark_if_watched(
+    video: &Video,
+    store: &dyn VideoStore,
+    player: &dyn VideoPlayer,
+    threshold: WatchThreshold,
+) -> Result<(), AppError> {
+    println!("Playing: {} [{}]", video.title, video.channel_name);
+    let played = player.play_tracked(&video.url)?;
+    if threshold.is_met_by(played) {
+        store.mark_watched(&video.id)?;
+        println!("Marked as watched ({played:.0}% played).");
+    } else {
+        println!(
+            "Stopped at {played:.0}%, below {}%; not marked as watched.",
+            threshold.percent()
+        );
+        if let Some(progress) = WatchProgress::from_percent(played) {
+            store.save_progress(&video.id, progress)?;
+        }
+    }
+    Ok(())
+}
+
+/// How far each started but unwatched video was played.
+pub fn watch_progress(store: &dyn VideoStore) -> Result<HashMap<VideoId, WatchProgress>, AppError> {
+    Ok(store.load_progress()?)
+}
+
+/// Opens the video's page, for comments and chapters mpv doesn't show.
+pub fn open_in_browser(
+    video: &Video,
+    store: &dyn VideoStore,
+    opener: &dyn UrlOpener,
+    mark_watched: bool,
+) -> Result<(), AppError> {
+    println!("Opening: {} [{}]", video.title, video.channel_name);
+    opener.open_url(&video.url)?;
+    if mark_watched {
+        store.mark_watched(&video.id)?;
+    }
+    Ok(())
+}
+
+/// What running the download queue takes.
+pub struct DownloadRun<'a> {
+    pub downloader: &'a dyn Downloader,
+    pub downloads: &'a dyn DownloadStore,
+    /// Tells downloads interrupted with their process from running ones.
+    pub processes: &'a dyn ProcessControl,
+    /// This process, recorded on the downloads it starts.
+    pub pid: u32,
+    pub concurrency: NonZeroUsize,
+}
+
+/// How each download of a run went, in queue order.
+pub type DownloadOutcomes = Vec<(VideoId, Result<PathBuf, FetchError>)>;
+
+/// Queues a video for download and runs the queue, marking the video
+/// watched when asked only once it is saved. Returns the file it was saved
+/// as, without downloading again a video saved before; `None` if another
+/// blepo process is downloading it right now.
+pub fn download(
+    video_id: &VideoId,
+    video: Option<&Video>,
+    run: &DownloadRun,
+    store: &dyn VideoStore,
+    mark_watched: bool,
+    report: &(dyn Fn(&Download) + Sync),
+) -> Result<Option<PathBuf>, AppError> {
+    let mut queue = run.downloads.load_downloads()?;
+    download::enqueue(&mut queue, video_id.clone(), video.cloned());
+    run.downloads.save_downloads(&queue)?;
+    let saved = queue
+        .iter()
+        .find(|d| &d.id == video_id)
+        .and_then(Download::saved_path)
+        .map(Path::to_path_buf);
+    let path = match saved {
+        Some(path) => path,
+        None => match run_downloads(run, report)?
+            .into_iter()
+            .find(|(id, _)| id == video_id)
+        {
+            Some((_, result)) => result?,
+            None => return Ok(None),
+        },
+    };
+    if mark_watched {
+        store.mark_watched(video_id)?;
+    }
+    Ok(Some(path))
+}
+
+/// Queues the videos of `auto_download` channels not downloaded yet, oldest
+/// first, and those that failed last time. Premieres and streams that haven't
+/// ended are left for a later run. Returns how many were queued.
+pub fn queue_auto_downloads(
+    videos: &[Video],
+    channels: &[Channel],
+    downloads: &dyn DownloadStore,
+) -> Result<usize, AppError> {
+    let archived: HashSet<&ChannelId> = channels
+        .iter()
+        .filter(|c| c.auto_download)
+        .map(|c| &c.id)
+        .collect();
+    if archived.is_empty() {
+        return Ok(0);
+    }
+    let mut pending: Vec<&Video> = videos
+        .iter()
+        .filter(|v| archived.contains(&v.channel_id) && !v.kind.is_live_or_upcoming())
+        .collect();
+    pending.sort_by_key(|v| v.published);
+    let mut queue = downloads.load_downloads()?;
+    let queued = pending
+        .into_iter()
+        .filter(|v| download::enqueue(&mut queue, v.id.clone(), Some((*v).clone())))
+        .count();
+    if queued > 0 {
+        downloads.save_downloads(&queue)?;
+    }
+    Ok(queued)
+}
+
+/// Queues every failed download again.
+pub fn retry_failed_downloads(downloads: &dyn DownloadStore) -> Result<(), AppError> {
+    let mut queue = downloads.load_downloads()?;
+    let failed: Vec<VideoId> = queue
+        .iter()
+        .filter(|d| matches!(d.state, DownloadState::Failed { .. }))
+        .map(|d| d.id.clone())
+        .collect();
+    for id in failed {
+        download::enqueue(&mut queue, id, None);
+    }
+    downloads.save_downloads(&queue)?;
+    Ok(())
+}
+
+/// The files downloaded videos were saved as, by video ID.
+pub fn downloaded_files(
+    downloads: &dyn DownloadStore,
+) -> Result<HashMap<VideoId, PathBuf>, AppError> {
+    Ok(downloads
+        .load_downloads()?
+        .into_iter()
+        .filter_map(|download| {
+            let path = download.saved_path()?.to_path_buf();
+            Some((download.id, path))
+        })
+        .collect())
+}
+
+/// Each pruned download, and whether its files were deleted.
+pub type PruneOutcomes = Vec<(Download, Result<(), StoreError>)>;
+
+/// Deletes the files of saved downloads watched more than `older_than`
+/// before `now`, except those of `kept` channels (see
+/// `download::prunable`), and drops them from the queue. One whose files
+/// can't be deleted stays in it.
+pub fn prune_downloads(
+    downloads: &dyn DownloadStore,
+    store: &dyn VideoStore,
+    kept: &HashSet<ChannelId>,
+    older_than: chrono::Duration,
+    now: DateTime<Utc>,
+) -> Result<PruneOutcomes, AppError> {
+    let queue = downloads.load_downloads()?;
+    let pruned: Vec<Download> = download::prunable(
+        &queue,
+        &store.load_watched()?,
+        &store.load_watched_at()?,
+        kept,
+        now - older_than,
+    )
+    .into_iter()
+    .cloned()
+    .collect();
+    let outcomes: PruneOutcomes = pruned
+        .into_iter()
+        .map(|download| {
+            let result = match download.saved_path() {
+                Some(path) => downloads.delete_files(path),
+                None => Ok(()),
+            };
+            (download, result)
+        })
+        .collect();
+    let deleted: HashSet<&VideoId> = outcomes
+        .iter()
+        .filter(|(_, result)| result.is_ok())
+        .map(|(download, _)| &download.id)
+        .collect();
+    if !deleted.is_empty() {
+        // Reloaded, as another blepo may have queued videos meanwhile.
+        let mut queue = downloads.load_downloads()?;
+        queue.retain(|d| !deleted.contains(&d.id));
+        downloads.save_downloads(&queue)?;
+    }
+    Ok(outcomes)
+}
+
+/// Runs the queued downloads, and those interrupted when their process
+/// ended, at most `concurrency` at once. The queue is saved as each starts
+/// and ends, so a run cut short is resumed by the next one, and `report`
+/// sees each download then.
+pub fn run_downloads(
+    run: &DownloadRun,
+    report: &(dyn Fn(&Download) + Sync),
+) -> Result<DownloadOutcomes, AppError> {
+    let queue = run.downloads.load_downloads()?;
+    let due: Vec<(VideoId, Option<ChannelId>)> =
+        download::due(&queue, |pid| run.processes.is_running(pid))
+            .into_iter()
+            .map(|d| (d.id.clone(), d.video.as_ref().map(|v| v.channel_id.clone())))
+            .collect();
+    let (downloader, downloads, pid) = (run.downloader, run.downloads, run.pid);
+    // Workers update the queue one at a time, each from what is on disk.
+    let queue_lock = Mutex::new(());
+    let update = |id: &VideoId, state: DownloadState| -> Result<(), StoreError> {
+        let _held = queue_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut queue = downloads.load_downloads()?;
+        let index = match queue.iter().position(|d| &d.id == id) {
+            Some(index) => index,
+            None => {
+                download::enqueue(&mut queue, id.clone(), None);
+                queue.len() - 1
+            }
+        };
+        queue[index].state = state;
+        downloads.save_downloads(&queue)?;
+        report(&queue[index]);
+        Ok(())
+    };
+    let results = map_bounded(&due, run.concurrency, |(id, channel_id)| {
+        update(id, DownloadState::Downloading { pid })?;
+        let result = downloader.download(id, channel_id.as_ref());
+        let state = match &result {
+            Ok(path) => DownloadState::Saved {
+                path: path.clone(),
+                at: Utc::now(),
+            },
+            Err(e) => DownloadState::Failed {
+                error: e.to_string(),
+            },
+        };
+        update(id, state)?;
+        Ok::<_, StoreError>(result)
+    });
+    due.into_iter()
+        .zip(results)
+        .map(|((id, _), result)| Ok((id, result?)))
+        .collect()
+}
+
+/// Adds the channel that uploaded `video_id` to the config, unless it is
+/// there already, and marks the video watched when asked. Returns the
+/// channel and whether it was added.
+pub fn add_channel_from_video(
+    video_id: &VideoId,
+    lookup: &dyn UploaderLookup,
+    channels: &dyn ChannelList,
+    store: &dyn VideoStore,
+    mark_watched: bool,
+) -> Result<(Uploader, bool), AppError> {
+    let uploader = lookup.uploader(video_id)?;
+    let added = !channels.contains(&uploader.id);
+    if added {
+        channels.add_channel(&uploader)?;
+    }
+    if mark_watched {
+        store.mark_watched(video_id)?;
+    }
+    Ok((uploader, added))
+}
+
+/// The player `video`'s channel configured for itself, if any.
+pub fn channel_player<'a>(
+    video: &Video,
+    players: &'a HashMap<ChannelId, ChannelPlayer>,
+) -> Option<&'a ChannelPlayer> {
+    players.get(&video.channel_id)
+}
+
+/// Keeps the numbering shown to the user for `blepo open <n>`.
+pub fn remember_listing(videos: &[Video], listing: &dyn ListingStore) -> Result<(), AppError> {
+    Ok(listing.save_listing(videos)?)
+}
+
+/// The video shown as `number` in the last listing.
+pub fn listed_video(
+    number: VideoNumber,
+    listing: &dyn ListingStore,
+) -> Result<Option<Video>, AppError> {
+    Ok(listing.load_listing()?.into_iter().nth(number.to_index()))
+}
+
+pub fn mark_as_watched(video: &Video, store: &dyn VideoStore) -> Result<(), AppError> {
+    store.mark_watched(&video.id)?;
+    Ok(())
+}
+
+pub fn mark_all_as_watched(videos: &[Video], store: &dyn VideoStore) -> Result<(), AppError> {
+    let ids: Vec<&VideoId> = videos.iter().map(|v| &v.id).collect();
+    store.mark_watched_batch(&ids)?;
+    println!("Marked {} videos as watched.", videos.len());
+    Ok(())
+}
+
+pub fn undo(journal: &dyn ActionJournal, count: NonZeroUsize) -> Result<(), AppError> {
+    let undone = journal.undo(count)?;
+    if undone.is_empty() {
+        println!("Nothing to undo.");
+    }
+    for entry in &undone {
+        println!(
+            "Undid: {} ({})",
+            entry.action,
+            entry.at.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::ports::{
+        FeedFetcher, FetchError, LocalFuture, ShortsChecker, VideoPlayer, VideoStore,
+    };
+    use crate::domain::channel::{Channel, ChannelId, ChannelTag};
+    use crate::domain::filter::TitlePatterns;
+    use crate::domain::journal::{JournalAction, JournalEntry};
+    use crate::domain::video::{Video, VideoId, VideoKind};
+    use chrono::Duration;
+    use std::cell::RefCell;
+    use std::collections::{HashMap, HashSet};
+
+    struct MockFetcher {
+        videos: Vec<Video>,
+    }
+
+    impl FeedFetcher for MockFetcher {
+        fn fetch<'a>(
+            &'a self,
+            _channel: &'a Channel,
+        ) -> LocalFuture<'a, Result<Vec<Video>, FetchError>> {
+            Box::pin(async move { Ok(self.videos.clone()) })
+        }
+    }
+
+    /// Returns one video per channel, with an ID derived from the channel's.
+    struct PerChannelFetcher;
+
+    impl FeedFetcher for PerChannelFetcher {
+        fn fetch<'a>(
+            &'a self,
+            channel: &'a Channel,
+        ) -> LocalFuture<'a, Result<Vec<Video>, FetchError>> {
+            Box::pin(async move { Ok(vec![make_video(&format!("v-{}", channel.id), "Recent", 1)]) })
+        }
+    }
+
+    struct FailingFetcher;
+
+    impl FeedFetcher for FailingFetcher {
+        fn fetch<'a>(
+            &'a self,
+            _channel: &'a Channel,
+        ) -> LocalFuture<'a, Result<Vec<Video>, FetchError>> {
+            Box::pin(async move { Err(FetchError::Network("connection refused".to_string())) })
+        }
+    }
+
+    struct MockStore {
+        watched: RefCell<HashSet<VideoId>>,
+        watched_at: RefCell<HashMap<VideoId, DateTime<Utc>>>,
+        progress: RefCell<HashMap<VideoId, WatchProgress>>,
+    }
+
+    impl MockStore {
+        fn new() -> Self {
+            Self {
+                watched: RefCell::new(HashSet::new()),
+                watched_at: RefCell::new(HashMap::new()),
+                progress: RefCell::new(HashMap::new()),
+            }
+        }
+    }
+
+    impl VideoStore for MockStore {
+        fn load_watched(&self) -> Result<HashSet<VideoId>, StoreError> {
+            Ok(self.watched.borrow().clone())
+        }
+
+        fn load_watched_at(&self) -> Result<HashMap<VideoId, DateTime<Utc>>, StoreError> {
+            Ok(self.watched_at.borrow().clone())
+        }
+
+        fn mark_watched(&self, video_id: &VideoId) -> Result<(), StoreError> {
+            self.watched.borrow_mut().insert(video_id.clone());
+            self.watched_at
+                .borrow_mut()
+                .entry(video_id.clone())
+                .or_insert_with(Utc::now);
+            self.progress.borrow_mut().remove(video_id);
+            Ok(())
+        }
+
+        fn load_progress(&self) -> Result<HashMap<VideoId, WatchProgress>, StoreError> {
+            Ok(self.progress.borrow().clone())
+        }
+
+        fn save_progress(
+            &self,
+            video_id: &VideoId,
+            progress: WatchProgress,
+        ) -> Result<(), StoreError> {
+            self.progress
+                .borrow_mut()
+                .insert(video_id.clone(), progress);
+            Ok(())
+        }
+    }
+
+    struct MockJournal {
+        entries: RefCell<Vec<JournalEntry>>,
+    }
+
+    impl ActionJournal for MockJournal {
+        fn undo(&self, count: NonZeroUsize) -> Result<Vec<JournalEntry>, StoreError> {
+            let mut entries = self.entries.borrow_mut();
+            let keep = entries.len().saturating_sub(count.get());
+            Ok(entries.split_off(keep).into_iter().rev().collect())
+        }
+    }
+
+    struct MockPlayer {
+        played: RefCell<Vec<String>>,
+        /// Percent reached by tracked playback.
+        reached: f64,
+    }
+
+    impl MockPlayer {
+        fn new() -> Self {
+            Self::stopping_at(100.0)
+        }
+
+        fn stopping_at(reached: f64) -> Self {
+            Self {
+                played: RefCell::new(Vec::new()),
+                reached,
+            }
+        }
+    }
+
+    impl VideoPlayer for MockPlayer {
+        fn play(&self, url: &str) -> Result<Option<u32>, PlayError> {
+            self.played.borrow_mut().push(url.to_string());
+            Ok(Some(4242))
+        }
+
+        fn play_attached(&self, url: &str) -> Result<(), PlayError> {
+            self.play(url).map(|_| ())
+        }
+
+        fn play_tracked(&self, url: &str) -> Result<f64, PlayError> {
+            self.play(url)?;
+            Ok(self.reached)
+        }
+
+        /// Finishes every entry but the last, as if quit during it.
+        fn play_playlist(
+            &self,
+            urls: &[&str],
+            finished: &mut dyn FnMut(usize),
+        ) -> Result<(), PlayError> {
+            for (i, url) in urls.iter().enumerate() {
+                self.play(url)?;
+                if i + 1 < urls.len() {
+                    finished(i);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    struct FailingPlayer;
+
+    impl VideoPlayer for FailingPlayer {
+        fn play(&self, _url: &str) -> Result<Option<u32>, PlayError> {
+            Err(PlayError::PlayerFailed("mpv crashed".to_string()))
+        }
+
+        fn play_attached(&self, url: &str) -> Result<(), PlayError> {
+            self.play(url).map(|_| ())
+        }
+
+        fn play_tracked(&self, url: &str) -> Result<f64, PlayError> {
+            self.play(url).map(|_| 0.0)
+        }
+
+        fn play_playlist(
+            &self,
+            urls: &[&str],
+            _finished: &mut dyn FnMut(usize),
+        ) -> Result<(), PlayError> {
+            self.play_attached(urls[0])
+        }
+    }
+
+    struct MockMetadataFetcher {
+        failing: HashSet<VideoId>,
+        calls: std::sync::Mutex<Vec<VideoId>>,
+    }
+
+    impl MockMetadataFetcher {
+        fn failing_on(ids: &[&str]) -> Self {
+            Self {
+                failing: ids.iter().map(|id| VideoId::parse(*id).unwrap()).collect(),
+                calls: std::sync::Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl MetadataFetcher for MockMetadataFetcher {
+        fn fetch_metadata(&self, video_id: &VideoId) -> Result<VideoMetadata, FetchError> {
+            self.calls.lock().unwrap().push(video_id.clone());
+            if self.failing.contains(video_id) {
+                return Err(FetchError::Network("timeout".to_string()));
+            }
+            Ok(VideoMetadata {
+                title: format!("Title of {video_id}"),
+                view_count: Some(10),
+                ..VideoMetadata::default()
+            })
+        }
+    }
+
+    #[derive(Default)]
+    struct MockOpener {
+        opened: RefCell<Vec<String>>,
+    }
+
+    impl UrlOpener for MockOpener {
+        fn open_url(&self, url: &str) -> Result<(), PlayError> {
+            self.opened.borrow_mut().push(url.to_string());
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockDownloader {
+        fail: bool,
+    }
+
+    impl Downloader for MockDownloader {
+        fn download(
+            &self,
+            video_id: &VideoId,
+            _channel_id: Option<&ChannelId>,
+        ) -> Result<PathBuf, FetchError> {
+            if self.fail {
+                return Err(FetchError::Network("offline".to_string()));
+            }
+            Ok(PathBuf::from(format!("/videos/{video_id}.webm")))
+        }
+    }
+
+    #[derive(Default)]
+    struct MockDownloadStore {
+        queue: Mutex<Vec<Download>>,
+        deleted: Mutex<Vec<PathBuf>>,
+    }
+
+    impl MockDownloadStore {
+        fn with(queue: Vec<Download>) -> Self {
+            Self {
+                queue: Mutex::new(queue),
+                ..Self::default()
+            }
+        }
+
+        fn state(&self, id: &str) -> Option<DownloadState> {
+            let queue = self.queue.lock().unwrap();
+            let download = queue.iter().find(|d| d.id.to_string() == id)?;
+            Some(download.state.clone())
+        }
+    }
+
+    impl DownloadStore for MockDownloadStore {
+        fn load_downloads(&self) -> Result<Vec<Download>, StoreError> {
+            Ok(self.queue.lock().unwrap().clone())
+        }
+
+        fn save_downloads(&self, downloads: &[Download]) -> Result<(), StoreError> {
+            *self.queue.lock().unwrap() = downloads.to_vec();
+            Ok(())
+        }
+
+        fn delete_files(&self, path: &Path) -> Result<(), StoreError> {
+            if path.starts_with("/readonly") {
+                return Err(StoreError::Write("permission denied".to_string()));
+            }
+            self.deleted.lock().unwrap().push(path.to_path_buf());
+            Ok(())
+        }
+    }
+
+    /// Processes known to be running, by ID.
+    struct MockProcesses(HashSet<u32>);
+
+    impl ProcessControl for MockProcesses {
+        fn is_running(&self, pid: u32) -> bool {
+            self.0.contains(&pid)
+        }
+
+        fn identity(&self, pid: u32) -> Option<String> {
+            self.is_running(pid).then(|| format!("process {pid}"))
+        }
+
+        fn terminate(&self, _pid: u32) -> Result<(), PlayError> {
+            Ok(())
+        }
+    }
+
+    fn download_run<'a>(
+        downloader: &'a MockDownloader,
+        downloads: &'a MockDownloadStore,
+        processes: &'a MockProcesses,
+    ) -> DownloadRun<'a> {
+        DownloadRun {
+            downloader,
+            downloads,
+            processes,
+            pid: 100,
+            concurrency: NonZeroUsize::new(2).unwrap(),
+        }
+    }
+
+    fn queued(id: &str, state: DownloadState) -> Download {
+        Download {
+            id: VideoId::parse(id).unwrap(),
+            video: None,
+            state,
+        }
+    }
+
+    struct MockListingStore(Vec<Video>);
+
+    impl ListingStore for MockListingStore {
+        fn save_listing(&self, _videos: &[Video]) -> Result<(), StoreError> {
+            Ok(())
+        }
+
+        fn load_listing(&self) -> Result<Vec<Video>, StoreError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockQueueStore {
+        queued: RefCell<Vec<Video>>,
+    }
+
+    impl QueueStore for MockQueueStore {
+        fn load_queue(&self) -> Result<Vec<Video>, StoreError> {
+            Ok(self.queued.borrow().clone())
+        }
+
+        fn save_queue(&self, videos: &[Video]) -> Result<(), StoreError> {
+            *self.queued.borrow_mut() = videos.to_vec();
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockChannelNames {
+        names: RefCell<HashMap<ChannelId, String>>,
+    }
+
+    impl ChannelNameStore for MockChannelNames {
+        fn load_channel_names(&self) -> Result<HashMap<ChannelId, String>, StoreError> {
+            Ok(self.names.borrow().clone())
+        }
+
+        fn save_channel_names(&self, names: &HashMap<ChannelId, String>) -> Result<(), StoreError> {
+            *self.names.borrow_mut() = names.clone();
+            Ok(())
+        }
+    }
+
+    struct MockUploader;
+
+    impl UploaderLookup for MockUploader {
+        fn uploader(&self, _video_id: &VideoId) -> Result<Uploader, FetchError> {
+            Ok(Uploader {
+                id: ChannelId::parse("UCnew").unwrap(),
+                name: "New".to_string(),
+            })
+        }
+    }
+
+    #[derive(Default)]
+    struct MockChannelList {
+        added: RefCell<Vec<Uploader>>,
+    }
+
+    impl ChannelList for MockChannelList {
+        fn contains(&self, id: &ChannelId) -> bool {
+            self.added.borrow().iter().any(|u| &u.id == id)
+        }
+
+        fn add_channel(&self, uploader: &Uploader) -> Result<(), StoreError> {
+            self.added.borrow_mut().push(uploader.clone());
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockSnoozes {
+        snoozed: RefCell<HashMap<ChannelId, NaiveDate>>,
+    }
+
+    impl SnoozeStore for MockSnoozes {
+        fn load_snoozes(&self) -> Result<HashMap<ChannelId, NaiveDate>, StoreError> {
+            Ok(self.snoozed.borrow().clone())
+        }
+
+        fn save_snoozes(&self, snoozes: &HashMap<ChannelId, NaiveDate>) -> Result<(), StoreError> {
+            *self.snoozed.borrow_mut() = snoozes.clone();
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockJobs {
+        jobs: RefCell<Vec<PlaybackJob>>,
+        running: HashSet<u32>,
+        reused: HashSet<u32>,
+        stuck: HashSet<u32>,
+        stopped: RefCell<Vec<u32>>,
+    }
+
+    impl MockJobs {
+        fn with_running(pids: &[u32]) -> Self {
+            Self {
+                jobs: RefCell::new(
+                    pids.iter()
+                        .map(|&pid| PlaybackJob {
+                            pid,
+                            identity: Some(format!("player {pid}")),
+                            video: make_video(&format!("v{pid}"), &format!("Video {pid}"), 1),
+                            started: Utc::now(),
+                        })
+                        .collect(),
+                ),
+                running: pids.iter().copied().collect(),
+                reused: HashSet::new(),
+                stuck: HashSet::new(),
+                stopped: RefCell::new(Vec::new()),
+            }
+        }
+
+        fn pids(&self) -> Vec<u32> {
+            self.jobs.borrow().iter().map(|job| job.pid).collect()
+        }
+    }
+
+    impl JobStore for MockJobs {
+        fn load_jobs(&self) -> Result<Vec<PlaybackJob>, StoreError> {
+            Ok(self.jobs.borrow().clone())
+        }
+
+        fn save_jobs(&self, jobs: &[PlaybackJob]) -> Result<(), StoreError> {
+            *self.jobs.borrow_mut() = jobs.to_vec();
+            Ok(())
+        }
+    }
+
+    impl ProcessControl for MockJobs {
+        fn is_running(&self, pid: u32) -> bool {
+            self.running.contains(&pid)
+        }
+
+        /// A reused ID reports another process than the one launched.
+        fn identity(&self, pid: u32) -> Option<String> {
+            let process = if self.reused.contains(&pid) {
+                "other"
+            } else {
+                "player"
+            };
+            self.is_running(pid).then(|| format!("{process} {pid}"))
+        }
+
+        fn terminate(&self, pid: u32) -> Result<(), PlayError> {
+            if self.stuck.contains(&pid) {
+                return Err(PlayError::PlayerFailed(format!("cannot stop {pid}")));
+            }
+            self.stopped.borrow_mut().push(pid);
+            Ok(())
+        }
+    }
+
+    fn queued_ids(queue_store: &MockQueueStore) -> Vec<String> {
+        queue_store
+            .queued
+            .borrow()
+            .iter()
+            .map(|v| v.id.to_string())
+            .collect()
+    }
+
+    struct MockSegmentFetcher(Result<Vec<SkipSegment>, u16>);
+
+    impl SkipSegmentFetcher for MockSegmentFetcher {
+        fn fetch_segments(&self, _video_id: &VideoId) -> Result<Vec<SkipSegment>, FetchError> {
+            self.0.clone().map_err(FetchError::HttpError)
+        }
+    }
+
+    #[derive(Default)]
+    struct MockMetadataStore {
+        entries: RefCell<HashMap<VideoId, VideoMetadata>>,
+    }
+
+    impl MetadataStore for MockMetadataStore {
+        fn load_metadata(&self, video_id: &VideoId) -> Result<Option<VideoMetadata>, StoreError> {
+            Ok(self.entries.borrow().get(video_id).cloned())
+        }
+
+        fn save_metadata(&self, entries: &[(VideoId, VideoMetadata)]) -> Result<(), StoreError> {
+            self.entries.borrow_mut().extend(entries.iter().cloned());
+            Ok(())
+        }
+    }
+
+    struct MockShortsChecker {
+        short_ids: HashSet<VideoId>,
+    }
+
+    impl MockShortsChecker {
+        fn none() -> Self {
+            Self {
+                short_ids: HashSet::new(),
+            }
+        }
+
+        fn with_shorts(ids: &[&str]) -> Self {
+            Self {
+                short_ids: ids.iter().map(|id| VideoId::parse(*id).unwrap()).collect(),
+            }
+        }
+    }
+
+    impl ShortsChecker for MockShortsChecker {
+        fn is_short<'a>(
+            &'a self,
+            video_id: &'a VideoId,
+        ) -> LocalFuture<'a, Result<bool, FetchError>> {
+            Box::pin(async move { Ok(self.short_ids.contains(video_id)) })
+        }
+    }
+
+    fn make_video(id: &str, title: &str, days_ago: i64) -> Video {
+        Video {
+            id: VideoId::parse(id).unwrap(),
+            title: title.to_string(),
+            url: format!("https://youtube.com/watch?v={id}"),
+            published: Utc::now() - Duration::days(days_ago),
+            channel_name: "Test Channel".to_string(),
+            channel_id: ChannelId::parse("UC123").unwrap(),
+            kind: VideoKind::Upload,
+            duration: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn test_channel() -> Channel {
+        Channel {
+            name: "Test Channel".to_string(),
+            id: ChannelId::parse("UC123").unwrap(),
+            include_streams: false,
+            title_filter: Default::default(),
+            duration_range: Default::default(),
+            tags: Vec::new(),
+            name_from_feed: false,
+            alias: None,
+            snoozed_until: None,
+            player: None,
+            auto_download: false,
+        }
+    }
+
+    fn options() -> FetchOptions {
+        FetchOptions {
+            fetch_window: FetchWindow::from_days(7).unwrap(),
+            fetch_concurrency: NonZeroUsize::new(4).unwrap(),
+            shorts_concurrency: NonZeroUsize::new(4).unwrap(),
+            include_shorts: false,
+            title_filter: TitleFilter::default(),
+            muted_words: MutedWords::default(),
+            duration_range: DurationRange::default(),
+            live_handling: LiveHandling::Section,
+            show_muted: false,
+            quiet: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_returns_recent_unwatched() {
+        let videos = vec![make_video("v1", "Recent", 1), make_video("v2", "Old", 30)];
+        let fetcher = MockFetcher { videos };
+        let store = MockStore::new();
+        let shorts = MockShortsChecker::none();
+
+        let result = fetch_videos(&[test_channel()], &fetcher, &store, &shorts, options())
+            .await
+            .unwrap()
+            .videos;
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id.to_string(), "v1");
+    }
+
+    /// Takes a while for "Slow" and fails "Broken".
+    struct UnevenFetcher;
+
+    impl FeedFetcher for UnevenFetcher {
+        fn fetch<'a>(
+            &'a self,
+            channel: &'a Channel,
+        ) -> LocalFuture<'a, Result<Vec<Video>, FetchError>> {
+            Box::pin(async move {
+                match channel.name.as_str() {
+                    "Slow" => tokio::time::sleep(std::time::Duration::from_millis(30)).await,
+                    "Broken" => return FailingFetcher.fetch(channel).await,
+                    _ => {}
+                }
+                Ok(vec![make_video(
+                    &format!("v-{}", channel.name),
+                    "Recent",
+                    1,
+                )])
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_times_each_channel_slowest_first() {
+        let named = |name: &str| Channel {
+            name: name.to_string(),
+            ..test_channel()
+        };
+        let channels = [named("Fast"), named("Slow"), named("Broken")];
+
+        let timing = fetch_videos(
+            &channels,
+            &UnevenFetcher,
+            &MockStore::new(),
+            &MockShortsChecker::none(),
+            options(),
+        )
+        .await
+        .unwrap()
+        .timing;
+
+        assert_eq!(timing.channels[0].channel_name, "Slow");
+        assert_eq!(timing.channels[0].videos, Some(1));
+        assert!(timing.channels[0].elapsed >= std::time::Duration::from_millis(30));
+        assert!(timing.fetching >= timing.channels[0].elapsed);
+        let broken = timing.channels.iter().find(|c| c.channel_name == "Broken");
+        assert_eq!(broken.unwrap().videos, None);
+        assert_eq!(timing.shorts_checked, 2);
+    }
+
+    struct MockProbe;
+
+    impl FeedProbe for MockProbe {
+        fn probe<'a>(
+            &'a self,
+            channel: &'a Channel,
+        ) -> LocalFuture<'a, Result<FeedSummary, FetchError>> {
+            Box::pin(async move {
+                match channel.id.to_string().as_str() {
+                    "UCgone" => Err(FetchError::HttpError(404)),
+                    "UCempty" => Ok(FeedSummary {
+                        title: Some("Empty".to_string()),
+                        entries: 0,
+                    }),
+                    _ => Ok(FeedSummary {
+                        title: Some("New Name".to_string()),
+                        entries: 3,
+                    }),
+                }
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_dead_empty_and_renamed_channels() {
+        let channel = |id: &str, name: &str| Channel {
+            id: ChannelId::parse(id).unwrap(),
+            name: name.to_string(),
+            ..test_channel()
+        };
+        let channels = [
+            channel("UCgone", "Gone"),
+            channel("UCempty", "Empty"),
+            channel("UCold", "Old Name"),
+            channel("UCfine", "new name"),
+            Channel {
+                name_from_feed: true,
+                ..channel("UCunnamed", "UCunnamed")
+            },
+        ];
+
+        let problems: Vec<(&str, ChannelProblem)> =
+            check_channels(&channels, &MockProbe, NonZeroUsize::new(2).unwrap())
+                .await
+                .into_iter()
+                .map(|(c, problem)| (c.name.as_str(), problem))
+                .collect();
+
+        assert_eq!(
+            problems,
+            [
+                ("Gone", ChannelProblem::NotFound),
+                ("Empty", ChannelProblem::Empty),
+                ("Old Name", ChannelProblem::Renamed("New Name".to_string())),
+            ]
+        );
+    }
+
+    struct MockCatalog {
+        videos: Vec<Video>,
+    }
+
+    impl UploadCatalog for MockCatalog {
+        fn known_uploads(&self, channel: &Channel) -> Option<Vec<Video>> {
+            (channel.id.to_string() != "UCnever").then(|| {
+                self.videos
+                    .iter()
+                    .filter(|v| v.channel_id == channel.id)
+                    .cloned()
+                    .collect()
+            })
+        }
+    }
+
+    #[test]
+    fn channel_stats_put_least_watched_first() {
+        let video = |id: &str, channel: &str, days_ago: i64| Video {
+            channel_id: ChannelId::parse(channel).unwrap(),
+            ..make_video(id, id, days_ago)
+        };
+        let channel = |id: &str| Channel {
+            id: ChannelId::parse(id).unwrap(),
+            name: id.to_string(),
+            ..test_channel()
+        };
+        let catalog = MockCatalog {
+            videos: vec![
+                video("a1", "UCloved", 1),
+                video("a2", "UCloved", 13),
+                video("b1", "UCignored", 2),
+                video("b2", "UCignored", 20),
+                video("b3", "UCignored", 27),
+                Video {
+                    kind: VideoKind::Upcoming,
+                    published: Utc::now() + Duration::days(1),
+                    ..video("b4", "UCignored", 0)
+                },
+            ],
+        };
+        let store = MockStore::new();
+        store.mark_watched(&VideoId::parse("a1").unwrap()).unwrap();
+        store.mark_watched(&VideoId::parse("a2").unwrap()).unwrap();
+        let channels = [channel("UCnever"), channel("UCloved"), channel("UCignored")];
+
+        let stats = channel_stats(&channels, &catalog, &store, Utc::now()).unwrap();
+
+        let names: Vec<&str> = stats.iter().map(|(c, _)| c.name.as_str()).collect();
+        assert_eq!(names, ["UCignored", "UCloved", "UCnever"]);
+        let ignored = stats[0].1.as_ref().unwrap();
+        assert_eq!((ignored.uploads, ignored.watched), (3, 0));
+        assert!((ignored.per_week - 3.0 / (27.0 / 7.0)).abs() < 0.01);
+        let loved = stats[1].1.as_ref().unwrap();
+        assert_eq!((loved.uploads, loved.watched), (2, 2));
+        assert!(stats[2].1.is_none());
+    }
+
+    struct MockNotifier {
+        sent: RefCell<Vec<String>>,
+    }
+
+    impl Notifier for MockNotifier {
+        fn notify(&self, video: &Video) -> Result<(), FetchError> {
+            if video.title == "Broken" {
+                return Err(FetchError::HttpError(500));
+            }
+            self.sent.borrow_mut().push(video.title.clone());
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockDigestStore {
+        cursor: RefCell<Option<DigestCursor>>,
+    }
+
+    impl DigestStore for MockDigestStore {
+        fn load_digest(&self) -> Result<Option<DigestCursor>, StoreError> {
+            Ok(self.cursor.borrow().clone())
+        }
+
+        fn save_digest(&self, cursor: &DigestCursor) -> Result<(), StoreError> {
+            *self.cursor.borrow_mut() = Some(cursor.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn digest_lists_only_videos_no_earlier_digest_saw() {
+        let store = MockDigestStore::default();
+        let morning = Utc::now() - Duration::hours(8);
+        let old = make_video("old", "Old", 3);
+        let late = make_video("late", "Published long ago, fetched now", 60);
+
+        let first = digest(std::slice::from_ref(&old), &store, morning).unwrap();
+        let second = digest(&[late, old.clone()], &store, Utc::now()).unwrap();
+        let third = digest(std::slice::from_ref(&old), &store, Utc::now()).unwrap();
+
+        assert_eq!(first.since, None);
+        assert_eq!(first.videos.len(), 1);
+        assert_eq!(second.since, Some(morning));
+        let ids: Vec<String> = second.videos.iter().map(|v| v.id.to_string()).collect();
+        assert_eq!(ids, ["late"]);
+        assert!(third.videos.is_empty());
+    }
+
+    #[test]
+    fn digest_forgets_videos_gone_for_a_month() {
+        let store = MockDigestStore::default();
+        let gone = make_video("gone", "Gone", 1);
+        let long_ago = Utc::now() - Duration::days(DIGEST_MEMORY_DAYS + 1);
+
+        digest(std::slice::from_ref(&gone), &store, long_ago).unwrap();
+        digest(&[], &store, Utc::now()).unwrap();
+
+        assert!(store
+            .cursor
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .first_seen
+            .is_empty());
+    }
+
+    struct MockNotifiedStore {
+        notified: RefCell<Option<HashSet<VideoId>>>,
+    }
+
+    impl NotifiedStore for MockNotifiedStore {
+        fn load_notified(&self) -> Result<Option<HashSet<VideoId>>, StoreError> {
+            Ok(self.notified.borrow().clone())
+        }
+
+        fn save_notified(&self, video_ids: &HashSet<VideoId>) -> Result<(), StoreError> {
+            *self.notified.borrow_mut() = Some(video_ids.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn notifies_only_videos_not_seen_before() {
+        let notifier = MockNotifier {
+            sent: RefCell::new(Vec::new()),
+        };
+        let store = MockNotifiedStore {
+            notified: RefCell::new(None),
+        };
+        let old = make_video("old", "Old", 3);
+
+        let first = notify_new_videos(std::slice::from_ref(&old), &notifier, &store).unwrap();
+        let videos = [
+            make_video("new2", "Newer", 0),
+            make_video("bad", "Broken", 1),
+            make_video("new1", "New", 2),
+            old,
+        ];
+        let second = notify_new_videos(&videos, &notifier, &store).unwrap();
+        let third = notify_new_videos(&videos[..2], &notifier, &store).unwrap();
+
+        assert!(first.first_run && first.sent == 0);
+        assert_eq!(
+            second,
+            NotifyOutcome {
+                first_run: false,
+                sent: 2,
+                failed: 1
+            }
+        );
+        assert_eq!(*notifier.sent.borrow(), ["New", "Newer"]);
+        assert_eq!((third.sent, third.failed), (0, 1));
+        let remembered = store.notified.borrow().clone().unwrap();
+        assert_eq!(remembered, HashSet::from([VideoId::parse("new2").unwrap()]));
+    }
+
+    #[test]
+    fn learns_names_of_unnamed_channels_only() {
+        let unnamed = Channel {
+            name: "UC123".to_string(),
+            name_from_feed: true,
+            ..test_channel()
+        };
+        let named = Channel {
+            id: ChannelId::parse("UC456").unwrap(),
+            ..test_channel()
+        };
+        let mut other = make_video("v2", "Other", 1);
+        other.channel_id = named.id.clone();
+        other.channel_name = "Feed Title".to_string();
+        let names = MockChannelNames::default();
+
+        learn_channel_names(
+            &[unnamed, named],
+            &[make_video("v1", "First", 1), other],
+            &names,
+        )
+        .unwrap();
+
+        let learned = names.names.borrow();
+        assert_eq!(learned.len(), 1);
+        assert_eq!(learned[&ChannelId::parse("UC123").unwrap()], "Test Channel");
+    }
+
+    #[test]
+    fn snoozes_and_wakes_channels() {
+        let day = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let gone = ChannelId::parse("UCgone").unwrap();
+        let snoozes = MockSnoozes::default();
+        snoozes.snoozed.borrow_mut().insert(gone.clone(), day(5));
+
+        snooze_channel(&test_channel(), Some(day(20)), day(10), &snoozes).unwrap();
+        assert!(!snoozes.snoozed.borrow().contains_key(&gone));
+        let mut channels = [Channel {
+            snoozed_until: Some(day(15)),
+            ..test_channel()
+        }];
+        apply_snoozes(&mut channels, &snoozes).unwrap();
+        assert!(channels[0].is_snoozed(day(19)));
+        assert!(!channels[0].is_snoozed(day(20)));
+
+        snooze_channel(&test_channel(), None, day(10), &snoozes).unwrap();
+        assert!(snoozes.snoozed.borrow().is_empty());
+    }
+
+    #[test]
+    fn adds_the_uploader_once_and_marks_the_video() {
+        let video = VideoId::parse("v1").unwrap();
+        let channels = MockChannelList::default();
+        let store = MockStore::new();
+
+        let (uploader, added) =
+            add_channel_from_video(&video, &MockUploader, &channels, &store, false).unwrap();
+        assert_eq!(uploader.name, "New");
+        assert!(added);
+        assert!(store.watched.borrow().is_empty());
+
+        let (_, added) =
+            add_channel_from_video(&video, &MockUploader, &channels, &store, true).unwrap();
+        assert!(!added);
+        assert_eq!(channels.added.borrow().len(), 1);
+        assert!(store.watched.borrow().contains(&video));
+    }
+
+    #[test]
+    fn looks_up_channel_players_by_channel_id() {
+        let video = make_video("v1", "Song", 1);
+        let players = HashMap::from([(video.channel_id.clone(), ChannelPlayer::Browser)]);
+        let other = Video {
+            channel_id: ChannelId::parse("UCother").unwrap(),
+            ..video.clone()
+        };
+
+        assert_eq!(
+            channel_player(&video, &players),
+            Some(&ChannelPlayer::Browser)
+        );
+        assert_eq!(channel_player(&other, &players), None);
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_continues_on_channel_failure() {
+        let fetcher = FailingFetcher;
+        let store = MockStore::new();
+        let shorts = MockShortsChecker::none();
+
+        let result = fetch_videos(&[test_channel()], &fetcher, &store, &shorts, options())
+            .await
+            .unwrap();
+
+        assert!(result.videos.is_empty());
+        assert_eq!(result.report.len(), 1);
+        let failure = &result.report.failures[0];
+        assert_eq!(failure.channel_id, test_channel().id);
+        assert_eq!(
+            failure.suggestion(),
+            "check the connection and the proxy setting"
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_excludes_watched() {
+        let videos = vec![make_video("v1", "First", 1), make_video("v2", "Second", 2)];
+        let fetcher = MockFetcher { videos };
+        let store = MockStore::new();
+        store.mark_watched(&VideoId::parse("v1").unwrap()).unwrap();
+        let shorts = MockShortsChecker::none();
+
+        let unwatched = fetch_videos(&[test_channel()], &fetcher, &store, &shorts, options())
+            .await
+            .unwrap()
+            .videos;
+
+        assert_eq!(unwatched.len(), 1);
+        assert_eq!(unwatched[0].id.to_string(), "v2");
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_returns_newest_first() {
+        let videos = vec![make_video("v1", "Older", 3), make_video("v2", "Newer", 1)];
+        let fetcher = MockFetcher { videos };
+        let store = MockStore::new();
+        let shorts = MockShortsChecker::none();
+
+        let result = fetch_videos(&[test_channel()], &fetcher, &store, &shorts, options())
+            .await
+            .unwrap()
+            .videos;
+
+        assert_eq!(result[0].id.to_string(), "v2");
+        assert_eq!(result[1].id.to_string(), "v1");
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_lists_videos_in_progress_first() {
+        let videos = vec![make_video("v1", "Older", 3), make_video("v2", "Newer", 1)];
+        let fetcher = MockFetcher { videos };
+        let store = MockStore::new();
+        let started = WatchProgress::from_percent(40.0).unwrap();
+        store
+            .save_progress(&VideoId::parse("v1").unwrap(), started)
+            .unwrap();
+        let shorts = MockShortsChecker::none();
+
+        let result = fetch_videos(&[test_channel()], &fetcher, &store, &shorts, options())
+            .await
+            .unwrap()
+            .videos;
+
+        assert_eq!(result[0].id.to_string(), "v1");
+        assert_eq!(result[1].id.to_string(), "v2");
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_excludes_shorts() {
+        let videos = vec![
+            make_video("v1", "Regular", 1),
+            make_video("short1", "A Short", 1),
+            make_video("v2", "Also Regular", 2),
+        ];
+        let fetcher = MockFetcher { videos };
+        let store = MockStore::new();
+        let shorts = MockShortsChecker::with_shorts(&["short1"]);
+
+        let result = fetch_videos(&[test_channel()], &fetcher, &store, &shorts, options())
+            .await
+            .unwrap()
+            .videos;
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].id.to_string(), "v1");
+        assert_eq!(result[1].id.to_string(), "v2");
+    }
+
+    struct FailingShortsChecker;
+
+    impl ShortsChecker for FailingShortsChecker {
+        fn is_short<'a>(
+            &'a self,
+            _video_id: &'a VideoId,
+        ) -> LocalFuture<'a, Result<bool, FetchError>> {
+            Box::pin(async move { Err(FetchError::Network("timeout".to_string())) })
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_keeps_videos_when_shorts_check_fails() {
+        let fetcher = MockFetcher {
+            videos: vec![make_video("v1", "Regular", 1)],
+        };
+        let store = MockStore::new();
+
+        let result = fetch_videos(
+            &[test_channel()],
+            &fetcher,
+            &store,
+            &FailingShortsChecker,
+            options(),
+        )
+        .await
+        .unwrap()
+        .videos;
+
+        assert_eq!(result.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_applies_global_and_channel_title_filters() {
+        let videos = vec![
+            make_video("v1", "Episode 1", 1),
+            make_video("v2", "Episode 2 trailer", 1),
+            make_video("v3", "Livestream", 1),
+        ];
+        let fetcher = MockFetcher { videos };
+        let store = MockStore::new();
+        let shorts = MockShortsChecker::none();
+        let patterns = |p: &str| TitlePatterns::parse(&[p.to_string()]).unwrap();
+        let channel = Channel {
+            title_filter: TitleFilter {
+                include: patterns("^Episode"),
+                ..TitleFilter::default()
+            },
+            ..test_channel()
+        };
+        let options = FetchOptions {
+            title_filter: TitleFilter {
+                exclude: patterns("trailer"),
+                ..TitleFilter::default()
+            },
+            ..options()
+        };
+
+        let result = fetch_videos(&[channel], &fetcher, &store, &shorts, options)
+            .await
+            .unwrap()
+            .videos;
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id.to_string(), "v1");
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_tags_videos_with_their_channel_tags() {
+        let fetcher = MockFetcher {
+            videos: vec![make_video("v1", "Video", 1)],
+        };
+        let store = MockStore::new();
+        let shorts = MockShortsChecker::none();
+        let channel = Channel {
+            tags: vec![ChannelTag::parse("music").unwrap()],
+            ..test_channel()
+        };
+
+        let result = fetch_videos(&[channel], &fetcher, &store, &shorts, options())
+            .await
+            .unwrap()
+            .videos;
+
+        assert_eq!(result[0].tags, [ChannelTag::parse("music").unwrap()]);
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_applies_duration_ranges() {
+        let sized = |id, mins: u64| Video {
+            duration: Some(std::time::Duration::from_secs(mins * 60)),
+            ..make_video(id, "Video", 1)
+        };
+        let fetcher = MockFetcher {
+            videos: vec![sized("clip", 1), sized("talk", 45), sized("vod", 480)],
+        };
+        let store = MockStore::new();
+        let shorts = MockShortsChecker::none();
+        let channel = Channel {
+            duration_range: DurationRange::parse(Some("2m"), None).unwrap(),
+            ..test_channel()
+        };
+        let options = FetchOptions {
+            duration_range: DurationRange::parse(None, Some("3h")).unwrap(),
+            ..options()
+        };
+
+        let result = fetch_videos(&[channel], &fetcher, &store, &shorts, options)
+            .await
+            .unwrap()
+            .videos;
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id.to_string(), "talk");
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_hides_muted_words_unless_shown() {
+        let videos = vec![
+            make_video("v1", "Tutorial", 1),
+            make_video("v2", "Drama REACTION", 1),
+        ];
+        let fetcher = MockFetcher { videos };
+        let store = MockStore::new();
+        let shorts = MockShortsChecker::none();
+        let options = FetchOptions {
+            muted_words: MutedWords::new(&["reaction".to_string()]),
+            ..options()
+        };
+
+        let hidden = fetch_videos(
+            &[test_channel()],
+            &fetcher,
+            &store,
+            &shorts,
+            options.clone(),
+        )
+        .await
+        .unwrap()
+        .videos;
+        let shown = fetch_videos(
+            &[test_channel()],
+            &fetcher,
+            &store,
+            &shorts,
+            FetchOptions {
+                show_muted: true,
+                ..options
+            },
+        )
+        .await
+        .unwrap()
+        .videos;
+
+        assert_eq!(hidden.len(), 1);
+        assert_eq!(hidden[0].id.to_string(), "v1");
+        assert_eq!(shown.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_tags_shorts_when_included() {
+        let videos = vec![
+            make_video("v1", "Regular", 1),
+            make_video("short1", "A Short", 2),
+        ];
+        let fetcher = MockFetcher { videos };
+        let store = MockStore::new();
+        let shorts = MockShortsChecker::with_shorts(&["short1"]);
+        let options = FetchOptions {
+            include_shorts: true,
+            ..options()
+        };
+
+        let result = fetch_videos(&[test_channel()], &fetcher, &store, &shorts, options)
+            .await
+            .unwrap()
+            .videos;
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].kind, VideoKind::Upload);
+        assert_eq!(result[1].kind, VideoKind::Short);
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_checks_many_videos_for_shorts_with_bounded_concurrency() {
+        let videos: Vec<Video> = (0..60)
+            .map(|i| make_video(&format!("v{i}"), "Video", 1))
+            .collect();
+        let fetcher = MockFetcher { videos };
+        let store = MockStore::new();
+        let shorts = MockShortsChecker::with_shorts(&["v7", "v42"]);
+        let options = FetchOptions {
+            shorts_concurrency: NonZeroUsize::new(3).unwrap(),
+            ..options()
+        };
+
+        let result = fetch_videos(&[test_channel()], &fetcher, &store, &shorts, options)
+            .await
+            .unwrap()
+            .videos;
+
+        assert_eq!(result.len(), 58);
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_lists_a_video_shared_by_two_channels_once() {
+        let fetcher = MockFetcher {
+            videos: vec![make_video("v1", "Collab", 1)],
+        };
+        let store = MockStore::new();
+        let shorts = MockShortsChecker::none();
+        let other = Channel {
+            name: "Clips".to_string(),
+            id: ChannelId::parse("UC456").unwrap(),
+            ..test_channel()
+        };
+
+        let result = fetch_videos(
+            &[test_channel(), other],
+            &fetcher,
+            &store,
+            &shorts,
+            options(),
+        )
+        .await
+        .unwrap()
+        .videos;
+
+        assert_eq!(result.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_hides_scheduled_when_live_handling_is_hide() {
+        let videos = vec![
+            make_video("v1", "Published", 1),
+            make_video("v2", "Scheduled Tomorrow", -1),
+            make_video("v3", "Scheduled Next Week", -7),
+        ];
+        let fetcher = MockFetcher { videos };
+        let store = MockStore::new();
+        let shorts = MockShortsChecker::none();
+        let options = FetchOptions {
+            live_handling: LiveHandling::Hide,
+            ..options()
+        };
+
+        let result = fetch_videos(&[test_channel()], &fetcher, &store, &shorts, options)
+            .await
+            .unwrap()
+            .videos;
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id.to_string(), "v1");
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_lists_scheduled_and_live_in_their_own_section() {
+        let videos = vec![
+            make_video("v2", "Scheduled Next Week", -7),
+            Video {
+                kind: VideoKind::Live,
+                ..make_video("live", "Live now", 0)
+            },
+            make_video("v1", "Published", 1),
+            make_video("v3", "Scheduled Tomorrow", -1),
+        ];
+        let fetcher = MockFetcher { videos };
+        let store = MockStore::new();
+        let shorts = MockShortsChecker::with_shorts(&["live"]);
+
+        let result = fetch_videos(&[test_channel()], &fetcher, &store, &shorts, options())
+            .await
+            .unwrap()
+            .videos;
+
+        let ids: Vec<String> = result.iter().map(|v| v.id.to_string()).collect();
+        assert_eq!(ids, ["v1", "live", "v3", "v2"]);
+        assert_eq!(result[1].kind, VideoKind::Live);
+        assert_eq!(result[2].kind, VideoKind::Upcoming);
+    }
+
+    #[tokio::test]
+    async fn fetch_videos_fetches_every_channel_with_bounded_concurrency() {
+        let channels: Vec<Channel> = (0..10)
+            .map(|i| Channel {
+                name: format!("Channel {i}"),
+                id: ChannelId::parse(format!("UC{i}")).unwrap(),
+                include_streams: false,
+                title_filter: Default::default(),
+                duration_range: Default::default(),
+                tags: Vec::new(),
+                name_from_feed: false,
+                alias: None,
+                snoozed_until: None,
+                player: None,
+                auto_download: false,
+            })
+            .collect();
+        let store = MockStore::new();
+        let shorts = MockShortsChecker::none();
+        let options = FetchOptions {
+            fetch_concurrency: NonZeroUsize::new(2).unwrap(),
+            ..options()
+        };
+
+        let result = fetch_videos(&channels, &PerChannelFetcher, &store, &shorts, options)
+            .await
+            .unwrap()
+            .videos;
+
+        assert_eq!(result.len(), 10);
+    }
+
+    #[test]
+    fn mark_and_play_marks_as_watched() {
+        let video = make_video("v1", "First", 1);
+        let store = MockStore::new();
+        let player = MockPlayer::new();
+
+        let jobs = MockJobs::default();
+
+        mark_and_play(
+            &video,
+            &store,
+            &player,
+            &jobs,
+            &MockJobs::with_running(&[4242]),
+        )
+        .unwrap();
+
+        assert!(store
+            .load_watched()
+            .unwrap()
+            .contains(&VideoId::parse("v1").unwrap()));
+        assert_eq!(player.played.borrow()[0], "https://youtube.com/watch?v=v1");
+        assert_eq!(jobs.pids(), [4242]);
+        assert_eq!(
+            jobs.jobs.borrow()[0].identity.as_deref(),
+            Some("player 4242")
+        );
+    }
+
+    #[test]
+    fn forgets_jobs_whose_player_exited() {
+        let mut jobs = MockJobs::with_running(&[1, 2, 3]);
+        jobs.running.remove(&3);
+
+        let running = running_jobs(&jobs, &jobs).unwrap();
+
+        assert_eq!(running.len(), 2);
+        assert_eq!(jobs.pids(), [1, 2]);
+    }
+
+    #[test]
+    fn does_not_stop_a_process_that_reused_a_job_id() {
+        let mut jobs = MockJobs::with_running(&[1, 2]);
+        jobs.reused.insert(2);
+        jobs.jobs.borrow_mut()[0].identity = None;
+
+        stop_jobs(None, &jobs, &jobs).unwrap();
+
+        assert!(jobs.stopped.borrow().is_empty());
+        assert!(jobs.pids().is_empty());
+    }
+
+    #[test]
+    fn stops_one_job_or_all() {
+        let jobs = MockJobs::with_running(&[1, 2, 3]);
+
+        stop_jobs(Some(VideoNumber::parse(2).unwrap()), &jobs, &jobs).unwrap();
+        assert_eq!(*jobs.stopped.borrow(), [2]);
+        assert_eq!(jobs.pids(), [1, 3]);
+
+        stop_jobs(None, &jobs, &jobs).unwrap();
+        assert_eq!(*jobs.stopped.borrow(), [2, 1, 3]);
+        assert!(jobs.pids().is_empty());
+    }
 
-    let is_short: Vec<bool> = std::thread::scope(|s| {
-        let handles: Vec<_> = unwatched
-            .iter()
-            .map(|v| s.spawn(|| shorts_checker.is_short(&v.id)))
-            .collect();
+    #[test]
+    fn stopping_a_missing_job_fails() {
+        let jobs = MockJobs::with_running(&[1]);
 
-        handles.into_iter().map(|h| h.join().unwrap()).collect()
-    });
+        let result = stop_jobs(Some(VideoNumber::parse(2).unwrap()), &jobs, &jobs);
 
-    let non_shorts: Vec<Video> = unwatched
-        .into_iter()
-        .zip(is_short)
-        .filter_map(|(v, short)| if short { None } else { Some(v) })
-        .collect();
+        assert!(matches!(result, Err(AppError::NoSuchJob(_))));
+        assert!(jobs.stopped.borrow().is_empty());
+    }
 
-    Ok(non_shorts)
-}
+    #[test]
+    fn keeps_jobs_that_could_not_be_stopped() {
+        let mut jobs = MockJobs::with_running(&[1, 2, 3]);
+        jobs.stuck.insert(2);
 
-pub fn mark_and_play(
-    video: &Video,
-    store: &dyn VideoStore,
-    player: &dyn VideoPlayer,
-) -> Result<(), AppError> {
-    println!("Playing: {} [{}]", video.title, video.channel_name);
-    player.play(&video.url)?;
-    store.mark_watched(&video.id)?;
-    Ok(())
-}
+        let result = stop_jobs(None, &jobs, &jobs);
 
-pub fn mark_as_watched(video: &Video, store: &dyn VideoStore) -> Result<(), AppError> {
-    store.mark_watched(&video.id)?;
-    Ok(())
-}
+        assert!(matches!(result, Err(AppError::Play(_))));
+        assert_eq!(*jobs.stopped.borrow(), [1, 3]);
+        assert_eq!(jobs.pids(), [2]);
+    }
 
-pub fn mark_all_as_watched(videos: &[Video], store: &dyn VideoStore) -> Result<(), AppError> {
-    let ids: Vec<&VideoId> = videos.iter().map(|v| &v.id).collect();
-    store.mark_watched_batch(&ids)?;
-    println!("Marked {} videos as watched.", videos.len());
-    Ok(())
-}
+    #[test]
+    fn mark_as_watched_marks_without_playing() {
+        let video = make_video("v1", "First", 1);
+        let store = MockStore::new();
+        let player = MockPlayer::new();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::application::ports::{
-        FeedFetcher, FetchError, ShortsChecker, VideoPlayer, VideoStore,
-    };
-    use crate::domain::channel::{Channel, ChannelId};
-    use crate::domain::video::{Video, VideoId};
-    use std::cell::RefCell;
-    use std::collections::HashSet;
+        mark_as_watched(&video, &store).unwrap();
 
-    struct MockFetcher {
-        videos: Vec<Video>,
+        assert!(store
+            .load_watched()
+            .unwrap()
+            .contains(&VideoId::parse("v1").unwrap()));
+        assert!(player.played.borrow().is_empty());
     }
 
-    impl FeedFetcher for MockFetcher {
-        fn fetch(&self, _channel: &Channel) -> Result<Vec<Video>, FetchError> {
-            Ok(self.videos.clone())
-        }
+    #[test]
+    fn mark_and_play_returns_error_on_player_failure() {
+        let video = make_video("v1", "First", 1);
+        let store = MockStore::new();
+        let player = FailingPlayer;
+
+        let jobs = MockJobs::default();
+        let result = mark_and_play(&video, &store, &player, &jobs, &jobs);
+
+        assert!(result.is_err());
     }
 
-    struct FailingFetcher;
+    #[test]
+    fn opens_in_browser_without_marking_by_default() {
+        let video = make_video("v1", "First", 1);
+        let store = MockStore::new();
+        let opener = MockOpener::default();
 
-    impl FeedFetcher for FailingFetcher {
-        fn fetch(&self, _channel: &Channel) -> Result<Vec<Video>, FetchError> {
-            Err(FetchError::Network("connection refused".to_string()))
-        }
+        open_in_browser(&video, &store, &opener, false).unwrap();
+
+        assert_eq!(*opener.opened.borrow(), [video.url.as_str()]);
+        assert!(store.load_watched().unwrap().is_empty());
     }
 
-    struct MockStore {
-        watched: RefCell<HashSet<VideoId>>,
+    #[test]
+    fn opening_in_browser_can_mark_watched() {
+        let video = make_video("v1", "First", 1);
+        let store = MockStore::new();
+
+        open_in_browser(&video, &store, &MockOpener::default(), true).unwrap();
+
+        assert!(store.load_watched().unwrap().contains(&video.id));
     }
 
-    impl MockStore {
-        fn new() -> Self {
-            Self {
-                watched: RefCell::new(HashSet::new()),
-            }
-        }
+    #[test]
+    fn downloads_and_marks_watched_when_asked() {
+        let id = VideoId::parse("v1").unwrap();
+        let store = MockStore::new();
+        let downloads = MockDownloadStore::default();
+        let processes = MockProcesses(HashSet::new());
+        let downloader = MockDownloader::default();
+        let run = download_run(&downloader, &downloads, &processes);
+
+        let kept = download(&id, None, &run, &store, false, &|_| {}).unwrap();
+        assert!(store.load_watched().unwrap().is_empty());
+        let again = download(&id, None, &run, &store, true, &|_| {}).unwrap();
+
+        assert_eq!(kept, Some(PathBuf::from("/videos/v1.webm")));
+        assert_eq!(again, kept);
+        assert!(matches!(
+            downloads.state("v1"),
+            Some(DownloadState::Saved { .. })
+        ));
+        assert!(store.load_watched().unwrap().contains(&id));
     }
 
-    impl VideoStore for MockStore {
-        fn load_watched(&self) -> Result<HashSet<VideoId>, StoreError> {
-            Ok(self.watched.borrow().clone())
-        }
+    #[test]
+    fn a_failed_download_is_not_marked_watched() {
+        let id = VideoId::parse("v1").unwrap();
+        let store = MockStore::new();
+        let downloads = MockDownloadStore::default();
+        let processes = MockProcesses(HashSet::new());
+        let downloader = MockDownloader { fail: true };
+        let run = download_run(&downloader, &downloads, &processes);
 
-        fn mark_watched(&self, video_id: &VideoId) -> Result<(), StoreError> {
-            self.watched.borrow_mut().insert(video_id.clone());
-            Ok(())
-        }
-    }
+        let result = download(&id, None, &run, &store, true, &|_| {});
 
-    struct MockPlayer {
-        played: RefCell<Vec<String>>,
+        assert!(result.is_err());
+        assert!(matches!(
+            downloads.state("v1"),
+            Some(DownloadState::Failed { .. })
+        ));
+        assert!(store.load_watched().unwrap().is_empty());
     }
 
-    impl MockPlayer {
-        fn new() -> Self {
-            Self {
-                played: RefCell::new(Vec::new()),
-            }
-        }
+    #[test]
+    fn leaves_a_download_running_elsewhere_alone() {
+        let id = VideoId::parse("v1").unwrap();
+        let downloads =
+            MockDownloadStore::with(vec![queued("v1", DownloadState::Downloading { pid: 7 })]);
+        let processes = MockProcesses(HashSet::from([7]));
+        let downloader = MockDownloader::default();
+        let run = download_run(&downloader, &downloads, &processes);
+
+        let result = download(&id, None, &run, &MockStore::new(), false, &|_| {}).unwrap();
+
+        assert_eq!(result, None);
+        assert_eq!(
+            downloads.state("v1"),
+            Some(DownloadState::Downloading { pid: 7 })
+        );
     }
 
-    impl VideoPlayer for MockPlayer {
-        fn play(&self, url: &str) -> Result<(), PlayError> {
-            self.played.borrow_mut().push(url.to_string());
-            Ok(())
-        }
+    #[test]
+    fn runs_queued_and_interrupted_downloads() {
+        let downloads = MockDownloadStore::with(vec![
+            queued("queued", DownloadState::Queued),
+            queued("interrupted", DownloadState::Downloading { pid: 7 }),
+            queued(
+                "failed",
+                DownloadState::Failed {
+                    error: "offline".to_string(),
+                },
+            ),
+        ]);
+        let processes = MockProcesses(HashSet::new());
+        let downloader = MockDownloader::default();
+        let started = Mutex::new(Vec::new());
+
+        let outcomes = run_downloads(
+            &download_run(&downloader, &downloads, &processes),
+            &|download| {
+                if download.state == (DownloadState::Downloading { pid: 100 }) {
+                    started.lock().unwrap().push(download.id.to_string());
+                }
+            },
+        )
+        .unwrap();
+
+        let ran: Vec<String> = outcomes.iter().map(|(id, _)| id.to_string()).collect();
+        assert_eq!(ran, ["queued", "interrupted"]);
+        started.lock().unwrap().sort();
+        assert_eq!(*started.lock().unwrap(), ["interrupted", "queued"]);
+        assert_eq!(
+            downloads.queue.lock().unwrap()[1].saved_path(),
+            Some(Path::new("/videos/interrupted.webm"))
+        );
+        assert!(matches!(
+            downloads.state("failed"),
+            Some(DownloadState::Failed { .. })
+        ));
     }
 
-    struct FailingPlayer;
+    #[test]
+    fn retries_failed_downloads() {
+        let downloads = MockDownloadStore::with(vec![queued(
+            "failed",
+            DownloadState::Failed {
+                error: "offline".to_string(),
+            },
+        )]);
 
-    impl VideoPlayer for FailingPlayer {
-        fn play(&self, _url: &str) -> Result<(), PlayError> {
-            Err(PlayError::PlayerFailed("mpv crashed".to_string()))
-        }
+        retry_failed_downloads(&downloads).unwrap();
+
+        assert_eq!(downloads.state("failed"), Some(DownloadState::Queued));
     }
 
-    struct MockShortsChecker {
-        short_ids: HashSet<VideoId>,
+    #[test]
+    fn finds_the_files_of_saved_downloads() {
+        let downloads = MockDownloadStore::with(vec![
+            queued(
+                "saved",
+                DownloadState::Saved {
+                    path: PathBuf::from("/videos/saved.webm"),
+                    at: Utc::now(),
+                },
+            ),
+            queued("waiting", DownloadState::Queued),
+        ]);
+
+        let files = downloaded_files(&downloads).unwrap();
+
+        assert_eq!(
+            files,
+            HashMap::from([(
+                VideoId::parse("saved").unwrap(),
+                PathBuf::from("/videos/saved.webm")
+            )])
+        );
     }
 
-    impl MockShortsChecker {
-        fn none() -> Self {
-            Self {
-                short_ids: HashSet::new(),
-            }
+    #[test]
+    fn prunes_downloads_watched_long_ago() {
+        let saved = |id: &str, dir: &str| {
+            queued(
+                id,
+                DownloadState::Saved {
+                    path: PathBuf::from(format!("/{dir}/{id}.webm")),
+                    at: Utc::now() - chrono::Duration::days(60),
+                },
+            )
+        };
+        let downloads = MockDownloadStore::with(vec![
+            saved("watched", "videos"),
+            saved("stuck", "readonly"),
+            saved("unwatched", "videos"),
+        ]);
+        let store = MockStore::new();
+        for id in ["watched", "stuck"] {
+            store
+                .watched
+                .borrow_mut()
+                .insert(VideoId::parse(id).unwrap());
         }
 
-        fn with_shorts(ids: &[&str]) -> Self {
-            Self {
-                short_ids: ids.iter().map(|id| VideoId::parse(*id).unwrap()).collect(),
-            }
-        }
-    }
+        let outcomes = prune_downloads(
+            &downloads,
+            &store,
+            &HashSet::new(),
+            chrono::Duration::days(30),
+            Utc::now(),
+        )
+        .unwrap();
 
-    impl ShortsChecker for MockShortsChecker {
-        fn is_short(&self, video_id: &VideoId) -> bool {
-            self.short_ids.contains(video_id)
-        }
+        let failed: Vec<String> = outcomes
+            .iter()
+            .filter(|(_, result)| result.is_err())
+            .map(|(d, _)| d.id.to_string())
+            .collect();
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(failed, ["stuck"]);
+        assert_eq!(
+            *downloads.deleted.lock().unwrap(),
+            [PathBuf::from("/videos/watched.webm")]
+        );
+        assert_eq!(downloads.state("watched"), None);
+        assert!(downloads.state("stuck").is_some());
+        assert!(downloads.state("unwatched").is_some());
     }
 
-    fn make_video(id: &str, title: &str, days_ago: i64) -> Video {
-        Video {
-            id: VideoId::parse(id).unwrap(),
-            title: title.to_string(),
-            url: format!("https://youtube.com/watch?v={id}"),
-            published: Utc::now() - Duration::days(days_ago),
-            channel_name: "Test Channel".to_string(),
-            channel_id: ChannelId::parse("UC123").unwrap(),
-        }
+    #[test]
+    fn queues_new_videos_of_archived_channels_oldest_first() {
+        let archived = Channel {
+            auto_download: true,
+            ..test_channel()
+        };
+        let other = Channel {
+            id: ChannelId::parse("UCother").unwrap(),
+            ..test_channel()
+        };
+        let videos = vec![
+            make_video("new", "New", 1),
+            make_video("old", "Old", 3),
+            make_video("saved", "Saved", 2),
+            Video {
+                kind: VideoKind::Upcoming,
+                ..make_video("soon", "Soon", 0)
+            },
+            Video {
+                channel_id: other.id.clone(),
+                ..make_video("elsewhere", "Elsewhere", 1)
+            },
+        ];
+        let downloads = MockDownloadStore::with(vec![queued(
+            "saved",
+            DownloadState::Saved {
+                path: PathBuf::from("/videos/saved.webm"),
+                at: Utc::now(),
+            },
+        )]);
+
+        let count = queue_auto_downloads(&videos, &[archived, other], &downloads).unwrap();
+
+        let ids: Vec<String> = downloads
+            .queue
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|d| d.id.to_string())
+            .collect();
+        assert_eq!(count, 2);
+        assert_eq!(ids, ["saved", "old", "new"]);
     }
 
-    fn test_channel() -> Channel {
-        Channel {
-            name: "Test Channel".to_string(),
-            id: ChannelId::parse("UC123").unwrap(),
-        }
+    #[test]
+    fn finds_video_by_number_in_last_listing() {
+        let listing = MockListingStore(vec![
+            make_video("v1", "First", 1),
+            make_video("v2", "Second", 2),
+        ]);
+
+        let second = listed_video(VideoNumber::parse(2).unwrap(), &listing).unwrap();
+        let missing = listed_video(VideoNumber::parse(3).unwrap(), &listing).unwrap();
+
+        assert_eq!(second.map(|v| v.title), Some("Second".to_string()));
+        assert!(missing.is_none());
     }
 
-    fn seven_days() -> FetchWindowDays {
-        FetchWindowDays::parse(7).unwrap()
+    #[test]
+    fn enqueues_each_video_once() {
+        let queue_store = MockQueueStore::default();
+        let video = make_video("v1", "First", 1);
+
+        enqueue(&video, &queue_store).unwrap();
+        enqueue(&video, &queue_store).unwrap();
+
+        assert_eq!(queued_ids(&queue_store), ["v1"]);
     }
 
     #[test]
-    fn fetch_videos_returns_recent_unwatched() {
-        let videos = vec![make_video("v1", "Recent", 1), make_video("v2", "Old", 30)];
-        let fetcher = MockFetcher { videos };
+    fn pending_queue_drops_videos_watched_meanwhile() {
+        let queue_store = MockQueueStore::default();
         let store = MockStore::new();
-        let shorts = MockShortsChecker::none();
+        for id in ["v1", "v2"] {
+            enqueue(&make_video(id, id, 1), &queue_store).unwrap();
+        }
+        store.mark_watched(&VideoId::parse("v1").unwrap()).unwrap();
 
-        let result =
-            fetch_videos(&[test_channel()], &fetcher, &store, &shorts, seven_days()).unwrap();
+        let pending = pending_queue(&queue_store, &store).unwrap();
 
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0].id.to_string(), "v1");
+        assert_eq!(pending.len(), 1);
+        assert_eq!(queued_ids(&queue_store), ["v2"]);
     }
 
     #[test]
-    fn fetch_videos_continues_on_channel_failure() {
-        let fetcher = FailingFetcher;
+    fn removes_and_reorders_queue_entries() {
+        let queue_store = MockQueueStore::default();
         let store = MockStore::new();
-        let shorts = MockShortsChecker::none();
+        for id in ["v1", "v2", "v3"] {
+            enqueue(&make_video(id, id, 1), &queue_store).unwrap();
+        }
+        let number = |n| VideoNumber::parse(n).unwrap();
 
-        let result = fetch_videos(&[test_channel()], &fetcher, &store, &shorts, seven_days());
+        move_in_queue(number(3), number(1), &queue_store, &store).unwrap();
+        remove_from_queue(number(2), &queue_store, &store).unwrap();
 
-        assert!(result.is_ok());
-        assert!(result.unwrap().is_empty());
+        assert_eq!(queued_ids(&queue_store), ["v3", "v2"]);
     }
 
     #[test]
-    fn fetch_videos_excludes_watched() {
-        let videos = vec![make_video("v1", "First", 1), make_video("v2", "Second", 2)];
-        let fetcher = MockFetcher { videos };
+    fn playing_queue_keeps_only_unfinished_videos() {
+        let queue_store = MockQueueStore::default();
         let store = MockStore::new();
-        store.mark_watched(&VideoId::parse("v1").unwrap()).unwrap();
-        let shorts = MockShortsChecker::none();
+        for id in ["v1", "v2", "v3"] {
+            enqueue(&make_video(id, id, 1), &queue_store).unwrap();
+        }
 
-        let unwatched =
-            fetch_videos(&[test_channel()], &fetcher, &store, &shorts, seven_days()).unwrap();
+        play_queue(&queue_store, &store, &MockPlayer::new()).unwrap();
 
-        assert_eq!(unwatched.len(), 1);
-        assert_eq!(unwatched[0].id.to_string(), "v2");
+        assert_eq!(queued_ids(&queue_store), ["v3"]);
     }
 
     #[test]
-    fn fetch_videos_returns_newest_first() {
-        let videos = vec![make_video("v1", "Older", 3), make_video("v2", "Newer", 1)];
-        let fetcher = MockFetcher { videos };
+    fn returns_sponsor_segments() {
+        let segment = SkipSegment {
+            start: 10.0,
+            end: 42.5,
+        };
+
+        let segments = skip_segments(
+            &make_video("v1", "First", 1),
+            &MockSegmentFetcher(Ok(vec![segment])),
+        );
+
+        assert_eq!(segments, [segment]);
+    }
+
+    #[test]
+    fn failed_segment_lookup_plays_in_full() {
+        let segments = skip_segments(&make_video("v1", "First", 1), &MockSegmentFetcher(Err(503)));
+
+        assert!(segments.is_empty());
+    }
+
+    #[test]
+    fn playlist_marks_videos_playback_moved_past() {
+        let (v1, v2, v3) = (
+            make_video("v1", "First", 1),
+            make_video("v2", "Second", 2),
+            make_video("v3", "Third", 3),
+        );
         let store = MockStore::new();
-        let shorts = MockShortsChecker::none();
+        let player = MockPlayer::new();
 
-        let result =
-            fetch_videos(&[test_channel()], &fetcher, &store, &shorts, seven_days()).unwrap();
+        play_playlist(&[&v1, &v2, &v3], &store, &player).unwrap();
 
-        assert_eq!(result[0].id.to_string(), "v2");
-        assert_eq!(result[1].id.to_string(), "v1");
+        let watched = store.load_watched().unwrap();
+        assert_eq!(player.played.borrow().len(), 3);
+        assert!(watched.contains(&v1.id) && watched.contains(&v2.id));
+        assert!(!watched.contains(&v3.id));
     }
 
     #[test]
-    fn fetch_videos_excludes_shorts() {
-        let videos = vec![
-            make_video("v1", "Regular", 1),
-            make_video("short1", "A Short", 1),
-            make_video("v2", "Also Regular", 2),
-        ];
-        let fetcher = MockFetcher { videos };
+    fn plays_all_oldest_first() {
+        let videos = vec![make_video("new", "New", 1), make_video("old", "Old", 3)];
         let store = MockStore::new();
-        let shorts = MockShortsChecker::with_shorts(&["short1"]);
+        let player = MockPlayer::new();
 
-        let result =
-            fetch_videos(&[test_channel()], &fetcher, &store, &shorts, seven_days()).unwrap();
+        play_all(&videos, &store, &player).unwrap();
 
-        assert_eq!(result.len(), 2);
-        assert_eq!(result[0].id.to_string(), "v1");
-        assert_eq!(result[1].id.to_string(), "v2");
+        assert_eq!(
+            *player.played.borrow(),
+            [videos[1].url.as_str(), videos[0].url.as_str()]
+        );
+        assert!(store.load_watched().unwrap().contains(&videos[1].id));
     }
 
     #[test]
-    fn fetch_videos_excludes_scheduled() {
-        let videos = vec![
-            make_video("v1", "Published", 1),
-            make_video("v2", "Scheduled Tomorrow", -1),
-            make_video("v3", "Scheduled Next Week", -7),
-        ];
-        let fetcher = MockFetcher { videos };
+    fn failed_playlist_marks_nothing() {
+        let video = make_video("v1", "First", 1);
         let store = MockStore::new();
-        let shorts = MockShortsChecker::none();
 
-        let result =
-            fetch_videos(&[test_channel()], &fetcher, &store, &shorts, seven_days()).unwrap();
+        let result = play_playlist(&[&video], &store, &FailingPlayer);
 
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0].id.to_string(), "v1");
+        assert!(result.is_err());
+        assert!(store.load_watched().unwrap().is_empty());
     }
 
     #[test]
-    fn mark_and_play_marks_as_watched() {
+    fn attached_playback_marks_watched_after_player_exits() {
         let video = make_video("v1", "First", 1);
         let store = MockStore::new();
         let player = MockPlayer::new();
 
-        mark_and_play(&video, &store, &player).unwrap();
+        play_attached(&video, &store, &player).unwrap();
 
-        assert!(store
-            .load_watched()
-            .unwrap()
-            .contains(&VideoId::parse("v1").unwrap()));
-        assert_eq!(player.played.borrow()[0], "https://youtube.com/watch?v=v1");
+        assert_eq!(*player.played.borrow(), [video.url.as_str()]);
+        assert!(store.load_watched().unwrap().contains(&video.id));
     }
 
     #[test]
-    fn mark_as_watched_marks_without_playing() {
+    fn failed_attached_playback_keeps_video_unwatched() {
         let video = make_video("v1", "First", 1);
         let store = MockStore::new();
-        let player = MockPlayer::new();
 
-        mark_as_watched(&video, &store).unwrap();
+        let result = play_attached(&video, &store, &FailingPlayer);
 
-        assert!(store
-            .load_watched()
-            .unwrap()
-            .contains(&VideoId::parse("v1").unwrap()));
-        assert!(player.played.borrow().is_empty());
+        assert!(matches!(result, Err(AppError::Play(_))));
+        assert!(store.load_watched().unwrap().is_empty());
     }
 
     #[test]
-    fn mark_and_play_returns_error_on_player_failure() {
+    fn tracked_playback_marks_watched_past_threshold() {
         let video = make_video("v1", "First", 1);
         let store = MockStore::new();
-        let player = FailingPlayer;
+        let threshold = WatchThreshold::parse(80).unwrap();
 
-        let result = mark_and_play(&video, &store, &player);
+        play_and_mark_if_watched(&video, &store, &MockPlayer::stopping_at(85.0), threshold)
+            .unwrap();
 
-        assert!(result.is_err());
+        assert!(store.load_watched().unwrap().contains(&video.id));
+    }
+
+    #[test]
+    fn tracked_playback_below_threshold_keeps_video_unwatched() {
+        let video = make_video("v1", "First", 1);
+        let store = MockStore::new();
+        let player = MockPlayer::stopping_at(3.0);
+        let threshold = WatchThreshold::parse(80).unwrap();
+
+        play_and_mark_if_watched(&video, &store, &player, threshold).unwrap();
+
+        assert_eq!(player.played.borrow().len(), 1);
+        assert!(store.load_watched().unwrap().is_empty());
+        assert_eq!(
+            store
+                .load_progress()
+                .unwrap()
+                .get(&video.id)
+                .map(|p| p.percent()),
+            Some(3)
+        );
     }
 
     #[test]
@@ -407,4 +3123,59 @@ mod tests {
         let watched = store.load_watched().unwrap();
         assert!(watched.is_empty());
     }
+
+    #[test]
+    fn undo_pops_entries_from_the_journal() {
+        let entry = JournalEntry {
+            at: Utc::now(),
+            action: JournalAction::MarkedWatched {
+                video_ids: vec![VideoId::parse("v1").unwrap()],
+                progress: HashMap::new(),
+            },
+        };
+        let journal = MockJournal {
+            entries: RefCell::new(vec![entry.clone(), entry]),
+        };
+
+        undo(&journal, NonZeroUsize::new(1).unwrap()).unwrap();
+
+        assert_eq!(journal.entries.borrow().len(), 1);
+    }
+
+    #[test]
+    fn undo_with_empty_journal_succeeds() {
+        let journal = MockJournal {
+            entries: RefCell::new(Vec::new()),
+        };
+
+        assert!(undo(&journal, NonZeroUsize::new(3).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn enrich_fetches_only_videos_without_metadata() {
+        let videos = vec![make_video("v1", "Known", 1), make_video("v2", "New", 1)];
+        let store = MockMetadataStore::default();
+        store
+            .save_metadata(&[(videos[0].id.clone(), VideoMetadata::default())])
+            .unwrap();
+        let fetcher = MockMetadataFetcher::failing_on(&[]);
+
+        enrich(&videos, &fetcher, &store, NonZeroUsize::MIN, false).unwrap();
+
+        assert_eq!(*fetcher.calls.lock().unwrap(), [videos[1].id.clone()]);
+        let info = video_info(&videos[1].id, &store).unwrap().unwrap();
+        assert_eq!(info.title, "Title of v2");
+    }
+
+    #[test]
+    fn enrich_skips_failed_videos() {
+        let videos = vec![make_video("v1", "Broken", 1), make_video("v2", "Fine", 1)];
+        let store = MockMetadataStore::default();
+        let fetcher = MockMetadataFetcher::failing_on(&["v1"]);
+
+        enrich(&videos, &fetcher, &store, NonZeroUsize::MIN, true).unwrap();
+
+        assert_eq!(video_info(&videos[0].id, &store).unwrap(), None);
+        assert!(video_info(&videos[1].id, &store).unwrap().is_some());
+    }
 }
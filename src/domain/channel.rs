@@ -1,5 +1,10 @@
+use chrono::{Days, NaiveDate};
 use serde::{Deserialize, Serialize};
 
+use super::filter::{DurationRange, TitleFilter};
+use super::playback::ChannelPlayer;
+use super::video::{is_youtube_id, sum_of_units, IdRules};
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ChannelId(String);
 
@@ -7,6 +12,9 @@ pub struct ChannelId(String);
 pub enum ChannelIdError {
     Empty,
     InvalidPrefix,
+    InvalidCharacter,
+    /// Not `UC` and 22 characters, under `IdRules::YouTube`.
+    NotYouTube(String),
 }
 
 impl std::fmt::Display for ChannelIdError {
@@ -16,6 +24,13 @@ impl std::fmt::Display for ChannelIdError {
             ChannelIdError::InvalidPrefix => {
                 write!(f, "channel ID must start with 'UC'")
             }
+            ChannelIdError::InvalidCharacter => {
+                write!(f, "channel ID cannot contain whitespace")
+            }
+            ChannelIdError::NotYouTube(id) => write!(
+                f,
+                "'{id}' is not a YouTube channel ID ('UC' and 22 letters, digits, '-' or '_')"
+            ),
         }
     }
 }
@@ -23,6 +38,8 @@ impl std::fmt::Display for ChannelIdError {
 impl std::error::Error for ChannelIdError {}
 
 impl ChannelId {
+    /// Any ID starting with `UC`, without whitespace; see `parse_with` to
+    /// also check that it looks like YouTube's.
     pub fn parse(id: impl Into<String>) -> Result<Self, ChannelIdError> {
         let id = id.into();
         if id.is_empty() {
@@ -31,8 +48,41 @@ impl ChannelId {
         if !id.starts_with("UC") {
             return Err(ChannelIdError::InvalidPrefix);
         }
+        if id.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            return Err(ChannelIdError::InvalidCharacter);
+        }
         Ok(Self(id))
     }
+
+    pub fn parse_with(id: impl Into<String>, rules: IdRules) -> Result<Self, ChannelIdError> {
+        let id = Self::parse(id)?;
+        match rules {
+            IdRules::YouTube if !is_youtube_id(&id.0, 24) => Err(ChannelIdError::NotYouTube(id.0)),
+            _ => Ok(id),
+        }
+    }
+
+    /// Like `parse_with`, but also takes the channel's link as the browser
+    /// gives it: `youtube.com/channel/UC...`, with or without scheme and
+    /// `www.` or `m.`, and with any tab or query after the ID.
+    pub fn parse_url_or_id(raw: &str, rules: IdRules) -> Result<Self, ChannelIdError> {
+        let url = raw.trim();
+        let rest = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))
+            .unwrap_or(url);
+        let rest = ["www.", "m."]
+            .iter()
+            .find_map(|sub| rest.strip_prefix(sub))
+            .unwrap_or(rest);
+        match rest.strip_prefix("youtube.com/channel/") {
+            Some(path) => Self::parse_with(
+                path.split(['/', '?', '&', '#']).next().unwrap_or_default(),
+                rules,
+            ),
+            None => Self::parse_with(url, rules),
+        }
+    }
 }
 
 impl std::fmt::Display for ChannelId {
@@ -41,10 +91,283 @@ impl std::fmt::Display for ChannelId {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChannelHandle(String);
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ChannelHandleError {
+    MissingAt,
+    Empty,
+    InvalidCharacter,
+}
+
+impl std::fmt::Display for ChannelHandleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChannelHandleError::MissingAt => write!(f, "handle must start with '@'"),
+            ChannelHandleError::Empty => write!(f, "handle cannot be empty"),
+            ChannelHandleError::InvalidCharacter => {
+                write!(f, "handle cannot contain whitespace or '/'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ChannelHandleError {}
+
+impl ChannelHandle {
+    pub fn parse(handle: impl Into<String>) -> Result<Self, ChannelHandleError> {
+        let handle = handle.into();
+        let name = handle
+            .strip_prefix('@')
+            .ok_or(ChannelHandleError::MissingAt)?;
+        if name.is_empty() {
+            return Err(ChannelHandleError::Empty);
+        }
+        if name.contains(|c: char| c.is_whitespace() || c == '/') {
+            return Err(ChannelHandleError::InvalidCharacter);
+        }
+        Ok(Self(handle))
+    }
+}
+
+impl std::fmt::Display for ChannelHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LegacyUsername(String);
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct LegacyUsernameError;
+
+impl std::fmt::Display for LegacyUsernameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "username cannot be empty or contain whitespace or '/'")
+    }
+}
+
+impl std::error::Error for LegacyUsernameError {}
+
+impl LegacyUsername {
+    pub fn parse(name: impl Into<String>) -> Result<Self, LegacyUsernameError> {
+        let name = name.into();
+        if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == '/') {
+            return Err(LegacyUsernameError);
+        }
+        Ok(Self(name))
+    }
+}
+
+impl std::fmt::Display for LegacyUsername {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A label grouping channels, e.g. `music`; matched ignoring case.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ChannelTag(String);
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ChannelTagError(String);
+
+impl std::fmt::Display for ChannelTagError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "tag '{}' must be non-empty, without whitespace, ',' or '#'",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ChannelTagError {}
+
+impl ChannelTag {
+    pub fn parse(tag: impl Into<String>) -> Result<Self, ChannelTagError> {
+        let tag = tag.into();
+        if tag.is_empty() || tag.contains(|c: char| c.is_whitespace() || c == ',' || c == '#') {
+            return Err(ChannelTagError(tag));
+        }
+        Ok(Self(tag))
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        self.0.eq_ignore_ascii_case(name)
+    }
+}
+
+impl std::fmt::Display for ChannelTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// How long `blepo snooze` hides a channel: a number of days or weeks such
+/// as `30d` or `1w3d`, or the date it comes back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnoozeLength {
+    Days(u64),
+    Until(NaiveDate),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SnoozeLengthError(String);
+
+impl std::fmt::Display for SnoozeLengthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "'{}' is not a snooze length (expected e.g. 30d, 2w or 2025-06-01)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for SnoozeLengthError {}
+
+impl SnoozeLength {
+    pub fn parse(raw: &str) -> Result<Self, SnoozeLengthError> {
+        if let Ok(date) = NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d") {
+            return Ok(Self::Until(date));
+        }
+        sum_of_units(raw, &[('w', 7), ('d', 1)])
+            .and_then(|days| u64::try_from(days).ok())
+            .filter(|&days| days > 0)
+            .map(Self::Days)
+            .ok_or_else(|| SnoozeLengthError(raw.to_string()))
+    }
+
+    /// The first day the channel is listed again; `None` when that is not
+    /// after `today` or past the calendar's end.
+    pub fn until(self, today: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Self::Days(days) => today.checked_add_days(Days::new(days)),
+            Self::Until(date) => Some(date),
+        }
+        .filter(|&date| date > today)
+    }
+}
+
+/// How a configured channel is identified before it is resolved to a `ChannelId`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ChannelRef {
+    Id(ChannelId),
+    Handle(ChannelHandle),
+    Username(LegacyUsername),
+}
+
+impl std::fmt::Display for ChannelRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChannelRef::Id(id) => write!(f, "{id}"),
+            ChannelRef::Handle(handle) => write!(f, "{handle}"),
+            ChannelRef::Username(name) => write!(f, "user/{name}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Channel {
     pub name: String,
     pub id: ChannelId,
+    pub include_streams: bool,
+    pub title_filter: TitleFilter,
+    pub duration_range: DurationRange,
+    pub tags: Vec<ChannelTag>,
+    /// Configured without a name: `name` is the title last learned from its
+    /// feed, and fetchers label videos with the feed's current title.
+    pub name_from_feed: bool,
+    /// Shown in the listing instead of `name`; both match `--channel`.
+    pub alias: Option<String>,
+    /// Left out of listings before this day.
+    pub snoozed_until: Option<NaiveDate>,
+    /// Plays the channel's videos instead of the usual player.
+    pub player: Option<ChannelPlayer>,
+    /// Downloads each new video when fetched, archiving the channel.
+    pub auto_download: bool,
+}
+
+impl Channel {
+    pub fn is_snoozed(&self, today: NaiveDate) -> bool {
+        self.snoozed_until.is_some_and(|until| today < until)
+    }
+
+    fn labels(&self) -> impl Iterator<Item = String> + '_ {
+        std::iter::once(&self.name)
+            .chain(&self.alias)
+            .map(|label| label.trim().to_lowercase())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ChannelMatchError {
+    NotFound(String),
+    /// The query partly matches these channels, by name.
+    Ambiguous(String, Vec<String>),
+}
+
+impl std::fmt::Display for ChannelMatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChannelMatchError::NotFound(query) => {
+                write!(f, "no channel named '{query}' in the config")
+            }
+            ChannelMatchError::Ambiguous(query, names) => write!(
+                f,
+                "'{query}' could be any of: {}; give more of the name",
+                names.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ChannelMatchError {}
+
+/// The channels `query` names, ignoring case: those it names in full (by
+/// name, alias or ID), else the one whose name or alias starts with it,
+/// else the one containing it. Channels may share a name, so a full match
+/// can return several; a partial one must be unique.
+pub fn match_channels<'a>(
+    channels: &'a [Channel],
+    query: &str,
+) -> Result<Vec<&'a Channel>, ChannelMatchError> {
+    let wanted = query.trim().to_lowercase();
+    let matching = |fits: &dyn Fn(&str) -> bool| -> Vec<&'a Channel> {
+        channels
+            .iter()
+            .filter(|c| c.labels().any(|label| fits(&label)))
+            .collect()
+    };
+    let full: Vec<&Channel> = channels
+        .iter()
+        .filter(|c| c.id.0 == query.trim() || c.labels().any(|label| label == wanted))
+        .collect();
+    if !full.is_empty() {
+        return Ok(full);
+    }
+    if wanted.is_empty() {
+        return Err(ChannelMatchError::NotFound(query.to_string()));
+    }
+    for partial in [
+        matching(&|label| label.starts_with(&wanted)),
+        matching(&|label| label.contains(&wanted)),
+    ] {
+        match partial.as_slice() {
+            [] => {}
+            [_] => return Ok(partial),
+            _ => {
+                return Err(ChannelMatchError::Ambiguous(
+                    query.to_string(),
+                    partial.iter().map(|c| c.name.clone()).collect(),
+                ))
+            }
+        }
+    }
+    Err(ChannelMatchError::NotFound(query.to_string()))
 }
 
 #[cfg(test)]
@@ -70,6 +393,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn youtube_rules_want_uc_and_22_id_characters() {
+        let youtube = |raw: &str| ChannelId::parse_with(raw, IdRules::YouTube);
+
+        assert!(youtube("UC_x5XG1OV2P6uZZ5FSM9Ttw").is_ok());
+        assert_eq!(
+            youtube("UC123"),
+            Err(ChannelIdError::NotYouTube("UC123".to_string()))
+        );
+        assert!(youtube("UC_x5XG1OV2P6uZZ5FSM9Tt.").is_err());
+        assert_eq!(youtube("notavalidid"), Err(ChannelIdError::InvalidPrefix));
+        assert!(ChannelId::parse_with("UC123", IdRules::Relaxed).is_ok());
+        assert_eq!(
+            ChannelId::parse_with("UC 123", IdRules::Relaxed),
+            Err(ChannelIdError::InvalidCharacter)
+        );
+    }
+
+    #[test]
+    fn reads_the_id_from_a_channel_link() {
+        let id = ChannelId::parse("UC_x5XG1OV2P6uZZ5FSM9Ttw").unwrap();
+
+        for raw in [
+            "https://www.youtube.com/channel/UC_x5XG1OV2P6uZZ5FSM9Ttw/videos",
+            "https://youtube.com/channel/UC_x5XG1OV2P6uZZ5FSM9Ttw",
+            "m.youtube.com/channel/UC_x5XG1OV2P6uZZ5FSM9Ttw?si=abc",
+            " UC_x5XG1OV2P6uZZ5FSM9Ttw",
+            "UC_x5XG1OV2P6uZZ5FSM9Ttw",
+        ] {
+            assert_eq!(
+                ChannelId::parse_url_or_id(raw, IdRules::YouTube),
+                Ok(id.clone()),
+                "{raw}"
+            );
+        }
+        assert_eq!(
+            ChannelId::parse_url_or_id(
+                "https://www.youtube.com/@GoogleDevelopers",
+                IdRules::YouTube
+            ),
+            Err(ChannelIdError::InvalidPrefix)
+        );
+        assert_eq!(
+            ChannelId::parse_url_or_id("https://www.youtube.com/channel/", IdRules::YouTube),
+            Err(ChannelIdError::Empty)
+        );
+    }
+
     #[test]
     fn channel_id_equality() {
         let a = ChannelId::parse("UC123").unwrap();
@@ -78,4 +449,134 @@ mod tests {
         assert_eq!(a, b);
         assert_ne!(a, c);
     }
+
+    #[test]
+    fn parses_valid_handle() {
+        let handle = ChannelHandle::parse("@SomeCreator").unwrap();
+        assert_eq!(handle.to_string(), "@SomeCreator");
+    }
+
+    #[test]
+    fn rejects_handle_without_at() {
+        assert_eq!(
+            ChannelHandle::parse("SomeCreator"),
+            Err(ChannelHandleError::MissingAt)
+        );
+    }
+
+    #[test]
+    fn rejects_bare_at_handle() {
+        assert_eq!(ChannelHandle::parse("@"), Err(ChannelHandleError::Empty));
+    }
+
+    #[test]
+    fn rejects_handle_with_whitespace_or_slash() {
+        assert_eq!(
+            ChannelHandle::parse("@some creator"),
+            Err(ChannelHandleError::InvalidCharacter)
+        );
+        assert_eq!(
+            ChannelHandle::parse("@some/creator"),
+            Err(ChannelHandleError::InvalidCharacter)
+        );
+    }
+
+    #[test]
+    fn parses_legacy_username() {
+        assert_eq!(
+            LegacyUsername::parse("GoogleDevelopers")
+                .unwrap()
+                .to_string(),
+            "GoogleDevelopers"
+        );
+        assert_eq!(LegacyUsername::parse(""), Err(LegacyUsernameError));
+        assert_eq!(LegacyUsername::parse("a b"), Err(LegacyUsernameError));
+    }
+
+    #[test]
+    fn parses_tags_and_matches_ignoring_case() {
+        let tag = ChannelTag::parse("Music").unwrap();
+
+        assert!(tag.matches("music"));
+        assert!(!tag.matches("rust"));
+        assert!(ChannelTag::parse("").is_err());
+        assert!(ChannelTag::parse("live music").is_err());
+        assert!(ChannelTag::parse("#rust").is_err());
+    }
+
+    #[test]
+    fn snoozes_for_a_length_or_until_a_date() {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 20).unwrap();
+        let until = |raw| SnoozeLength::parse(raw).unwrap().until(today);
+
+        assert_eq!(until("30d"), NaiveDate::from_ymd_opt(2024, 2, 19));
+        assert_eq!(until("1w3d"), NaiveDate::from_ymd_opt(2024, 1, 30));
+        assert_eq!(until("2024-03-01"), NaiveDate::from_ymd_opt(2024, 3, 1));
+        assert_eq!(until("2024-01-20"), None);
+        assert!(SnoozeLength::parse("0d").is_err());
+        assert!(SnoozeLength::parse("36h").is_err());
+        assert!(SnoozeLength::parse("soon").is_err());
+    }
+
+    fn channel(id: &str, name: &str, alias: Option<&str>) -> Channel {
+        Channel {
+            name: name.to_string(),
+            id: ChannelId::parse(id).unwrap(),
+            include_streams: false,
+            title_filter: Default::default(),
+            duration_range: Default::default(),
+            tags: Vec::new(),
+            name_from_feed: false,
+            alias: alias.map(str::to_string),
+            snoozed_until: None,
+            player: None,
+            auto_download: false,
+        }
+    }
+
+    #[test]
+    fn matches_channels_in_full_then_by_prefix_then_anywhere() {
+        let channels = [
+            channel("UC1", "Linus Tech Tips", Some("LTT")),
+            channel("UC2", "Techmoan", None),
+            channel("UC3", "Tech Ingredients", None),
+            channel("UC4", "Veritasium", None),
+        ];
+        let names = |query: &str| -> Vec<String> {
+            match_channels(&channels, query)
+                .unwrap()
+                .iter()
+                .map(|c| c.name.clone())
+                .collect()
+        };
+
+        assert_eq!(names("techmoan"), ["Techmoan"]);
+        assert_eq!(names(" ltt "), ["Linus Tech Tips"]);
+        assert_eq!(names("UC4"), ["Veritasium"]);
+        assert_eq!(names("veri"), ["Veritasium"]);
+        assert_eq!(names("ingredients"), ["Tech Ingredients"]);
+        assert_eq!(
+            match_channels(&channels, "tech").unwrap_err(),
+            ChannelMatchError::Ambiguous(
+                "tech".to_string(),
+                vec!["Techmoan".to_string(), "Tech Ingredients".to_string()]
+            )
+        );
+        assert_eq!(
+            match_channels(&channels, "nope").unwrap_err(),
+            ChannelMatchError::NotFound("nope".to_string())
+        );
+        assert!(match_channels(&channels, " ").is_err());
+    }
+
+    #[test]
+    fn channel_ref_display_distinguishes_variants() {
+        let id = ChannelRef::Id(ChannelId::parse("UC123").unwrap());
+        let handle = ChannelRef::Handle(ChannelHandle::parse("@x").unwrap());
+        let user = ChannelRef::Username(LegacyUsername::parse("x").unwrap());
+
+        assert_eq!(id.to_string(), "UC123");
+        assert_eq!(handle.to_string(), "@x");
+        assert_eq!(user.to_string(), "user/x");
+    }
 }
@@ -0,0 +1,264 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Utc};
+
+/// How the listing shows when a video was published.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DateStyle {
+    /// `2024-01-20`
+    #[default]
+    Absolute,
+    /// `2h ago`
+    Relative,
+    /// `2024-01-20, 2h ago`
+    Both,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DateStyleError(String);
+
+impl std::fmt::Display for DateStyleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown date_style '{}' (expected absolute, relative or both)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for DateStyleError {}
+
+impl DateStyle {
+    pub fn parse(raw: &str) -> Result<Self, DateStyleError> {
+        match raw {
+            "absolute" => Ok(DateStyle::Absolute),
+            "relative" => Ok(DateStyle::Relative),
+            "both" => Ok(DateStyle::Both),
+            other => Err(DateStyleError(other.to_string())),
+        }
+    }
+}
+
+/// How blepo says the listing is ready, for users who switch away during a
+/// slow fetch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReadyAlert {
+    #[default]
+    Off,
+    /// Ring the terminal bell.
+    Bell,
+    /// Show a desktop notification.
+    Desktop,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ReadyAlertError(String);
+
+impl std::fmt::Display for ReadyAlertError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown ready_alert '{}' (expected off, bell or desktop)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ReadyAlertError {}
+
+impl ReadyAlert {
+    pub fn parse(raw: &str) -> Result<Self, ReadyAlertError> {
+        match raw {
+            "off" => Ok(ReadyAlert::Off),
+            "bell" => Ok(ReadyAlert::Bell),
+            "desktop" => Ok(ReadyAlert::Desktop),
+            other => Err(ReadyAlertError(other.to_string())),
+        }
+    }
+}
+
+/// A strftime pattern for listing dates, checked up front so a typo is
+/// reported at startup rather than panicking mid-listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateFormat(String);
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DateFormatError(String);
+
+impl std::fmt::Display for DateFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid date_format '{}'", self.0)
+    }
+}
+
+impl std::error::Error for DateFormatError {}
+
+impl DateFormat {
+    pub fn parse(raw: &str) -> Result<Self, DateFormatError> {
+        if raw.is_empty() || StrftimeItems::new(raw).any(|item| matches!(item, Item::Error)) {
+            return Err(DateFormatError(raw.to_string()));
+        }
+        Ok(Self(raw.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for DateFormat {
+    fn default() -> Self {
+        Self("%Y-%m-%d".to_string())
+    }
+}
+
+/// A text colour or emphasis from the theme, as an SGR parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color(u8);
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ColorError(String);
+
+impl std::fmt::Display for ColorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown color '{}' (expected none, bold, dim, a color such as cyan, or bright_cyan)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ColorError {}
+
+const COLOR_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+impl Color {
+    /// `none` parses to `None`: that part of the line stays plain.
+    pub fn parse(raw: &str) -> Result<Option<Self>, ColorError> {
+        let code = match raw {
+            "none" => return Ok(None),
+            "bold" => 1,
+            "dim" => 2,
+            _ => {
+                let (base, name) = match raw.strip_prefix("bright_") {
+                    Some(name) => (90, name),
+                    None => (30, raw),
+                };
+                let index = COLOR_NAMES
+                    .iter()
+                    .position(|&n| n == name)
+                    .ok_or_else(|| ColorError(raw.to_string()))?;
+                base + index as u8
+            }
+        };
+        Ok(Some(Self(code)))
+    }
+
+    pub fn paint(self, text: &str) -> String {
+        format!("\x1b[{}m{text}\x1b[0m", self.0)
+    }
+}
+
+/// Colours for the parts of a listing line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub channel: Option<Color>,
+    pub date: Option<Color>,
+    pub new: Option<Color>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            channel: Some(Color(36)),
+            date: Some(Color(2)),
+            new: Some(Color(32)),
+        }
+    }
+}
+
+/// `just now`, `15m ago`, `2h ago`, `yesterday`, `5 days ago`; scheduled
+/// videos read `in 3h`, `tomorrow`, `in 4 days`.
+pub fn relative_age(published: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let elapsed = now - published;
+    let future = elapsed < chrono::Duration::zero();
+    let elapsed = elapsed.abs();
+    let (minutes, hours, days) = (
+        elapsed.num_minutes(),
+        elapsed.num_hours(),
+        elapsed.num_days(),
+    );
+    let span = match (minutes, hours, days) {
+        (0, _, _) => return "just now".to_string(),
+        (m, 0, _) => format!("{m}m"),
+        (_, h, 0) => format!("{h}h"),
+        (_, _, 1) => return if future { "tomorrow" } else { "yesterday" }.to_string(),
+        (_, _, d) => format!("{d} days"),
+    };
+    if future {
+        format!("in {span}")
+    } else {
+        format!("{span} ago")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn now() -> DateTime<Utc> {
+        "2024-01-20T15:00:00Z".parse().unwrap()
+    }
+
+    fn ago(duration: Duration) -> String {
+        relative_age(now() - duration, now())
+    }
+
+    #[test]
+    fn parses_date_style() {
+        assert_eq!(DateStyle::parse("relative"), Ok(DateStyle::Relative));
+        assert_eq!(DateStyle::parse("both"), Ok(DateStyle::Both));
+        assert!(DateStyle::parse("iso").is_err());
+    }
+
+    #[test]
+    fn parses_date_format() {
+        assert_eq!(DateFormat::parse("%a %d %b").unwrap().as_str(), "%a %d %b");
+        assert!(DateFormat::parse("%Q").is_err());
+        assert!(DateFormat::parse("").is_err());
+    }
+
+    #[test]
+    fn parses_colors() {
+        assert_eq!(Color::parse("cyan"), Ok(Some(Color(36))));
+        assert_eq!(Color::parse("bright_red"), Ok(Some(Color(91))));
+        assert_eq!(Color::parse("dim"), Ok(Some(Color(2))));
+        assert_eq!(Color::parse("none"), Ok(None));
+        assert!(Color::parse("teal").is_err());
+    }
+
+    #[test]
+    fn paints_with_reset() {
+        assert_eq!(Color(32).paint("new"), "\x1b[32mnew\x1b[0m");
+    }
+
+    #[test]
+    fn describes_past_ages() {
+        assert_eq!(ago(Duration::seconds(30)), "just now");
+        assert_eq!(ago(Duration::minutes(15)), "15m ago");
+        assert_eq!(ago(Duration::hours(2)), "2h ago");
+        assert_eq!(ago(Duration::hours(30)), "yesterday");
+        assert_eq!(ago(Duration::days(5)), "5 days ago");
+    }
+
+    #[test]
+    fn describes_scheduled_times() {
+        assert_eq!(ago(-Duration::hours(3)), "in 3h");
+        assert_eq!(ago(-Duration::hours(36)), "tomorrow");
+        assert_eq!(ago(-Duration::days(4)), "in 4 days");
+    }
+}
@@ -0,0 +1,239 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::channel::ChannelId;
+use super::video::{Video, VideoId};
+
+/// A video in the download queue and how far it got.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Download {
+    pub id: VideoId,
+    /// Known when queued from a listing or a fetch, not for a bare ID.
+    pub video: Option<Video>,
+    #[serde(flatten)]
+    pub state: DownloadState,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum DownloadState {
+    Queued,
+    /// Being downloaded by the blepo process `pid`; if that process is gone,
+    /// the download was interrupted and is resumed on the next run.
+    Downloading {
+        pid: u32,
+    },
+    Saved {
+        path: PathBuf,
+        at: DateTime<Utc>,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+impl Download {
+    /// "Title [Channel]", or the ID when the video isn't known.
+    pub fn label(&self) -> String {
+        match &self.video {
+            Some(video) => format!("{} [{}]", video.title, video.channel_name),
+            None => self.id.to_string(),
+        }
+    }
+
+    pub fn saved_path(&self) -> Option<&Path> {
+        match &self.state {
+            DownloadState::Saved { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+}
+
+/// Queues `id` unless it is queued, downloading or saved already; a failed
+/// download is queued again. Returns whether it was queued.
+pub fn enqueue(downloads: &mut Vec<Download>, id: VideoId, video: Option<Video>) -> bool {
+    match downloads.iter_mut().find(|d| d.id == id) {
+        Some(download) if matches!(download.state, DownloadState::Failed { .. }) => {
+            download.state = DownloadState::Queued;
+            download.video = video.or(download.video.take());
+            true
+        }
+        Some(_) => false,
+        None => {
+            downloads.push(Download {
+                id,
+                video,
+                state: DownloadState::Queued,
+            });
+            true
+        }
+    }
+}
+
+/// The downloads due to run, in queue order: queued ones and those whose
+/// process `is_running` says is gone.
+pub fn due(downloads: &[Download], is_running: impl Fn(u32) -> bool) -> Vec<&Download> {
+    downloads
+        .iter()
+        .filter(|d| match d.state {
+            DownloadState::Queued => true,
+            DownloadState::Downloading { pid } => !is_running(pid),
+            _ => false,
+        })
+        .collect()
+}
+
+/// Saved downloads of videos marked watched before `cutoff`, except those
+/// of `kept` channels. A video in `watched` but not `watched_at`, marked
+/// before blepo recorded when, counts as watched when it was saved.
+pub fn prunable<'a>(
+    downloads: &'a [Download],
+    watched: &HashSet<VideoId>,
+    watched_at: &HashMap<VideoId, DateTime<Utc>>,
+    kept: &HashSet<ChannelId>,
+    cutoff: DateTime<Utc>,
+) -> Vec<&'a Download> {
+    downloads
+        .iter()
+        .filter(|d| watched.contains(&d.id))
+        .filter(|d| {
+            d.video
+                .as_ref()
+                .is_none_or(|video| !kept.contains(&video.channel_id))
+        })
+        .filter(|d| match &d.state {
+            DownloadState::Saved { at, .. } => *watched_at.get(&d.id).unwrap_or(at) < cutoff,
+            _ => false,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::video::VideoKind;
+
+    fn id(raw: &str) -> VideoId {
+        VideoId::parse(raw).unwrap()
+    }
+
+    fn download(raw: &str, state: DownloadState) -> Download {
+        Download {
+            id: id(raw),
+            video: None,
+            state,
+        }
+    }
+
+    #[test]
+    fn enqueues_new_and_failed_downloads_only() {
+        let mut downloads = vec![
+            download(
+                "saved",
+                DownloadState::Saved {
+                    path: PathBuf::from("/videos/saved.webm"),
+                    at: Utc::now(),
+                },
+            ),
+            download(
+                "failed",
+                DownloadState::Failed {
+                    error: "yt-dlp failed".to_string(),
+                },
+            ),
+        ];
+
+        assert!(enqueue(&mut downloads, id("new"), None));
+        assert!(!enqueue(&mut downloads, id("new"), None));
+        assert!(!enqueue(&mut downloads, id("saved"), None));
+        assert!(enqueue(&mut downloads, id("failed"), None));
+
+        assert_eq!(downloads.len(), 3);
+        assert_eq!(downloads[1].state, DownloadState::Queued);
+        assert_eq!(downloads[2].state, DownloadState::Queued);
+    }
+
+    #[test]
+    fn interrupted_downloads_are_due_again() {
+        let downloads = vec![
+            download("queued", DownloadState::Queued),
+            download("running", DownloadState::Downloading { pid: 1 }),
+            download("interrupted", DownloadState::Downloading { pid: 2 }),
+            download(
+                "failed",
+                DownloadState::Failed {
+                    error: "yt-dlp failed".to_string(),
+                },
+            ),
+        ];
+
+        let due: Vec<VideoId> = due(&downloads, |pid| pid == 1)
+            .into_iter()
+            .map(|d| d.id.clone())
+            .collect();
+
+        assert_eq!(due, [id("queued"), id("interrupted")]);
+    }
+
+    #[test]
+    fn prunes_saved_videos_watched_before_the_cutoff() {
+        let now = Utc::now();
+        let days_ago = |days| now - chrono::Duration::days(days);
+        let saved = |raw: &str, days| {
+            download(
+                raw,
+                DownloadState::Saved {
+                    path: PathBuf::from(format!("/videos/{raw}.webm")),
+                    at: days_ago(days),
+                },
+            )
+        };
+        let kept_channel = ChannelId::parse("UCkept").unwrap();
+        let mut kept_video = saved("kept", 90);
+        kept_video.video = Some(Video {
+            id: id("kept"),
+            title: "Kept".to_string(),
+            url: "https://youtube.com/watch?v=kept".to_string(),
+            published: days_ago(100),
+            channel_name: "Kept".to_string(),
+            channel_id: kept_channel.clone(),
+            kind: VideoKind::Upload,
+            duration: None,
+            tags: Vec::new(),
+        });
+        let downloads = vec![
+            saved("old", 90),
+            saved("recent", 90),
+            saved("legacy", 90),
+            saved("unwatched", 90),
+            saved("fresh_legacy", 1),
+            kept_video,
+            download("queued", DownloadState::Queued),
+        ];
+        let watched: HashSet<VideoId> =
+            ["old", "recent", "legacy", "fresh_legacy", "kept", "queued"]
+                .into_iter()
+                .map(id)
+                .collect();
+        let watched_at = HashMap::from([
+            (id("old"), days_ago(40)),
+            (id("recent"), days_ago(5)),
+            (id("kept"), days_ago(40)),
+        ]);
+
+        let pruned: Vec<VideoId> = prunable(
+            &downloads,
+            &watched,
+            &watched_at,
+            &HashSet::from([kept_channel]),
+            days_ago(30),
+        )
+        .into_iter()
+        .map(|d| d.id.clone())
+        .collect();
+
+        assert_eq!(pruned, [id("old"), id("legacy")]);
+    }
+}
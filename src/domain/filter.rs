@@ -0,0 +1,330 @@
+use regex::Regex;
+use std::time::Duration;
+
+use super::video::Video;
+
+/// A list of title patterns. Plain words are valid patterns; prefix with
+/// `(?i)` for a case-insensitive match.
+#[derive(Debug, Clone, Default)]
+pub struct TitlePatterns(Vec<Regex>);
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct TitlePatternError {
+    pattern: String,
+    reason: String,
+}
+
+impl std::fmt::Display for TitlePatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid title pattern '{}': {}",
+            self.pattern, self.reason
+        )
+    }
+}
+
+impl std::error::Error for TitlePatternError {}
+
+impl TitlePatterns {
+    pub fn parse(patterns: &[String]) -> Result<Self, TitlePatternError> {
+        patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| TitlePatternError {
+                    pattern: pattern.clone(),
+                    reason: e.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn any_match(&self, title: &str) -> bool {
+        self.0.iter().any(|re| re.is_match(title))
+    }
+}
+
+/// Keeps a title when it matches no `exclude` pattern and, if any `include`
+/// patterns are given, at least one of them.
+#[derive(Debug, Clone, Default)]
+pub struct TitleFilter {
+    pub include: TitlePatterns,
+    pub exclude: TitlePatterns,
+}
+
+impl TitleFilter {
+    pub fn allows(&self, title: &str) -> bool {
+        (self.include.is_empty() || self.include.any_match(title)) && !self.exclude.any_match(title)
+    }
+}
+
+/// Case-insensitive substrings; a title containing any of them is muted.
+#[derive(Debug, Clone, Default)]
+pub struct MutedWords(Vec<String>);
+
+impl MutedWords {
+    pub fn new(words: &[String]) -> Self {
+        Self(
+            words
+                .iter()
+                .map(|w| w.trim().to_lowercase())
+                .filter(|w| !w.is_empty())
+                .collect(),
+        )
+    }
+
+    pub fn mutes(&self, title: &str) -> bool {
+        let title = title.to_lowercase();
+        self.0.iter().any(|word| title.contains(word.as_str()))
+    }
+}
+
+/// Bounds on video length. Videos of unknown length always pass.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DurationRange {
+    min: Option<Duration>,
+    max: Option<Duration>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DurationRangeError {
+    Invalid(String),
+    MinAboveMax,
+}
+
+impl std::fmt::Display for DurationRangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DurationRangeError::Invalid(raw) => {
+                write!(f, "invalid duration '{raw}' (expected e.g. 90s, 2m, 1h30m)")
+            }
+            DurationRangeError::MinAboveMax => {
+                write!(f, "min_duration must not exceed max_duration")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DurationRangeError {}
+
+impl DurationRange {
+    pub fn parse(min: Option<&str>, max: Option<&str>) -> Result<Self, DurationRangeError> {
+        let min = min.map(parse_duration).transpose()?;
+        let max = max.map(parse_duration).transpose()?;
+        if let (Some(min), Some(max)) = (min, max) {
+            if min > max {
+                return Err(DurationRangeError::MinAboveMax);
+            }
+        }
+        Ok(Self { min, max })
+    }
+
+    pub fn allows(&self, duration: Option<Duration>) -> bool {
+        let Some(duration) = duration else {
+            return true;
+        };
+        self.min.is_none_or(|min| duration >= min) && self.max.is_none_or(|max| duration <= max)
+    }
+}
+
+/// Parses `90s`, `2m`, `1h30m` and the like.
+fn parse_duration(raw: &str) -> Result<Duration, DurationRangeError> {
+    let invalid = || DurationRangeError::Invalid(raw.to_string());
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for c in raw.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        let value: u64 = digits.parse().map_err(|_| invalid())?;
+        total = value
+            .checked_mul(unit)
+            .and_then(|secs| total.checked_add(secs))
+            .ok_or_else(invalid)?;
+        digits.clear();
+    }
+    if !digits.is_empty() || raw.trim().is_empty() {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(total))
+}
+
+#[must_use]
+pub fn filter_by_duration<'a>(videos: &'a [Video], range: &DurationRange) -> Vec<&'a Video> {
+    videos.iter().filter(|v| range.allows(v.duration)).collect()
+}
+
+#[must_use]
+pub fn filter_by_title<'a>(videos: &'a [Video], filter: &TitleFilter) -> Vec<&'a Video> {
+    videos.iter().filter(|v| filter.allows(&v.title)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::channel::ChannelId;
+    use crate::domain::video::{VideoId, VideoKind};
+    use chrono::Utc;
+
+    fn patterns(raw: &[&str]) -> TitlePatterns {
+        let raw: Vec<String> = raw.iter().map(|p| p.to_string()).collect();
+        TitlePatterns::parse(&raw).unwrap()
+    }
+
+    fn make_video(id: &str, title: &str) -> Video {
+        Video {
+            id: VideoId::parse(id).unwrap(),
+            title: title.to_string(),
+            url: format!("https://youtube.com/watch?v={id}"),
+            published: Utc::now(),
+            channel_name: "Test Channel".to_string(),
+            channel_id: ChannelId::parse("UC123").unwrap(),
+            kind: VideoKind::Upload,
+            duration: None,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn empty_filter_allows_everything() {
+        assert!(TitleFilter::default().allows("anything"));
+    }
+
+    #[test]
+    fn exclude_hides_matching_titles() {
+        let filter = TitleFilter {
+            exclude: patterns(&["#shorts", "(?i)trailer"]),
+            ..TitleFilter::default()
+        };
+
+        assert!(!filter.allows("Funny cat #shorts"));
+        assert!(!filter.allows("Official TRAILER"));
+        assert!(filter.allows("Full episode"));
+    }
+
+    #[test]
+    fn include_keeps_only_matching_titles() {
+        let filter = TitleFilter {
+            include: patterns(&["^Episode \\d+"]),
+            ..TitleFilter::default()
+        };
+
+        assert!(filter.allows("Episode 12: the return"));
+        assert!(!filter.allows("Behind the scenes"));
+    }
+
+    #[test]
+    fn exclude_wins_over_include() {
+        let filter = TitleFilter {
+            include: patterns(&["Episode"]),
+            exclude: patterns(&["recap"]),
+        };
+
+        assert!(!filter.allows("Episode 3 recap"));
+    }
+
+    #[test]
+    fn rejects_invalid_pattern() {
+        let err = TitlePatterns::parse(&["(unclosed".to_string()]).unwrap_err();
+
+        assert!(err.to_string().contains("'(unclosed'"));
+    }
+
+    #[test]
+    fn mutes_words_case_insensitively() {
+        let muted = MutedWords::new(&["Reaction".to_string(), "  ".to_string()]);
+
+        assert!(muted.mutes("My REACTION to the keynote"));
+        assert!(!muted.mutes("Keynote recap"));
+    }
+
+    #[test]
+    fn no_muted_words_mutes_nothing() {
+        assert!(!MutedWords::default().mutes("anything"));
+    }
+
+    fn mins(n: u64) -> Option<Duration> {
+        Some(Duration::from_secs(n * 60))
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+    }
+
+    #[test]
+    fn rejects_malformed_durations() {
+        for raw in ["", "10", "1x", "m", "1h 30m"] {
+            assert!(parse_duration(raw).is_err(), "{raw:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn duration_range_bounds_known_lengths() {
+        let range = DurationRange::parse(Some("2m"), Some("3h")).unwrap();
+
+        assert!(!range.allows(mins(1)));
+        assert!(range.allows(mins(2)));
+        assert!(range.allows(mins(180)));
+        assert!(!range.allows(mins(480)));
+    }
+
+    #[test]
+    fn duration_range_keeps_unknown_lengths() {
+        let range = DurationRange::parse(Some("2m"), None).unwrap();
+
+        assert!(range.allows(None));
+    }
+
+    #[test]
+    fn rejects_min_above_max() {
+        assert_eq!(
+            DurationRange::parse(Some("1h"), Some("10m")),
+            Err(DurationRangeError::MinAboveMax)
+        );
+    }
+
+    #[test]
+    fn filters_videos_by_duration() {
+        let mut long = make_video("v1", "Eight hour VOD");
+        long.duration = mins(480);
+        let videos = vec![long, make_video("v2", "Unknown length")];
+        let range = DurationRange::parse(None, Some("3h")).unwrap();
+
+        let kept = filter_by_duration(&videos, &range);
+
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].id.to_string(), "v2");
+    }
+
+    #[test]
+    fn filters_videos_by_title() {
+        let videos = vec![
+            make_video("v1", "Keep me"),
+            make_video("v2", "Skip trailer"),
+        ];
+        let filter = TitleFilter {
+            exclude: patterns(&["trailer"]),
+            ..TitleFilter::default()
+        };
+
+        let kept = filter_by_title(&videos, &filter);
+
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].id.to_string(), "v1");
+    }
+}
@@ -0,0 +1,17 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::video::Video;
+
+/// A player launched in the background, known by its process ID.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaybackJob {
+    pub pid: u32,
+    /// What told the process apart at launch (see
+    /// `ProcessControl::identity`); the same `pid` with another identity is
+    /// a different process that reused the ID.
+    #[serde(default)]
+    pub identity: Option<String>,
+    pub video: Video,
+    pub started: DateTime<Utc>,
+}
@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::video::{VideoId, WatchProgress};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum JournalAction {
+    /// `progress` holds the resume positions the mark cleared.
+    MarkedWatched {
+        video_ids: Vec<VideoId>,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        progress: HashMap<VideoId, WatchProgress>,
+    },
+}
+
+impl JournalAction {
+    pub fn revert(
+        &self,
+        watched: &mut HashSet<VideoId>,
+        saved_progress: &mut HashMap<VideoId, WatchProgress>,
+    ) {
+        match self {
+            JournalAction::MarkedWatched {
+                video_ids,
+                progress,
+            } => {
+                for id in video_ids {
+                    watched.remove(id);
+                }
+                saved_progress.extend(progress.iter().map(|(id, p)| (id.clone(), *p)));
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for JournalAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JournalAction::MarkedWatched { video_ids, .. } if video_ids.len() == 1 => {
+                write!(f, "marked {} as watched", video_ids[0])
+            }
+            JournalAction::MarkedWatched { video_ids, .. } => {
+                write!(f, "marked {} videos as watched", video_ids.len())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub at: DateTime<Utc>,
+    pub action: JournalAction,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(raw: &[&str]) -> Vec<VideoId> {
+        raw.iter().map(|id| VideoId::parse(*id).unwrap()).collect()
+    }
+
+    fn marked(raw: &[&str]) -> JournalAction {
+        JournalAction::MarkedWatched {
+            video_ids: ids(raw),
+            progress: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn reverting_marked_watched_removes_only_its_videos() {
+        let mut watched: HashSet<VideoId> = ids(&["v1", "v2", "v3"]).into_iter().collect();
+        let action = marked(&["v1", "v3"]);
+
+        action.revert(&mut watched, &mut HashMap::new());
+
+        assert_eq!(watched, ids(&["v2"]).into_iter().collect());
+    }
+
+    #[test]
+    fn reverting_marked_watched_restores_cleared_progress() {
+        let started = WatchProgress::from_percent(40.0).unwrap();
+        let action = JournalAction::MarkedWatched {
+            video_ids: ids(&["v1", "v2"]),
+            progress: HashMap::from([(ids(&["v1"]).remove(0), started)]),
+        };
+        let mut progress = HashMap::new();
+
+        action.revert(&mut HashSet::new(), &mut progress);
+
+        assert_eq!(progress, HashMap::from([(ids(&["v1"]).remove(0), started)]));
+    }
+
+    #[test]
+    fn reads_entries_written_without_progress() {
+        let action: JournalAction =
+            serde_json::from_str(r#"{"action":"marked_watched","video_ids":["v1"]}"#).unwrap();
+
+        assert_eq!(action, marked(&["v1"]));
+    }
+
+    #[test]
+    fn describes_single_and_batch_marks() {
+        let single = marked(&["v1"]);
+        let batch = marked(&["v1", "v2"]);
+
+        assert_eq!(single.to_string(), "marked v1 as watched");
+        assert_eq!(batch.to_string(), "marked 2 videos as watched");
+    }
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// Details fetched on demand by the opt-in enrichment pass; not part of the
+/// regular listing.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VideoMetadata {
+    pub title: String,
+    pub channel_name: Option<String>,
+    pub view_count: Option<u64>,
+    pub like_count: Option<u64>,
+    pub description: Option<String>,
+}
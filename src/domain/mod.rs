@@ -1,2 +1,11 @@
 pub mod channel;
+pub mod display;
+pub mod download;
+pub mod filter;
+pub mod job;
+pub mod journal;
+pub mod metadata;
+pub mod playback;
+pub mod queue;
+pub mod sponsor;
 pub mod video;
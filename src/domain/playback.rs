@@ -0,0 +1,182 @@
+/// Speed every video starts at, as a multiple of normal speed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlaybackSpeed(f64);
+
+#[derive(Debug, PartialEq)]
+pub struct PlaybackSpeedError(f64);
+
+impl std::fmt::Display for PlaybackSpeedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is not between 0.25 and 4", self.0)
+    }
+}
+
+impl std::error::Error for PlaybackSpeedError {}
+
+impl PlaybackSpeed {
+    pub fn parse(speed: f64) -> Result<Self, PlaybackSpeedError> {
+        if (0.25..=4.0).contains(&speed) {
+            Ok(Self(speed))
+        } else {
+            Err(PlaybackSpeedError(speed))
+        }
+    }
+
+    pub fn as_f64(self) -> f64 {
+        self.0
+    }
+}
+
+/// Subtitle languages to show, most preferred first, and whether YouTube's
+/// automatic captions may stand in for missing ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subtitles {
+    languages: Vec<String>,
+    auto_generated: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SubtitlesError(String);
+
+impl std::fmt::Display for SubtitlesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for SubtitlesError {}
+
+impl Subtitles {
+    /// A comma-separated list of language codes such as `"en,de"`; the word
+    /// `auto` anywhere in it allows automatic captions.
+    pub fn parse(raw: &str) -> Result<Self, SubtitlesError> {
+        let mut languages = Vec::new();
+        let mut auto_generated = false;
+        for part in raw.split(',').map(str::trim) {
+            match part {
+                "auto" => auto_generated = true,
+                code if !code.is_empty()
+                    && code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') =>
+                {
+                    languages.push(code.to_string());
+                }
+                other => {
+                    return Err(SubtitlesError(format!(
+                        "'{other}' is not a language code (e.g. en, pt-BR)"
+                    )))
+                }
+            }
+        }
+        if languages.is_empty() {
+            return Err(SubtitlesError(
+                "name at least one language, e.g. \"en,auto\"".to_string(),
+            ));
+        }
+        Ok(Self {
+            languages,
+            auto_generated,
+        })
+    }
+
+    pub fn languages(&self) -> &[String] {
+        &self.languages
+    }
+
+    pub fn auto_generated(&self) -> bool {
+        self.auto_generated
+    }
+}
+
+/// How one channel's videos are played instead of the usual way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelPlayer {
+    /// mpv with these options after `mpv_args`, e.g. `--no-video` for
+    /// audio only.
+    Mpv(Vec<String>),
+    /// The browser, e.g. for videos mpv can't play because of DRM.
+    Browser,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ChannelPlayerError(String);
+
+impl std::fmt::Display for ChannelPlayerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ChannelPlayerError {}
+
+impl ChannelPlayer {
+    /// From a channel's `player` (`mpv` or `browser`) and `player_args`;
+    /// `None` when it sets neither.
+    pub fn parse(
+        player: Option<&str>,
+        args: &[String],
+    ) -> Result<Option<Self>, ChannelPlayerError> {
+        if let Some(arg) = args.iter().find(|arg| !arg.starts_with('-')) {
+            return Err(ChannelPlayerError(format!(
+                "'{arg}' is not an option (options start with --)"
+            )));
+        }
+        match player {
+            None if args.is_empty() => Ok(None),
+            None | Some("mpv") => Ok(Some(Self::Mpv(args.to_vec()))),
+            Some("browser") if args.is_empty() => Ok(Some(Self::Browser)),
+            Some("browser") => Err(ChannelPlayerError(
+                "player_args only apply to mpv, not the browser".to_string(),
+            )),
+            Some(other) => Err(ChannelPlayerError(format!(
+                "unknown player '{other}' (expected mpv or browser)"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speed_stays_in_a_sane_range() {
+        assert_eq!(PlaybackSpeed::parse(1.5).unwrap().as_f64(), 1.5);
+        assert!(PlaybackSpeed::parse(0.1).is_err());
+        assert!(PlaybackSpeed::parse(8.0).is_err());
+    }
+
+    #[test]
+    fn parses_subtitle_languages_and_auto() {
+        let subtitles = Subtitles::parse("en, pt-BR,auto").unwrap();
+
+        assert_eq!(subtitles.languages(), ["en", "pt-BR"]);
+        assert!(subtitles.auto_generated());
+        assert!(!Subtitles::parse("en").unwrap().auto_generated());
+    }
+
+    #[test]
+    fn parses_channel_players() {
+        let args = ["--no-video".to_string()];
+
+        assert_eq!(ChannelPlayer::parse(None, &[]), Ok(None));
+        assert_eq!(
+            ChannelPlayer::parse(None, &args),
+            Ok(Some(ChannelPlayer::Mpv(args.to_vec())))
+        );
+        assert_eq!(
+            ChannelPlayer::parse(Some("browser"), &[]),
+            Ok(Some(ChannelPlayer::Browser))
+        );
+        assert!(ChannelPlayer::parse(Some("browser"), &args).is_err());
+        assert!(ChannelPlayer::parse(Some("vlc"), &[]).is_err());
+        assert!(ChannelPlayer::parse(None, &["fs".to_string()]).is_err());
+    }
+
+    #[test]
+    fn rejects_subtitles_without_a_language() {
+        assert!(Subtitles::parse("auto").is_err());
+        assert!(Subtitles::parse("").is_err());
+        assert!(Subtitles::parse("en,,de").is_err());
+        assert!(Subtitles::parse("en;de").is_err());
+    }
+}
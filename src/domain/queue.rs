@@ -0,0 +1,80 @@
+use super::video::{Video, VideoNumber};
+
+/// Appends `video` unless it is already queued; returns whether it was added.
+pub fn enqueue(queue: &mut Vec<Video>, video: Video) -> bool {
+    if queue.iter().any(|queued| queued.id == video.id) {
+        return false;
+    }
+    queue.push(video);
+    true
+}
+
+/// Takes the video at `from` out and reinserts it at `to`, shifting the
+/// ones in between. `None` if either position is past the end.
+pub fn move_entry(queue: &mut Vec<Video>, from: VideoNumber, to: VideoNumber) -> Option<()> {
+    let (from, to) = (from.to_index(), to.to_index());
+    if from >= queue.len() || to >= queue.len() {
+        return None;
+    }
+    let video = queue.remove(from);
+    queue.insert(to, video);
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::channel::ChannelId;
+    use crate::domain::video::{VideoId, VideoKind};
+    use chrono::Utc;
+
+    fn make_video(id: &str) -> Video {
+        Video {
+            id: VideoId::parse(id).unwrap(),
+            title: format!("Title {id}"),
+            url: format!("https://youtube.com/watch?v={id}"),
+            published: Utc::now(),
+            channel_name: "Test Channel".to_string(),
+            channel_id: ChannelId::parse("UC123").unwrap(),
+            kind: VideoKind::Upload,
+            duration: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn ids(queue: &[Video]) -> Vec<String> {
+        queue.iter().map(|v| v.id.to_string()).collect()
+    }
+
+    fn number(n: usize) -> VideoNumber {
+        VideoNumber::parse(n).unwrap()
+    }
+
+    #[test]
+    fn enqueue_skips_videos_already_queued() {
+        let mut queue = vec![make_video("a")];
+
+        assert!(enqueue(&mut queue, make_video("b")));
+        assert!(!enqueue(&mut queue, make_video("a")));
+        assert_eq!(ids(&queue), ["a", "b"]);
+    }
+
+    #[test]
+    fn moves_entry_shifting_the_rest() {
+        let mut queue = vec![make_video("a"), make_video("b"), make_video("c")];
+
+        move_entry(&mut queue, number(3), number(1)).unwrap();
+        assert_eq!(ids(&queue), ["c", "a", "b"]);
+
+        move_entry(&mut queue, number(1), number(3)).unwrap();
+        assert_eq!(ids(&queue), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn move_past_the_end_leaves_queue_alone() {
+        let mut queue = vec![make_video("a"), make_video("b")];
+
+        assert_eq!(move_entry(&mut queue, number(1), number(3)), None);
+        assert_eq!(ids(&queue), ["a", "b"]);
+    }
+}
@@ -0,0 +1,7 @@
+/// A stretch of a video, in seconds, that playback jumps over. Segments come
+/// from SponsorBlock, crowd-sourced and looked up at play time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkipSegment {
+    pub start: f64,
+    pub end: f64,
+}
@@ -1,31 +1,109 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 
-use super::channel::ChannelId;
+use super::channel::{ChannelId, ChannelTag};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct VideoId(String);
 
 #[derive(Debug, PartialEq, Eq)]
-pub struct VideoIdError;
+pub enum VideoIdError {
+    Empty,
+    InvalidCharacter,
+    /// Not shaped like a YouTube video ID, under `IdRules::YouTube`.
+    NotYouTube(String),
+}
 
 impl std::fmt::Display for VideoIdError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "video ID cannot be empty")
+        match self {
+            VideoIdError::Empty => write!(f, "video ID cannot be empty"),
+            VideoIdError::InvalidCharacter => {
+                write!(f, "video ID cannot contain whitespace")
+            }
+            VideoIdError::NotYouTube(id) => write!(
+                f,
+                "'{id}' is not a YouTube video ID (11 letters, digits, '-' or '_')"
+            ),
+        }
     }
 }
 
 impl std::error::Error for VideoIdError {}
 
+/// How strictly IDs read from feeds, yt-dlp and the config are checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IdRules {
+    /// YouTube's own shapes: video IDs of 11 characters, channel IDs of `UC`
+    /// and 22 more, all letters, digits, `-` or `_`.
+    #[default]
+    YouTube,
+    /// Any non-empty ID without whitespace (channel IDs still start with
+    /// `UC`), for the placeholder IDs of the test fixtures.
+    #[cfg(test)]
+    Relaxed,
+}
+
+/// Whether `id` is `len` characters of YouTube's ID alphabet.
+pub(super) fn is_youtube_id(id: &str, len: usize) -> bool {
+    id.len() == len
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
 impl VideoId {
+    /// Any non-empty ID without whitespace; see `parse_with` to also check
+    /// that it looks like YouTube's.
     pub fn parse(id: impl Into<String>) -> Result<Self, VideoIdError> {
         let id = id.into();
         if id.is_empty() {
-            return Err(VideoIdError);
+            return Err(VideoIdError::Empty);
+        }
+        if id.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            return Err(VideoIdError::InvalidCharacter);
         }
         Ok(Self(id))
     }
+
+    pub fn parse_with(id: impl Into<String>, rules: IdRules) -> Result<Self, VideoIdError> {
+        let id = Self::parse(id)?;
+        match rules {
+            IdRules::YouTube if !is_youtube_id(&id.0, 11) => Err(VideoIdError::NotYouTube(id.0)),
+            _ => Ok(id),
+        }
+    }
+
+    /// The video a YouTube link points at: `youtube.com/watch?v=`,
+    /// `youtu.be/`, `/shorts/`, `/live/` or `/embed/` links, with or without
+    /// scheme and `www.`, `m.` or `music.`.
+    pub fn from_url(url: &str) -> Option<Self> {
+        let url = url.trim();
+        let rest = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))
+            .unwrap_or(url);
+        let rest = ["www.", "m.", "music."]
+            .iter()
+            .find_map(|sub| rest.strip_prefix(sub))
+            .unwrap_or(rest);
+        let id = if let Some(path) = rest.strip_prefix("youtu.be/") {
+            path
+        } else if let Some(query) = rest.strip_prefix("youtube.com/watch?") {
+            query.split('&').find_map(|pair| pair.strip_prefix("v="))?
+        } else {
+            ["shorts/", "live/", "embed/"]
+                .iter()
+                .find_map(|kind| rest.strip_prefix("youtube.com/")?.strip_prefix(kind))?
+        };
+        let id = id.split(['?', '&', '#', '/']).next()?;
+        id.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            .then(|| Self::parse(id).ok())
+            .flatten()
+    }
 }
 
 impl std::fmt::Display for VideoId {
@@ -34,6 +112,58 @@ impl std::fmt::Display for VideoId {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VideoKind {
+    Upload,
+    LivestreamVod,
+    Short,
+    /// A scheduled premiere or livestream that hasn't started yet.
+    Upcoming,
+    Live,
+}
+
+impl VideoKind {
+    pub fn is_live_or_upcoming(self) -> bool {
+        matches!(self, VideoKind::Upcoming | VideoKind::Live)
+    }
+}
+
+/// What to do with upcoming premieres and streams that are live right now.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LiveHandling {
+    Hide,
+    /// List them after the regular videos, under their own heading.
+    #[default]
+    Section,
+    Include,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct LiveHandlingError(String);
+
+impl std::fmt::Display for LiveHandlingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown live_handling '{}' (expected hide, section or include)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for LiveHandlingError {}
+
+impl LiveHandling {
+    pub fn parse(raw: &str) -> Result<Self, LiveHandlingError> {
+        match raw {
+            "hide" => Ok(LiveHandling::Hide),
+            "section" => Ok(LiveHandling::Section),
+            "include" => Ok(LiveHandling::Include),
+            other => Err(LiveHandlingError(other.to_string())),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Video {
     pub id: VideoId,
@@ -42,33 +172,88 @@ pub struct Video {
     pub published: DateTime<Utc>,
     pub channel_name: String,
     pub channel_id: ChannelId,
+    pub kind: VideoKind,
+    /// Known only for yt-dlp listings; RSS feeds don't carry it.
+    #[serde(default)]
+    pub duration: Option<Duration>,
+    /// The tags of the channel it came from.
+    #[serde(default)]
+    pub tags: Vec<ChannelTag>,
 }
 
+/// How far back videos are fetched.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct FetchWindowDays(i64);
+pub struct FetchWindow(chrono::Duration);
 
 #[derive(Debug, PartialEq, Eq)]
-pub struct FetchWindowDaysError;
+pub enum FetchWindowError {
+    NotPositive,
+    TooLong,
+    Invalid(String),
+}
 
-impl std::fmt::Display for FetchWindowDaysError {
+impl std::fmt::Display for FetchWindowError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "fetch_window_days must be positive")
+        match self {
+            FetchWindowError::NotPositive => write!(f, "the fetch window must be positive"),
+            FetchWindowError::TooLong => write!(f, "the fetch window is too long"),
+            FetchWindowError::Invalid(raw) => {
+                write!(f, "'{raw}' is not a duration (expected e.g. 36h, 2w, 90d)")
+            }
+        }
     }
 }
 
-impl std::error::Error for FetchWindowDaysError {}
+impl std::error::Error for FetchWindowError {}
 
-impl FetchWindowDays {
-    pub fn parse(days: i64) -> Result<Self, FetchWindowDaysError> {
-        if days <= 0 {
-            return Err(FetchWindowDaysError);
+impl FetchWindow {
+    pub fn from_days(days: i64) -> Result<Self, FetchWindowError> {
+        Self::from_hours(days.checked_mul(24).ok_or(FetchWindowError::TooLong)?)
+    }
+
+    /// Parses `36h`, `90d`, `2w` and combinations such as `1w3d`.
+    pub fn parse(raw: &str) -> Result<Self, FetchWindowError> {
+        let hours = sum_of_units(raw, &[('w', 7 * 24), ('d', 24), ('h', 1)])
+            .ok_or_else(|| FetchWindowError::Invalid(raw.to_string()))?;
+        Self::from_hours(hours)
+    }
+
+    fn from_hours(hours: i64) -> Result<Self, FetchWindowError> {
+        if hours <= 0 {
+            return Err(FetchWindowError::NotPositive);
         }
-        Ok(Self(days))
+        chrono::Duration::try_hours(hours)
+            .map(Self)
+            .ok_or(FetchWindowError::TooLong)
     }
 
-    pub fn as_i64(self) -> i64 {
+    pub fn as_duration(self) -> chrono::Duration {
         self.0
     }
+
+    /// Whole days the window reaches into, counting a partial day as one.
+    pub fn days_covered(self) -> i64 {
+        (self.0.num_hours() + 23) / 24
+    }
+}
+
+/// Adds up a length such as `1w3d` in the smallest of `units`, each given
+/// with its size in that unit. `None` if malformed or out of range.
+pub(crate) fn sum_of_units(raw: &str, units: &[(char, i64)]) -> Option<i64> {
+    let raw = raw.trim();
+    let mut total = 0i64;
+    let mut digits = String::new();
+    for c in raw.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let &(_, size) = units.iter().find(|&&(unit, _)| unit == c)?;
+        let value: i64 = digits.parse().ok()?;
+        total = total.checked_add(value.checked_mul(size)?)?;
+        digits.clear();
+    }
+    (digits.is_empty() && !raw.is_empty()).then_some(total)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -98,6 +283,108 @@ impl VideoNumber {
     }
 }
 
+impl std::fmt::Display for VideoNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Whether blepo hands the video to mpv and exits, or waits for mpv and
+/// returns to the prompt.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlaybackMode {
+    #[default]
+    Detached,
+    /// Wait for mpv; the video is marked watched only if mpv exits cleanly.
+    Attached,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PlaybackModeError(String);
+
+impl std::fmt::Display for PlaybackModeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown playback_mode '{}' (expected detached or attached)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for PlaybackModeError {}
+
+impl PlaybackMode {
+    pub fn parse(raw: &str) -> Result<Self, PlaybackModeError> {
+        match raw {
+            "detached" => Ok(PlaybackMode::Detached),
+            "attached" => Ok(PlaybackMode::Attached),
+            other => Err(PlaybackModeError(other.to_string())),
+        }
+    }
+}
+
+/// Share of a video, in percent, that must be played before it counts as
+/// watched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchThreshold(u8);
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct WatchThresholdError(u64);
+
+impl std::fmt::Display for WatchThresholdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}% is not between 1 and 100", self.0)
+    }
+}
+
+impl std::error::Error for WatchThresholdError {}
+
+impl WatchThreshold {
+    pub fn parse(percent: u64) -> Result<Self, WatchThresholdError> {
+        match u8::try_from(percent) {
+            Ok(p @ 1..=100) => Ok(Self(p)),
+            _ => Err(WatchThresholdError(percent)),
+        }
+    }
+
+    pub fn percent(self) -> u8 {
+        self.0
+    }
+
+    pub fn is_met_by(self, played_percent: f64) -> bool {
+        played_percent >= f64::from(self.0)
+    }
+}
+
+/// How far, in whole percent, playback got into a video that was started but
+/// not watched to the end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct WatchProgress(u8);
+
+impl WatchProgress {
+    /// `None` when rounding leaves nothing worth showing: under 1%, or 100%.
+    pub fn from_percent(played_percent: f64) -> Option<Self> {
+        let rounded = played_percent.round();
+        (1.0..100.0)
+            .contains(&rounded)
+            .then_some(Self(rounded as u8))
+    }
+
+    pub fn percent(self) -> u8 {
+        self.0
+    }
+}
+
+/// Where `blepo digest` left off: when it last ran, and when it first saw
+/// each video it has listed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DigestCursor {
+    pub last_run: DateTime<Utc>,
+    pub first_seen: HashMap<VideoId, DateTime<Utc>>,
+}
+
 #[must_use]
 pub fn filter_unwatched<'a>(videos: &'a [Video], watched: &HashSet<VideoId>) -> Vec<&'a Video> {
     videos.iter().filter(|v| !watched.contains(&v.id)).collect()
@@ -115,8 +402,113 @@ pub fn filter_by_date_range(
         .collect()
 }
 
+/// Drops repeated video IDs (e.g. a video on both a main and a clips channel),
+/// keeping the earliest published copy at the position of the first one seen.
+#[must_use]
+pub fn dedupe_by_id(videos: Vec<Video>) -> Vec<Video> {
+    let mut seen: HashMap<VideoId, usize> = HashMap::new();
+    let mut unique: Vec<Video> = Vec::with_capacity(videos.len());
+    for video in videos {
+        match seen.get(&video.id) {
+            Some(&i) if video.published < unique[i].published => unique[i] = video,
+            Some(_) => {}
+            None => {
+                seen.insert(video.id.clone(), unique.len());
+                unique.push(video);
+            }
+        }
+    }
+    unique
+}
+
+/// Feeds list scheduled premieres with their future start time; anything
+/// published after `now` hasn't aired yet.
+pub fn mark_scheduled_as_upcoming(videos: &mut [Video], now: DateTime<Utc>) {
+    for video in videos.iter_mut().filter(|v| v.published > now) {
+        if !video.kind.is_live_or_upcoming() {
+            video.kind = VideoKind::Upcoming;
+        }
+    }
+}
+
+/// Moves upcoming and live videos after the rest, soonest first, keeping the
+/// order of everything else.
+pub fn move_live_to_end(videos: &mut Vec<Video>) {
+    let (mut live, regular): (Vec<Video>, Vec<Video>) = std::mem::take(videos)
+        .into_iter()
+        .partition(|v| v.kind.is_live_or_upcoming());
+    live.sort_by_key(|v| v.published);
+    *videos = regular;
+    videos.extend(live);
+}
+
+/// Moves videos with progress ahead of the rest, keeping the order of both.
+/// Upcoming and live videos stay where they are.
+pub fn move_in_progress_to_front(
+    videos: &mut Vec<Video>,
+    progress: &HashMap<VideoId, WatchProgress>,
+) {
+    let (started, rest): (Vec<Video>, Vec<Video>) = std::mem::take(videos)
+        .into_iter()
+        .partition(|v| !v.kind.is_live_or_upcoming() && progress.contains_key(&v.id));
+    *videos = started;
+    videos.extend(rest);
+}
+
+/// Display order that clusters each channel's videos, channels ordered by
+/// their newest video. Returns indices into `videos`; videos of one channel
+/// keep their relative order.
+pub fn channel_grouped_order(videos: &[Video]) -> Vec<usize> {
+    let mut newest: HashMap<&ChannelId, DateTime<Utc>> = HashMap::new();
+    let mut first_seen: HashMap<&ChannelId, usize> = HashMap::new();
+    for (i, video) in videos.iter().enumerate() {
+        let latest = newest.entry(&video.channel_id).or_insert(video.published);
+        *latest = (*latest).max(video.published);
+        first_seen.entry(&video.channel_id).or_insert(i);
+    }
+    let mut order: Vec<usize> = (0..videos.len()).collect();
+    order.sort_by_key(|&i| {
+        let channel = &videos[i].channel_id;
+        (std::cmp::Reverse(newest[channel]), first_seen[channel])
+    });
+    order
+}
+
 pub fn sort_newest_first(videos: &mut [Video]) {
-    videos.sort_by(|a, b| b.published.cmp(&a.published));
+    videos.sort_by_key(|v| std::cmp::Reverse(v.published));
+}
+
+/// One of the playable videos, chosen by `seed`; upcoming ones are skipped
+/// since they can't be played yet.
+pub fn random_pick(videos: &[Video], seed: u64) -> Option<&Video> {
+    let playable: Vec<&Video> = videos
+        .iter()
+        .filter(|v| v.kind != VideoKind::Upcoming)
+        .collect();
+    if playable.is_empty() {
+        return None;
+    }
+    let index = (seed % playable.len() as u64) as usize;
+    Some(playable[index])
+}
+
+/// The most recently published video that can be played now.
+pub fn newest_playable(videos: &[Video]) -> Option<&Video> {
+    videos
+        .iter()
+        .filter(|v| v.kind != VideoKind::Upcoming)
+        .max_by_key(|v| v.published)
+}
+
+/// Videos to catch up on in one sitting: oldest first, leaving out upcoming
+/// and live ones, which can't be played through.
+pub fn catch_up_order(videos: &[Video]) -> Vec<&Video> {
+    let mut order: Vec<&Video> = videos
+        .iter()
+        .filter(|v| !v.kind.is_live_or_upcoming())
+        .collect();
+    order.sort_by_key(|v| v.published);
+    order
 }
 
 #[cfg(test)]
@@ -132,6 +524,9 @@ mod tests {
             published: Utc::now() - chrono::Duration::days(days_ago),
             channel_name: "Test Channel".to_string(),
             channel_id: ChannelId::parse("UC123").unwrap(),
+            kind: VideoKind::Upload,
+            duration: None,
+            tags: Vec::new(),
         }
     }
 
@@ -141,9 +536,48 @@ mod tests {
         assert_eq!(id.to_string(), "dQw4w9WgXcQ");
     }
 
+    #[test]
+    fn reads_video_ids_from_links() {
+        let id = |url| VideoId::from_url(url).map(|id| id.to_string());
+
+        for url in [
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+            "youtube.com/watch?feature=share&v=dQw4w9WgXcQ&t=42",
+            "https://youtu.be/dQw4w9WgXcQ?si=abc",
+            "https://m.youtube.com/shorts/dQw4w9WgXcQ",
+            "https://music.youtube.com/watch?v=dQw4w9WgXcQ#x",
+            "https://www.youtube.com/live/dQw4w9WgXcQ",
+        ] {
+            assert_eq!(id(url).as_deref(), Some("dQw4w9WgXcQ"), "{url}");
+        }
+        assert_eq!(id("https://www.youtube.com/@channel"), None);
+        assert_eq!(id("https://youtu.be/"), None);
+        assert_eq!(id("https://example.com/watch?v=dQw4w9WgXcQ"), None);
+    }
+
     #[test]
     fn rejects_empty_video_id() {
-        assert_eq!(VideoId::parse(""), Err(VideoIdError));
+        assert_eq!(VideoId::parse(""), Err(VideoIdError::Empty));
+        assert_eq!(VideoId::parse("a b"), Err(VideoIdError::InvalidCharacter));
+    }
+
+    #[test]
+    fn youtube_rules_want_eleven_id_characters() {
+        let youtube = |raw: &str| VideoId::parse_with(raw, IdRules::YouTube);
+
+        assert!(youtube("dQw4w9WgXcQ").is_ok());
+        assert!(youtube("a-b_c123XYZ").is_ok());
+        assert_eq!(
+            youtube("dQw4w9WgXc"),
+            Err(VideoIdError::NotYouTube("dQw4w9WgXc".to_string()))
+        );
+        assert!(youtube("dQw4w9WgXc!").is_err());
+        assert!(youtube("dQw4w9WgXcQQ").is_err());
+        assert!(VideoId::parse_with("item-42", IdRules::Relaxed).is_ok());
+        assert_eq!(
+            VideoId::parse_with("", IdRules::Relaxed),
+            Err(VideoIdError::Empty)
+        );
     }
 
     #[test]
@@ -199,6 +633,197 @@ mod tests {
         assert_eq!(videos[2].id.to_string(), "v1");
     }
 
+    #[test]
+    fn parses_live_handling() {
+        assert_eq!(LiveHandling::parse("hide"), Ok(LiveHandling::Hide));
+        assert_eq!(LiveHandling::parse("section"), Ok(LiveHandling::Section));
+        assert_eq!(LiveHandling::parse("include"), Ok(LiveHandling::Include));
+        assert!(LiveHandling::parse("show").is_err());
+    }
+
+    #[test]
+    fn marks_future_videos_as_upcoming() {
+        let mut videos = vec![
+            make_video("v1", "Aired", 1),
+            make_video("v2", "Premiere", -1),
+        ];
+
+        mark_scheduled_as_upcoming(&mut videos, Utc::now());
+
+        assert_eq!(videos[0].kind, VideoKind::Upload);
+        assert_eq!(videos[1].kind, VideoKind::Upcoming);
+    }
+
+    #[test]
+    fn moves_live_videos_after_regular_ones_soonest_first() {
+        let mut videos = vec![
+            Video {
+                kind: VideoKind::Upcoming,
+                ..make_video("later", "Later", -3)
+            },
+            make_video("v1", "Newest", 1),
+            Video {
+                kind: VideoKind::Live,
+                ..make_video("live", "Live now", 0)
+            },
+            make_video("v2", "Older", 2),
+        ];
+
+        move_live_to_end(&mut videos);
+
+        let ids: Vec<String> = videos.iter().map(|v| v.id.to_string()).collect();
+        assert_eq!(ids, ["v1", "v2", "live", "later"]);
+    }
+
+    #[test]
+    fn random_pick_skips_upcoming_videos() {
+        let mut upcoming = make_video("soon", "Soon", 0);
+        upcoming.kind = VideoKind::Upcoming;
+        let videos = vec![upcoming, make_video("a", "A", 1), make_video("b", "B", 2)];
+
+        let picks: HashSet<&str> = (0..10)
+            .filter_map(|seed| random_pick(&videos, seed))
+            .map(|v| v.title.as_str())
+            .collect();
+
+        assert_eq!(picks, HashSet::from(["A", "B"]));
+        assert!(random_pick(&videos[..1], 7).is_none());
+    }
+
+    #[test]
+    fn newest_playable_skips_upcoming_videos() {
+        let mut upcoming = make_video("soon", "Soon", 0);
+        upcoming.kind = VideoKind::Upcoming;
+        let videos = vec![
+            make_video("old", "Old", 3),
+            upcoming,
+            make_video("new", "New", 1),
+        ];
+
+        assert_eq!(
+            newest_playable(&videos).map(|v| v.title.as_str()),
+            Some("New")
+        );
+        assert!(newest_playable(&[]).is_none());
+    }
+
+    #[test]
+    fn catches_up_oldest_first_without_live_videos() {
+        let mut live = make_video("live", "Live", 0);
+        live.kind = VideoKind::Live;
+        let videos = vec![
+            make_video("new", "New", 1),
+            live,
+            make_video("old", "Old", 5),
+        ];
+
+        let titles: Vec<&str> = catch_up_order(&videos)
+            .iter()
+            .map(|v| v.title.as_str())
+            .collect();
+
+        assert_eq!(titles, ["Old", "New"]);
+    }
+
+    #[test]
+    fn parses_playback_mode() {
+        assert_eq!(PlaybackMode::parse("attached"), Ok(PlaybackMode::Attached));
+        assert_eq!(PlaybackMode::parse("detached"), Ok(PlaybackMode::Detached));
+        assert!(PlaybackMode::parse("foreground").is_err());
+    }
+
+    #[test]
+    fn watch_threshold_is_a_percentage() {
+        assert!(WatchThreshold::parse(0).is_err());
+        assert!(WatchThreshold::parse(101).is_err());
+        let threshold = WatchThreshold::parse(80).unwrap();
+
+        assert!(!threshold.is_met_by(79.9));
+        assert!(threshold.is_met_by(80.0));
+    }
+
+    #[test]
+    fn watch_progress_keeps_partial_playback_only() {
+        assert_eq!(WatchProgress::from_percent(0.4), None);
+        assert_eq!(
+            WatchProgress::from_percent(44.6).map(WatchProgress::percent),
+            Some(45)
+        );
+        assert_eq!(WatchProgress::from_percent(99.7), None);
+    }
+
+    #[test]
+    fn moves_in_progress_videos_to_front() {
+        let mut live = make_video("live", "Live", 0);
+        live.kind = VideoKind::Live;
+        let mut videos = vec![
+            make_video("v1", "V1", 1),
+            make_video("v2", "V2", 2),
+            make_video("v3", "V3", 3),
+            live,
+        ];
+        let progress: HashMap<VideoId, WatchProgress> = ["v3", "live"]
+            .into_iter()
+            .map(|id| (VideoId::parse(id).unwrap(), WatchProgress(30)))
+            .collect();
+
+        move_in_progress_to_front(&mut videos, &progress);
+
+        let ids: Vec<String> = videos.iter().map(|v| v.id.to_string()).collect();
+        assert_eq!(ids, ["v3", "v1", "v2", "live"]);
+    }
+
+    #[test]
+    fn groups_channels_by_newest_video() {
+        let from = |channel: &str, id: &str, days_ago| Video {
+            channel_id: ChannelId::parse(channel).unwrap(),
+            ..make_video(id, id, days_ago)
+        };
+        let videos = vec![
+            from("UCa", "a1", 1),
+            from("UCb", "b1", 2),
+            from("UCa", "a2", 3),
+            from("UCc", "c1", 0),
+            from("UCb", "b2", 4),
+        ];
+
+        let order: Vec<&str> = channel_grouped_order(&videos)
+            .into_iter()
+            .map(|i| videos[i].title.as_str())
+            .collect();
+
+        assert_eq!(order, ["c1", "a1", "a2", "b1", "b2"]);
+    }
+
+    #[test]
+    fn dedupe_keeps_earliest_published_copy() {
+        let videos = vec![
+            Video {
+                channel_name: "Clips".to_string(),
+                ..make_video("v1", "Reupload", 1)
+            },
+            make_video("v2", "Other", 2),
+            Video {
+                channel_name: "Main".to_string(),
+                ..make_video("v1", "Original", 3)
+            },
+        ];
+
+        let unique = dedupe_by_id(videos);
+
+        assert_eq!(unique.len(), 2);
+        assert_eq!(unique[0].id.to_string(), "v1");
+        assert_eq!(unique[0].channel_name, "Main");
+        assert_eq!(unique[1].id.to_string(), "v2");
+    }
+
+    #[test]
+    fn dedupe_leaves_unique_videos_untouched() {
+        let videos = vec![make_video("v1", "A", 1), make_video("v2", "B", 2)];
+
+        assert_eq!(dedupe_by_id(videos).len(), 2);
+    }
+
     #[test]
     fn filter_by_date_range_with_exact_boundary() {
         let boundary = Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap();
@@ -211,6 +836,9 @@ mod tests {
                 published: Utc.with_ymd_and_hms(2024, 1, 14, 23, 59, 59).unwrap(),
                 channel_name: "Test".to_string(),
                 channel_id: ChannelId::parse("UC1").unwrap(),
+                kind: VideoKind::Upload,
+                duration: None,
+                tags: Vec::new(),
             },
             Video {
                 id: VideoId::parse("v2").unwrap(),
@@ -219,6 +847,9 @@ mod tests {
                 published: boundary,
                 channel_name: "Test".to_string(),
                 channel_id: ChannelId::parse("UC1").unwrap(),
+                kind: VideoKind::Upload,
+                duration: None,
+                tags: Vec::new(),
             },
             Video {
                 id: VideoId::parse("v3").unwrap(),
@@ -227,6 +858,9 @@ mod tests {
                 published: Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 1).unwrap(),
                 channel_name: "Test".to_string(),
                 channel_id: ChannelId::parse("UC1").unwrap(),
+                kind: VideoKind::Upload,
+                duration: None,
+                tags: Vec::new(),
             },
         ];
 
@@ -256,18 +890,46 @@ mod tests {
 
     #[test]
     fn parses_valid_fetch_window_days() {
-        let days = FetchWindowDays::parse(7).unwrap();
-        assert_eq!(days.as_i64(), 7);
+        let window = FetchWindow::from_days(7).unwrap();
+        assert_eq!(window.as_duration(), chrono::Duration::days(7));
+        assert_eq!(window.days_covered(), 7);
     }
 
     #[test]
     fn rejects_zero_fetch_window_days() {
-        assert_eq!(FetchWindowDays::parse(0), Err(FetchWindowDaysError));
+        assert_eq!(
+            FetchWindow::from_days(0),
+            Err(FetchWindowError::NotPositive)
+        );
     }
 
     #[test]
     fn rejects_negative_fetch_window_days() {
-        assert_eq!(FetchWindowDays::parse(-1), Err(FetchWindowDaysError));
+        assert_eq!(
+            FetchWindow::from_days(-1),
+            Err(FetchWindowError::NotPositive)
+        );
+    }
+
+    #[test]
+    fn parses_fetch_window_durations() {
+        let hours = |raw| FetchWindow::parse(raw).unwrap().as_duration().num_hours();
+
+        assert_eq!(hours("36h"), 36);
+        assert_eq!(hours("90d"), 90 * 24);
+        assert_eq!(hours("2w"), 14 * 24);
+        assert_eq!(hours("1w3d"), 10 * 24);
+        assert_eq!(FetchWindow::parse("36h").unwrap().days_covered(), 2);
+    }
+
+    #[test]
+    fn rejects_malformed_fetch_windows() {
+        for raw in ["", "7", "3x", "d", "0h", "99999999999999999w"] {
+            assert!(
+                FetchWindow::parse(raw).is_err(),
+                "{raw:?} should be rejected"
+            );
+        }
     }
 
     #[test]
@@ -0,0 +1,55 @@
+use std::process::{Command, Stdio};
+
+use crate::application::ports::{PlayError, UrlOpener};
+
+/// Opens URLs with the platform's default handler: `open` on macOS,
+/// `start` on Windows and `xdg-open` elsewhere.
+pub struct SystemBrowser;
+
+impl UrlOpener for SystemBrowser {
+    fn open_url(&self, url: &str) -> Result<(), PlayError> {
+        let (program, args) = opener_command(url);
+        Command::new(program)
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| PlayError::PlayerFailed(format!("failed to launch {program}: {e}")))?;
+        Ok(())
+    }
+}
+
+fn opener_command(url: &str) -> (&'static str, Vec<String>) {
+    let (program, mut args) = opener();
+    args.push(url.to_string());
+    (program, args)
+}
+
+/// The platform's opener and the arguments it takes before the URL.
+pub(crate) fn opener() -> (&'static str, Vec<String>) {
+    if cfg!(target_os = "macos") {
+        ("open", Vec::new())
+    } else if cfg!(windows) {
+        // The empty argument is the window title `start` would otherwise take
+        // from the quoted URL.
+        (
+            "cmd",
+            vec!["/C".to_string(), "start".to_string(), String::new()],
+        )
+    } else {
+        ("xdg-open", Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes_url_as_last_argument() {
+        let (_, args) = opener_command("https://youtu.be/x");
+
+        assert_eq!(args.last().map(String::as_str), Some("https://youtu.be/x"));
+    }
+}
@@ -0,0 +1,99 @@
+use std::process::{Command, Stdio};
+
+use super::dependency::CATT;
+use crate::application::ports::{PlayError, VideoPlayer};
+
+/// Casts to a Chromecast or DLNA renderer through catt, which resolves
+/// YouTube URLs with yt-dlp on its own.
+pub struct CattPlayer {
+    device: String,
+}
+
+impl CattPlayer {
+    pub fn new(device: String) -> Result<Self, PlayError> {
+        CATT.require()
+            .map_err(|e| PlayError::PlayerFailed(e.to_string()))?;
+        Ok(Self { device })
+    }
+
+    /// With `block`, catt stays until the device has finished playing.
+    fn args(&self, url: &str, block: bool) -> Vec<String> {
+        let mut args = vec!["-d".to_string(), self.device.clone(), "cast".to_string()];
+        if block {
+            args.push("--block".to_string());
+        }
+        args.push(url.to_string());
+        args
+    }
+
+    fn run(&self, args: Vec<String>) -> Result<(), PlayError> {
+        let output = Command::new("catt")
+            .args(args)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| PlayError::PlayerFailed(format!("failed to launch catt: {e}")))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(PlayError::PlayerFailed(format!(
+                "cannot cast to {}: {}",
+                self.device,
+                stderr.trim()
+            )))
+        }
+    }
+}
+
+impl VideoPlayer for CattPlayer {
+    /// Returns once the device has started playing; nothing is left
+    /// running locally.
+    fn play(&self, url: &str) -> Result<Option<u32>, PlayError> {
+        self.run(self.args(url, false))?;
+        Ok(None)
+    }
+
+    fn play_attached(&self, url: &str) -> Result<(), PlayError> {
+        self.run(self.args(url, true))
+    }
+
+    fn play_tracked(&self, _url: &str) -> Result<f64, PlayError> {
+        Err(PlayError::PlayerFailed(
+            "catt cannot report how far a video was played".to_string(),
+        ))
+    }
+
+    /// Casts each video in turn, waiting for one to end before the next.
+    fn play_playlist(
+        &self,
+        urls: &[&str],
+        finished: &mut dyn FnMut(usize),
+    ) -> Result<(), PlayError> {
+        for (i, url) in urls.iter().enumerate() {
+            self.run(self.args(url, true))?;
+            finished(i);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn casts_to_named_device() {
+        let player = CattPlayer {
+            device: "Living Room".to_string(),
+        };
+
+        assert_eq!(
+            player.args("https://youtu.be/x", false),
+            ["-d", "Living Room", "cast", "https://youtu.be/x"]
+        );
+        assert_eq!(
+            player.args("https://youtu.be/x", true),
+            ["-d", "Living Room", "cast", "--block", "https://youtu.be/x"]
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::json_file::{read_json, write_json};
+use crate::application::ports::{ChannelNameStore, StoreError};
+use crate::domain::channel::ChannelId;
+
+/// Titles of channels configured without a name, as their feeds last gave
+/// them, in `<data_dir>/channel_names.json`.
+pub struct JsonChannelNameStore {
+    path: PathBuf,
+}
+
+impl JsonChannelNameStore {
+    pub fn new(data_dir: &Path) -> Result<Self, StoreError> {
+        fs::create_dir_all(data_dir)
+            .map_err(|e| StoreError::Write(format!("cannot create data dir: {e}")))?;
+        Ok(Self {
+            path: data_dir.join("channel_names.json"),
+        })
+    }
+}
+
+impl ChannelNameStore for JsonChannelNameStore {
+    fn load_channel_names(&self) -> Result<HashMap<ChannelId, String>, StoreError> {
+        read_json(&self.path, "channel names").map(Option::unwrap_or_default)
+    }
+
+    fn save_channel_names(&self, names: &HashMap<ChannelId, String>) -> Result<(), StoreError> {
+        write_json(&self.path, names, "channel names")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn remembers_learned_names() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonChannelNameStore::new(dir.path()).unwrap();
+        assert!(store.load_channel_names().unwrap().is_empty());
+
+        let id = ChannelId::parse("UCtest").unwrap();
+        store
+            .save_channel_names(&HashMap::from([(id.clone(), "Chan".to_string())]))
+            .unwrap();
+
+        assert_eq!(store.load_channel_names().unwrap()[&id], "Chan");
+    }
+}
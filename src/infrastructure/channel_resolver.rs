@@ -0,0 +1,244 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use reqwest::StatusCode;
+use tracing::warn;
+
+use super::http::send_past_consent;
+use super::json_file::{read_json, write_json};
+use super::rate_limit::RateLimiter;
+use crate::application::ports::{ChannelResolver, FetchError, StoreError};
+use crate::domain::channel::{ChannelId, ChannelRef};
+use crate::domain::video::IdRules;
+
+const YOUTUBE_URL: &str = "https://www.youtube.com";
+const CANONICAL_MARKER: &str = r#"<link rel="canonical" href="https://www.youtube.com/channel/"#;
+const EXTERNAL_ID_MARKER: &str = r#""externalId":""#;
+
+pub struct HttpChannelResolver {
+    client: reqwest::blocking::Client,
+    limiter: Arc<RateLimiter>,
+}
+
+impl HttpChannelResolver {
+    pub fn new(client: reqwest::blocking::Client, limiter: Arc<RateLimiter>) -> Self {
+        Self { client, limiter }
+    }
+}
+
+impl ChannelResolver for HttpChannelResolver {
+    fn resolve(&self, reference: &ChannelRef) -> Result<ChannelId, FetchError> {
+        let url = match reference {
+            ChannelRef::Id(id) => return Ok(id.clone()),
+            ChannelRef::Handle(handle) => format!("{YOUTUBE_URL}/{handle}"),
+            ChannelRef::Username(name) => format!("{YOUTUBE_URL}/user/{name}"),
+        };
+
+        self.limiter.wait();
+        let response = send_past_consent(self.client.get(&url))?;
+
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err(self.limiter.rate_limited(response.headers()));
+        }
+        if !response.status().is_success() {
+            return Err(FetchError::HttpError(response.status().as_u16()));
+        }
+
+        let body = response
+            .text()
+            .map_err(|e| FetchError::Network(e.to_string()))?;
+
+        extract_channel_id(&body)
+            .ok_or_else(|| FetchError::Parse(format!("no channel ID found on {url}")))
+    }
+}
+
+pub fn extract_channel_id(html: &str) -> Option<ChannelId> {
+    [CANONICAL_MARKER, EXTERNAL_ID_MARKER]
+        .iter()
+        .find_map(|marker| {
+            let start = html.find(marker)? + marker.len();
+            let rest = &html[start..];
+            let end = rest.find('"')?;
+            ChannelId::parse_with(&rest[..end], IdRules::YouTube).ok()
+        })
+}
+
+/// Never answers, so a `CachedChannelResolver` around it only knows the
+/// handles earlier runs resolved.
+pub struct NoChannelLookup;
+
+impl ChannelResolver for NoChannelLookup {
+    fn resolve(&self, reference: &ChannelRef) -> Result<ChannelId, FetchError> {
+        Err(FetchError::Network(format!(
+            "not resolving {reference} offline"
+        )))
+    }
+}
+
+/// Remembers resolved IDs in the data dir so each handle hits the network only once.
+pub struct CachedChannelResolver<R> {
+    inner: R,
+    cache_path: PathBuf,
+    cache: RefCell<HashMap<String, ChannelId>>,
+}
+
+impl<R: ChannelResolver> CachedChannelResolver<R> {
+    pub fn new(inner: R, data_dir: &Path) -> Result<Self, StoreError> {
+        let cache_path = data_dir.join("channel_ids.json");
+        let cache = read_json(&cache_path, "channel ID cache")?.unwrap_or_default();
+        Ok(Self {
+            inner,
+            cache_path,
+            cache: RefCell::new(cache),
+        })
+    }
+}
+
+impl<R: ChannelResolver> ChannelResolver for CachedChannelResolver<R> {
+    fn resolve(&self, reference: &ChannelRef) -> Result<ChannelId, FetchError> {
+        if let ChannelRef::Id(id) = reference {
+            return Ok(id.clone());
+        }
+
+        let key = reference.to_string();
+        if let Some(id) = self.cache.borrow().get(&key) {
+            return Ok(id.clone());
+        }
+
+        let id = self.inner.resolve(reference)?;
+        let mut cache = self.cache.borrow_mut();
+        cache.insert(key, id.clone());
+        if let Err(e) = write_json(&self.cache_path, &*cache, "channel ID cache") {
+            warn!("{e}");
+        }
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::channel::ChannelHandle;
+    use std::cell::Cell;
+    use tempfile::TempDir;
+
+    struct CountingResolver {
+        calls: Cell<usize>,
+    }
+
+    impl ChannelResolver for CountingResolver {
+        fn resolve(&self, _reference: &ChannelRef) -> Result<ChannelId, FetchError> {
+            self.calls.set(self.calls.get() + 1);
+            Ok(ChannelId::parse("UCresolved").unwrap())
+        }
+    }
+
+    fn handle(raw: &str) -> ChannelRef {
+        ChannelRef::Handle(ChannelHandle::parse(raw).unwrap())
+    }
+
+    #[test]
+    fn extracts_id_from_canonical_link() {
+        let html = r#"<html><head><link rel="canonical" href="https://www.youtube.com/channel/UC_x5XG1OV2P6uZZ5FSM9Ttw"></head></html>"#;
+
+        assert_eq!(
+            extract_channel_id(html).unwrap().to_string(),
+            "UC_x5XG1OV2P6uZZ5FSM9Ttw"
+        );
+    }
+
+    #[test]
+    fn extracts_id_from_external_id_json() {
+        let html = r#"<script>var ytInitialData = {"metadata":{"externalId":"UCsBjURrPoezykLs9EqgamOA"}};</script>"#;
+
+        assert_eq!(
+            extract_channel_id(html).unwrap().to_string(),
+            "UCsBjURrPoezykLs9EqgamOA"
+        );
+    }
+
+    #[test]
+    fn returns_none_when_page_has_no_channel_id() {
+        assert!(extract_channel_id("<html>consent required</html>").is_none());
+    }
+
+    #[test]
+    fn cache_resolves_each_handle_once() {
+        let dir = TempDir::new().unwrap();
+        let inner = CountingResolver {
+            calls: Cell::new(0),
+        };
+        let resolver = CachedChannelResolver::new(inner, dir.path()).unwrap();
+
+        resolver.resolve(&handle("@a")).unwrap();
+        resolver.resolve(&handle("@a")).unwrap();
+
+        assert_eq!(resolver.inner.calls.get(), 1);
+    }
+
+    #[test]
+    fn cache_persists_across_instances() {
+        let dir = TempDir::new().unwrap();
+        let first = CachedChannelResolver::new(
+            CountingResolver {
+                calls: Cell::new(0),
+            },
+            dir.path(),
+        )
+        .unwrap();
+        first.resolve(&handle("@a")).unwrap();
+
+        let second = CachedChannelResolver::new(
+            CountingResolver {
+                calls: Cell::new(0),
+            },
+            dir.path(),
+        )
+        .unwrap();
+        let id = second.resolve(&handle("@a")).unwrap();
+
+        assert_eq!(id.to_string(), "UCresolved");
+        assert_eq!(second.inner.calls.get(), 0);
+    }
+
+    #[test]
+    fn offline_cache_only_knows_resolved_handles() {
+        let dir = TempDir::new().unwrap();
+        CachedChannelResolver::new(
+            CountingResolver {
+                calls: Cell::new(0),
+            },
+            dir.path(),
+        )
+        .unwrap()
+        .resolve(&handle("@a"))
+        .unwrap();
+
+        let offline = CachedChannelResolver::new(NoChannelLookup, dir.path()).unwrap();
+
+        assert_eq!(
+            offline.resolve(&handle("@a")).unwrap().to_string(),
+            "UCresolved"
+        );
+        assert!(offline.resolve(&handle("@b")).is_err());
+    }
+
+    #[test]
+    fn channel_ids_bypass_cache_and_network() {
+        let dir = TempDir::new().unwrap();
+        let resolver = CachedChannelResolver::new(
+            CountingResolver {
+                calls: Cell::new(0),
+            },
+            dir.path(),
+        )
+        .unwrap();
+        let id = ChannelId::parse("UC123").unwrap();
+
+        assert_eq!(resolver.resolve(&ChannelRef::Id(id.clone())).unwrap(), id);
+        assert_eq!(resolver.inner.calls.get(), 0);
+    }
+}
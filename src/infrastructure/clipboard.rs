@@ -0,0 +1,32 @@
+/// The system clipboard, opened on first use. On X11 and Wayland the copied
+/// text is served by this process, so it is kept open until blepo exits;
+/// clipboard managers take the text over from there.
+#[derive(Default)]
+pub struct SystemClipboard {
+    inner: Option<arboard::Clipboard>,
+}
+
+#[derive(Debug)]
+pub struct ClipboardError(String);
+
+impl std::fmt::Display for ClipboardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "clipboard unavailable: {}", self.0)
+    }
+}
+
+impl std::error::Error for ClipboardError {}
+
+impl SystemClipboard {
+    pub fn copy(&mut self, text: &str) -> Result<(), ClipboardError> {
+        let clipboard = match &mut self.inner {
+            Some(clipboard) => clipboard,
+            None => self
+                .inner
+                .insert(arboard::Clipboard::new().map_err(|e| ClipboardError(e.to_string()))?),
+        };
+        clipboard
+            .set_text(text)
+            .map_err(|e| ClipboardError(e.to_string()))
+    }
+}
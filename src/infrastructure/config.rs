@@ -1,21 +1,109 @@
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
 use std::fs;
-use std::path::PathBuf;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use directories::ProjectDirs;
+use chrono::NaiveDate;
+use directories::{BaseDirs, ProjectDirs, UserDirs};
 use serde::Deserialize;
+use tracing::warn;
 
-use crate::domain::channel::{Channel, ChannelId};
-use crate::domain::video::FetchWindowDays;
+use super::config_keys::{closest, field_names};
+use super::cookies::Cookies;
+use super::http::ProxyUrl;
+use super::retry::RetryPolicy;
+use super::webhook::WebhookUrl;
+use super::ytdlp_downloader::{output_template, DownloadOptions};
+use crate::application::ports::ChannelResolver;
+use crate::domain::channel::{
+    Channel, ChannelHandle, ChannelId, ChannelRef, ChannelTag, LegacyUsername,
+};
+use crate::domain::display::{Color, DateFormat, DateStyle, ReadyAlert, Theme};
+use crate::domain::filter::{
+    DurationRange, MutedWords, TitleFilter, TitlePatternError, TitlePatterns,
+};
+use crate::domain::playback::{ChannelPlayer, PlaybackSpeed, Subtitles};
+use crate::domain::video::{FetchWindow, IdRules, LiveHandling, PlaybackMode, WatchThreshold};
 
 const DEFAULT_FETCH_WINDOW_DAYS: i64 = 7;
+const DEFAULT_FETCH_CONCURRENCY: usize = 8;
+const DEFAULT_SHORTS_CONCURRENCY: usize = 8;
+const DEFAULT_REQUEST_INTERVAL_MS: u64 = 100;
+const DEFAULT_FETCH_TTL_MINUTES: u64 = 10;
+/// yt-dlp output template: `<channel>/<date> <title> [<id>].<ext>`.
+const DEFAULT_DOWNLOAD_TEMPLATE: &str =
+    "%(channel)s/%(upload_date>%Y-%m-%d)s %(title)s [%(id)s].%(ext)s";
+
+/// Settings a `BLEPO_<KEY>` environment variable overrides, and whether its
+/// value is plain text rather than a TOML value such as `14`, `true` or
+/// `["--fs"]`.
+const ENV_SETTINGS: &[(&str, bool)] = &[
+    ("fetch_window_days", false),
+    ("fetch_window", true),
+    ("fetch_concurrency", false),
+    ("fetch_ttl_minutes", false),
+    ("shorts_concurrency", false),
+    ("include_streams", false),
+    ("include_shorts", false),
+    ("enrich", false),
+    ("thumbnails", false),
+    ("sponsorblock", false),
+    ("open_marks_watched", false),
+    ("download_dir", true),
+    ("download_template", true),
+    ("download_marks_watched", false),
+    ("download_nfo", false),
+    ("download_hook", true),
+    ("download_subtitles", true),
+    ("download_thumbnail", false),
+    ("download_rate_limit", true),
+    ("max_concurrent_downloads", false),
+    ("prune_downloads_after_days", false),
+    ("proxy", true),
+    ("cookies_file", true),
+    ("cookies_from_browser", true),
+    ("notify_url", true),
+    ("mpv_args", false),
+    ("playback_mode", true),
+    ("watched_threshold_percent", false),
+    ("playback_speed", false),
+    ("subtitles", true),
+    ("include_titles", false),
+    ("exclude_titles", false),
+    ("muted_words", false),
+    ("live_handling", true),
+    ("date_style", true),
+    ("date_format", true),
+    ("ready_alert", true),
+    ("min_duration", true),
+    ("max_duration", true),
+    ("request_interval_ms", false),
+];
 
 #[derive(Debug)]
 pub enum ConfigError {
     NotFound(PathBuf),
     Read(String),
     InvalidToml(String),
-    InvalidChannel { name: String, reason: String },
+    InvalidChannel {
+        name: String,
+        reason: String,
+    },
     InvalidFetchWindow(String),
+    InvalidSetting {
+        key: &'static str,
+        reason: String,
+    },
+    /// Keys nothing reads, rejected under `--strict-config`.
+    UnknownKeys(Vec<String>),
+    /// Two channels with the same name or the same channel; reported by
+    /// `blepo config check` only.
+    DuplicateChannel {
+        name: String,
+        first: String,
+    },
 }
 
 impl std::fmt::Display for ConfigError {
@@ -26,7 +114,7 @@ impl std::fmt::Display for ConfigError {
                 "config file not found at {}\n\nCreate it with:\n\n\
                  [[channels]]\n\
                  name = \"Channel Name\"\n\
-                 id = \"UCxxxxxxxxxxxxxxxxxxxxxx\"\n",
+                 id = \"UCxxxxxxxxxxxxxxxxxxxxxx\"  # or handle = \"@name\"\n",
                 path.display()
             ),
             ConfigError::Read(msg) => write!(f, "cannot read config: {msg}"),
@@ -35,7 +123,17 @@ impl std::fmt::Display for ConfigError {
                 write!(f, "invalid channel \"{name}\": {reason}")
             }
             ConfigError::InvalidFetchWindow(msg) => {
-                write!(f, "invalid fetch_window_days: {msg}")
+                write!(f, "invalid fetch window: {msg}")
+            }
+            ConfigError::InvalidSetting { key, reason } => write!(f, "invalid {key}: {reason}"),
+            ConfigError::UnknownKeys(keys) => write!(f, "{}", keys.join("\n")),
+            ConfigError::DuplicateChannel { name, first }
+                if name.to_lowercase() == first.to_lowercase() =>
+            {
+                write!(f, "more than one channel is named \"{name}\"")
+            }
+            ConfigError::DuplicateChannel { name, first } => {
+                write!(f, "channel \"{name}\" is the same channel as \"{first}\"")
             }
         }
     }
@@ -45,38 +143,698 @@ impl std::error::Error for ConfigError {}
 
 #[derive(Debug, Deserialize)]
 struct ConfigFile {
+    /// Read on its own before the rest; see `read_includes`.
+    #[serde(rename = "include")]
+    _include: Option<Vec<String>>,
     fetch_window_days: Option<i64>,
+    fetch_window: Option<String>,
+    fetch_concurrency: Option<usize>,
+    fetch_ttl_minutes: Option<u64>,
+    shorts_concurrency: Option<usize>,
+    include_streams: Option<bool>,
+    include_shorts: Option<bool>,
+    enrich: Option<bool>,
+    thumbnails: Option<bool>,
+    sponsorblock: Option<bool>,
+    open_marks_watched: Option<bool>,
+    download_dir: Option<String>,
+    download_template: Option<String>,
+    download_marks_watched: Option<bool>,
+    download_nfo: Option<bool>,
+    download_hook: Option<String>,
+    download_subtitles: Option<String>,
+    download_thumbnail: Option<bool>,
+    download_rate_limit: Option<String>,
+    max_concurrent_downloads: Option<usize>,
+    prune_downloads_after_days: Option<usize>,
+    proxy: Option<String>,
+    cookies_file: Option<String>,
+    cookies_from_browser: Option<String>,
+    notify_url: Option<String>,
+    mpv_args: Option<Vec<String>>,
+    playback_mode: Option<String>,
+    watched_threshold_percent: Option<u64>,
+    playback_speed: Option<f64>,
+    subtitles: Option<String>,
+    data_dir: Option<String>,
+    include_titles: Option<Vec<String>>,
+    exclude_titles: Option<Vec<String>>,
+    muted_words: Option<Vec<String>>,
+    live_handling: Option<String>,
+    date_style: Option<String>,
+    date_format: Option<String>,
+    ready_alert: Option<String>,
+    min_duration: Option<String>,
+    max_duration: Option<String>,
+    request_interval_ms: Option<u64>,
+    retry: Option<RetryEntry>,
+    theme: Option<ThemeEntry>,
     channels: Option<Vec<ChannelEntry>>,
 }
 
+#[derive(Debug, Deserialize)]
+struct RetryEntry {
+    retries: Option<u32>,
+    base_delay_ms: Option<u64>,
+    jitter_ms: Option<u64>,
+}
+
+impl RetryEntry {
+    fn into_policy(self) -> RetryPolicy {
+        let default = RetryPolicy::default();
+        RetryPolicy {
+            retries: self.retries.unwrap_or(default.retries),
+            base_delay: self
+                .base_delay_ms
+                .map_or(default.base_delay, Duration::from_millis),
+            jitter: self.jitter_ms.map_or(default.jitter, Duration::from_millis),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ThemeEntry {
+    channel: Option<String>,
+    date: Option<String>,
+    new: Option<String>,
+}
+
+impl ThemeEntry {
+    fn into_theme(self) -> Result<Theme, ConfigError> {
+        let default = Theme::default();
+        let color = |key: &'static str, raw: Option<String>, default: Option<Color>| {
+            raw.map_or(Ok(default), |raw| {
+                Color::parse(&raw).map_err(|e| ConfigError::InvalidSetting {
+                    key,
+                    reason: e.to_string(),
+                })
+            })
+        };
+        Ok(Theme {
+            channel: color("theme.channel", self.channel, default.channel)?,
+            date: color("theme.date", self.date, default.date)?,
+            new: color("theme.new", self.new, default.new)?,
+        })
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct ChannelEntry {
-    name: String,
-    id: String,
+    name: Option<String>,
+    id: Option<String>,
+    handle: Option<String>,
+    username: Option<String>,
+    include_streams: Option<bool>,
+    include_titles: Option<Vec<String>>,
+    exclude_titles: Option<Vec<String>>,
+    min_duration: Option<String>,
+    max_duration: Option<String>,
+    tags: Option<Vec<String>>,
+    alias: Option<String>,
+    snoozed_until: Option<String>,
+    player: Option<String>,
+    player_args: Option<Vec<String>>,
+    auto_download: Option<bool>,
+    download_template: Option<String>,
+    download_subtitles: Option<String>,
+    download_thumbnail: Option<bool>,
+    keep_downloads: Option<bool>,
+}
+
+#[derive(Debug)]
+pub struct ChannelConfig {
+    /// `None` leaves naming the channel to its feed.
+    pub name: Option<String>,
+    pub reference: ChannelRef,
+    pub include_streams: bool,
+    pub title_filter: TitleFilter,
+    pub duration_range: DurationRange,
+    pub tags: Vec<ChannelTag>,
+    /// Shown in the listing instead of the name.
+    pub alias: Option<String>,
+    /// Left out of listings before this day.
+    pub snoozed_until: Option<NaiveDate>,
+    pub player: Option<ChannelPlayer>,
+    pub auto_download: bool,
+    /// yt-dlp output template for this channel's downloads.
+    pub download_template: Option<String>,
+    pub download_subtitles: Option<Subtitles>,
+    pub download_thumbnail: Option<bool>,
+    /// Never prune this channel's downloads.
+    pub keep_downloads: bool,
+}
+
+impl ChannelConfig {
+    /// The configured name, else the `id`, `handle` or `username`.
+    pub fn label(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| self.reference.to_string())
+    }
+
+    /// Unnamed channels take the title their feed last gave them, if known.
+    fn into_channel(self, id: ChannelId, learned: &HashMap<ChannelId, String>) -> Channel {
+        let name_from_feed = self.name.is_none();
+        let name = self
+            .name
+            .or_else(|| learned.get(&id).cloned())
+            .unwrap_or_else(|| id.to_string());
+        Channel {
+            name,
+            id,
+            include_streams: self.include_streams,
+            title_filter: self.title_filter,
+            duration_range: self.duration_range,
+            tags: self.tags,
+            name_from_feed,
+            alias: self.alias,
+            snoozed_until: self.snoozed_until,
+            player: self.player,
+            auto_download: self.auto_download,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct AppConfig {
-    pub fetch_window_days: FetchWindowDays,
-    pub channels: Vec<Channel>,
+    pub fetch_window: FetchWindow,
+    pub fetch_concurrency: NonZeroUsize,
+    pub shorts_concurrency: NonZeroUsize,
+    pub include_shorts: bool,
+    /// Fetch views, likes and description for listed videos (slow).
+    pub enrich: bool,
+    /// Draw thumbnails in terminals that support inline images.
+    pub thumbnails: bool,
+    /// Applied to every channel, on top of each channel's own filter.
+    pub title_filter: TitleFilter,
+    pub muted_words: MutedWords,
+    pub live_handling: LiveHandling,
+    pub date_style: DateStyle,
+    /// strftime pattern for listing dates, shown in local time.
+    pub date_format: DateFormat,
+    /// Listing colours, used when stdout is a terminal and NO_COLOR is unset.
+    pub theme: Theme,
+    /// Said when a slow interactive listing is ready.
+    pub ready_alert: ReadyAlert,
+    /// Applied to every channel, on top of each channel's own range.
+    pub duration_range: DurationRange,
+    /// How long a channel's last fetch is reused before hitting the network again.
+    pub fetch_ttl: Duration,
+    pub retry: RetryPolicy,
+    pub proxy: Option<ProxyUrl>,
+    /// Sent by every yt-dlp run, mpv's included.
+    pub cookies: Option<Cookies>,
+    /// Where `blepo notify` announces new videos.
+    pub notify_url: Option<WebhookUrl>,
+    /// Extra options appended to every mpv invocation.
+    pub mpv_args: Vec<String>,
+    /// Skip sponsor segments known to SponsorBlock during playback.
+    pub sponsorblock: bool,
+    /// Opening a video in the browser also marks it watched.
+    pub open_marks_watched: bool,
+    /// Where `blepo download` saves videos.
+    pub download_dir: PathBuf,
+    /// yt-dlp output template for downloads, relative to `download_dir`
+    /// unless absolute; `{placeholders}` are translated already.
+    pub download_template: String,
+    /// Downloading a video also marks it watched.
+    pub download_marks_watched: bool,
+    /// Write Kodi/Jellyfin `.nfo` files and thumbnails next to downloads.
+    pub download_nfo: bool,
+    /// Shell command run after each download, e.g. to move it to a NAS.
+    pub download_hook: Option<String>,
+    /// Subtitle files saved along with downloads.
+    pub download_subtitles: Option<Subtitles>,
+    /// Save thumbnails along with downloads.
+    pub download_thumbnail: bool,
+    /// Bytes per second all downloads together may use.
+    pub download_rate_limit: Option<u64>,
+    pub max_concurrent_downloads: NonZeroUsize,
+    /// Delete downloads this long after they were watched, on every fetch.
+    pub prune_downloads_after_days: Option<NonZeroUsize>,
+    pub playback_mode: PlaybackMode,
+    /// Play in the foreground and mark watched only past this share of the
+    /// video. `None` marks videos watched as soon as they are opened.
+    pub watch_threshold: Option<WatchThreshold>,
+    /// Speed every video starts at; `None` leaves mpv's default.
+    pub playback_speed: Option<PlaybackSpeed>,
+    /// Preferred subtitle languages; `None` leaves subtitles to mpv.
+    pub subtitles: Option<Subtitles>,
+    /// Minimum spacing between requests to youtube.com.
+    pub request_interval: Duration,
+    pub channels: Vec<ChannelConfig>,
     pub data_dir: PathBuf,
 }
 
-pub fn load_config() -> Result<AppConfig, ConfigError> {
-    let dirs = ProjectDirs::from("", "", "blepo").ok_or(ConfigError::Read(
-        "cannot determine home directory".to_string(),
-    ))?;
+impl AppConfig {
+    /// Resolves handles and usernames to channel IDs, naming unnamed channels
+    /// from `learned` feed titles. Channels that cannot be resolved are
+    /// skipped with a warning, like channels that fail to fetch.
+    pub fn resolve_channels(
+        self,
+        resolver: &dyn ChannelResolver,
+        learned: &HashMap<ChannelId, String>,
+    ) -> Vec<Channel> {
+        self.channels
+            .into_iter()
+            .filter_map(|config| match resolver.resolve(&config.reference) {
+                Ok(id) => Some(config.into_channel(id, learned)),
+                Err(e) => {
+                    match &config.name {
+                        Some(name) => {
+                            warn!("cannot resolve {name} ({}): {e}", config.reference)
+                        }
+                        None => warn!("cannot resolve {}: {e}", config.reference),
+                    }
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Like `resolve_channels`, but leaves out the channels `resolver` can't
+    /// resolve without a word: offline, those were never fetched either.
+    pub fn resolve_known_channels(
+        self,
+        resolver: &dyn ChannelResolver,
+        learned: &HashMap<ChannelId, String>,
+    ) -> Vec<Channel> {
+        self.channels
+            .into_iter()
+            .filter_map(|config| {
+                let id = resolver.resolve(&config.reference).ok()?;
+                Some(config.into_channel(id, learned))
+            })
+            .collect()
+    }
+
+    /// The channels whose downloads are kept forever, by ID. One that can't
+    /// be resolved is left out.
+    pub fn kept_channels(&self, resolver: &dyn ChannelResolver) -> HashSet<ChannelId> {
+        self.channels
+            .iter()
+            .filter(|config| config.keep_downloads)
+            .filter_map(|config| resolver.resolve(&config.reference).ok())
+            .collect()
+    }
+
+    /// How downloads are saved unless their channel says otherwise.
+    pub fn download_options(&self) -> DownloadOptions {
+        DownloadOptions {
+            template: self.download_template.clone(),
+            subtitles: self.download_subtitles.clone(),
+            thumbnail: self.download_thumbnail,
+        }
+    }
+
+    /// The download options of channels that set any of their own, by ID.
+    /// A channel that can't be resolved is left out; fetching warns about it.
+    pub fn channel_download_options(
+        &self,
+        resolver: &dyn ChannelResolver,
+    ) -> HashMap<ChannelId, DownloadOptions> {
+        let defaults = self.download_options();
+        self.channels
+            .iter()
+            .filter(|config| {
+                config.download_template.is_some()
+                    || config.download_subtitles.is_some()
+                    || config.download_thumbnail.is_some()
+            })
+            .filter_map(|config| {
+                let id = resolver.resolve(&config.reference).ok()?;
+                let options = DownloadOptions {
+                    template: config
+                        .download_template
+                        .clone()
+                        .unwrap_or_else(|| defaults.template.clone()),
+                    subtitles: config
+                        .download_subtitles
+                        .clone()
+                        .or_else(|| defaults.subtitles.clone()),
+                    thumbnail: config.download_thumbnail.unwrap_or(defaults.thumbnail),
+                };
+                Some((id, options))
+            })
+            .collect()
+    }
+}
 
-    let config_path = dirs.config_dir().join("config.toml");
+/// Reads the config from `path` when given, else from `$BLEPO_CONFIG`, else
+/// from the platform's config directory. `$BLEPO_<KEY>` variables override
+/// the settings read there, and `$BLEPO_DATA_DIR` the data directory.
+/// Unknown keys are warned about, or rejected when `strict`.
+pub fn load_config(path: Option<PathBuf>, strict: bool) -> Result<AppConfig, ConfigError> {
+    let dirs = project_dirs()?;
+    let config_path = config_file(path)?;
     let data_dir = dirs.data_dir().to_path_buf();
 
-    load_config_from_path(&config_path, data_dir)
+    let overrides = env_overrides(|name| std::env::var(name).ok());
+    let mut config = load_config_from_path(&config_path, data_dir, overrides, strict)?;
+    if let Some(dir) = std::env::var_os("BLEPO_DATA_DIR").filter(|v| !v.is_empty()) {
+        config.data_dir = PathBuf::from(dir);
+    }
+    Ok(config)
+}
+
+/// The config file `load_config` would read, whether or not it exists.
+pub fn config_file(path: Option<PathBuf>) -> Result<PathBuf, ConfigError> {
+    Ok(config_path(
+        path,
+        std::env::var_os("BLEPO_CONFIG"),
+        project_dirs()?.config_dir().join("config.toml"),
+    ))
+}
+
+/// Everything wrong with the config, instead of only the first problem:
+/// unknown keys, invalid settings and channels, and channels listed twice.
+/// Fails only when the file can't be read or isn't TOML at all.
+pub fn check_config(path: Option<PathBuf>) -> Result<Vec<ConfigError>, ConfigError> {
+    let data_dir = project_dirs()?.data_dir().to_path_buf();
+    let overrides = env_overrides(|name| std::env::var(name).ok());
+    let (content, layers) = read_layers(&config_file(path)?, overrides)?;
+    Ok(check_config_str(&content, data_dir, &layers))
+}
+
+fn check_config_str(content: &str, data_dir: PathBuf, layers: &Layers) -> Vec<ConfigError> {
+    let (config, unknown) = match read_config_file(content, layers) {
+        Ok(read) => read,
+        Err(e) => return vec![e],
+    };
+    let mut problems = Vec::new();
+    if !unknown.is_empty() {
+        problems.push(ConfigError::UnknownKeys(unknown));
+    }
+    let config = build_config(config, data_dir, &mut problems);
+    problems.extend(duplicate_channels(&config.channels));
+    problems
+}
+
+/// Channels sharing a name (which `--channel` couldn't tell apart, ignoring
+/// case) or an `id`, `handle` or `username`.
+fn duplicate_channels(channels: &[ChannelConfig]) -> Vec<ConfigError> {
+    let mut names: HashMap<String, String> = HashMap::new();
+    let mut references: HashMap<&ChannelRef, String> = HashMap::new();
+    let mut duplicates = Vec::new();
+    for channel in channels {
+        let first = channel
+            .name
+            .as_ref()
+            .and_then(|name| names.get(&name.to_lowercase()))
+            .or_else(|| references.get(&channel.reference));
+        match first {
+            Some(first) => duplicates.push(ConfigError::DuplicateChannel {
+                name: channel.label(),
+                first: first.clone(),
+            }),
+            None => {
+                if let Some(name) = &channel.name {
+                    names.insert(name.to_lowercase(), name.clone());
+                }
+                references.insert(&channel.reference, channel.label());
+            }
+        }
+    }
+    duplicates
+}
+
+fn project_dirs() -> Result<ProjectDirs, ConfigError> {
+    ProjectDirs::from("", "", "blepo").ok_or(ConfigError::Read(
+        "cannot determine home directory".to_string(),
+    ))
+}
+
+/// An absolute path, or one starting with `~/` for the home directory.
+fn expand_data_dir(raw: &str, home: Option<&Path>) -> Result<PathBuf, String> {
+    let path = match (raw.strip_prefix("~/"), home) {
+        (Some(rest), Some(home)) => home.join(rest),
+        (Some(_), None) => return Err("cannot determine home directory".to_string()),
+        (None, _) => PathBuf::from(raw),
+    };
+    if path.is_absolute() {
+        Ok(path)
+    } else {
+        Err(format!("'{raw}' is not an absolute path"))
+    }
+}
+
+/// A download template as yt-dlp takes it: `~/` expanded and
+/// `{placeholders}` translated (see `output_template`).
+fn download_template(raw: &str, home: Option<&Path>) -> Result<String, String> {
+    if raw.trim().is_empty() {
+        return Err("must not be empty".to_string());
+    }
+    let expanded = match (raw.strip_prefix("~/"), home) {
+        (Some(rest), Some(home)) => home.join(rest).display().to_string(),
+        (Some(_), None) => return Err("cannot determine home directory".to_string()),
+        (None, _) => raw.to_string(),
+    };
+    output_template(&expanded)
+}
+
+/// `blepo/` in the platform's videos folder, else `downloads/` in the data
+/// directory.
+fn default_download_dir(data_dir: &Path) -> PathBuf {
+    UserDirs::new()
+        .and_then(|dirs| dirs.video_dir().map(|dir| dir.join("blepo")))
+        .unwrap_or_else(|| data_dir.join("downloads"))
+}
+
+/// The non-empty `BLEPO_<KEY>` variables among `ENV_SETTINGS`.
+fn env_overrides(var: impl Fn(&str) -> Option<String>) -> Vec<(&'static str, String)> {
+    ENV_SETTINGS
+        .iter()
+        .filter_map(|&(key, _)| {
+            var(&env_name(key))
+                .filter(|v| !v.is_empty())
+                .map(|v| (key, v))
+        })
+        .collect()
+}
+
+fn env_name(key: &str) -> String {
+    format!("BLEPO_{}", key.to_uppercase())
+}
+
+/// Settings layered over the main config file: included files in order,
+/// then environment variables.
+#[derive(Default)]
+struct Layers {
+    includes: Vec<toml::Table>,
+    overrides: Vec<(&'static str, String)>,
+}
+
+impl Layers {
+    fn is_empty(&self) -> bool {
+        self.includes.is_empty() && self.overrides.is_empty()
+    }
+
+    fn apply(&self, table: &mut toml::Table) -> Result<(), ConfigError> {
+        for included in &self.includes {
+            merge(table, included.clone());
+        }
+        apply_overrides(table, &self.overrides)
+    }
+}
+
+/// Sets a top-level setting; either spelling of the fetch window replaces
+/// the other.
+fn set_setting(table: &mut toml::Table, key: String, value: toml::Value) {
+    match key.as_str() {
+        "fetch_window" => table.remove("fetch_window_days"),
+        "fetch_window_days" => table.remove("fetch_window"),
+        _ => None,
+    };
+    table.insert(key, value);
+}
+
+/// Layers an included file over `base`: its channels are added to the
+/// others, its tables merged key by key, and its other settings win.
+fn merge(base: &mut toml::Table, included: toml::Table) {
+    for (key, value) in included {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Array(channels)), toml::Value::Array(more)) if key == "channels" => {
+                channels.extend(more);
+            }
+            (Some(toml::Value::Table(table)), toml::Value::Table(more)) => {
+                table.extend(more);
+            }
+            (_, value) => set_setting(base, key, value),
+        }
+    }
+}
+
+/// Reads the files named by the main config's `include` list, relative to
+/// its directory. A `*` in a file name matches any characters; the files it
+/// matches are read in name order, and may be none.
+fn read_includes(content: &str, config_dir: &Path) -> Result<Vec<toml::Table>, ConfigError> {
+    #[derive(Deserialize)]
+    struct Includes {
+        include: Option<Vec<String>>,
+    }
+
+    let includes: Includes =
+        toml::from_str(content).map_err(|e| ConfigError::InvalidToml(e.to_string()))?;
+    let mut paths = Vec::new();
+    for pattern in includes.include.unwrap_or_default() {
+        let path = config_dir.join(&pattern);
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        if !name.contains('*') {
+            paths.push(path);
+            continue;
+        }
+        let dir = path.parent().unwrap_or(config_dir);
+        let entries = fs::read_dir(dir).map_err(|e| ConfigError::InvalidSetting {
+            key: "include",
+            reason: format!("{pattern}: {e}"),
+        })?;
+        let mut matches: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|candidate| {
+                candidate
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| wildcard_matches(name, n))
+            })
+            .collect();
+        matches.sort();
+        paths.extend(matches);
+    }
+
+    paths
+        .iter()
+        .map(|path| {
+            let content = fs::read_to_string(path)
+                .map_err(|e| ConfigError::Read(format!("{}: {e}", path.display())))?;
+            let table: toml::Table = toml::from_str(&content)
+                .map_err(|e| ConfigError::InvalidToml(format!("{}: {e}", path.display())))?;
+            if table.contains_key("include") {
+                return Err(ConfigError::InvalidSetting {
+                    key: "include",
+                    reason: format!(
+                        "{} cannot include other files; only the main config can",
+                        path.display()
+                    ),
+                });
+            }
+            Ok(table)
+        })
+        .collect()
+}
+
+/// Whether `name` matches `pattern`, where `*` stands for any characters.
+/// Hidden files only match patterns that start with a dot.
+fn wildcard_matches(pattern: &str, name: &str) -> bool {
+    if name.starts_with('.') && !pattern.starts_with('.') {
+        return false;
+    }
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if parts.len() == 1 {
+        return name == pattern;
+    }
+    if name.len() < first.len() + last.len() || !name.starts_with(first) || !name.ends_with(last) {
+        return false;
+    }
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn apply_overrides(
+    table: &mut toml::Table,
+    overrides: &[(&'static str, String)],
+) -> Result<(), ConfigError> {
+    for &(key, ref raw) in overrides {
+        let text = ENV_SETTINGS.iter().any(|&(k, text)| k == key && text);
+        let value = if text {
+            toml::Value::String(raw.clone())
+        } else {
+            toml::from_str::<toml::Table>(&format!("value = {raw}"))
+                .ok()
+                .and_then(|mut parsed| parsed.remove("value"))
+                .ok_or_else(|| ConfigError::InvalidSetting {
+                    key,
+                    reason: format!("{} = '{raw}' is not a valid value", env_name(key)),
+                })?
+        };
+        set_setting(table, key.to_string(), value);
+    }
+    Ok(())
+}
+
+/// Names a key serde skipped, and the valid key it was probably meant as.
+fn unknown_key(path: &serde_ignored::Path) -> String {
+    let mut segments = Vec::new();
+    path_segments(path, &mut segments);
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+    let (key, place, known) = match segments.as_slice() {
+        [key] => (*key, String::new(), field_names::<ConfigFile>()),
+        ["retry", key] => (*key, " in [retry]".to_string(), field_names::<RetryEntry>()),
+        ["theme", key] => (*key, " in [theme]".to_string(), field_names::<ThemeEntry>()),
+        ["channels", index, key] => {
+            let number = index.parse::<usize>().map_or(0, |i| i + 1);
+            (
+                *key,
+                format!(" in channel #{number}"),
+                field_names::<ChannelEntry>(),
+            )
+        }
+        _ => return format!("unknown config key `{}`", segments.join(".")),
+    };
+    match closest(key, known) {
+        Some(suggestion) => {
+            format!("unknown config key `{key}`{place}, did you mean `{suggestion}`?")
+        }
+        None => format!("unknown config key `{key}`{place}"),
+    }
+}
+
+fn path_segments(path: &serde_ignored::Path, segments: &mut Vec<String>) {
+    match path {
+        serde_ignored::Path::Root => {}
+        serde_ignored::Path::Seq { parent, index } => {
+            path_segments(parent, segments);
+            segments.push(index.to_string());
+        }
+        serde_ignored::Path::Map { parent, key } => {
+            path_segments(parent, segments);
+            segments.push(key.clone());
+        }
+        serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => path_segments(parent, segments),
+    }
+}
+
+fn config_path(flag: Option<PathBuf>, env: Option<OsString>, default: PathBuf) -> PathBuf {
+    flag.or_else(|| env.filter(|v| !v.is_empty()).map(PathBuf::from))
+        .unwrap_or(default)
 }
 
 fn load_config_from_path(
     config_path: &std::path::Path,
     data_dir: PathBuf,
+    overrides: Vec<(&'static str, String)>,
+    strict: bool,
 ) -> Result<AppConfig, ConfigError> {
+    let (content, layers) = read_layers(config_path, overrides)?;
+    parse_config_str(&content, data_dir, &layers, strict)
+}
+
+/// The main file's content, and what goes over it.
+fn read_layers(
+    config_path: &Path,
+    overrides: Vec<(&'static str, String)>,
+) -> Result<(String, Layers), ConfigError> {
     if !config_path.exists() {
         return Err(ConfigError::NotFound(config_path.to_path_buf()));
     }
@@ -84,50 +842,563 @@ fn load_config_from_path(
     let content = fs::read_to_string(config_path)
         .map_err(|e| ConfigError::Read(format!("{}: {e}", config_path.display())))?;
 
-    parse_config_str(&content, data_dir)
+    let layers = Layers {
+        includes: read_includes(&content, config_path.parent().unwrap_or(Path::new(".")))?,
+        overrides,
+    };
+    Ok((content, layers))
 }
 
-fn parse_config_str(content: &str, data_dir: PathBuf) -> Result<AppConfig, ConfigError> {
-    let config: ConfigFile =
-        toml::from_str(content).map_err(|e| ConfigError::InvalidToml(e.to_string()))?;
+fn parse_config_str(
+    content: &str,
+    data_dir: PathBuf,
+    layers: &Layers,
+    strict: bool,
+) -> Result<AppConfig, ConfigError> {
+    let (config, unknown) = read_config_file(content, layers)?;
+    if strict && !unknown.is_empty() {
+        return Err(ConfigError::UnknownKeys(unknown));
+    }
+    for message in &unknown {
+        warn!("{message} (ignored)");
+    }
+    let mut problems = Vec::new();
+    let config = build_config(config, data_dir, &mut problems);
+    match problems.into_iter().next() {
+        Some(first) => Err(first),
+        None => Ok(config),
+    }
+}
+
+/// Deserializes the file with its layers, noting the keys nothing reads.
+fn read_config_file(
+    content: &str,
+    layers: &Layers,
+) -> Result<(ConfigFile, Vec<String>), ConfigError> {
+    let invalid_toml = |e: toml::de::Error| ConfigError::InvalidToml(e.to_string());
+    let mut unknown = Vec::new();
+    let note_unknown = |path: serde_ignored::Path| unknown.push(unknown_key(&path));
+    // Going through a table loses the line numbers in error messages, so
+    // only do it when there is something to layer over the file.
+    let config: ConfigFile = if layers.is_empty() {
+        serde_ignored::deserialize(toml::Deserializer::new(content), note_unknown)
+            .map_err(invalid_toml)?
+    } else {
+        let mut table: toml::Table = toml::from_str(content).map_err(invalid_toml)?;
+        layers.apply(&mut table)?;
+        serde_ignored::deserialize(toml::Value::Table(table), note_unknown).map_err(invalid_toml)?
+    };
+    Ok((config, unknown))
+}
+
+/// Records the error of a setting that failed to parse, in file order, so
+/// every problem can be reported; the setting falls back to its default.
+fn noted<T>(problems: &mut Vec<ConfigError>, result: Result<T, ConfigError>) -> Option<T> {
+    result.map_err(|e| problems.push(e)).ok()
+}
+
+/// Validates every setting, adding what is wrong to `problems`.
+fn build_config(
+    config: ConfigFile,
+    data_dir: PathBuf,
+    problems: &mut Vec<ConfigError>,
+) -> AppConfig {
+    let default_window =
+        || FetchWindow::from_days(DEFAULT_FETCH_WINDOW_DAYS).expect("default is positive");
+    let fetch_window = match (config.fetch_window.as_deref(), config.fetch_window_days) {
+        (Some(_), Some(_)) => Err(ConfigError::InvalidFetchWindow(
+            "set fetch_window or fetch_window_days, not both".to_string(),
+        )),
+        (Some(raw), None) => {
+            FetchWindow::parse(raw).map_err(|e| ConfigError::InvalidFetchWindow(e.to_string()))
+        }
+        (None, days) => FetchWindow::from_days(days.unwrap_or(DEFAULT_FETCH_WINDOW_DAYS))
+            .map_err(|e| ConfigError::InvalidFetchWindow(e.to_string())),
+    };
+    let fetch_window = noted(problems, fetch_window).unwrap_or_else(default_window);
 
-    let raw_days = config
-        .fetch_window_days
-        .unwrap_or(DEFAULT_FETCH_WINDOW_DAYS);
-    let fetch_window_days = FetchWindowDays::parse(raw_days)
-        .map_err(|e| ConfigError::InvalidFetchWindow(e.to_string()))?;
+    let fetch_concurrency = noted(
+        problems,
+        positive("fetch_concurrency", config.fetch_concurrency),
+    )
+    .flatten()
+    .unwrap_or(NonZeroUsize::new(DEFAULT_FETCH_CONCURRENCY).expect("default is positive"));
+    let shorts_concurrency = noted(
+        problems,
+        positive("shorts_concurrency", config.shorts_concurrency),
+    )
+    .flatten()
+    .unwrap_or(NonZeroUsize::new(DEFAULT_SHORTS_CONCURRENCY).expect("default is positive"));
 
+    let include_streams = config.include_streams.unwrap_or(false);
+    let home = BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
     let channels = config
         .channels
         .unwrap_or_default()
         .into_iter()
-        .map(|entry| {
-            let id = ChannelId::parse(&entry.id).map_err(|e| ConfigError::InvalidChannel {
-                name: entry.name.clone(),
-                reason: e.to_string(),
-            })?;
-            Ok(Channel {
+        .map(|entry| -> Result<ChannelConfig, ConfigError> {
+            let label = [&entry.name, &entry.id, &entry.handle, &entry.username]
+                .into_iter()
+                .flatten()
+                .next()
+                .cloned()
+                .unwrap_or_else(|| "unnamed channel".to_string());
+            let invalid = |reason| ConfigError::InvalidChannel {
+                name: label.clone(),
+                reason,
+            };
+            let reference = parse_channel_ref(&entry).map_err(invalid)?;
+            let title_filter = TitleFilter {
+                include: title_patterns(&entry.include_titles)
+                    .map_err(|e| invalid(format!("include_titles: {e}")))?,
+                exclude: title_patterns(&entry.exclude_titles)
+                    .map_err(|e| invalid(format!("exclude_titles: {e}")))?,
+            };
+            let duration_range =
+                DurationRange::parse(entry.min_duration.as_deref(), entry.max_duration.as_deref())
+                    .map_err(|e| invalid(e.to_string()))?;
+            let tags = entry
+                .tags
+                .iter()
+                .flatten()
+                .map(|tag| ChannelTag::parse(tag.as_str()))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| invalid(format!("tags: {e}")))?;
+            if entry.alias.as_ref().is_some_and(|a| a.trim().is_empty()) {
+                return Err(invalid("alias: can't be empty".to_string()));
+            }
+            let snoozed_until = entry
+                .snoozed_until
+                .as_deref()
+                .map(|raw| NaiveDate::parse_from_str(raw, "%Y-%m-%d"))
+                .transpose()
+                .map_err(|e| invalid(format!("snoozed_until: {e} (expected e.g. 2025-06-01)")))?;
+            let player = ChannelPlayer::parse(
+                entry.player.as_deref(),
+                entry.player_args.as_deref().unwrap_or_default(),
+            )
+            .map_err(|e| invalid(e.to_string()))?;
+            let download_template = entry
+                .download_template
+                .as_deref()
+                .map(|raw| download_template(raw, home.as_deref()))
+                .transpose()
+                .map_err(|e| invalid(format!("download_template: {e}")))?;
+            let download_subtitles = entry
+                .download_subtitles
+                .as_deref()
+                .map(Subtitles::parse)
+                .transpose()
+                .map_err(|e| invalid(format!("download_subtitles: {e}")))?;
+            Ok(ChannelConfig {
                 name: entry.name,
-                id,
+                reference,
+                include_streams: entry.include_streams.unwrap_or(include_streams),
+                title_filter,
+                duration_range,
+                tags,
+                alias: entry.alias,
+                snoozed_until,
+                player,
+                auto_download: entry.auto_download.unwrap_or(false),
+                download_template,
+                download_subtitles,
+                download_thumbnail: entry.download_thumbnail,
+                keep_downloads: entry.keep_downloads.unwrap_or(false),
             })
         })
-        .collect::<Result<Vec<_>, _>>()?;
+        .filter_map(|channel| noted(problems, channel))
+        .collect();
+
+    let title_filter = TitleFilter {
+        include: noted(
+            problems,
+            title_patterns(&config.include_titles).map_err(|e| ConfigError::InvalidSetting {
+                key: "include_titles",
+                reason: e.to_string(),
+            }),
+        )
+        .unwrap_or_default(),
+        exclude: noted(
+            problems,
+            title_patterns(&config.exclude_titles).map_err(|e| ConfigError::InvalidSetting {
+                key: "exclude_titles",
+                reason: e.to_string(),
+            }),
+        )
+        .unwrap_or_default(),
+    };
+
+    let duration_range = DurationRange::parse(
+        config.min_duration.as_deref(),
+        config.max_duration.as_deref(),
+    )
+    .map_err(|e| ConfigError::InvalidSetting {
+        key: "min_duration/max_duration",
+        reason: e.to_string(),
+    });
+    let duration_range = noted(problems, duration_range).unwrap_or_default();
+
+    let live_handling = config
+        .live_handling
+        .as_deref()
+        .map(LiveHandling::parse)
+        .transpose()
+        .map_err(|e| ConfigError::InvalidSetting {
+            key: "live_handling",
+            reason: e.to_string(),
+        });
+    let live_handling = noted(problems, live_handling).flatten().unwrap_or_default();
+
+    let date_style = config
+        .date_style
+        .as_deref()
+        .map(DateStyle::parse)
+        .transpose()
+        .map_err(|e| ConfigError::InvalidSetting {
+            key: "date_style",
+            reason: e.to_string(),
+        });
+    let date_style = noted(problems, date_style).flatten().unwrap_or_default();
+
+    let ready_alert = config
+        .ready_alert
+        .as_deref()
+        .map(ReadyAlert::parse)
+        .transpose()
+        .map_err(|e| ConfigError::InvalidSetting {
+            key: "ready_alert",
+            reason: e.to_string(),
+        });
+    let ready_alert = noted(problems, ready_alert).flatten().unwrap_or_default();
+
+    let date_format = config
+        .date_format
+        .as_deref()
+        .map(DateFormat::parse)
+        .transpose()
+        .map_err(|e| ConfigError::InvalidSetting {
+            key: "date_format",
+            reason: e.to_string(),
+        });
+    let date_format = noted(problems, date_format).flatten().unwrap_or_default();
+
+    let theme = config.theme.map(ThemeEntry::into_theme).transpose();
+    let theme = noted(problems, theme).flatten().unwrap_or_default();
+
+    let retry = config
+        .retry
+        .map_or_else(RetryPolicy::default, RetryEntry::into_policy);
+
+    let mut mpv_args = config.mpv_args.unwrap_or_default();
+    if let Some(arg) = mpv_args.iter().find(|arg| !arg.starts_with('-')) {
+        problems.push(ConfigError::InvalidSetting {
+            key: "mpv_args",
+            reason: format!("'{arg}' is not an option (options start with --)"),
+        });
+        mpv_args.clear();
+    }
+
+    let playback_mode = config
+        .playback_mode
+        .as_deref()
+        .map(PlaybackMode::parse)
+        .transpose()
+        .map_err(|e| ConfigError::InvalidSetting {
+            key: "playback_mode",
+            reason: e.to_string(),
+        });
+    let playback_mode = noted(problems, playback_mode).flatten().unwrap_or_default();
+
+    let watch_threshold = config
+        .watched_threshold_percent
+        .map(WatchThreshold::parse)
+        .transpose()
+        .map_err(|e| ConfigError::InvalidSetting {
+            key: "watched_threshold_percent",
+            reason: e.to_string(),
+        });
+    let watch_threshold = noted(problems, watch_threshold).flatten();
+
+    let playback_speed = config
+        .playback_speed
+        .map(PlaybackSpeed::parse)
+        .transpose()
+        .map_err(|e| ConfigError::InvalidSetting {
+            key: "playback_speed",
+            reason: e.to_string(),
+        });
+    let playback_speed = noted(problems, playback_speed).flatten();
+
+    let subtitles = config
+        .subtitles
+        .as_deref()
+        .map(Subtitles::parse)
+        .transpose()
+        .map_err(|e| ConfigError::InvalidSetting {
+            key: "subtitles",
+            reason: e.to_string(),
+        });
+    let subtitles = noted(problems, subtitles).flatten();
+    let download_subtitles = config
+        .download_subtitles
+        .as_deref()
+        .map(Subtitles::parse)
+        .transpose()
+        .map_err(|e| ConfigError::InvalidSetting {
+            key: "download_subtitles",
+            reason: e.to_string(),
+        });
+    let download_subtitles = noted(problems, download_subtitles).flatten();
+
+    let data_dir = match config.data_dir.as_deref() {
+        Some(raw) => {
+            let expanded = expand_data_dir(raw, home.as_deref()).map_err(|reason| {
+                ConfigError::InvalidSetting {
+                    key: "data_dir",
+                    reason,
+                }
+            });
+            noted(problems, expanded).unwrap_or(data_dir)
+        }
+        None => data_dir,
+    };
+
+    let download_dir = match config.download_dir.as_deref() {
+        Some(raw) => {
+            let expanded = expand_data_dir(raw, home.as_deref()).map_err(|reason| {
+                ConfigError::InvalidSetting {
+                    key: "download_dir",
+                    reason,
+                }
+            });
+            noted(problems, expanded)
+        }
+        None => None,
+    }
+    .unwrap_or_else(|| default_download_dir(&data_dir));
+
+    let download_template = match config.download_template.as_deref() {
+        Some(raw) => {
+            let template = download_template(raw, home.as_deref()).map_err(|reason| {
+                ConfigError::InvalidSetting {
+                    key: "download_template",
+                    reason,
+                }
+            });
+            noted(problems, template)
+        }
+        None => None,
+    }
+    .unwrap_or_else(|| DEFAULT_DOWNLOAD_TEMPLATE.to_string());
+
+    let download_rate_limit = config
+        .download_rate_limit
+        .as_deref()
+        .map(rate_limit)
+        .transpose()
+        .map_err(|reason| ConfigError::InvalidSetting {
+            key: "download_rate_limit",
+            reason,
+        });
+    let download_rate_limit = noted(problems, download_rate_limit).flatten();
+    let max_concurrent_downloads = noted(
+        problems,
+        positive("max_concurrent_downloads", config.max_concurrent_downloads),
+    )
+    .flatten()
+    .unwrap_or(NonZeroUsize::MIN);
+    let prune_downloads_after_days = noted(
+        problems,
+        positive(
+            "prune_downloads_after_days",
+            config.prune_downloads_after_days,
+        ),
+    )
+    .flatten();
+
+    let download_hook = match config.download_hook {
+        Some(hook) if hook.trim().is_empty() => {
+            problems.push(ConfigError::InvalidSetting {
+                key: "download_hook",
+                reason: "must not be empty".to_string(),
+            });
+            None
+        }
+        hook => hook,
+    };
 
-    Ok(AppConfig {
-        fetch_window_days,
+    let proxy =
+        config
+            .proxy
+            .map(ProxyUrl::parse)
+            .transpose()
+            .map_err(|e| ConfigError::InvalidSetting {
+                key: "proxy",
+                reason: e.to_string(),
+            });
+    let proxy = noted(problems, proxy).flatten();
+
+    let cookies = match (config.cookies_file.as_deref(), config.cookies_from_browser) {
+        (Some(_), Some(_)) => Err(ConfigError::InvalidSetting {
+            key: "cookies_file",
+            reason: "set cookies_file or cookies_from_browser, not both".to_string(),
+        }),
+        (Some(raw), None) => expand_data_dir(raw, home.as_deref())
+            .and_then(|path| Cookies::file(&path).map_err(|e| e.to_string()))
+            .map(Some)
+            .map_err(|reason| ConfigError::InvalidSetting {
+                key: "cookies_file",
+                reason,
+            }),
+        (None, Some(spec)) => {
+            Cookies::browser(spec)
+                .map(Some)
+                .map_err(|e| ConfigError::InvalidSetting {
+                    key: "cookies_from_browser",
+                    reason: e.to_string(),
+                })
+        }
+        (None, None) => Ok(None),
+    };
+    let cookies = noted(problems, cookies).flatten();
+
+    let notify_url = config
+        .notify_url
+        .map(WebhookUrl::parse)
+        .transpose()
+        .map_err(|e| ConfigError::InvalidSetting {
+            key: "notify_url",
+            reason: e.to_string(),
+        });
+    let notify_url = noted(problems, notify_url).flatten();
+
+    let request_interval = Duration::from_millis(
+        config
+            .request_interval_ms
+            .unwrap_or(DEFAULT_REQUEST_INTERVAL_MS),
+    );
+
+    let fetch_ttl = Duration::from_secs(
+        config
+            .fetch_ttl_minutes
+            .unwrap_or(DEFAULT_FETCH_TTL_MINUTES)
+            * 60,
+    );
+
+    AppConfig {
+        fetch_window,
+        fetch_concurrency,
+        shorts_concurrency,
+        include_shorts: config.include_shorts.unwrap_or(false),
+        enrich: config.enrich.unwrap_or(false),
+        thumbnails: config.thumbnails.unwrap_or(true),
+        title_filter,
+        muted_words: MutedWords::new(&config.muted_words.unwrap_or_default()),
+        duration_range,
+        live_handling,
+        date_style,
+        date_format,
+        theme,
+        ready_alert,
+        fetch_ttl,
+        retry,
+        proxy,
+        cookies,
+        notify_url,
+        mpv_args,
+        sponsorblock: config.sponsorblock.unwrap_or(false),
+        open_marks_watched: config.open_marks_watched.unwrap_or(false),
+        download_dir,
+        download_template,
+        download_marks_watched: config.download_marks_watched.unwrap_or(false),
+        download_nfo: config.download_nfo.unwrap_or(false),
+        download_hook,
+        download_subtitles,
+        download_thumbnail: config.download_thumbnail.unwrap_or(false),
+        download_rate_limit,
+        max_concurrent_downloads,
+        prune_downloads_after_days,
+        playback_mode,
+        watch_threshold,
+        playback_speed,
+        subtitles,
+        request_interval,
         channels,
         data_dir,
-    })
+    }
+}
+
+fn positive(key: &'static str, value: Option<usize>) -> Result<Option<NonZeroUsize>, ConfigError> {
+    value
+        .map(|n| {
+            NonZeroUsize::new(n).ok_or(ConfigError::InvalidSetting {
+                key,
+                reason: "must be at least 1".to_string(),
+            })
+        })
+        .transpose()
+}
+
+/// A rate such as `500K` or `2.5M`, in bytes per second; `K`, `M` and `G`
+/// are powers of 1024, as yt-dlp reads them.
+fn rate_limit(raw: &str) -> Result<u64, String> {
+    let raw = raw.trim();
+    let (number, unit) = match raw.char_indices().last() {
+        Some((at, unit)) if unit.is_ascii_alphabetic() => (&raw[..at], unit),
+        _ => (raw, 'B'),
+    };
+    let multiplier: u64 = match unit.to_ascii_uppercase() {
+        'B' => 1,
+        'K' => 1 << 10,
+        'M' => 1 << 20,
+        'G' => 1 << 30,
+        _ => return Err(format!("unknown unit '{unit}' (expected e.g. 500K or 2M)")),
+    };
+    let amount: f64 = number
+        .parse()
+        .map_err(|_| format!("'{raw}' is not a rate (expected e.g. 500K or 2M)"))?;
+    let bytes = (amount * multiplier as f64) as u64;
+    if amount.is_finite() && bytes > 0 {
+        Ok(bytes)
+    } else {
+        Err("must be more than 0".to_string())
+    }
+}
+
+fn title_patterns(raw: &Option<Vec<String>>) -> Result<TitlePatterns, TitlePatternError> {
+    TitlePatterns::parse(raw.as_deref().unwrap_or_default())
+}
+
+fn parse_channel_ref(entry: &ChannelEntry) -> Result<ChannelRef, String> {
+    match (&entry.id, &entry.handle, &entry.username) {
+        (Some(id), None, None) => ChannelId::parse_url_or_id(id, IdRules::YouTube)
+            .map(ChannelRef::Id)
+            .map_err(|e| e.to_string()),
+        (None, Some(handle), None) => ChannelHandle::parse(handle)
+            .map(ChannelRef::Handle)
+            .map_err(|e| e.to_string()),
+        (None, None, Some(username)) => LegacyUsername::parse(username)
+            .map(ChannelRef::Username)
+            .map_err(|e| e.to_string()),
+        _ => Err("set exactly one of id, handle, or username".to_string()),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::application::ports::FetchError;
     use std::path::PathBuf;
     use tempfile::TempDir;
 
     fn parse(toml_str: &str) -> Result<AppConfig, ConfigError> {
-        parse_config_str(toml_str, PathBuf::from("/tmp/test"))
+        parse_config_str(
+            toml_str,
+            PathBuf::from("/tmp/test"),
+            &Layers::default(),
+            false,
+        )
     }
 
     #[test]
@@ -135,11 +1406,106 @@ mod tests {
         let dir = TempDir::new().unwrap();
         let missing = dir.path().join("config.toml");
 
-        let result = load_config_from_path(&missing, dir.path().to_path_buf());
+        let result = load_config_from_path(&missing, dir.path().to_path_buf(), Vec::new(), false);
 
         assert!(matches!(result, Err(ConfigError::NotFound(p)) if p == missing));
     }
 
+    #[test]
+    fn config_flag_wins_over_environment() {
+        let default = PathBuf::from("/home/me/.config/blepo/config.toml");
+        let flag = Some(PathBuf::from("flag.toml"));
+        let env = Some(OsString::from("env.toml"));
+
+        assert_eq!(
+            config_path(flag, env.clone(), default.clone()),
+            PathBuf::from("flag.toml")
+        );
+        assert_eq!(
+            config_path(None, env, default.clone()),
+            PathBuf::from("env.toml")
+        );
+        assert_eq!(
+            config_path(None, Some(OsString::new()), default.clone()),
+            default
+        );
+    }
+
+    #[test]
+    fn data_dir_overrides_default() {
+        assert_eq!(parse("").unwrap().data_dir, PathBuf::from("/tmp/test"));
+        assert_eq!(
+            parse(r#"data_dir = "/srv/sync/blepo""#).unwrap().data_dir,
+            PathBuf::from("/srv/sync/blepo")
+        );
+        assert!(matches!(
+            parse(r#"data_dir = "blepo""#),
+            Err(ConfigError::InvalidSetting {
+                key: "data_dir",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn download_template_expands_home() {
+        assert_eq!(
+            download_template(
+                "~/Videos/{channel}/{title}.{ext}",
+                Some(Path::new("/home/me"))
+            ),
+            Ok("/home/me/Videos/%(channel)s/%(title)s.%(ext)s".to_string())
+        );
+        assert!(download_template("~/Videos/{title}.{ext}", None).is_err());
+    }
+
+    #[test]
+    fn channels_can_have_their_own_download_options() {
+        let config = parse(
+            "download_subtitles = \"en\"\n\n\
+             [[channels]]\nname = \"Archive\"\nid = \"UC0000000000000000000123\"\n\
+             download_template = \"/archive/{title}.{ext}\"\n\
+             download_thumbnail = true\n\n\
+             [[channels]]\nname = \"Other\"\nid = \"UC0000000000000000000456\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.channel_download_options(&MapResolver),
+            HashMap::from([(
+                ChannelId::parse("UC0000000000000000000123").unwrap(),
+                DownloadOptions {
+                    template: "/archive/%(title)s.%(ext)s".to_string(),
+                    subtitles: Some(Subtitles::parse("en").unwrap()),
+                    thumbnail: true,
+                }
+            )])
+        );
+        assert!(matches!(
+            parse(
+                "[[channels]]\nid = \"UC0000000000000000000123\"\ndownload_subtitles = \"en;de\"\n"
+            ),
+            Err(ConfigError::InvalidChannel { .. })
+        ));
+        assert!(matches!(
+            parse(
+                "[[channels]]\nid = \"UC0000000000000000000123\"\ndownload_template = \"{what}\"\n"
+            ),
+            Err(ConfigError::InvalidChannel { .. })
+        ));
+    }
+
+    #[test]
+    fn data_dir_expands_home() {
+        let home = Path::new("/home/me");
+
+        assert_eq!(
+            expand_data_dir("~/Sync/blepo", Some(home)),
+            Ok(PathBuf::from("/home/me/Sync/blepo"))
+        );
+        assert!(expand_data_dir("~/Sync", None).is_err());
+    }
+
     #[test]
     fn load_config_reads_and_parses_file() {
         let dir = TempDir::new().unwrap();
@@ -151,69 +1517,1251 @@ mod tests {
 
             [[channels]]
             name = "Test"
-            id = "UC123"
+            id = "UC0000000000000000000123"
             "#,
         )
         .unwrap();
 
-        let config = load_config_from_path(&config_path, dir.path().to_path_buf()).unwrap();
+        let config =
+            load_config_from_path(&config_path, dir.path().to_path_buf(), Vec::new(), false)
+                .unwrap();
 
-        assert_eq!(config.fetch_window_days.as_i64(), 3);
+        assert_eq!(config.fetch_window, FetchWindow::from_days(3).unwrap());
         assert_eq!(config.channels.len(), 1);
         assert_eq!(config.data_dir, dir.path());
     }
 
     #[test]
-    fn load_config_rejects_invalid_file_content() {
-        let dir = TempDir::new().unwrap();
-        let config_path = dir.path().join("config.toml");
-        std::fs::write(&config_path, "not valid [[[").unwrap();
-
-        let result = load_config_from_path(&config_path, dir.path().to_path_buf());
+    fn check_reports_every_problem() {
+        let toml = r#"
+            fetch_window_days = 0
+            date_style = "sideways"
+            colour = "red"
 
-        assert!(matches!(result, Err(ConfigError::InvalidToml(_))));
-    }
+            [[channels]]
+            name = "Good"
+            id = "UC0000000000000000000001"
 
-    #[test]
-    fn parses_valid_config() {
-        let toml = r#"
-            fetch_window_days = 14
+            [[channels]]
+            name = "Bad"
+            id = "not-a-channel"
 
             [[channels]]
-            name = "Test Channel"
-            id = "UC123"
+            name = "good"
+            id = "UC0000000000000000000002"
 
             [[channels]]
-            name = "Another Channel"
-            id = "UC456"
+            name = "Again"
+            id = "UC0000000000000000000001"
         "#;
 
-        let config = parse(toml).unwrap();
+        let problems: Vec<String> =
+            check_config_str(toml, PathBuf::from("/tmp/test"), &Layers::default())
+                .iter()
+                .map(ToString::to_string)
+                .collect();
 
-        assert_eq!(config.fetch_window_days.as_i64(), 14);
-        assert_eq!(config.channels.len(), 2);
-        assert_eq!(config.channels[0].name, "Test Channel");
-        assert_eq!(config.channels[0].id.to_string(), "UC123");
+        assert_eq!(problems.len(), 6);
+        assert_eq!(problems[0], "unknown config key `colour`");
+        assert!(problems[1].starts_with("invalid fetch window"));
+        assert!(problems[2].starts_with("invalid channel \"Bad\""));
+        assert!(problems[3].starts_with("invalid date_style"));
+        assert_eq!(problems[4], "more than one channel is named \"good\"");
+        assert_eq!(
+            problems[5],
+            "channel \"Again\" is the same channel as \"Good\""
+        );
     }
 
     #[test]
-    fn uses_default_fetch_window() {
-        let toml = r#"
-            [[channels]]
-            name = "Test"
-            id = "UC123"
-        "#;
-
-        let config = parse(toml).unwrap();
+    fn check_finds_nothing_in_a_valid_config() {
+        let toml = "[[channels]]\nname = \"Test\"\nid = \"UC0000000000000000000123\"";
 
-        assert_eq!(config.fetch_window_days.as_i64(), DEFAULT_FETCH_WINDOW_DAYS);
+        assert!(check_config_str(toml, PathBuf::from("/tmp/test"), &Layers::default()).is_empty());
     }
 
     #[test]
-    fn handles_empty_channels() {
-        let config = parse("").unwrap();
+    fn load_config_merges_included_files() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("config.toml");
+        std::fs::create_dir(dir.path().join("channels")).unwrap();
+        std::fs::write(
+            &config_path,
+            r#"
+            include = ["channels/*.toml", "local.toml"]
+            fetch_window_days = 3
 
-        assert!(config.channels.is_empty());
+            [theme]
+            date = "red"
+
+            [[channels]]
+            name = "Main"
+            id = "UC0000000000000000000001"
+            "#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("channels/music.toml"),
+            "[[channels]]\nname = \"Music\"\nid = \"UC0000000000000000000002\"\ntags = [\"music\"]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("channels/rust.toml"),
+            "[[channels]]\nname = \"Rust\"\nid = \"UC0000000000000000000003\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("channels/notes.txt"), "not toml").unwrap();
+        std::fs::write(
+            dir.path().join("local.toml"),
+            "fetch_window = \"36h\"\n[theme]\nnew = \"none\"\n",
+        )
+        .unwrap();
+
+        let config =
+            load_config_from_path(&config_path, dir.path().to_path_buf(), Vec::new(), false)
+                .unwrap();
+
+        let names: Vec<String> = config.channels.iter().map(ChannelConfig::label).collect();
+        assert_eq!(names, ["Main", "Music", "Rust"]);
+        assert_eq!(
+            config.fetch_window.as_duration(),
+            chrono::Duration::hours(36)
+        );
+        assert_eq!(config.theme.date, Color::parse("red").unwrap());
+        assert_eq!(config.theme.new, None);
+    }
+
+    #[test]
+    fn included_files_cannot_include_more() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("config.toml");
+        std::fs::write(&config_path, "include = [\"more.toml\"]").unwrap();
+        std::fs::write(dir.path().join("more.toml"), "include = [\"config.toml\"]").unwrap();
+
+        let result =
+            load_config_from_path(&config_path, dir.path().to_path_buf(), Vec::new(), false);
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidSetting { key: "include", .. })
+        ));
+    }
+
+    #[test]
+    fn matches_wildcards() {
+        assert!(wildcard_matches("*.toml", "music.toml"));
+        assert!(wildcard_matches("ch*-*.toml", "channels-2.toml"));
+        assert!(!wildcard_matches("*.toml", "notes.txt"));
+        assert!(!wildcard_matches("*.toml", ".hidden.toml"));
+        assert!(!wildcard_matches("a*a", "a"));
+        assert!(wildcard_matches("local.toml", "local.toml"));
+    }
+
+    #[test]
+    fn load_config_rejects_invalid_file_content() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("config.toml");
+        std::fs::write(&config_path, "not valid [[[").unwrap();
+
+        let result =
+            load_config_from_path(&config_path, dir.path().to_path_buf(), Vec::new(), false);
+
+        assert!(matches!(result, Err(ConfigError::InvalidToml(_))));
+    }
+
+    #[test]
+    fn parses_valid_config() {
+        let toml = r#"
+            fetch_window_days = 14
+
+            [[channels]]
+            name = "Test Channel"
+            id = "UC0000000000000000000123"
+
+            [[channels]]
+            name = "Another Channel"
+            id = "UC0000000000000000000456"
+        "#;
+
+        let config = parse(toml).unwrap();
+
+        assert_eq!(config.fetch_window, FetchWindow::from_days(14).unwrap());
+        assert_eq!(config.channels.len(), 2);
+        assert_eq!(config.channels[0].name.as_deref(), Some("Test Channel"));
+        assert_eq!(
+            config.channels[0].reference.to_string(),
+            "UC0000000000000000000123"
+        );
+    }
+
+    #[test]
+    fn channel_id_can_be_the_channel_link() {
+        let toml = r#"
+            [[channels]]
+            name = "Pasted"
+            id = "https://www.youtube.com/channel/UC0000000000000000000123/videos"
+        "#;
+
+        let config = parse(toml).unwrap();
+
+        assert_eq!(
+            config.channels[0].reference,
+            ChannelRef::Id(ChannelId::parse("UC0000000000000000000123").unwrap())
+        );
+    }
+
+    #[test]
+    fn uses_default_fetch_window() {
+        let toml = r#"
+            [[channels]]
+            name = "Test"
+            id = "UC0000000000000000000123"
+        "#;
+
+        let config = parse(toml).unwrap();
+
+        assert_eq!(
+            config.fetch_window,
+            FetchWindow::from_days(DEFAULT_FETCH_WINDOW_DAYS).unwrap()
+        );
+    }
+
+    #[test]
+    fn parses_fetch_window_duration() {
+        let config = parse(r#"fetch_window = "36h""#).unwrap();
+
+        assert_eq!(
+            config.fetch_window.as_duration(),
+            chrono::Duration::hours(36)
+        );
+        assert!(matches!(
+            parse(r#"fetch_window = "soon""#),
+            Err(ConfigError::InvalidFetchWindow(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_both_fetch_window_keys() {
+        let result = parse(
+            r#"
+            fetch_window = "2w"
+            fetch_window_days = 14
+        "#,
+        );
+
+        assert!(matches!(result, Err(ConfigError::InvalidFetchWindow(_))));
+    }
+
+    #[test]
+    fn environment_overrides_settings() {
+        let env = |name: &str| match name {
+            "BLEPO_FETCH_WINDOW_DAYS" => Some("3".to_string()),
+            "BLEPO_ENRICH" => Some("true".to_string()),
+            "BLEPO_DATE_FORMAT" => Some("%d/%m".to_string()),
+            "BLEPO_MPV_ARGS" => Some(r#"["--fs"]"#.to_string()),
+            "BLEPO_PROXY" => Some(String::new()),
+            _ => None,
+        };
+        let layers = Layers {
+            overrides: env_overrides(env),
+            ..Layers::default()
+        };
+
+        let config = parse_config_str(
+            r#"
+            fetch_window = "2w"
+            enrich = false
+            "#,
+            PathBuf::from("/tmp/test"),
+            &layers,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(layers.overrides.len(), 4);
+        assert_eq!(config.fetch_window, FetchWindow::from_days(3).unwrap());
+        assert!(config.enrich);
+        assert_eq!(config.date_format.as_str(), "%d/%m");
+        assert_eq!(config.mpv_args, ["--fs"]);
+    }
+
+    #[test]
+    fn strict_mode_rejects_unknown_keys_with_suggestions() {
+        let toml = r#"
+            fetch_windows_days = 3
+
+            [theme]
+            chanel = "red"
+
+            [[channels]]
+            name = "Test"
+            id = "UC0000000000000000000123"
+            tag = ["music"]
+            colour = "blue"
+        "#;
+
+        let Err(ConfigError::UnknownKeys(keys)) =
+            parse_config_str(toml, PathBuf::from("/tmp/test"), &Layers::default(), true)
+        else {
+            panic!("expected unknown keys");
+        };
+
+        assert_eq!(
+            keys,
+            [
+                "unknown config key `fetch_windows_days`, did you mean `fetch_window_days`?",
+                "unknown config key `chanel` in [theme], did you mean `channel`?",
+                "unknown config key `tag` in channel #1, did you mean `tags`?",
+                "unknown config key `colour` in channel #1",
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_keys_only_warn_by_default() {
+        let config = parse("fetch_windows_days = 3").unwrap();
+
+        assert_eq!(
+            config.fetch_window,
+            FetchWindow::from_days(DEFAULT_FETCH_WINDOW_DAYS).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_environment_values() {
+        let layers = Layers {
+            overrides: vec![("fetch_concurrency", "three".to_string())],
+            ..Layers::default()
+        };
+
+        let result = parse_config_str("", PathBuf::from("/tmp/test"), &layers, false);
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidSetting {
+                key: "fetch_concurrency",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_download_limits() {
+        let config = parse("download_rate_limit = \"2M\"\nmax_concurrent_downloads = 3").unwrap();
+
+        assert_eq!(config.download_rate_limit, Some(2 * 1024 * 1024));
+        assert_eq!(config.max_concurrent_downloads.get(), 3);
+        assert_eq!(parse("").unwrap().download_rate_limit, None);
+        assert_eq!(
+            parse("").unwrap().max_concurrent_downloads,
+            NonZeroUsize::MIN
+        );
+        assert!(matches!(
+            parse("max_concurrent_downloads = 0"),
+            Err(ConfigError::InvalidSetting {
+                key: "max_concurrent_downloads",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_pruning_settings() {
+        let config = parse(
+            "prune_downloads_after_days = 30\n\n\
+             [[channels]]\nname = \"Kept\"\nid = \"UC0000000000000000000123\"\nkeep_downloads = true\n\n\
+             [[channels]]\nname = \"Other\"\nid = \"UC0000000000000000000456\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.prune_downloads_after_days, NonZeroUsize::new(30));
+        assert_eq!(
+            config.kept_channels(&MapResolver),
+            HashSet::from([ChannelId::parse("UC0000000000000000000123").unwrap()])
+        );
+        assert_eq!(parse("").unwrap().prune_downloads_after_days, None);
+        assert!(matches!(
+            parse("prune_downloads_after_days = 0"),
+            Err(ConfigError::InvalidSetting {
+                key: "prune_downloads_after_days",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_rate_limits() {
+        assert_eq!(rate_limit("500K"), Ok(500 * 1024));
+        assert_eq!(rate_limit("1.5m"), Ok(1536 * 1024));
+        assert_eq!(rate_limit("4096"), Ok(4096));
+        assert!(rate_limit("0K").is_err());
+        assert!(rate_limit("2X").is_err());
+        assert!(rate_limit("fast").is_err());
+        assert!(rate_limit("").is_err());
+    }
+
+    #[test]
+    fn parses_fetch_concurrency() {
+        let config = parse("fetch_concurrency = 3").unwrap();
+
+        assert_eq!(config.fetch_concurrency.get(), 3);
+    }
+
+    #[test]
+    fn uses_default_fetch_concurrency() {
+        let config = parse("").unwrap();
+
+        assert_eq!(config.fetch_concurrency.get(), DEFAULT_FETCH_CONCURRENCY);
+    }
+
+    #[test]
+    fn rejects_zero_fetch_concurrency() {
+        let result = parse("fetch_concurrency = 0");
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidSetting {
+                key: "fetch_concurrency",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn uses_default_retry_policy() {
+        let config = parse("").unwrap();
+
+        assert_eq!(config.retry, RetryPolicy::default());
+    }
+
+    #[test]
+    fn parses_retry_section_with_partial_overrides() {
+        let toml = r#"
+            [retry]
+            retries = 5
+            base_delay_ms = 100
+        "#;
+
+        let config = parse(toml).unwrap();
+
+        assert_eq!(config.retry.retries, 5);
+        assert_eq!(config.retry.base_delay, Duration::from_millis(100));
+        assert_eq!(config.retry.jitter, RetryPolicy::default().jitter);
+    }
+
+    #[test]
+    fn parses_global_and_channel_title_filters() {
+        let config = parse(
+            r#"
+exclude_titles = ["(?i)trailer"]
+
+[[channels]]
+name = "Podcast"
+id = "UC0000000000000000000123"
+include_titles = ["^Episode"]
+"#,
+        )
+        .unwrap();
+
+        assert!(!config.title_filter.allows("New TRAILER"));
+        let channel = &config.channels[0].title_filter;
+        assert!(channel.allows("Episode 4"));
+        assert!(!channel.allows("Clip"));
+    }
+
+    #[test]
+    fn parses_channel_tags() {
+        let config = parse(
+            r#"
+[[channels]]
+name = "Band"
+id = "UC0000000000000000000123"
+tags = ["music", "live"]
+
+[[channels]]
+name = "Untagged"
+id = "UC0000000000000000000456"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.channels[0].tags.len(), 2);
+        assert!(config.channels[0].tags[0].matches("Music"));
+        assert!(config.channels[1].tags.is_empty());
+        assert!(matches!(
+            parse("[[channels]]\nname = \"Bad\"\nid = \"UC0000000000000000000001\"\ntags = [\"two words\"]"),
+            Err(ConfigError::InvalidChannel { .. })
+        ));
+    }
+
+    #[test]
+    fn parses_channel_alias() {
+        let config =
+            parse("[[channels]]\nname = \"The Long Name\"\nid = \"UC0000000000000000000123\"\nalias = \"TLN\"")
+                .unwrap();
+
+        assert_eq!(config.channels[0].alias.as_deref(), Some("TLN"));
+        assert!(matches!(
+            parse("[[channels]]\nname = \"Bad\"\nid = \"UC0000000000000000000001\"\nalias = \" \""),
+            Err(ConfigError::InvalidChannel { .. })
+        ));
+    }
+
+    #[test]
+    fn parses_channel_players() {
+        let config = parse(
+            r#"
+[[channels]]
+name = "Music"
+id = "UC0000000000000000000123"
+player_args = ["--no-video"]
+
+[[channels]]
+name = "Protected"
+id = "UC0000000000000000000456"
+player = "browser"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.channels[0].player,
+            Some(ChannelPlayer::Mpv(vec!["--no-video".to_string()]))
+        );
+        assert_eq!(config.channels[1].player, Some(ChannelPlayer::Browser));
+        assert!(matches!(
+            parse(
+                "[[channels]]\nname = \"Bad\"\nid = \"UC0000000000000000000001\"\nplayer = \"vlc\""
+            ),
+            Err(ConfigError::InvalidChannel { .. })
+        ));
+    }
+
+    #[test]
+    fn auto_download_is_off_unless_set_on_a_channel() {
+        let config = parse(
+            "[[channels]]\nname = \"Archive\"\nid = \"UC0000000000000000000123\"\nauto_download = true\n\n\
+             [[channels]]\nname = \"Other\"\nid = \"UC0000000000000000000456\"",
+        )
+        .unwrap();
+
+        assert!(config.channels[0].auto_download);
+        assert!(!config.channels[1].auto_download);
+    }
+
+    #[test]
+    fn parses_channel_snooze_date() {
+        let config = parse(
+            "[[channels]]\nname = \"Spoilers\"\nid = \"UC0000000000000000000123\"\nsnoozed_until = \"2024-03-01\"",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.channels[0].snoozed_until,
+            NaiveDate::from_ymd_opt(2024, 3, 1)
+        );
+        assert!(matches!(
+            parse("[[channels]]\nname = \"Bad\"\nid = \"UC0000000000000000000001\"\nsnoozed_until = \"30d\""),
+            Err(ConfigError::InvalidChannel { .. })
+        ));
+    }
+
+    #[test]
+    fn parses_global_and_channel_duration_ranges() {
+        let config = parse(
+            r#"
+max_duration = "3h"
+
+[[channels]]
+name = "Clips"
+id = "UC0000000000000000000123"
+min_duration = "2m"
+"#,
+        )
+        .unwrap();
+
+        let eight_hours = Some(Duration::from_secs(8 * 3600));
+        let one_minute = Some(Duration::from_secs(60));
+        assert!(!config.duration_range.allows(eight_hours));
+        assert!(!config.channels[0].duration_range.allows(one_minute));
+    }
+
+    #[test]
+    fn rejects_invalid_duration() {
+        assert!(matches!(
+            parse(r#"min_duration = "soon""#),
+            Err(ConfigError::InvalidSetting { .. })
+        ));
+    }
+
+    #[test]
+    fn parses_live_handling() {
+        assert_eq!(parse("").unwrap().live_handling, LiveHandling::Section);
+        assert_eq!(
+            parse(r#"live_handling = "hide""#).unwrap().live_handling,
+            LiveHandling::Hide
+        );
+        assert!(matches!(
+            parse(r#"live_handling = "later""#),
+            Err(ConfigError::InvalidSetting {
+                key: "live_handling",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_ready_alert() {
+        assert_eq!(parse("").unwrap().ready_alert, ReadyAlert::Off);
+        assert_eq!(
+            parse(r#"ready_alert = "desktop""#).unwrap().ready_alert,
+            ReadyAlert::Desktop
+        );
+        assert!(matches!(
+            parse(r#"ready_alert = "loud""#),
+            Err(ConfigError::InvalidSetting {
+                key: "ready_alert",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_date_style() {
+        assert_eq!(parse("").unwrap().date_style, DateStyle::Absolute);
+        assert_eq!(
+            parse(r#"date_style = "both""#).unwrap().date_style,
+            DateStyle::Both
+        );
+        assert!(matches!(
+            parse(r#"date_style = "fuzzy""#),
+            Err(ConfigError::InvalidSetting {
+                key: "date_style",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_date_format() {
+        assert_eq!(parse("").unwrap().date_format, DateFormat::default());
+        assert_eq!(
+            parse(r#"date_format = "%d/%m""#)
+                .unwrap()
+                .date_format
+                .as_str(),
+            "%d/%m"
+        );
+        assert!(matches!(
+            parse(r#"date_format = "%Q""#),
+            Err(ConfigError::InvalidSetting {
+                key: "date_format",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_theme_over_defaults() {
+        let config = parse(
+            r#"
+            [theme]
+            channel = "bright_magenta"
+            new = "none"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.theme.channel,
+            Color::parse("bright_magenta").unwrap()
+        );
+        assert_eq!(config.theme.date, Theme::default().date);
+        assert_eq!(config.theme.new, None);
+    }
+
+    #[test]
+    fn rejects_unknown_theme_color() {
+        assert!(matches!(
+            parse("[theme]\ndate = \"teal\""),
+            Err(ConfigError::InvalidSetting {
+                key: "theme.date",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_muted_words() {
+        let config = parse(r#"muted_words = ["Reaction"]"#).unwrap();
+
+        assert!(config.muted_words.mutes("reaction video"));
+    }
+
+    #[test]
+    fn rejects_invalid_global_title_pattern() {
+        assert!(matches!(
+            parse(r#"exclude_titles = ["(oops"]"#),
+            Err(ConfigError::InvalidSetting {
+                key: "exclude_titles",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_channel_title_pattern() {
+        let result = parse(
+            r#"
+[[channels]]
+name = "Bad"
+id = "UC0000000000000000000123"
+include_titles = ["[a-"]
+"#,
+        );
+
+        match result {
+            Err(ConfigError::InvalidChannel { name, reason }) => {
+                assert_eq!(name, "Bad");
+                assert!(reason.starts_with("include_titles:"));
+            }
+            other => panic!("expected InvalidChannel, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn enrich_defaults_to_false() {
+        assert!(!parse("").unwrap().enrich);
+        assert!(parse("enrich = true").unwrap().enrich);
+    }
+
+    #[test]
+    fn thumbnails_default_to_true() {
+        assert!(parse("").unwrap().thumbnails);
+        assert!(!parse("thumbnails = false").unwrap().thumbnails);
+    }
+
+    #[test]
+    fn include_shorts_defaults_to_false() {
+        assert!(!parse("").unwrap().include_shorts);
+        assert!(parse("include_shorts = true").unwrap().include_shorts);
+    }
+
+    #[test]
+    fn parses_shorts_concurrency() {
+        let config = parse("shorts_concurrency = 2").unwrap();
+
+        assert_eq!(config.shorts_concurrency.get(), 2);
+    }
+
+    #[test]
+    fn rejects_zero_shorts_concurrency() {
+        assert!(matches!(
+            parse("shorts_concurrency = 0"),
+            Err(ConfigError::InvalidSetting {
+                key: "shorts_concurrency",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_fetch_ttl() {
+        let config = parse("fetch_ttl_minutes = 30").unwrap();
+
+        assert_eq!(config.fetch_ttl, Duration::from_secs(30 * 60));
+    }
+
+    #[test]
+    fn zero_fetch_ttl_disables_reuse() {
+        let config = parse("fetch_ttl_minutes = 0").unwrap();
+
+        assert_eq!(config.fetch_ttl, Duration::ZERO);
+    }
+
+    #[test]
+    fn parses_request_interval() {
+        let config = parse("request_interval_ms = 250").unwrap();
+
+        assert_eq!(config.request_interval, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn uses_default_request_interval() {
+        let config = parse("").unwrap();
+
+        assert_eq!(
+            config.request_interval,
+            Duration::from_millis(DEFAULT_REQUEST_INTERVAL_MS)
+        );
+    }
+
+    #[test]
+    fn channel_ids_follow_youtube_rules() {
+        let config = r#"
+            [[channels]]
+            name = "Mirror"
+            id = "UCmirror"
+        "#;
+
+        let err = parse(config).unwrap_err().to_string();
+        assert!(
+            err.contains("'UCmirror' is not a YouTube channel ID"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn parses_cookies() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let config = parse(&format!("cookies_file = {:?}", file.path())).unwrap();
+        assert_eq!(
+            config.cookies,
+            Some(Cookies::File(file.path().to_path_buf()))
+        );
+
+        let config = parse(r#"cookies_from_browser = "firefox:default""#).unwrap();
+        assert_eq!(
+            config.cookies,
+            Some(Cookies::Browser("firefox:default".to_string()))
+        );
+        assert_eq!(parse("").unwrap().cookies, None);
+    }
+
+    #[test]
+    fn rejects_bad_cookie_settings() {
+        let err = |toml: &str| parse(toml).unwrap_err().to_string();
+
+        assert!(err(r#"cookies_file = "/nonexistent/cookies.txt""#).contains("no such file"));
+        assert!(err(r#"cookies_from_browser = "lynx""#).contains("unsupported browser 'lynx'"));
+        assert!(err(r#"
+            cookies_file = "/tmp/cookies.txt"
+            cookies_from_browser = "firefox"
+            "#)
+        .contains("not both"));
+    }
+
+    #[test]
+    fn parses_mpv_args() {
+        assert!(parse("").unwrap().mpv_args.is_empty());
+        assert_eq!(
+            parse(r#"mpv_args = ["--fs", "--ytdl-format=bestvideo[height<=1080]+bestaudio"]"#)
+                .unwrap()
+                .mpv_args,
+            ["--fs", "--ytdl-format=bestvideo[height<=1080]+bestaudio"]
+        );
+    }
+
+    #[test]
+    fn rejects_mpv_args_that_are_not_options() {
+        assert!(matches!(
+            parse(r#"mpv_args = ["--fs", "video.mkv"]"#),
+            Err(ConfigError::InvalidSetting {
+                key: "mpv_args",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn sponsorblock_is_opt_in() {
+        assert!(!parse("").unwrap().sponsorblock);
+        assert!(parse("sponsorblock = true").unwrap().sponsorblock);
+    }
+
+    #[test]
+    fn opening_in_browser_does_not_mark_watched_by_default() {
+        assert!(!parse("").unwrap().open_marks_watched);
+        assert!(
+            parse("open_marks_watched = true")
+                .unwrap()
+                .open_marks_watched
+        );
+    }
+
+    #[test]
+    fn parses_download_settings() {
+        let config = parse(
+            r#"
+            download_dir = "/srv/videos"
+            download_template = "%(title)s.%(ext)s"
+            download_marks_watched = true
+            download_nfo = true
+            download_hook = "rsync -a \"$BLEPO_PATH\" nas:videos/"
+            download_subtitles = "en,auto"
+            download_thumbnail = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.download_dir, PathBuf::from("/srv/videos"));
+        assert_eq!(config.download_template, "%(title)s.%(ext)s");
+        assert!(config.download_marks_watched);
+        assert!(config.download_nfo);
+        assert_eq!(
+            config.download_subtitles,
+            Some(Subtitles::parse("en,auto").unwrap())
+        );
+        assert!(config.download_thumbnail);
+        assert!(!parse("").unwrap().download_thumbnail);
+        assert!(matches!(
+            parse(r#"download_subtitles = "en;de""#),
+            Err(ConfigError::InvalidSetting {
+                key: "download_subtitles",
+                ..
+            })
+        ));
+        assert!(!parse("").unwrap().download_marks_watched);
+        assert!(!parse("").unwrap().download_nfo);
+        assert_eq!(
+            config.download_hook.as_deref(),
+            Some(r#"rsync -a "$BLEPO_PATH" nas:videos/"#)
+        );
+        assert!(matches!(
+            parse(r#"download_hook = " ""#),
+            Err(ConfigError::InvalidSetting {
+                key: "download_hook",
+                ..
+            })
+        ));
+        assert_eq!(
+            parse("").unwrap().download_template,
+            DEFAULT_DOWNLOAD_TEMPLATE
+        );
+        assert!(matches!(
+            parse(r#"download_dir = "videos""#),
+            Err(ConfigError::InvalidSetting {
+                key: "download_dir",
+                ..
+            })
+        ));
+        assert_eq!(
+            parse(r#"download_template = "/srv/{channel}/{date}-{title}.{ext}""#)
+                .unwrap()
+                .download_template,
+            "/srv/%(channel)s/%(upload_date>%Y-%m-%d)s-%(title)s.%(ext)s"
+        );
+        assert!(matches!(
+            parse(r#"download_template = "{name}.{ext}""#),
+            Err(ConfigError::InvalidSetting {
+                key: "download_template",
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse(r#"download_template = " ""#),
+            Err(ConfigError::InvalidSetting {
+                key: "download_template",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_playback_mode() {
+        assert_eq!(parse("").unwrap().playback_mode, PlaybackMode::Detached);
+        assert_eq!(
+            parse(r#"playback_mode = "attached""#)
+                .unwrap()
+                .playback_mode,
+            PlaybackMode::Attached
+        );
+        assert!(matches!(
+            parse(r#"playback_mode = "background""#),
+            Err(ConfigError::InvalidSetting {
+                key: "playback_mode",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_watched_threshold_percent() {
+        assert_eq!(parse("").unwrap().watch_threshold, None);
+        assert_eq!(
+            parse("watched_threshold_percent = 80")
+                .unwrap()
+                .watch_threshold
+                .map(WatchThreshold::percent),
+            Some(80)
+        );
+        assert!(matches!(
+            parse("watched_threshold_percent = 0"),
+            Err(ConfigError::InvalidSetting {
+                key: "watched_threshold_percent",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_playback_speed_and_subtitles() {
+        let config = parse("playback_speed = 1.5\nsubtitles = \"en,auto\"").unwrap();
+
+        assert_eq!(config.playback_speed.map(PlaybackSpeed::as_f64), Some(1.5));
+        assert_eq!(config.subtitles.unwrap().languages(), ["en"]);
+        assert!(parse("").unwrap().subtitles.is_none());
+        assert!(matches!(
+            parse("playback_speed = 10"),
+            Err(ConfigError::InvalidSetting {
+                key: "playback_speed",
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse(r#"subtitles = "auto""#),
+            Err(ConfigError::InvalidSetting {
+                key: "subtitles",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_notify_url() {
+        let config = parse(r#"notify_url = "https://ntfy.sh/my-videos""#).unwrap();
+        assert_eq!(
+            config.notify_url,
+            Some(WebhookUrl::parse("https://ntfy.sh/my-videos").unwrap())
+        );
+
+        assert!(matches!(
+            parse(r#"notify_url = "ntfy.sh/my-videos""#),
+            Err(ConfigError::InvalidSetting {
+                key: "notify_url",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_proxy() {
+        let config = parse(r#"proxy = "socks5://127.0.0.1:1080""#).unwrap();
+
+        assert_eq!(config.proxy.unwrap().as_str(), "socks5://127.0.0.1:1080");
+    }
+
+    #[test]
+    fn rejects_invalid_proxy() {
+        let result = parse(r#"proxy = "127.0.0.1:1080""#);
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidSetting { key: "proxy", .. })
+        ));
+    }
+
+    #[test]
+    fn handles_empty_channels() {
+        let config = parse("").unwrap();
+
+        assert!(config.channels.is_empty());
+    }
+
+    #[test]
+    fn streams_are_excluded_by_default() {
+        let toml = r#"
+            [[channels]]
+            name = "Test"
+            id = "UC0000000000000000000123"
+        "#;
+
+        let config = parse(toml).unwrap();
+
+        assert!(!config.channels[0].include_streams);
+    }
+
+    #[test]
+    fn channel_inherits_global_include_streams() {
+        let toml = r#"
+            include_streams = true
+
+            [[channels]]
+            name = "Test"
+            id = "UC0000000000000000000123"
+        "#;
+
+        let config = parse(toml).unwrap();
+
+        assert!(config.channels[0].include_streams);
+    }
+
+    #[test]
+    fn channel_include_streams_overrides_global() {
+        let toml = r#"
+            include_streams = true
+
+            [[channels]]
+            name = "Uploads Only"
+            id = "UC0000000000000000000123"
+            include_streams = false
+
+            [[channels]]
+            name = "Streamer"
+            id = "UC0000000000000000000456"
+        "#;
+
+        let config = parse(toml).unwrap();
+
+        assert!(!config.channels[0].include_streams);
+        assert!(config.channels[1].include_streams);
+    }
+
+    #[test]
+    fn parses_channel_handle() {
+        let toml = r#"
+            [[channels]]
+            name = "Creator"
+            handle = "@SomeCreator"
+        "#;
+
+        let config = parse(toml).unwrap();
+
+        assert_eq!(
+            config.channels[0].reference,
+            ChannelRef::Handle(ChannelHandle::parse("@SomeCreator").unwrap())
+        );
+    }
+
+    #[test]
+    fn parses_legacy_username() {
+        let toml = r#"
+            [[channels]]
+            name = "Old School"
+            username = "GoogleDevelopers"
+        "#;
+
+        let config = parse(toml).unwrap();
+
+        assert_eq!(
+            config.channels[0].reference.to_string(),
+            "user/GoogleDevelopers"
+        );
+    }
+
+    #[test]
+    fn rejects_handle_without_at() {
+        let toml = r#"
+            [[channels]]
+            name = "Creator"
+            handle = "SomeCreator"
+        "#;
+
+        let result = parse(toml);
+
+        assert!(matches!(result, Err(ConfigError::InvalidChannel { .. })));
+    }
+
+    #[test]
+    fn rejects_channel_with_both_id_and_handle() {
+        let toml = r#"
+            [[channels]]
+            name = "Ambiguous"
+            id = "UC0000000000000000000123"
+            handle = "@creator"
+        "#;
+
+        let result = parse(toml);
+
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidChannel { reason, .. }) if reason.contains("exactly one")
+        ));
+    }
+
+    #[test]
+    fn rejects_channel_without_any_identifier() {
+        let toml = r#"
+            [[channels]]
+            name = "Nothing"
+        "#;
+
+        let result = parse(toml);
+
+        assert!(matches!(result, Err(ConfigError::InvalidChannel { .. })));
+    }
+
+    struct MapResolver;
+
+    impl ChannelResolver for MapResolver {
+        fn resolve(&self, reference: &ChannelRef) -> Result<ChannelId, FetchError> {
+            match reference {
+                ChannelRef::Id(id) => Ok(id.clone()),
+                ChannelRef::Handle(h) if h.to_string() == "@known" => {
+                    Ok(ChannelId::parse("UCknown00000000000000000").unwrap())
+                }
+                _ => Err(FetchError::HttpError(404)),
+            }
+        }
+    }
+
+    #[test]
+    fn resolve_channels_maps_handles_and_skips_failures() {
+        let toml = r#"
+            [[channels]]
+            name = "Direct"
+            id = "UC0000000000000000000123"
+
+            [[channels]]
+            name = "Known"
+            handle = "@known"
+
+            [[channels]]
+            name = "Gone"
+            handle = "@gone"
+        "#;
+
+        let channels = parse(toml)
+            .unwrap()
+            .resolve_channels(&MapResolver, &HashMap::new());
+
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0].id.to_string(), "UC0000000000000000000123");
+        assert_eq!(channels[1].name, "Known");
+        assert_eq!(channels[1].id.to_string(), "UCknown00000000000000000");
+    }
+
+    #[test]
+    fn resolve_known_channels_skips_the_rest() {
+        let toml = r#"
+            [[channels]]
+            name = "Known"
+            handle = "@known"
+
+            [[channels]]
+            name = "Gone"
+            handle = "@gone"
+        "#;
+
+        let channels = parse(toml)
+            .unwrap()
+            .resolve_known_channels(&MapResolver, &HashMap::new());
+
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0].name, "Known");
+    }
+
+    #[test]
+    fn unnamed_channels_take_their_learned_name() {
+        let toml = r#"
+            [[channels]]
+            id = "UC0000000000000000000123"
+
+            [[channels]]
+            handle = "@known"
+        "#;
+        let learned = HashMap::from([(
+            ChannelId::parse("UCknown00000000000000000").unwrap(),
+            "Known".to_string(),
+        )]);
+
+        let channels = parse(toml)
+            .unwrap()
+            .resolve_channels(&MapResolver, &learned);
+
+        assert_eq!(channels[0].name, "UC0000000000000000000123");
+        assert_eq!(channels[1].name, "Known");
+        assert!(channels.iter().all(|c| c.name_from_feed));
     }
 
     #[test]
@@ -230,7 +2778,7 @@ mod tests {
 
             [[channels]]
             name = "Test"
-            id = "UC123"
+            id = "UC0000000000000000000123"
         "#;
 
         let result = parse(toml);
@@ -245,7 +2793,7 @@ mod tests {
 
             [[channels]]
             name = "Test"
-            id = "UC123"
+            id = "UC0000000000000000000123"
         "#;
 
         let result = parse(toml);
@@ -0,0 +1,81 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use super::config::ChannelConfig;
+use crate::application::ports::{ChannelList, StoreError, Uploader};
+use crate::domain::channel::{ChannelId, ChannelRef};
+
+/// The channels of the main config file. New ones are appended as
+/// `[[channels]]` entries, which leaves its comments and layout alone.
+pub struct ConfigChannelList {
+    path: PathBuf,
+    ids: HashSet<ChannelId>,
+}
+
+impl ConfigChannelList {
+    /// Knows the channels configured by `id`; handles and usernames are not
+    /// resolved.
+    pub fn new(path: PathBuf, channels: &[ChannelConfig]) -> Self {
+        let ids = channels
+            .iter()
+            .filter_map(|channel| match &channel.reference {
+                ChannelRef::Id(id) => Some(id.clone()),
+                ChannelRef::Handle(_) | ChannelRef::Username(_) => None,
+            })
+            .collect();
+        Self { path, ids }
+    }
+}
+
+impl ChannelList for ConfigChannelList {
+    fn contains(&self, id: &ChannelId) -> bool {
+        self.ids.contains(id)
+    }
+
+    fn add_channel(&self, uploader: &Uploader) -> Result<(), StoreError> {
+        let mut content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(StoreError::Read(format!("cannot read config: {e}"))),
+        };
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        content.push_str(&format!(
+            "[[channels]]\nname = {}\nid = {}\n",
+            toml::Value::from(uploader.name.as_str()),
+            toml::Value::from(uploader.id.to_string()),
+        ));
+        fs::write(&self.path, content)
+            .map_err(|e| StoreError::Write(format!("cannot write config: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn appends_channels_keeping_the_rest() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "# mine\nfetch_window_days = 3").unwrap();
+        let list = ConfigChannelList::new(path.clone(), &[]);
+
+        list.add_channel(&Uploader {
+            id: ChannelId::parse("UC123").unwrap(),
+            name: "Say \"hi\"".to_string(),
+        })
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# mine\nfetch_window_days = 3\n\n[[channels]]\nname = 'Say \"hi\"'\nid = \"UC123\"\n"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use std::path::{Path, PathBuf};
+
+/// Browsers yt-dlp can read cookies from.
+const BROWSERS: [&str; 9] = [
+    "brave", "chrome", "chromium", "edge", "firefox", "opera", "safari", "vivaldi", "whale",
+];
+
+/// The cookies yt-dlp sends to YouTube, so age-restricted videos and
+/// members-only uploads the user is signed in for can be listed, played and
+/// downloaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cookies {
+    /// A Netscape-format cookies file.
+    File(PathBuf),
+    /// A browser's own cookie store, as yt-dlp's `--cookies-from-browser`
+    /// takes it: `BROWSER[+KEYRING][:PROFILE][::CONTAINER]`.
+    Browser(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CookiesError(String);
+
+impl std::fmt::Display for CookiesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for CookiesError {}
+
+impl Cookies {
+    pub fn file(path: &Path) -> Result<Self, CookiesError> {
+        if path.is_file() {
+            Ok(Self::File(path.to_path_buf()))
+        } else {
+            Err(CookiesError(format!("no such file: {}", path.display())))
+        }
+    }
+
+    pub fn browser(spec: impl Into<String>) -> Result<Self, CookiesError> {
+        let spec = spec.into();
+        let browser = spec
+            .split(['+', ':'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if !BROWSERS.contains(&browser.as_str()) {
+            return Err(CookiesError(format!(
+                "unsupported browser '{browser}' (expected one of {})",
+                BROWSERS.join(", ")
+            )));
+        }
+        Ok(Self::Browser(spec))
+    }
+
+    /// yt-dlp's options for them.
+    pub fn ytdlp_args(&self) -> [String; 2] {
+        match self {
+            Cookies::File(path) => ["--cookies".to_string(), path.display().to_string()],
+            Cookies::Browser(spec) => ["--cookies-from-browser".to_string(), spec.clone()],
+        }
+    }
+
+    /// The same, for the yt-dlp mpv runs.
+    pub fn mpv_arg(&self) -> String {
+        match self {
+            Cookies::File(path) => {
+                format!("--ytdl-raw-options-append=cookies={}", path.display())
+            }
+            Cookies::Browser(spec) => {
+                format!("--ytdl-raw-options-append=cookies-from-browser={spec}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn file_must_exist() {
+        let file = NamedTempFile::new().unwrap();
+
+        assert_eq!(
+            Cookies::file(file.path()),
+            Ok(Cookies::File(file.path().to_path_buf()))
+        );
+        assert!(Cookies::file(Path::new("/nonexistent/cookies.txt")).is_err());
+    }
+
+    #[test]
+    fn browser_must_be_one_ytdlp_reads() {
+        assert!(Cookies::browser("firefox").is_ok());
+        assert!(Cookies::browser("chrome+gnomekeyring:Profile 1").is_ok());
+        assert!(Cookies::browser("Firefox::Personal").is_ok());
+
+        let err = Cookies::browser("netscape").unwrap_err();
+        assert!(err.to_string().contains("unsupported browser 'netscape'"));
+    }
+
+    #[test]
+    fn passes_cookies_to_ytdlp_and_mpv() {
+        let file = Cookies::File(PathBuf::from("/home/me/cookies.txt"));
+        let browser = Cookies::Browser("firefox".to_string());
+
+        assert_eq!(file.ytdlp_args(), ["--cookies", "/home/me/cookies.txt"]);
+        assert_eq!(browser.ytdlp_args(), ["--cookies-from-browser", "firefox"]);
+        assert_eq!(
+            file.mpv_arg(),
+            "--ytdl-raw-options-append=cookies=/home/me/cookies.txt"
+        );
+        assert_eq!(
+            browser.mpv_arg(),
+            "--ytdl-raw-options-append=cookies-from-browser=firefox"
+        );
+    }
+}
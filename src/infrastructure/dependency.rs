@@ -0,0 +1,137 @@
+/// A program blepo runs, checked only right before it is run so that
+/// commands which don't need it work without it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dependency {
+    pub program: &'static str,
+    /// How to install it with Homebrew.
+    macos: &'static str,
+    /// How to install it on Debian and Ubuntu.
+    linux: &'static str,
+    /// Where to look on other systems.
+    homepage: &'static str,
+}
+
+pub const MPV: Dependency = Dependency {
+    program: "mpv",
+    macos: "brew install mpv",
+    linux: "sudo apt install mpv",
+    homepage: "https://mpv.io/installation/",
+};
+
+pub const YT_DLP: Dependency = Dependency {
+    program: "yt-dlp",
+    macos: "brew install yt-dlp",
+    linux: "sudo apt install yt-dlp",
+    homepage: "https://github.com/yt-dlp/yt-dlp#installation",
+};
+
+pub const CATT: Dependency = Dependency {
+    program: "catt",
+    macos: "pipx install catt",
+    linux: "pipx install catt",
+    homepage: "https://github.com/skorokithakis/catt",
+};
+
+impl Dependency {
+    /// Fails with how to install the program when it isn't on `PATH`.
+    pub fn require(&self) -> Result<(), MissingDependency> {
+        if is_installed(self.program) {
+            Ok(())
+        } else {
+            Err(MissingDependency {
+                program: self.program,
+                hint: self.install_hint(std::env::consts::OS),
+            })
+        }
+    }
+
+    fn install_hint(&self, os: &str) -> String {
+        match os {
+            "macos" => format!("install it with `{}`", self.macos),
+            "linux" => format!(
+                "install it with `{}` or your distribution's package manager",
+                self.linux
+            ),
+            _ => format!("see {} to install it", self.homepage),
+        }
+    }
+}
+
+/// A program blepo needed but couldn't find.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingDependency {
+    program: &'static str,
+    hint: String,
+}
+
+impl std::fmt::Display for MissingDependency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} is not installed (or not on PATH); {}",
+            self.program, self.hint
+        )
+    }
+}
+
+impl std::error::Error for MissingDependency {}
+
+/// Whether `name` is a file in one of the `PATH` directories; on Windows,
+/// `name.exe` counts too.
+pub fn is_installed(name: &str) -> bool {
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&path).any(|dir| {
+        let candidate = dir.join(name);
+        candidate.is_file() || (cfg!(windows) && candidate.with_extension("exe").is_file())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program(name: &'static str) -> Dependency {
+        Dependency {
+            program: name,
+            ..MPV
+        }
+    }
+
+    #[test]
+    fn finds_existing_binary() {
+        // "sh" exists on every Unix system
+        assert!(program("sh").require().is_ok());
+    }
+
+    #[test]
+    fn missing_binary_error_says_how_to_install_it() {
+        let err = program("nonexistent_binary_xyz_123")
+            .require()
+            .unwrap_err()
+            .to_string();
+
+        assert!(
+            err.starts_with("nonexistent_binary_xyz_123 is not installed"),
+            "error should include binary name, got: {err}"
+        );
+        assert!(err.contains("install"));
+    }
+
+    #[test]
+    fn hints_depend_on_the_platform() {
+        assert_eq!(
+            YT_DLP.install_hint("macos"),
+            "install it with `brew install yt-dlp`"
+        );
+        assert_eq!(
+            MPV.install_hint("linux"),
+            "install it with `sudo apt install mpv` or your distribution's package manager"
+        );
+        assert_eq!(
+            CATT.install_hint("freebsd"),
+            "see https://github.com/skorokithakis/catt to install it"
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use std::io;
+use std::process::{Command, Stdio};
+
+/// Shows desktop notifications: `notify-send` on Linux and the BSDs,
+/// `osascript` on macOS. Windows has no command-line equivalent.
+pub struct DesktopNotifier;
+
+impl DesktopNotifier {
+    pub fn notify(&self, title: &str, body: &str) -> io::Result<()> {
+        let (program, args) =
+            notification_command(std::env::consts::OS, title, body).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "desktop notifications are not supported on this system",
+                )
+            })?;
+        Command::new(program)
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| io::Error::new(e.kind(), format!("failed to launch {program}: {e}")))?;
+        Ok(())
+    }
+}
+
+fn notification_command(os: &str, title: &str, body: &str) -> Option<(&'static str, Vec<String>)> {
+    match os {
+        "macos" => Some((
+            "osascript",
+            vec![
+                "-e".to_string(),
+                format!(
+                    "display notification {} with title {}",
+                    applescript_string(body),
+                    applescript_string(title)
+                ),
+            ],
+        )),
+        "windows" => None,
+        _ => Some((
+            "notify-send",
+            vec![
+                "--app-name=blepo".to_string(),
+                title.to_string(),
+                body.to_string(),
+            ],
+        )),
+    }
+}
+
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_the_platform_command() {
+        assert_eq!(
+            notification_command("linux", "blepo", "12 unwatched videos"),
+            Some((
+                "notify-send",
+                vec![
+                    "--app-name=blepo".to_string(),
+                    "blepo".to_string(),
+                    "12 unwatched videos".to_string()
+                ]
+            ))
+        );
+        assert_eq!(
+            notification_command("macos", "blepo", r#"Say "hi""#),
+            Some((
+                "osascript",
+                vec![
+                    "-e".to_string(),
+                    r#"display notification "Say \"hi\"" with title "blepo""#.to_string()
+                ]
+            ))
+        );
+        assert_eq!(notification_command("windows", "blepo", "ready"), None);
+    }
+}
@@ -0,0 +1,31 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::json_file::{read_json, write_json};
+use crate::application::ports::{DigestStore, StoreError};
+use crate::domain::video::DigestCursor;
+
+/// Where `blepo digest` left off, in `<data_dir>/digest.json`.
+pub struct JsonDigestStore {
+    path: PathBuf,
+}
+
+impl JsonDigestStore {
+    pub fn new(data_dir: &Path) -> Result<Self, StoreError> {
+        fs::create_dir_all(data_dir)
+            .map_err(|e| StoreError::Write(format!("cannot create data dir: {e}")))?;
+        Ok(Self {
+            path: data_dir.join("digest.json"),
+        })
+    }
+}
+
+impl DigestStore for JsonDigestStore {
+    fn load_digest(&self) -> Result<Option<DigestCursor>, StoreError> {
+        read_json(&self.path, "digest cursor")
+    }
+
+    fn save_digest(&self, cursor: &DigestCursor) -> Result<(), StoreError> {
+        write_json(&self.path, cursor, "digest cursor")
+    }
+}
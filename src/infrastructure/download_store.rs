@@ -0,0 +1,147 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::json_file::{read_json, write_json};
+use crate::application::ports::{DownloadStore, StoreError};
+use crate::domain::download::Download;
+
+/// The download queue of `blepo download` and `auto_download`, finished
+/// downloads included, in `<data_dir>/downloads.json`.
+pub struct JsonDownloadStore {
+    path: PathBuf,
+}
+
+impl JsonDownloadStore {
+    pub fn new(data_dir: &Path) -> Result<Self, StoreError> {
+        fs::create_dir_all(data_dir)
+            .map_err(|e| StoreError::Write(format!("cannot create data dir: {e}")))?;
+        Ok(Self {
+            path: data_dir.join("downloads.json"),
+        })
+    }
+}
+
+impl DownloadStore for JsonDownloadStore {
+    fn load_downloads(&self) -> Result<Vec<Download>, StoreError> {
+        read_json(&self.path, "downloads").map(Option::unwrap_or_default)
+    }
+
+    fn save_downloads(&self, downloads: &[Download]) -> Result<(), StoreError> {
+        write_json(&self.path, &downloads, "downloads")
+    }
+
+    /// The video and the files blepo and yt-dlp save next to it: its
+    /// `.part`, `<name>.part`, `<name>.nfo`, `<name>.jpg`, `<name>-thumb.jpg`
+    /// and `<name>.<lang>.vtt`. Other files that merely start with the name,
+    /// such as another video's `<name>.5.mp4`, are left alone.
+    fn delete_files(&self, path: &Path) -> Result<(), StoreError> {
+        delete(path)?;
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".part");
+        delete(Path::new(&partial))?;
+        let (Some(folder), Some(name)) = (path.parent(), path.file_stem()) else {
+            return Ok(());
+        };
+        let name = name.to_string_lossy();
+        let belongs = |file: &str| file.strip_prefix(name.as_ref()).is_some_and(is_sidecar);
+        let entries = match fs::read_dir(folder) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                return Err(StoreError::Read(format!(
+                    "cannot list {}: {e}",
+                    folder.display()
+                )))
+            }
+        };
+        for entry in entries.flatten() {
+            if entry.file_name().to_str().is_some_and(belongs) {
+                delete(&entry.path())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What follows the video's name in the name of one of its sidecars.
+fn is_sidecar(suffix: &str) -> bool {
+    if [".part", ".nfo", ".jpg", "-thumb.jpg"].contains(&suffix) {
+        return true;
+    }
+    suffix
+        .strip_prefix('.')
+        .and_then(|rest| rest.strip_suffix(".vtt"))
+        .is_some_and(|lang| {
+            !lang.is_empty() && lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+fn delete(path: &Path) -> Result<(), StoreError> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(StoreError::Write(format!(
+            "cannot delete {}: {e}",
+            path.display()
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn deletes_a_video_with_the_files_next_to_it() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonDownloadStore::new(dir.path()).unwrap();
+        let names = [
+            "Fish [abc].webm",
+            "Fish [abc].nfo",
+            "Fish [abc].en.vtt",
+            "Fish [abc]-thumb.jpg",
+            "Fish [abcd].webm",
+            "tvshow.nfo",
+        ];
+        for name in names {
+            fs::write(dir.path().join(name), b"x").unwrap();
+        }
+
+        store
+            .delete_files(&dir.path().join("Fish [abc].webm"))
+            .unwrap();
+        store
+            .delete_files(&dir.path().join("gone/Gone.webm"))
+            .unwrap();
+
+        let left: Vec<bool> = names.iter().map(|n| dir.path().join(n).exists()).collect();
+        assert_eq!(left, [false, false, false, false, true, true]);
+    }
+
+    #[test]
+    fn keeps_another_video_whose_name_starts_the_same() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonDownloadStore::new(dir.path()).unwrap();
+        let names = [
+            "Episode 1.mp4",
+            "Episode 1.mp4.part",
+            "Episode 1.en-US.vtt",
+            "Episode 1.jpg",
+            "Episode 1.5.mp4",
+            "Episode 1.5.nfo",
+            "Episode 1.5.en.vtt",
+            "Episode 1.5-thumb.jpg",
+        ];
+        for name in names {
+            fs::write(dir.path().join(name), b"x").unwrap();
+        }
+
+        store
+            .delete_files(&dir.path().join("Episode 1.mp4"))
+            .unwrap();
+
+        let left: Vec<bool> = names.iter().map(|n| dir.path().join(n).exists()).collect();
+        assert_eq!(left, [false, false, false, false, true, true, true, true]);
+    }
+}
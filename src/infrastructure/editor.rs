@@ -0,0 +1,44 @@
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// Opens `path` in `$VISUAL`, else `$EDITOR`, else `vi`, and waits for the
+/// editor to close. The variable may carry options, e.g. `code --wait`.
+pub fn edit_file(path: &Path) -> io::Result<()> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .map(|v| v.to_string_lossy().into_owned())
+        .find(|v| !v.trim().is_empty());
+    let (program, args) = editor_command(editor.as_deref());
+    let status = Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .map_err(|e| io::Error::new(e.kind(), format!("failed to launch {program}: {e}")))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{program} exited with {status}")))
+    }
+}
+
+fn editor_command(editor: Option<&str>) -> (&str, Vec<&str>) {
+    let mut words = editor.unwrap_or("vi").split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    (program, words.collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_editor_options() {
+        assert_eq!(
+            editor_command(Some("code --wait")),
+            ("code", vec!["--wait"])
+        );
+        assert_eq!(editor_command(None), ("vi", vec![]));
+    }
+}
@@ -1,4 +1,6 @@
-use crate::application::ports::{FeedFetcher, FetchError};
+use tracing::info;
+
+use crate::application::ports::{FeedFetcher, FetchError, LocalFuture};
 use crate::domain::channel::Channel;
 use crate::domain::video::Video;
 
@@ -14,14 +16,19 @@ impl<P: FeedFetcher, F: FeedFetcher> FallbackFetcher<P, F> {
 }
 
 impl<P: FeedFetcher, F: FeedFetcher> FeedFetcher for FallbackFetcher<P, F> {
-    fn fetch(&self, channel: &Channel) -> Result<Vec<Video>, FetchError> {
-        match self.primary.fetch(channel) {
-            Err(FetchError::HttpError(404)) => {
-                eprintln!("RSS feed returned 404, trying yt-dlp...");
-                self.fallback.fetch(channel)
+    fn fetch<'a>(
+        &'a self,
+        channel: &'a Channel,
+    ) -> LocalFuture<'a, Result<Vec<Video>, FetchError>> {
+        Box::pin(async move {
+            match self.primary.fetch(channel).await {
+                Err(FetchError::HttpError(404)) => {
+                    info!("RSS feed returned 404, trying yt-dlp");
+                    self.fallback.fetch(channel).await
+                }
+                other => other,
             }
-            other => other,
-        }
+        })
     }
 }
 
@@ -45,13 +52,20 @@ mod tests {
     }
 
     impl FeedFetcher for MockFetcher {
-        fn fetch(&self, _channel: &Channel) -> Result<Vec<Video>, FetchError> {
-            match &self.result {
-                Ok(videos) => Ok(videos.clone()),
-                Err(FetchError::HttpError(code)) => Err(FetchError::HttpError(*code)),
-                Err(FetchError::Network(msg)) => Err(FetchError::Network(msg.clone())),
-                Err(FetchError::Parse(msg)) => Err(FetchError::Parse(msg.clone())),
-            }
+        fn fetch<'a>(
+            &'a self,
+            _channel: &'a Channel,
+        ) -> LocalFuture<'a, Result<Vec<Video>, FetchError>> {
+            Box::pin(async move {
+                match &self.result {
+                    Ok(videos) => Ok(videos.clone()),
+                    Err(FetchError::HttpError(code)) => Err(FetchError::HttpError(*code)),
+                    Err(FetchError::Network(msg)) => Err(FetchError::Network(msg.clone())),
+                    Err(FetchError::RateLimited(delay)) => Err(FetchError::RateLimited(*delay)),
+                    Err(FetchError::Parse(msg)) => Err(FetchError::Parse(msg.clone())),
+                    Err(FetchError::YtDlp(msg)) => Err(FetchError::YtDlp(msg.clone())),
+                }
+            })
         }
     }
 
@@ -59,64 +73,73 @@ mod tests {
         Channel {
             name: "Test".to_string(),
             id: ChannelId::parse("UC_x5XG1OV2P6uZZ5FSM9Ttw").unwrap(),
+            include_streams: false,
+            title_filter: Default::default(),
+            duration_range: Default::default(),
+            tags: Vec::new(),
+            name_from_feed: false,
+            alias: None,
+            snoozed_until: None,
+            player: None,
+            auto_download: false,
         }
     }
 
-    #[test]
-    fn returns_primary_result_on_success() {
+    #[tokio::test]
+    async fn returns_primary_result_on_success() {
         let fetcher = FallbackFetcher::new(MockFetcher::ok(vec![]), MockFetcher::ok(vec![]));
 
-        let result = fetcher.fetch(&test_channel());
+        let result = fetcher.fetch(&test_channel()).await;
         assert!(result.is_ok());
     }
 
-    #[test]
-    fn falls_back_on_404() {
+    #[tokio::test]
+    async fn falls_back_on_404() {
         let primary = MockFetcher::err(FetchError::HttpError(404));
         let fallback = MockFetcher::ok(vec![]);
         let fetcher = FallbackFetcher::new(primary, fallback);
 
-        let result = fetcher.fetch(&test_channel());
+        let result = fetcher.fetch(&test_channel()).await;
         assert!(result.is_ok());
     }
 
-    #[test]
-    fn does_not_fallback_on_500() {
+    #[tokio::test]
+    async fn does_not_fallback_on_500() {
         let primary = MockFetcher::err(FetchError::HttpError(500));
         let fallback = MockFetcher::ok(vec![]);
         let fetcher = FallbackFetcher::new(primary, fallback);
 
-        let result = fetcher.fetch(&test_channel());
+        let result = fetcher.fetch(&test_channel()).await;
         assert!(matches!(result, Err(FetchError::HttpError(500))));
     }
 
-    #[test]
-    fn does_not_fallback_on_network_error() {
+    #[tokio::test]
+    async fn does_not_fallback_on_network_error() {
         let primary = MockFetcher::err(FetchError::Network("timeout".to_string()));
         let fallback = MockFetcher::ok(vec![]);
         let fetcher = FallbackFetcher::new(primary, fallback);
 
-        let result = fetcher.fetch(&test_channel());
+        let result = fetcher.fetch(&test_channel()).await;
         assert!(matches!(result, Err(FetchError::Network(_))));
     }
 
-    #[test]
-    fn does_not_fallback_on_parse_error() {
+    #[tokio::test]
+    async fn does_not_fallback_on_parse_error() {
         let primary = MockFetcher::err(FetchError::Parse("bad xml".to_string()));
         let fallback = MockFetcher::ok(vec![]);
         let fetcher = FallbackFetcher::new(primary, fallback);
 
-        let result = fetcher.fetch(&test_channel());
+        let result = fetcher.fetch(&test_channel()).await;
         assert!(matches!(result, Err(FetchError::Parse(_))));
     }
 
-    #[test]
-    fn propagates_fallback_error() {
+    #[tokio::test]
+    async fn propagates_fallback_error() {
         let primary = MockFetcher::err(FetchError::HttpError(404));
-        let fallback = MockFetcher::err(FetchError::Network("yt-dlp failed".to_string()));
+        let fallback = MockFetcher::err(FetchError::YtDlp("yt-dlp failed".to_string()));
         let fetcher = FallbackFetcher::new(primary, fallback);
 
-        let result = fetcher.fetch(&test_channel());
-        assert!(matches!(result, Err(FetchError::Network(_))));
+        let result = fetcher.fetch(&test_channel()).await;
+        assert!(matches!(result, Err(FetchError::YtDlp(_))));
     }
 }
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::json_file::{read_json, write_json};
+use crate::application::ports::StoreError;
+use crate::domain::channel::ChannelId;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedFeed {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: String,
+}
+
+impl CachedFeed {
+    pub fn has_validators(&self) -> bool {
+        self.etag.is_some() || self.last_modified.is_some()
+    }
+}
+
+/// One JSON file per channel under `<data_dir>/feed_cache/`.
+pub struct FeedCache {
+    dir: PathBuf,
+}
+
+impl FeedCache {
+    pub fn new(data_dir: &Path) -> Result<Self, StoreError> {
+        let dir = data_dir.join("feed_cache");
+        fs::create_dir_all(&dir)
+            .map_err(|e| StoreError::Write(format!("cannot create feed cache dir: {e}")))?;
+        Ok(Self { dir })
+    }
+
+    pub fn load(&self, channel_id: &ChannelId) -> Option<CachedFeed> {
+        read_json(&self.path(channel_id), "feed cache")
+            .ok()
+            .flatten()
+    }
+
+    pub fn store(&self, channel_id: &ChannelId, feed: &CachedFeed) -> Result<(), StoreError> {
+        write_json(&self.path(channel_id), feed, "feed cache")
+    }
+
+    fn path(&self, channel_id: &ChannelId) -> PathBuf {
+        self.dir.join(format!("{channel_id}.json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn channel_id() -> ChannelId {
+        ChannelId::parse("UC123").unwrap()
+    }
+
+    #[test]
+    fn returns_none_for_unknown_channel() {
+        let dir = TempDir::new().unwrap();
+        let cache = FeedCache::new(dir.path()).unwrap();
+
+        assert!(cache.load(&channel_id()).is_none());
+    }
+
+    #[test]
+    fn stores_and_loads_feed() {
+        let dir = TempDir::new().unwrap();
+        let cache = FeedCache::new(dir.path()).unwrap();
+        let feed = CachedFeed {
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+            body: "<feed/>".to_string(),
+        };
+
+        cache.store(&channel_id(), &feed).unwrap();
+
+        assert_eq!(cache.load(&channel_id()), Some(feed));
+    }
+
+    #[test]
+    fn treats_corrupt_entry_as_missing() {
+        let dir = TempDir::new().unwrap();
+        let cache = FeedCache::new(dir.path()).unwrap();
+        fs::write(dir.path().join("feed_cache/UC123.json"), "not json").unwrap();
+
+        assert!(cache.load(&channel_id()).is_none());
+    }
+
+    #[test]
+    fn feed_without_etag_or_last_modified_has_no_validators() {
+        let feed = CachedFeed {
+            etag: None,
+            last_modified: None,
+            body: String::new(),
+        };
+
+        assert!(!feed.has_validators());
+    }
+}
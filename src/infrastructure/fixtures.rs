@@ -0,0 +1,95 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use tracing::warn;
+
+use crate::domain::channel::ChannelId;
+
+/// What the fetchers read off the network, saved per channel by
+/// `--record-fixtures <dir>` and read back instead by `--replay <dir>`, to
+/// reproduce a parsing bug offline or turn it into a test fixture. Each
+/// channel has up to three files: `<id>.xml`, the RSS feed, and
+/// `<id>.videos.jsonl` and `<id>.streams.jsonl`, yt-dlp's listing of each
+/// tab.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fixtures {
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+impl Fixtures {
+    pub fn is_replay(&self) -> bool {
+        matches!(self, Fixtures::Replay(_))
+    }
+
+    /// Saves `body` as the channel's `suffix` file when recording. Failing
+    /// to only warns: the fetch itself went fine.
+    pub fn record(&self, channel_id: &ChannelId, suffix: &str, body: &str) {
+        let Fixtures::Record(dir) = self else {
+            return;
+        };
+        let path = fixture_path(dir, channel_id, suffix);
+        if let Err(e) = fs::create_dir_all(dir).and_then(|()| fs::write(&path, body)) {
+            warn!("cannot record {}: {e}", path.display());
+        }
+    }
+
+    /// The channel's `suffix` file when replaying; `None` when recording,
+    /// so the fetcher goes to the network.
+    pub fn replayed(&self, channel_id: &ChannelId, suffix: &str) -> Option<io::Result<String>> {
+        let Fixtures::Replay(dir) = self else {
+            return None;
+        };
+        let path = fixture_path(dir, channel_id, suffix);
+        Some(
+            fs::read_to_string(&path)
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display()))),
+        )
+    }
+}
+
+fn fixture_path(dir: &Path, channel_id: &ChannelId, suffix: &str) -> PathBuf {
+    dir.join(format!("{channel_id}.{suffix}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn channel_id() -> ChannelId {
+        ChannelId::parse("UC123").unwrap()
+    }
+
+    #[test]
+    fn replays_what_was_recorded() {
+        let dir = TempDir::new().unwrap();
+        let recorder = Fixtures::Record(dir.path().join("fixtures"));
+        let replayer = Fixtures::Replay(dir.path().join("fixtures"));
+
+        recorder.record(&channel_id(), "xml", "<feed/>");
+
+        assert!(recorder.replayed(&channel_id(), "xml").is_none());
+        assert_eq!(
+            replayer.replayed(&channel_id(), "xml").unwrap().unwrap(),
+            "<feed/>"
+        );
+        assert!(dir.path().join("fixtures/UC123.xml").is_file());
+    }
+
+    #[test]
+    fn missing_fixtures_are_not_found() {
+        let dir = TempDir::new().unwrap();
+        let replayer = Fixtures::Replay(dir.path().to_path_buf());
+
+        replayer.record(&channel_id(), "xml", "<feed/>");
+
+        let err = replayer
+            .replayed(&channel_id(), "xml")
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("UC123.xml"));
+    }
+}
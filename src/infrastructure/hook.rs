@@ -0,0 +1,71 @@
+use std::io;
+use std::os::fd::AsFd;
+use std::process::{Command, Stdio};
+
+use super::nfo::SavedVideo;
+
+/// Runs `download_hook` through `sh -c` once a video is saved, with the
+/// video in its environment: `BLEPO_PATH`, `BLEPO_VIDEO_ID`, `BLEPO_TITLE`
+/// and `BLEPO_CHANNEL` (empty when yt-dlp didn't say). Its output goes to
+/// stderr, so it can't end up in a listing printed for a script.
+pub fn run_download_hook(command: &str, video: &SavedVideo) -> io::Result<()> {
+    let stderr = io::stderr().as_fd().try_clone_to_owned()?;
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("BLEPO_PATH", &video.filepath)
+        .env("BLEPO_VIDEO_ID", &video.id)
+        .env("BLEPO_TITLE", video.title.as_deref().unwrap_or_default())
+        .env(
+            "BLEPO_CHANNEL",
+            video.channel.as_deref().unwrap_or_default(),
+        )
+        .stdin(Stdio::null())
+        .stdout(Stdio::from(stderr))
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("exited with {status}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn saved(dir: &TempDir) -> SavedVideo {
+        SavedVideo {
+            filepath: dir.path().join("video.webm"),
+            id: "abc123".to_string(),
+            title: Some("Fish & Chips".to_string()),
+            channel: None,
+            channel_id: None,
+            upload_date: None,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn passes_the_video_in_the_environment() {
+        let dir = TempDir::new().unwrap();
+
+        run_download_hook(
+            r#"printf '%s|%s|%s' "$BLEPO_VIDEO_ID" "$BLEPO_TITLE" "$BLEPO_CHANNEL" > "$BLEPO_PATH.txt""#,
+            &saved(&dir),
+        )
+        .unwrap();
+
+        let written = fs::read_to_string(dir.path().join("video.webm.txt")).unwrap();
+        assert_eq!(written, "abc123|Fish & Chips|");
+    }
+
+    #[test]
+    fn a_failing_hook_is_an_error() {
+        let dir = TempDir::new().unwrap();
+
+        assert!(run_download_hook("exit 3", &saved(&dir)).is_err());
+    }
+}
@@ -0,0 +1,291 @@
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::{HeaderMap, COOKIE, LOCATION};
+use reqwest::redirect::Policy;
+use reqwest::{StatusCode, Url};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+use crate::application::ports::FetchError;
+
+const PROXY_SCHEMES: [&str; 4] = ["http", "https", "socks5", "socks5h"];
+const MAX_REDIRECTS: usize = 10;
+/// Where YouTube sends visitors from some regions to accept or reject
+/// cookies before showing anything.
+const CONSENT_HOSTS: [&str; 2] = ["consent.youtube.com", "consent.google.com"];
+/// Rejects the optional cookies, which is all the interstitial wants to know.
+const CONSENT_COOKIE: &str = "SOCS=CAI";
+/// The target of the request and cache events `--debug-http` shows.
+pub const HTTP_LOG_TARGET: &str = "blepo::http";
+
+/// A proxy URL that both reqwest and yt-dlp accept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyUrl(String);
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ProxyUrlError(String);
+
+impl std::fmt::Display for ProxyUrlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ProxyUrlError {}
+
+impl ProxyUrl {
+    pub fn parse(url: impl Into<String>) -> Result<Self, ProxyUrlError> {
+        let url = url.into();
+        let scheme = url
+            .split_once("://")
+            .map(|(scheme, _)| scheme)
+            .ok_or_else(|| ProxyUrlError(format!("'{url}' is missing a scheme")))?;
+        if !PROXY_SCHEMES.contains(&scheme) {
+            return Err(ProxyUrlError(format!(
+                "unsupported scheme '{scheme}' (expected one of {})",
+                PROXY_SCHEMES.join(", ")
+            )));
+        }
+        reqwest::Proxy::all(&url).map_err(|e| ProxyUrlError(e.to_string()))?;
+        Ok(Self(url))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_http(&self) -> bool {
+        self.0.starts_with("http://") || self.0.starts_with("https://")
+    }
+}
+
+/// Builds the one connection-pooled HTTP client shared by every adapter;
+/// clones share the pool. TLS negotiates HTTP/2 via ALPN, so concurrent
+/// requests to youtube.com are multiplexed over a few connections. It
+/// routes through `proxy` when given. Without an explicit proxy, reqwest
+/// honors the standard `HTTP_PROXY`/`HTTPS_PROXY`/`ALL_PROXY`/`NO_PROXY`
+/// environment variables.
+pub fn build_client(proxy: Option<&ProxyUrl>) -> Client {
+    let mut builder = Client::builder().redirect(redirect_policy());
+    if let Some(proxy) = proxy {
+        builder =
+            builder.proxy(reqwest::Proxy::all(proxy.as_str()).expect("validated by ProxyUrl"));
+    }
+    builder.build().expect("failed to build HTTP client")
+}
+
+/// Like `build_client`, but async, for the fetch pipeline: many feeds and
+/// Shorts checks in flight on one thread instead of a thread each.
+pub fn build_async_client(proxy: Option<&ProxyUrl>) -> reqwest::Client {
+    let mut builder = reqwest::Client::builder().redirect(redirect_policy());
+    if let Some(proxy) = proxy {
+        builder =
+            builder.proxy(reqwest::Proxy::all(proxy.as_str()).expect("validated by ProxyUrl"));
+    }
+    builder.build().expect("failed to build HTTP client")
+}
+
+/// Sends a request to youtube.com, answering the cookie consent interstitial
+/// if YouTube redirects to it: the request is sent again once with the
+/// consent cookie, so callers only ever see the page they asked for.
+pub fn send_past_consent(request: RequestBuilder) -> Result<Response, FetchError> {
+    let retry = request.try_clone();
+    let response = send(request)?;
+    if !leads_to_consent(response.status(), response.headers()) {
+        return Ok(response);
+    }
+    let Some(retry) = retry else {
+        return Err(consent_error());
+    };
+    debug!(target: HTTP_LOG_TARGET, "answering the cookie consent page");
+    let response = send(retry.header(COOKIE, CONSENT_COOKIE))?;
+    if leads_to_consent(response.status(), response.headers()) {
+        return Err(consent_error());
+    }
+    Ok(response)
+}
+
+/// `send_past_consent` for the async client.
+pub async fn send_past_consent_async(
+    request: reqwest::RequestBuilder,
+) -> Result<reqwest::Response, FetchError> {
+    let retry = request.try_clone();
+    let response = send_async(request).await?;
+    if !leads_to_consent(response.status(), response.headers()) {
+        return Ok(response);
+    }
+    let Some(retry) = retry else {
+        return Err(consent_error());
+    };
+    debug!(target: HTTP_LOG_TARGET, "answering the cookie consent page");
+    let response = send_async(retry.header(COOKIE, CONSENT_COOKIE)).await?;
+    if leads_to_consent(response.status(), response.headers()) {
+        return Err(consent_error());
+    }
+    Ok(response)
+}
+
+/// Sends `request`, logging what came back for `--debug-http`. The size is
+/// the `Content-Length`, when the server sends one.
+fn send(request: RequestBuilder) -> Result<Response, FetchError> {
+    let started = Instant::now();
+    let result = request.send();
+    let elapsed = started.elapsed();
+    match &result {
+        Ok(response) => log_response(
+            response.status(),
+            response.content_length(),
+            response.url(),
+            elapsed,
+        ),
+        Err(e) => debug!(target: HTTP_LOG_TARGET, error = %e, ?elapsed, "request failed"),
+    }
+    result.map_err(|e| FetchError::Network(e.to_string()))
+}
+
+async fn send_async(request: reqwest::RequestBuilder) -> Result<reqwest::Response, FetchError> {
+    let started = Instant::now();
+    let result = request.send().await;
+    let elapsed = started.elapsed();
+    match &result {
+        Ok(response) => log_response(
+            response.status(),
+            response.content_length(),
+            response.url(),
+            elapsed,
+        ),
+        Err(e) => debug!(target: HTTP_LOG_TARGET, error = %e, ?elapsed, "request failed"),
+    }
+    result.map_err(|e| FetchError::Network(e.to_string()))
+}
+
+fn log_response(status: StatusCode, bytes: Option<u64>, url: &Url, elapsed: Duration) {
+    debug!(
+        target: HTTP_LOG_TARGET,
+        status = status.as_u16(),
+        bytes,
+        ?elapsed,
+        "{url}"
+    );
+}
+
+fn consent_error() -> FetchError {
+    FetchError::Network("YouTube keeps redirecting to its cookie consent page".to_string())
+}
+
+/// The redirect policy stops at the interstitial, so it shows up as a
+/// redirect whose `Location` is a consent page.
+fn leads_to_consent(status: StatusCode, headers: &HeaderMap) -> bool {
+    status.is_redirection()
+        && headers
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| Url::parse(location).ok())
+            .is_some_and(|url| is_consent_url(&url))
+}
+
+/// Follows redirects like reqwest's default, except away from `/shorts/`
+/// pages: the Shorts check reads that redirect as "not a Short"; and not
+/// into the consent interstitial, which `send_past_consent` answers instead.
+fn redirect_policy() -> Policy {
+    Policy::custom(|attempt| {
+        if attempt.previous().len() > MAX_REDIRECTS {
+            attempt.error("too many redirects")
+        } else if attempt.previous().last().is_some_and(is_shorts_url)
+            || is_consent_url(attempt.url())
+        {
+            attempt.stop()
+        } else {
+            attempt.follow()
+        }
+    })
+}
+
+fn is_shorts_url(url: &Url) -> bool {
+    url.path().starts_with("/shorts/")
+}
+
+fn is_consent_url(url: &Url) -> bool {
+    url.host_str()
+        .is_some_and(|host| CONSENT_HOSTS.contains(&host))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_http_and_socks_proxies() {
+        assert!(ProxyUrl::parse("http://proxy.corp:3128").is_ok());
+        assert!(ProxyUrl::parse("socks5://127.0.0.1:1080").is_ok());
+        assert!(ProxyUrl::parse("socks5h://127.0.0.1:1080").is_ok());
+    }
+
+    #[test]
+    fn rejects_proxy_without_scheme() {
+        assert!(ProxyUrl::parse("proxy.corp:3128").is_err());
+    }
+
+    #[test]
+    fn rejects_unsupported_scheme() {
+        let err = ProxyUrl::parse("ftp://proxy.corp").unwrap_err();
+
+        assert!(err.to_string().contains("unsupported scheme 'ftp'"));
+    }
+
+    #[test]
+    fn distinguishes_http_from_socks() {
+        assert!(ProxyUrl::parse("http://p:1").unwrap().is_http());
+        assert!(!ProxyUrl::parse("socks5://p:1").unwrap().is_http());
+    }
+
+    #[test]
+    fn builds_client_with_proxy() {
+        let proxy = ProxyUrl::parse("socks5://127.0.0.1:1080").unwrap();
+
+        build_client(Some(&proxy));
+    }
+
+    #[test]
+    fn builds_async_client_with_proxy() {
+        let proxy = ProxyUrl::parse("socks5://127.0.0.1:1080").unwrap();
+
+        build_async_client(Some(&proxy));
+    }
+
+    #[test]
+    fn recognizes_shorts_urls() {
+        let url = |s| Url::parse(s).unwrap();
+
+        assert!(is_shorts_url(&url("https://www.youtube.com/shorts/abc")));
+        assert!(!is_shorts_url(&url("https://www.youtube.com/watch?v=abc")));
+        assert!(!is_shorts_url(&url("https://www.youtube.com/@shorts")));
+    }
+
+    #[test]
+    fn recognizes_consent_urls() {
+        let url = |s| Url::parse(s).unwrap();
+
+        assert!(is_consent_url(&url(
+            "https://consent.youtube.com/m?continue=https%3A%2F%2Fwww.youtube.com%2F"
+        )));
+        assert!(is_consent_url(&url("https://consent.google.com/ml")));
+        assert!(!is_consent_url(&url("https://www.youtube.com/consent")));
+    }
+
+    #[test]
+    fn recognizes_redirects_to_consent() {
+        let location = |url: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(LOCATION, url.parse().unwrap());
+            headers
+        };
+        let consent = location("https://consent.youtube.com/m");
+
+        assert!(leads_to_consent(StatusCode::FOUND, &consent));
+        assert!(!leads_to_consent(StatusCode::OK, &consent));
+        assert!(!leads_to_consent(
+            StatusCode::FOUND,
+            &location("https://www.youtube.com/watch?v=abc")
+        ));
+    }
+}
@@ -0,0 +1,101 @@
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+
+/// Held for a whole run so that two blepo processes, e.g. a cron job and an
+/// interactive session, don't overwrite each other's state in the data dir.
+/// The OS releases it when the process exits, however it exits; players
+/// blepo starts don't inherit it.
+pub struct InstanceLock {
+    _file: File,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LockError {
+    /// Another process holds the lock; its pid, when it could be read.
+    Busy(Option<u32>),
+    Io(String),
+}
+
+impl std::fmt::Display for LockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LockError::Busy(pid) => {
+                write!(f, "another blepo instance is running")?;
+                if let Some(pid) = pid {
+                    write!(f, " (pid {pid})")?;
+                }
+                write!(f, "; try again once it is done, or pass --wait")
+            }
+            LockError::Io(msg) => write!(f, "cannot lock the data dir: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for LockError {}
+
+impl InstanceLock {
+    /// Takes `<data_dir>/blepo.lock`, or fails with `Busy` at once.
+    pub fn try_acquire(data_dir: &Path) -> Result<Self, LockError> {
+        let mut file = open(data_dir)?;
+        match file.try_lock() {
+            Ok(()) => Self::claim(file),
+            Err(TryLockError::WouldBlock) => Err(LockError::Busy(holder(&mut file))),
+            Err(TryLockError::Error(e)) => Err(LockError::Io(e.to_string())),
+        }
+    }
+
+    /// Takes `<data_dir>/blepo.lock`, waiting for whoever holds it.
+    pub fn wait(data_dir: &Path) -> Result<Self, LockError> {
+        let file = open(data_dir)?;
+        file.lock().map_err(|e| LockError::Io(e.to_string()))?;
+        Self::claim(file)
+    }
+
+    /// Writes our pid in, for whoever is kept waiting to be told.
+    fn claim(mut file: File) -> Result<Self, LockError> {
+        file.set_len(0)
+            .and_then(|()| file.rewind())
+            .and_then(|()| write!(file, "{}", std::process::id()))
+            .map_err(|e| LockError::Io(e.to_string()))?;
+        Ok(Self { _file: file })
+    }
+}
+
+fn open(data_dir: &Path) -> Result<File, LockError> {
+    fs::create_dir_all(data_dir).map_err(|e| LockError::Io(e.to_string()))?;
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(data_dir.join("blepo.lock"))
+        .map_err(|e| LockError::Io(e.to_string()))
+}
+
+/// The pid the holder wrote, if it got that far.
+fn holder(file: &mut File) -> Option<u32> {
+    let mut pid = String::new();
+    file.read_to_string(&mut pid).ok()?;
+    pid.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn one_instance_at_a_time() {
+        let dir = TempDir::new().unwrap();
+
+        let lock = InstanceLock::try_acquire(dir.path()).unwrap();
+        assert_eq!(
+            InstanceLock::try_acquire(dir.path()).err(),
+            Some(LockError::Busy(Some(std::process::id())))
+        );
+
+        drop(lock);
+        assert!(InstanceLock::try_acquire(dir.path()).is_ok());
+    }
+}
@@ -0,0 +1,73 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::json_file::{read_json, write_json};
+use crate::application::ports::{JobStore, StoreError};
+use crate::domain::job::PlaybackJob;
+
+/// Players launched in the background, in `<data_dir>/jobs.json`.
+pub struct JsonJobStore {
+    path: PathBuf,
+}
+
+impl JsonJobStore {
+    pub fn new(data_dir: &Path) -> Result<Self, StoreError> {
+        fs::create_dir_all(data_dir)
+            .map_err(|e| StoreError::Write(format!("cannot create data dir: {e}")))?;
+        Ok(Self {
+            path: data_dir.join("jobs.json"),
+        })
+    }
+}
+
+impl JobStore for JsonJobStore {
+    fn load_jobs(&self) -> Result<Vec<PlaybackJob>, StoreError> {
+        read_json(&self.path, "jobs").map(Option::unwrap_or_default)
+    }
+
+    fn save_jobs(&self, jobs: &[PlaybackJob]) -> Result<(), StoreError> {
+        write_json(&self.path, &jobs, "jobs")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::channel::ChannelId;
+    use crate::domain::video::{Video, VideoId, VideoKind};
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn keeps_jobs_between_runs() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonJobStore::new(dir.path()).unwrap();
+        assert!(store.load_jobs().unwrap().is_empty());
+
+        let job = PlaybackJob {
+            pid: 1234,
+            identity: Some("Tue Jun  3 18:30:00 2025 mpv".to_string()),
+            video: Video {
+                id: VideoId::parse("v1").unwrap(),
+                title: "Title".to_string(),
+                url: "https://www.youtube.com/watch?v=v1".to_string(),
+                published: Utc::now(),
+                channel_name: "Chan".to_string(),
+                channel_id: ChannelId::parse("UCtest").unwrap(),
+                kind: VideoKind::Upload,
+                duration: None,
+                tags: Vec::new(),
+            },
+            started: Utc::now(),
+        };
+        store.save_jobs(&[job]).unwrap();
+
+        let jobs = store.load_jobs().unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].pid, 1234);
+        assert_eq!(
+            jobs[0].identity.as_deref(),
+            Some("Tue Jun  3 18:30:00 2025 mpv")
+        );
+    }
+}
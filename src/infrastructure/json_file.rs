@@ -0,0 +1,73 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::application::ports::StoreError;
+
+pub(crate) fn read_json<T: DeserializeOwned>(
+    path: &Path,
+    what: &str,
+) -> Result<Option<T>, StoreError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|e| StoreError::Read(format!("cannot read {what}: {e}")))?;
+
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| StoreError::Read(format!("invalid {what} json: {e}")))
+}
+
+/// Writes to a sibling temp file and renames it over the target, so readers
+/// never observe a half-written file.
+pub(crate) fn write_json<T: Serialize>(
+    path: &Path,
+    value: &T,
+    what: &str,
+) -> Result<(), StoreError> {
+    stage_json(path, value, what)?.commit()
+}
+
+/// `value` written to a sibling temp file, waiting for `commit` to rename
+/// it over `path`. Staging every file of a change before committing any
+/// means a failed write leaves all of them as they were.
+pub(crate) struct StagedJson {
+    path: PathBuf,
+    tmp_path: PathBuf,
+    what: String,
+}
+
+pub(crate) fn stage_json<T: Serialize>(
+    path: &Path,
+    value: &T,
+    what: &str,
+) -> Result<StagedJson, StoreError> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| StoreError::Write(format!("cannot serialize {what}: {e}")))?;
+
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json)
+        .map_err(|e| StoreError::Write(format!("cannot write {what}: {e}")))?;
+    Ok(StagedJson {
+        path: path.to_path_buf(),
+        tmp_path,
+        what: what.to_string(),
+    })
+}
+
+impl StagedJson {
+    pub(crate) fn commit(self) -> Result<(), StoreError> {
+        fs::rename(&self.tmp_path, &self.path)
+            .map_err(|e| StoreError::Write(format!("cannot write {}: {e}", self.what)))
+    }
+}
+
+/// A staged file never committed is cleaned up.
+impl Drop for StagedJson {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.tmp_path);
+    }
+}
@@ -1,12 +1,21 @@
-use std::collections::HashSet;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
-use crate::application::ports::{StoreError, VideoStore};
-use crate::domain::video::VideoId;
+use super::json_file::{read_json, stage_json, write_json};
+use crate::application::ports::{ActionJournal, StoreError, VideoStore};
+use crate::domain::journal::{JournalAction, JournalEntry};
+use crate::domain::video::{VideoId, WatchProgress};
+
+const MAX_JOURNAL_ENTRIES: usize = 100;
 
 pub struct JsonVideoStore {
     watched_path: PathBuf,
+    journal_path: PathBuf,
+    progress_path: PathBuf,
+    watched_at_path: PathBuf,
 }
 
 impl JsonVideoStore {
@@ -16,45 +25,128 @@ impl JsonVideoStore {
 
         Ok(Self {
             watched_path: data_dir.join("watched.json"),
+            journal_path: data_dir.join("journal.json"),
+            progress_path: data_dir.join("progress.json"),
+            watched_at_path: data_dir.join("watched_at.json"),
         })
     }
+
+    fn load_journal(&self) -> Result<Vec<JournalEntry>, StoreError> {
+        read_json(&self.journal_path, "journal").map(Option::unwrap_or_default)
+    }
+
+    fn record(&self, action: JournalAction) -> Result<(), StoreError> {
+        let mut journal = self.load_journal()?;
+        journal.push(JournalEntry {
+            at: Utc::now(),
+            action,
+        });
+        let excess = journal.len().saturating_sub(MAX_JOURNAL_ENTRIES);
+        journal.drain(..excess);
+        write_json(&self.journal_path, &journal, "journal")
+    }
 }
 
 impl VideoStore for JsonVideoStore {
     fn load_watched(&self) -> Result<HashSet<VideoId>, StoreError> {
-        if !self.watched_path.exists() {
-            return Ok(HashSet::new());
-        }
-
-        let content = fs::read_to_string(&self.watched_path)
-            .map_err(|e| StoreError::Read(format!("cannot read watched: {e}")))?;
+        read_json(&self.watched_path, "watched").map(Option::unwrap_or_default)
+    }
 
-        serde_json::from_str(&content)
-            .map_err(|e| StoreError::Read(format!("invalid watched json: {e}")))
+    fn load_watched_at(&self) -> Result<HashMap<VideoId, DateTime<Utc>>, StoreError> {
+        read_json(&self.watched_at_path, "watched_at").map(Option::unwrap_or_default)
     }
 
     fn mark_watched(&self, video_id: &VideoId) -> Result<(), StoreError> {
+        self.mark_watched_batch(&[video_id])
+    }
+
+    /// Journals only the videos this call newly marked, along with the
+    /// resume progress it cleared; marking videos that are all watched
+    /// already only drops their progress.
+    fn mark_watched_batch(&self, video_ids: &[&VideoId]) -> Result<(), StoreError> {
         let mut watched = self.load_watched()?;
-        watched.insert(video_id.clone());
+        let newly_watched: Vec<VideoId> = video_ids
+            .iter()
+            .filter(|id| watched.insert((**id).clone()))
+            .map(|id| (*id).clone())
+            .collect();
+
+        let mut progress = self.load_progress()?;
+        let cleared: HashMap<VideoId, WatchProgress> = progress
+            .iter()
+            .filter(|(id, _)| watched.contains(*id))
+            .map(|(id, p)| (id.clone(), *p))
+            .collect();
+
+        if !newly_watched.is_empty() {
+            write_json(&self.watched_path, &watched, "watched")?;
+
+            let mut watched_at = self.load_watched_at()?;
+            let now = Utc::now();
+            for id in &newly_watched {
+                watched_at.entry(id.clone()).or_insert(now);
+            }
+            write_json(&self.watched_at_path, &watched_at, "watched_at")?;
+        }
+
+        if !cleared.is_empty() {
+            progress.retain(|id, _| !cleared.contains_key(id));
+            write_json(&self.progress_path, &progress, "progress")?;
+        }
 
-        let json = serde_json::to_string_pretty(&watched)
-            .map_err(|e| StoreError::Write(format!("cannot serialize watched: {e}")))?;
+        if newly_watched.is_empty() {
+            return Ok(());
+        }
 
-        fs::write(&self.watched_path, json)
-            .map_err(|e| StoreError::Write(format!("cannot write watched: {e}")))
+        self.record(JournalAction::MarkedWatched {
+            video_ids: newly_watched,
+            progress: cleared,
+        })
     }
 
-    fn mark_watched_batch(&self, video_ids: &[&VideoId]) -> Result<(), StoreError> {
-        let mut watched = self.load_watched()?;
-        for id in video_ids {
-            watched.insert((*id).clone());
+    fn load_progress(&self) -> Result<HashMap<VideoId, WatchProgress>, StoreError> {
+        read_json(&self.progress_path, "progress").map(Option::unwrap_or_default)
+    }
+
+    fn save_progress(&self, video_id: &VideoId, progress: WatchProgress) -> Result<(), StoreError> {
+        let mut all = self.load_progress()?;
+        all.insert(video_id.clone(), progress);
+        write_json(&self.progress_path, &all, "progress")
+    }
+}
+
+impl ActionJournal for JsonVideoStore {
+    /// Stages every file before putting any in place, so a failed write
+    /// leaves the undo unapplied and its entries in the journal. The journal
+    /// goes in last: if a rename fails before it, undoing again reverts the
+    /// same entries, which changes nothing already reverted.
+    fn undo(&self, count: NonZeroUsize) -> Result<Vec<JournalEntry>, StoreError> {
+        let mut journal = self.load_journal()?;
+        let keep = journal.len().saturating_sub(count.get());
+        let undone: Vec<JournalEntry> = journal.split_off(keep).into_iter().rev().collect();
+        if undone.is_empty() {
+            return Ok(undone);
         }
 
-        let json = serde_json::to_string_pretty(&watched)
-            .map_err(|e| StoreError::Write(format!("cannot serialize watched: {e}")))?;
+        let mut watched = self.load_watched()?;
+        let mut progress = self.load_progress()?;
+        for entry in &undone {
+            entry.action.revert(&mut watched, &mut progress);
+        }
 
-        fs::write(&self.watched_path, json)
-            .map_err(|e| StoreError::Write(format!("cannot write watched: {e}")))
+        let mut watched_at = self.load_watched_at()?;
+        watched_at.retain(|id, _| watched.contains(id));
+
+        let staged = [
+            stage_json(&self.watched_path, &watched, "watched")?,
+            stage_json(&self.watched_at_path, &watched_at, "watched_at")?,
+            stage_json(&self.progress_path, &progress, "progress")?,
+            stage_json(&self.journal_path, &journal, "journal")?,
+        ];
+        for file in staged {
+            file.commit()?;
+        }
+        Ok(undone)
     }
 }
 
@@ -134,4 +226,191 @@ mod tests {
         assert!(watched.contains(&VideoId::parse("v1").unwrap()));
         assert!(watched.contains(&v2));
     }
+
+    #[test]
+    fn remembers_when_videos_were_first_watched() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonVideoStore::new(&dir.path().to_path_buf()).unwrap();
+        let v1 = VideoId::parse("v1").unwrap();
+        let v2 = VideoId::parse("v2").unwrap();
+
+        store.mark_watched(&v1).unwrap();
+        let first = store.load_watched_at().unwrap()[&v1];
+        store.mark_watched_batch(&[&v1, &v2]).unwrap();
+
+        let watched_at = store.load_watched_at().unwrap();
+        assert_eq!(watched_at[&v1], first);
+        assert!(watched_at.contains_key(&v2));
+
+        undo_once(&store);
+        assert!(!store.load_watched_at().unwrap().contains_key(&v2));
+    }
+
+    #[test]
+    fn saves_progress_until_marked_watched() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonVideoStore::new(&dir.path().to_path_buf()).unwrap();
+        let v1 = VideoId::parse("v1").unwrap();
+        let v2 = VideoId::parse("v2").unwrap();
+        let started = WatchProgress::from_percent(40.0).unwrap();
+
+        store.save_progress(&v1, started).unwrap();
+        store.save_progress(&v2, started).unwrap();
+        store.mark_watched(&v1).unwrap();
+
+        let progress = store.load_progress().unwrap();
+        assert_eq!(progress.len(), 1);
+        assert_eq!(progress.get(&v2), Some(&started));
+    }
+
+    #[test]
+    fn undo_restores_progress_cleared_by_the_mark() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonVideoStore::new(&dir.path().to_path_buf()).unwrap();
+        let v1 = VideoId::parse("v1").unwrap();
+        let started = WatchProgress::from_percent(40.0).unwrap();
+
+        store.save_progress(&v1, started).unwrap();
+        store.mark_watched(&v1).unwrap();
+        assert!(store.load_progress().unwrap().is_empty());
+
+        undo_once(&store);
+
+        assert_eq!(store.load_progress().unwrap().get(&v1), Some(&started));
+        assert!(!store.load_watched().unwrap().contains(&v1));
+    }
+
+    fn undo_once(store: &JsonVideoStore) -> Vec<JournalEntry> {
+        store.undo(NonZeroUsize::new(1).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn undo_reverts_last_mark() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonVideoStore::new(&dir.path().to_path_buf()).unwrap();
+
+        store.mark_watched(&VideoId::parse("v1").unwrap()).unwrap();
+        store.mark_watched(&VideoId::parse("v2").unwrap()).unwrap();
+
+        let undone = undo_once(&store);
+
+        assert_eq!(undone.len(), 1);
+        let watched = store.load_watched().unwrap();
+        assert!(watched.contains(&VideoId::parse("v1").unwrap()));
+        assert!(!watched.contains(&VideoId::parse("v2").unwrap()));
+    }
+
+    #[test]
+    fn undo_reverts_whole_batch() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonVideoStore::new(&dir.path().to_path_buf()).unwrap();
+
+        let v1 = VideoId::parse("v1").unwrap();
+        let v2 = VideoId::parse("v2").unwrap();
+        store.mark_watched_batch(&[&v1, &v2]).unwrap();
+
+        undo_once(&store);
+
+        assert!(store.load_watched().unwrap().is_empty());
+    }
+
+    #[test]
+    fn undo_keeps_videos_watched_before_the_mark() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonVideoStore::new(&dir.path().to_path_buf()).unwrap();
+
+        let v1 = VideoId::parse("v1").unwrap();
+        let v2 = VideoId::parse("v2").unwrap();
+        store.mark_watched(&v1).unwrap();
+        store.mark_watched_batch(&[&v1, &v2]).unwrap();
+
+        let undone = undo_once(&store);
+
+        assert_eq!(undone[0].action.to_string(), "marked v2 as watched");
+        let watched = store.load_watched().unwrap();
+        assert!(watched.contains(&v1));
+        assert!(!watched.contains(&v2));
+    }
+
+    #[test]
+    fn marking_only_watched_videos_is_not_journaled() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonVideoStore::new(&dir.path().to_path_buf()).unwrap();
+
+        let v1 = VideoId::parse("v1").unwrap();
+        store.mark_watched(&v1).unwrap();
+        store.mark_watched(&v1).unwrap();
+
+        assert_eq!(store.load_journal().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn undo_multiple_returns_most_recent_first() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonVideoStore::new(&dir.path().to_path_buf()).unwrap();
+
+        store.mark_watched(&VideoId::parse("v1").unwrap()).unwrap();
+        store.mark_watched(&VideoId::parse("v2").unwrap()).unwrap();
+        store.mark_watched(&VideoId::parse("v3").unwrap()).unwrap();
+
+        let undone = store.undo(NonZeroUsize::new(2).unwrap()).unwrap();
+
+        assert_eq!(undone[0].action.to_string(), "marked v3 as watched");
+        assert_eq!(undone[1].action.to_string(), "marked v2 as watched");
+        let watched = store.load_watched().unwrap();
+        assert_eq!(watched.len(), 1);
+        assert!(watched.contains(&VideoId::parse("v1").unwrap()));
+    }
+
+    #[test]
+    fn undo_beyond_journal_undoes_everything() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonVideoStore::new(&dir.path().to_path_buf()).unwrap();
+
+        store.mark_watched(&VideoId::parse("v1").unwrap()).unwrap();
+
+        let undone = store.undo(NonZeroUsize::new(5).unwrap()).unwrap();
+
+        assert_eq!(undone.len(), 1);
+        assert!(store.load_watched().unwrap().is_empty());
+        assert!(undo_once(&store).is_empty());
+    }
+
+    #[test]
+    fn failed_undo_changes_nothing() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonVideoStore::new(&dir.path().to_path_buf()).unwrap();
+        let v1 = VideoId::parse("v1").unwrap();
+        let v2 = VideoId::parse("v2").unwrap();
+
+        store.mark_watched(&v1).unwrap();
+        store.mark_watched(&v2).unwrap();
+        let blocker = dir.path().join("progress.json.tmp");
+        fs::create_dir(&blocker).unwrap();
+
+        assert!(store.undo(NonZeroUsize::new(1).unwrap()).is_err());
+        assert!(store.load_watched().unwrap().contains(&v2));
+        assert!(store.load_watched_at().unwrap().contains_key(&v2));
+        assert_eq!(store.load_journal().unwrap().len(), 2);
+        assert!(!dir.path().join("watched.json.tmp").exists());
+
+        fs::remove_dir(&blocker).unwrap();
+        let undone = undo_once(&store);
+        assert_eq!(undone[0].action.to_string(), "marked v2 as watched");
+        assert!(!store.load_watched().unwrap().contains(&v2));
+    }
+
+    #[test]
+    fn journal_keeps_only_recent_entries() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonVideoStore::new(&dir.path().to_path_buf()).unwrap();
+
+        for i in 0..MAX_JOURNAL_ENTRIES + 5 {
+            store
+                .mark_watched(&VideoId::parse(format!("v{i}")).unwrap())
+                .unwrap();
+        }
+
+        assert_eq!(store.load_journal().unwrap().len(), MAX_JOURNAL_ENTRIES);
+    }
 }
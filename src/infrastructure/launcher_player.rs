@@ -0,0 +1,122 @@
+use std::process::{Child, Command, Stdio};
+
+use super::browser::opener;
+use super::dependency::is_installed;
+use crate::application::ports::{PlayError, VideoPlayer};
+
+/// Players tried, in order, when mpv is missing, with the options they take
+/// before the URL.
+const CANDIDATES: &[(&str, &[&str])] = &[
+    ("iina", &[]),
+    ("vlc", &["--play-and-exit"]),
+    ("celluloid", &[]),
+];
+
+/// A player other than mpv, which blepo can only launch and wait for.
+pub struct LauncherPlayer {
+    program: &'static str,
+    args: Vec<String>,
+}
+
+impl LauncherPlayer {
+    /// The first installed of IINA (macOS only), VLC and Celluloid, or else
+    /// the platform's opener, which leaves the video to the browser.
+    pub fn detect() -> Self {
+        Self::first_installed(is_installed, cfg!(target_os = "macos"))
+    }
+
+    fn first_installed(installed: impl Fn(&str) -> bool, macos: bool) -> Self {
+        CANDIDATES
+            .iter()
+            .filter(|(program, _)| *program != "iina" || macos)
+            .find(|(program, _)| installed(program))
+            .map(|&(program, args)| Self {
+                program,
+                args: args.iter().map(|a| a.to_string()).collect(),
+            })
+            .unwrap_or_else(|| {
+                let (program, args) = opener();
+                Self { program, args }
+            })
+    }
+
+    pub fn name(&self) -> &str {
+        self.program
+    }
+
+    fn spawn(&self, url: &str) -> Result<Child, PlayError> {
+        Command::new(self.program)
+            .args(&self.args)
+            .arg(url)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| PlayError::PlayerFailed(format!("failed to launch {}: {e}", self.program)))
+    }
+}
+
+impl VideoPlayer for LauncherPlayer {
+    fn play(&self, url: &str) -> Result<Option<u32>, PlayError> {
+        Ok(Some(self.spawn(url)?.id()))
+    }
+
+    fn play_attached(&self, url: &str) -> Result<(), PlayError> {
+        let status = self.spawn(url)?.wait().map_err(|e| {
+            PlayError::PlayerFailed(format!("cannot wait for {}: {e}", self.program))
+        })?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(PlayError::PlayerFailed(format!(
+                "{} exited with {status}",
+                self.program
+            )))
+        }
+    }
+
+    fn play_tracked(&self, _url: &str) -> Result<f64, PlayError> {
+        Err(PlayError::PlayerFailed(format!(
+            "{} cannot report how far a video was played",
+            self.program
+        )))
+    }
+
+    /// Plays one video at a time, each counting as finished once the player
+    /// exits cleanly.
+    fn play_playlist(
+        &self,
+        urls: &[&str],
+        finished: &mut dyn FnMut(usize),
+    ) -> Result<(), PlayError> {
+        for (i, url) in urls.iter().enumerate() {
+            self.play_attached(url)?;
+            finished(i);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_players_in_order() {
+        let all = |_: &str| true;
+
+        assert_eq!(LauncherPlayer::first_installed(all, true).name(), "iina");
+        let vlc = LauncherPlayer::first_installed(all, false);
+        assert_eq!(vlc.name(), "vlc");
+        assert_eq!(vlc.args, ["--play-and-exit"]);
+        let celluloid = LauncherPlayer::first_installed(|p| p == "celluloid", false);
+        assert_eq!(celluloid.name(), "celluloid");
+    }
+
+    #[test]
+    fn falls_back_to_platform_opener() {
+        let player = LauncherPlayer::first_installed(|_| false, true);
+
+        assert_eq!(player.name(), opener().0);
+    }
+}
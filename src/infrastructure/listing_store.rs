@@ -0,0 +1,78 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::json_file::{read_json, write_json};
+use crate::application::ports::{ListingStore, StoreError};
+use crate::domain::video::Video;
+
+/// The last listing, in display order, in `<data_dir>/last_listing.json`.
+pub struct JsonListingStore {
+    path: PathBuf,
+}
+
+impl JsonListingStore {
+    pub fn new(data_dir: &Path) -> Result<Self, StoreError> {
+        fs::create_dir_all(data_dir)
+            .map_err(|e| StoreError::Write(format!("cannot create data dir: {e}")))?;
+        Ok(Self {
+            path: data_dir.join("last_listing.json"),
+        })
+    }
+}
+
+impl ListingStore for JsonListingStore {
+    fn save_listing(&self, videos: &[Video]) -> Result<(), StoreError> {
+        write_json(&self.path, &videos, "listing")
+    }
+
+    fn load_listing(&self) -> Result<Vec<Video>, StoreError> {
+        read_json(&self.path, "listing").map(Option::unwrap_or_default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::channel::ChannelId;
+    use crate::domain::video::{VideoId, VideoKind};
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    fn video(id: &str) -> Video {
+        Video {
+            id: VideoId::parse(id).unwrap(),
+            title: format!("Title {id}"),
+            url: format!("https://www.youtube.com/watch?v={id}"),
+            published: Utc::now(),
+            channel_name: "Chan".to_string(),
+            channel_id: ChannelId::parse("UCtest").unwrap(),
+            kind: VideoKind::Upload,
+            duration: None,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn empty_before_first_listing() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonListingStore::new(dir.path()).unwrap();
+
+        assert!(store.load_listing().unwrap().is_empty());
+    }
+
+    #[test]
+    fn keeps_listing_order() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonListingStore::new(dir.path()).unwrap();
+
+        store.save_listing(&[video("b"), video("a")]).unwrap();
+        let ids: Vec<String> = store
+            .load_listing()
+            .unwrap()
+            .iter()
+            .map(|v| v.id.to_string())
+            .collect();
+
+        assert_eq!(ids, ["b", "a"]);
+    }
+}
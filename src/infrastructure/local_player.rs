@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::application::ports::{PlayError, VideoPlayer};
+use crate::domain::video::VideoId;
+
+/// Plays the downloaded copy of a video instead of streaming it, when the
+/// file is still there; other videos go to the player as links.
+pub struct LocalFirstPlayer {
+    player: Box<dyn VideoPlayer>,
+    files: HashMap<VideoId, PathBuf>,
+}
+
+impl LocalFirstPlayer {
+    pub fn new(player: Box<dyn VideoPlayer>, files: HashMap<VideoId, PathBuf>) -> Self {
+        Self { player, files }
+    }
+
+    fn source(&self, url: &str) -> String {
+        VideoId::from_url(url)
+            .and_then(|id| self.files.get(&id))
+            .filter(|path| path.is_file())
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| url.to_string())
+    }
+}
+
+impl VideoPlayer for LocalFirstPlayer {
+    fn play(&self, url: &str) -> Result<Option<u32>, PlayError> {
+        self.player.play(&self.source(url))
+    }
+
+    fn play_attached(&self, url: &str) -> Result<(), PlayError> {
+        self.player.play_attached(&self.source(url))
+    }
+
+    fn play_tracked(&self, url: &str) -> Result<f64, PlayError> {
+        self.player.play_tracked(&self.source(url))
+    }
+
+    fn play_playlist(
+        &self,
+        urls: &[&str],
+        finished: &mut dyn FnMut(usize),
+    ) -> Result<(), PlayError> {
+        let sources: Vec<String> = urls.iter().map(|url| self.source(url)).collect();
+        let sources: Vec<&str> = sources.iter().map(String::as_str).collect();
+        self.player.play_playlist(&sources, finished)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::fs;
+    use std::rc::Rc;
+    use tempfile::TempDir;
+
+    #[derive(Default)]
+    struct MockPlayer {
+        played: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl VideoPlayer for MockPlayer {
+        fn play(&self, url: &str) -> Result<Option<u32>, PlayError> {
+            self.played.borrow_mut().push(url.to_string());
+            Ok(None)
+        }
+
+        fn play_attached(&self, url: &str) -> Result<(), PlayError> {
+            self.play(url).map(|_| ())
+        }
+
+        fn play_tracked(&self, url: &str) -> Result<f64, PlayError> {
+            self.play(url).map(|_| 100.0)
+        }
+
+        fn play_playlist(
+            &self,
+            urls: &[&str],
+            _finished: &mut dyn FnMut(usize),
+        ) -> Result<(), PlayError> {
+            self.played
+                .borrow_mut()
+                .extend(urls.iter().map(|url| url.to_string()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn plays_downloaded_files_that_are_still_there() {
+        let dir = TempDir::new().unwrap();
+        let saved = dir.path().join("saved.webm");
+        fs::write(&saved, b"video").unwrap();
+        let files = HashMap::from([
+            (VideoId::parse("saved").unwrap(), saved.clone()),
+            (
+                VideoId::parse("deleted").unwrap(),
+                dir.path().join("gone.webm"),
+            ),
+        ]);
+        let mock = MockPlayer::default();
+        let played = Rc::clone(&mock.played);
+        let player = LocalFirstPlayer::new(Box::new(mock), files);
+
+        player
+            .play_playlist(
+                &[
+                    "https://www.youtube.com/watch?v=saved",
+                    "https://www.youtube.com/watch?v=deleted",
+                    "https://www.youtube.com/watch?v=other",
+                ],
+                &mut |_| {},
+            )
+            .unwrap();
+
+        assert_eq!(
+            *played.borrow(),
+            [
+                saved.display().to_string(),
+                "https://www.youtube.com/watch?v=deleted".to_string(),
+                "https://www.youtube.com/watch?v=other".to_string(),
+            ]
+        );
+    }
+}
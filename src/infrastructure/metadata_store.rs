@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::json_file::{read_json, write_json};
+use crate::application::ports::{MetadataStore, StoreError};
+use crate::domain::metadata::VideoMetadata;
+use crate::domain::video::VideoId;
+
+/// Enriched video details, keyed by video ID, in `<data_dir>/metadata.json`.
+pub struct JsonMetadataStore {
+    path: PathBuf,
+}
+
+impl JsonMetadataStore {
+    pub fn new(data_dir: &Path) -> Result<Self, StoreError> {
+        fs::create_dir_all(data_dir)
+            .map_err(|e| StoreError::Write(format!("cannot create data dir: {e}")))?;
+        Ok(Self {
+            path: data_dir.join("metadata.json"),
+        })
+    }
+
+    fn load(&self) -> Result<HashMap<VideoId, VideoMetadata>, StoreError> {
+        read_json(&self.path, "metadata").map(Option::unwrap_or_default)
+    }
+}
+
+impl MetadataStore for JsonMetadataStore {
+    fn load_metadata(&self, video_id: &VideoId) -> Result<Option<VideoMetadata>, StoreError> {
+        Ok(self.load()?.remove(video_id))
+    }
+
+    fn save_metadata(&self, entries: &[(VideoId, VideoMetadata)]) -> Result<(), StoreError> {
+        let mut all = self.load()?;
+        all.extend(entries.iter().cloned());
+        write_json(&self.path, &all, "metadata")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn metadata(title: &str) -> VideoMetadata {
+        VideoMetadata {
+            title: title.to_string(),
+            view_count: Some(42),
+            ..VideoMetadata::default()
+        }
+    }
+
+    #[test]
+    fn missing_video_has_no_metadata() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonMetadataStore::new(dir.path()).unwrap();
+
+        assert_eq!(
+            store.load_metadata(&VideoId::parse("v1").unwrap()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn saved_metadata_accumulates() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonMetadataStore::new(dir.path()).unwrap();
+        let (v1, v2) = (VideoId::parse("v1").unwrap(), VideoId::parse("v2").unwrap());
+
+        store
+            .save_metadata(&[(v1.clone(), metadata("First"))])
+            .unwrap();
+        store
+            .save_metadata(&[(v2.clone(), metadata("Second"))])
+            .unwrap();
+
+        assert_eq!(store.load_metadata(&v1).unwrap(), Some(metadata("First")));
+        assert_eq!(store.load_metadata(&v2).unwrap(), Some(metadata("Second")));
+    }
+}
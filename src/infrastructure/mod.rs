@@ -1,7 +1,45 @@
+pub mod browser;
+pub mod catt_player;
+pub mod channel_names;
+pub mod channel_resolver;
+pub mod clipboard;
 pub mod config;
+pub mod config_channels;
+pub mod config_keys;
+pub mod cookies;
+pub mod dependency;
+pub mod desktop_notifier;
+pub mod digest_store;
+pub mod download_store;
+pub mod editor;
 pub mod fallback_fetcher;
+pub mod feed_cache;
+pub mod fixtures;
+pub mod hook;
+pub mod http;
+pub mod instance_lock;
+pub mod job_store;
+pub mod json_file;
 pub mod json_store;
+pub mod launcher_player;
+pub mod listing_store;
+pub mod local_player;
+pub mod metadata_store;
 pub mod mpv_player;
+pub mod nfo;
+pub mod notified_store;
+pub mod processes;
+pub mod queue_store;
+pub mod rate_limit;
+pub mod retry;
 pub mod rss_fetcher;
 pub mod shorts_checker;
+pub mod snooze_store;
+pub mod sponsorblock;
+pub mod streams_fetcher;
+pub mod thumbnails;
+pub mod ttl_fetcher;
+pub mod webhook;
+pub mod ytdlp_downloader;
 pub mod ytdlp_fetcher;
+pub mod ytdlp_metadata;
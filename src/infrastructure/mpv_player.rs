@@ -1,45 +1,300 @@
-use std::process::{Command, Stdio};
+#[cfg(unix)]
+use std::io::{BufRead, BufReader, Write};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+#[cfg(unix)]
+use std::time::{Duration, Instant};
 
+use super::cookies::Cookies;
+use super::dependency::{MPV, YT_DLP};
+use super::http::ProxyUrl;
 use crate::application::ports::{PlayError, VideoPlayer};
+use crate::domain::playback::{PlaybackSpeed, Subtitles};
+use crate::domain::sponsor::SkipSegment;
 
-pub struct MpvPlayer;
+pub struct MpvPlayer {
+    proxy: Option<ProxyUrl>,
+    cookies: Option<Cookies>,
+    skip_args: Vec<String>,
+    preference_args: Vec<String>,
+    extra_args: Vec<String>,
+}
 
 impl MpvPlayer {
+    /// Fails when mpv isn't installed. yt-dlp, which mpv needs to play
+    /// YouTube links, is only checked once one is played.
     pub fn new() -> Result<Self, PlayError> {
-        check_dependency("mpv")?;
-        check_dependency("yt-dlp")?;
-        Ok(Self)
+        MPV.require()
+            .map_err(|e| PlayError::PlayerFailed(e.to_string()))?;
+        Ok(Self {
+            proxy: None,
+            cookies: None,
+            skip_args: Vec::new(),
+            preference_args: Vec::new(),
+            extra_args: Vec::new(),
+        })
     }
-}
 
-impl VideoPlayer for MpvPlayer {
-    fn play(&self, url: &str) -> Result<(), PlayError> {
+    pub fn with_proxy(self, proxy: Option<ProxyUrl>) -> Self {
+        Self { proxy, ..self }
+    }
+
+    /// Sends the user's cookies, for age-restricted and members-only videos.
+    pub fn with_cookies(self, cookies: Option<Cookies>) -> Self {
+        Self { cookies, ..self }
+    }
+
+    /// Loads the skip `script` and hands it the segments to jump over.
+    pub fn with_skip_segments(self, script: &Path, segments: &[SkipSegment]) -> Self {
+        if segments.is_empty() {
+            return self;
+        }
+        let segments: Vec<String> = segments
+            .iter()
+            .map(|s| format!("{}-{}", s.start, s.end))
+            .collect();
+        let skip_args = vec![
+            format!("--script={}", script.display()),
+            format!(
+                "--script-opts-append=blepo_sponsorblock-segments={}",
+                segments.join("|")
+            ),
+        ];
+        Self { skip_args, ..self }
+    }
+
+    /// Starting speed and subtitle languages; yt-dlp fetches only the
+    /// subtitles asked for, automatic captions included if allowed.
+    pub fn with_preferences(
+        self,
+        speed: Option<PlaybackSpeed>,
+        subtitles: Option<&Subtitles>,
+    ) -> Self {
+        let mut preference_args = Vec::new();
+        if let Some(speed) = speed {
+            preference_args.push(format!("--speed={}", speed.as_f64()));
+        }
+        if let Some(subtitles) = subtitles {
+            let languages = subtitles.languages().join(",");
+            preference_args.push(format!("--slang={languages}"));
+            preference_args.push(format!("--ytdl-raw-options-append=sub-langs={languages}"));
+            if subtitles.auto_generated() {
+                preference_args.push("--ytdl-raw-options-append=write-auto-subs=".to_string());
+            }
+        }
+        Self {
+            preference_args,
+            ..self
+        }
+    }
+
+    /// Options from the user's config, passed after blepo's own so they can
+    /// override them.
+    pub fn with_args(self, extra_args: Vec<String>) -> Self {
+        Self { extra_args, ..self }
+    }
+
+    fn spawn(&self, args: Vec<String>) -> Result<Child, PlayError> {
         Command::new("mpv")
-            .arg(url)
+            .args(args)
             .stdin(Stdio::null())
             .stdout(Stdio::null())
             .stderr(Stdio::null())
             .spawn()
-            .map_err(|e| PlayError::PlayerFailed(format!("failed to launch mpv: {e}")))?;
+            .map_err(|e| PlayError::PlayerFailed(format!("failed to launch mpv: {e}")))
+    }
 
-        Ok(())
+    /// mpv hands links to yt-dlp; files on disk play without it.
+    fn check_sources(urls: &[&str]) -> Result<(), PlayError> {
+        if urls.iter().all(|url| Path::new(url).is_file()) {
+            return Ok(());
+        }
+        YT_DLP
+            .require()
+            .map_err(|e| PlayError::PlayerFailed(e.to_string()))
+    }
+
+    fn args(&self, url: &str) -> Vec<String> {
+        self.playlist_args(&[url])
     }
-}
 
-pub(crate) fn check_dependency(name: &str) -> Result<(), PlayError> {
-    Command::new("which")
-        .arg(name)
-        .output()
-        .map_err(|e| PlayError::PlayerFailed(format!("cannot check for {name}: {e}")))
-        .and_then(|output| {
-            if output.status.success() {
-                Ok(())
-            } else {
-                Err(PlayError::PlayerFailed(format!(
-                    "{name} is not installed. Install it with: brew install {name}"
-                )))
+    fn playlist_args(&self, urls: &[&str]) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(proxy) = &self.proxy {
+            args.push(format!(
+                "--ytdl-raw-options-append=proxy={}",
+                proxy.as_str()
+            ));
+            if proxy.is_http() {
+                args.push(format!("--http-proxy={}", proxy.as_str()));
             }
+        }
+        if let Some(cookies) = &self.cookies {
+            args.push(cookies.mpv_arg());
+        }
+        args.extend(self.skip_args.iter().cloned());
+        args.extend(self.preference_args.iter().cloned());
+        args.extend(self.extra_args.iter().cloned());
+        args.extend(urls.iter().map(|url| url.to_string()));
+        args
+    }
+
+    /// Runs mpv in the foreground with an IPC socket handed to `observe`,
+    /// which reads events until mpv exits.
+    #[cfg(unix)]
+    fn run_with_ipc<T>(
+        &self,
+        args: Vec<String>,
+        observe: impl FnOnce(UnixStream) -> Result<T, PlayError>,
+    ) -> Result<T, PlayError> {
+        let socket = std::env::temp_dir().join(format!("blepo-mpv-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&socket);
+        let mut ipc_args = vec![format!("--input-ipc-server={}", socket.display())];
+        ipc_args.extend(args);
+        let mut child = self.spawn(ipc_args)?;
+
+        let observed = connect(&socket, &mut child).and_then(observe);
+        let exited = wait_success(&mut child);
+        let _ = std::fs::remove_file(&socket);
+        let observed = observed?;
+        exited.map(|()| observed)
+    }
+}
+
+impl VideoPlayer for MpvPlayer {
+    fn play(&self, url: &str) -> Result<Option<u32>, PlayError> {
+        Self::check_sources(&[url])?;
+        Ok(Some(self.spawn(self.args(url))?.id()))
+    }
+
+    fn play_attached(&self, url: &str) -> Result<(), PlayError> {
+        Self::check_sources(&[url])?;
+        let mut child = self.spawn(self.args(url))?;
+        wait_success(&mut child)
+    }
+
+    #[cfg(unix)]
+    fn play_tracked(&self, url: &str) -> Result<f64, PlayError> {
+        Self::check_sources(&[url])?;
+        self.run_with_ipc(self.args(url), observe_position)
+    }
+
+    /// mpv's IPC server is a Unix socket here; without it playback can't be
+    /// followed, so a clean exit counts as played to the end.
+    #[cfg(not(unix))]
+    fn play_tracked(&self, url: &str) -> Result<f64, PlayError> {
+        self.play_attached(url).map(|()| 100.0)
+    }
+
+    #[cfg(unix)]
+    fn play_playlist(
+        &self,
+        urls: &[&str],
+        finished: &mut dyn FnMut(usize),
+    ) -> Result<(), PlayError> {
+        Self::check_sources(urls)?;
+        self.run_with_ipc(self.playlist_args(urls), |stream| {
+            finished_entries(BufReader::new(stream), finished);
+            Ok(())
         })
+    }
+
+    /// Without IPC, plays one video at a time, each counting as finished
+    /// once mpv exits cleanly.
+    #[cfg(not(unix))]
+    fn play_playlist(
+        &self,
+        urls: &[&str],
+        finished: &mut dyn FnMut(usize),
+    ) -> Result<(), PlayError> {
+        for (i, url) in urls.iter().enumerate() {
+            self.play_attached(url)?;
+            finished(i);
+        }
+        Ok(())
+    }
+}
+
+/// mpv exits non-zero when the file could not be played, e.g. a deleted or
+/// geo-blocked video.
+fn wait_success(child: &mut Child) -> Result<(), PlayError> {
+    let status = child
+        .wait()
+        .map_err(|e| PlayError::PlayerFailed(format!("cannot wait for mpv: {e}")))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(PlayError::PlayerFailed(format!("mpv exited with {status}")))
+    }
+}
+
+/// How long mpv gets to open its IPC socket before we give up on it.
+#[cfg(unix)]
+const IPC_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[cfg(unix)]
+fn connect(socket: &Path, child: &mut Child) -> Result<UnixStream, PlayError> {
+    let started = Instant::now();
+    loop {
+        if let Ok(stream) = UnixStream::connect(socket) {
+            return Ok(stream);
+        }
+        if let Ok(Some(status)) = child.try_wait() {
+            return Err(PlayError::PlayerFailed(format!(
+                "mpv exited before playback started ({status})"
+            )));
+        }
+        if started.elapsed() > IPC_CONNECT_TIMEOUT {
+            let _ = child.kill();
+            return Err(PlayError::PlayerFailed(
+                "mpv did not open its IPC socket".to_string(),
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Follows `percent-pos` until mpv closes the socket on exit.
+#[cfg(unix)]
+fn observe_position(mut stream: UnixStream) -> Result<f64, PlayError> {
+    stream
+        .write_all(b"{\"command\":[\"observe_property\",1,\"percent-pos\"]}\n")
+        .map_err(|e| PlayError::PlayerFailed(format!("mpv IPC: {e}")))?;
+    Ok(furthest_percent(BufReader::new(stream)))
+}
+
+/// Highest `percent-pos` reported in mpv's event stream. Seeking back does
+/// not undo progress already made.
+#[cfg(unix)]
+fn furthest_percent(events: impl BufRead) -> f64 {
+    events
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(&line).ok())
+        .filter(|event| event["event"] == "property-change" && event["name"] == "percent-pos")
+        .filter_map(|event| event["data"].as_f64())
+        .fold(0.0, f64::max)
+}
+
+/// Reports each playlist entry, by 0-based index, that played to its end or
+/// was skipped with "next". Quitting or failing does not count.
+#[cfg(unix)]
+fn finished_entries(events: impl BufRead, finished: &mut dyn FnMut(usize)) {
+    for event in events
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(&line).ok())
+    {
+        let advanced =
+            event["event"] == "end-file" && (event["reason"] == "eof" || event["reason"] == "stop");
+        // mpv numbers entries from 1 in the order they were given.
+        match event["playlist_entry_id"].as_u64() {
+            Some(id @ 1..) if advanced => finished(id as usize - 1),
+            _ => {}
+        }
+    }
 }
 
 #[cfg(test)]
@@ -47,26 +302,223 @@ mod tests {
     use super::*;
 
     #[test]
-    fn check_dependency_finds_existing_binary() {
-        // "sh" exists on every Unix system
-        assert!(check_dependency("sh").is_ok());
+    fn passes_url_alone_without_proxy() {
+        let player = MpvPlayer {
+            proxy: None,
+            cookies: None,
+            skip_args: Vec::new(),
+            preference_args: Vec::new(),
+            extra_args: Vec::new(),
+        };
+
+        assert_eq!(player.args("https://youtu.be/x"), ["https://youtu.be/x"]);
+    }
+
+    #[test]
+    fn routes_ytdl_and_http_through_http_proxy() {
+        let player = MpvPlayer {
+            proxy: Some(ProxyUrl::parse("http://proxy:3128").unwrap()),
+            cookies: None,
+            skip_args: Vec::new(),
+            preference_args: Vec::new(),
+            extra_args: Vec::new(),
+        };
+
+        assert_eq!(
+            player.args("https://youtu.be/x"),
+            [
+                "--ytdl-raw-options-append=proxy=http://proxy:3128",
+                "--http-proxy=http://proxy:3128",
+                "https://youtu.be/x",
+            ]
+        );
+    }
+
+    #[test]
+    fn socks_proxy_only_applies_to_ytdl() {
+        let player = MpvPlayer {
+            proxy: Some(ProxyUrl::parse("socks5://127.0.0.1:1080").unwrap()),
+            cookies: None,
+            skip_args: Vec::new(),
+            preference_args: Vec::new(),
+            extra_args: Vec::new(),
+        };
+
+        let args = player.args("https://youtu.be/x");
+
+        assert!(!args.iter().any(|a| a.starts_with("--http-proxy")));
+        assert_eq!(
+            args[0],
+            "--ytdl-raw-options-append=proxy=socks5://127.0.0.1:1080"
+        );
+    }
+
+    #[test]
+    fn hands_cookies_to_ytdl() {
+        let player = MpvPlayer {
+            proxy: None,
+            cookies: Some(Cookies::Browser("firefox".to_string())),
+            skip_args: Vec::new(),
+            preference_args: Vec::new(),
+            extra_args: Vec::new(),
+        };
+
+        assert_eq!(
+            player.args("https://youtu.be/x"),
+            [
+                "--ytdl-raw-options-append=cookies-from-browser=firefox",
+                "https://youtu.be/x",
+            ]
+        );
     }
 
     #[test]
-    fn check_dependency_rejects_missing_binary() {
-        let result = check_dependency("nonexistent_binary_xyz_123");
+    fn appends_configured_args_before_url() {
+        let player = MpvPlayer {
+            proxy: Some(ProxyUrl::parse("socks5://127.0.0.1:1080").unwrap()),
+            cookies: None,
+            skip_args: Vec::new(),
+            preference_args: Vec::new(),
+            extra_args: vec!["--fs".to_string(), "--profile=tv".to_string()],
+        };
 
-        assert!(result.is_err());
+        assert_eq!(
+            player.args("https://youtu.be/x"),
+            [
+                "--ytdl-raw-options-append=proxy=socks5://127.0.0.1:1080",
+                "--fs",
+                "--profile=tv",
+                "https://youtu.be/x",
+            ]
+        );
     }
 
     #[test]
-    fn check_dependency_error_includes_binary_name() {
-        let result = check_dependency("nonexistent_binary_xyz_123");
+    fn translates_speed_and_subtitles() {
+        let player = MpvPlayer {
+            proxy: None,
+            cookies: None,
+            skip_args: Vec::new(),
+            preference_args: Vec::new(),
+            extra_args: vec!["--speed=1".to_string()],
+        }
+        .with_preferences(
+            Some(PlaybackSpeed::parse(1.5).unwrap()),
+            Some(&Subtitles::parse("en,de,auto").unwrap()),
+        );
+
+        assert_eq!(
+            player.args("https://youtu.be/x"),
+            [
+                "--speed=1.5",
+                "--slang=en,de",
+                "--ytdl-raw-options-append=sub-langs=en,de",
+                "--ytdl-raw-options-append=write-auto-subs=",
+                "--speed=1",
+                "https://youtu.be/x",
+            ]
+        );
+    }
 
-        let err = result.unwrap_err();
-        assert!(
-            err.to_string().contains("nonexistent_binary_xyz_123"),
-            "error should include binary name, got: {err}"
+    #[test]
+    fn passes_skip_segments_to_script() {
+        let player = MpvPlayer {
+            proxy: None,
+            cookies: None,
+            skip_args: Vec::new(),
+            preference_args: Vec::new(),
+            extra_args: vec!["--fs".to_string()],
+        }
+        .with_skip_segments(
+            Path::new("/data/blepo_sponsorblock.lua"),
+            &[
+                SkipSegment {
+                    start: 12.5,
+                    end: 45.0,
+                },
+                SkipSegment {
+                    start: 300.0,
+                    end: 330.0,
+                },
+            ],
         );
+
+        assert_eq!(
+            player.args("https://youtu.be/x"),
+            [
+                "--script=/data/blepo_sponsorblock.lua",
+                "--script-opts-append=blepo_sponsorblock-segments=12.5-45|300-330",
+                "--fs",
+                "https://youtu.be/x",
+            ]
+        );
+    }
+
+    #[test]
+    fn no_script_without_segments() {
+        let player = MpvPlayer {
+            proxy: None,
+            cookies: None,
+            skip_args: Vec::new(),
+            preference_args: Vec::new(),
+            extra_args: Vec::new(),
+        }
+        .with_skip_segments(Path::new("/data/blepo_sponsorblock.lua"), &[]);
+
+        assert_eq!(player.args("https://youtu.be/x"), ["https://youtu.be/x"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn furthest_percent_keeps_the_maximum_position() {
+        let events = concat!(
+            "{\"request_id\":0,\"error\":\"success\"}\n",
+            "{\"event\":\"property-change\",\"id\":1,\"name\":\"percent-pos\"}\n",
+            "{\"event\":\"property-change\",\"id\":1,\"name\":\"percent-pos\",\"data\":12.5}\n",
+            "{\"event\":\"property-change\",\"id\":1,\"name\":\"percent-pos\",\"data\":87.0}\n",
+            "{\"event\":\"seek\"}\n",
+            "{\"event\":\"property-change\",\"id\":1,\"name\":\"percent-pos\",\"data\":3.0}\n",
+        );
+
+        assert_eq!(furthest_percent(events.as_bytes()), 87.0);
+    }
+
+    #[test]
+    fn playlist_args_list_every_url() {
+        let player = MpvPlayer {
+            proxy: None,
+            cookies: None,
+            skip_args: Vec::new(),
+            preference_args: Vec::new(),
+            extra_args: vec!["--fs".to_string()],
+        };
+
+        assert_eq!(
+            player.playlist_args(&["https://youtu.be/a", "https://youtu.be/b"]),
+            ["--fs", "https://youtu.be/a", "https://youtu.be/b"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reports_entries_that_ended_or_were_skipped() {
+        let events = concat!(
+            "{\"event\":\"start-file\",\"playlist_entry_id\":1}\n",
+            "{\"event\":\"end-file\",\"reason\":\"eof\",\"playlist_entry_id\":1}\n",
+            "{\"event\":\"end-file\",\"reason\":\"error\",\"playlist_entry_id\":2}\n",
+            "{\"event\":\"end-file\",\"reason\":\"stop\",\"playlist_entry_id\":3}\n",
+            "{\"event\":\"end-file\",\"reason\":\"quit\",\"playlist_entry_id\":4}\n",
+        );
+        let mut finished = Vec::new();
+
+        finished_entries(events.as_bytes(), &mut |i| finished.push(i));
+
+        assert_eq!(finished, [0, 2]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn furthest_percent_is_zero_without_events() {
+        assert_eq!(furthest_percent("garbage\n".as_bytes()), 0.0);
     }
 }
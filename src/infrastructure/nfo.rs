@@ -0,0 +1,141 @@
+use chrono::NaiveDate;
+use quick_xml::escape::escape;
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const XML_HEADER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>";
+
+/// What yt-dlp reports about a video once it is saved.
+#[derive(Debug, Deserialize)]
+pub struct SavedVideo {
+    pub filepath: PathBuf,
+    pub id: String,
+    pub title: Option<String>,
+    pub channel: Option<String>,
+    pub channel_id: Option<String>,
+    /// `YYYYMMDD`.
+    pub upload_date: Option<String>,
+    pub description: Option<String>,
+}
+
+impl SavedVideo {
+    fn aired(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(self.upload_date.as_deref()?, "%Y%m%d").ok()
+    }
+}
+
+/// Writes the files Kodi and Jellyfin read next to a saved video: an
+/// episode `.nfo` and, in the channel's folder unless that is `root`, a
+/// `tvshow.nfo` so the channel shows up as a show. A thumbnail yt-dlp saved
+/// as `<name>.jpg` is renamed `<name>-thumb.jpg`, the name both look for.
+pub fn write_sidecars(video: &SavedVideo, root: &Path) -> io::Result<()> {
+    fs::write(video.filepath.with_extension("nfo"), episode_nfo(video))?;
+    let thumbnail = video.filepath.with_extension("jpg");
+    if thumbnail.exists() {
+        let mut renamed = video.filepath.with_extension("").into_os_string();
+        renamed.push("-thumb.jpg");
+        fs::rename(thumbnail, renamed)?;
+    }
+    match (video.filepath.parent(), &video.channel) {
+        (Some(folder), Some(channel)) if folder != root => {
+            let show = folder.join("tvshow.nfo");
+            if !show.exists() {
+                fs::write(show, tvshow_nfo(channel, video.channel_id.as_deref()))?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn episode_nfo(video: &SavedVideo) -> String {
+    let mut lines = vec![XML_HEADER.to_string(), "<episodedetails>".to_string()];
+    let title = video.title.as_deref().unwrap_or(&video.id);
+    lines.push(format!("  <title>{}</title>", escape(title)));
+    if let Some(channel) = &video.channel {
+        lines.push(format!("  <showtitle>{}</showtitle>", escape(channel)));
+    }
+    if let Some(description) = &video.description {
+        lines.push(format!("  <plot>{}</plot>", escape(description)));
+    }
+    if let Some(aired) = video.aired() {
+        lines.push(format!("  <aired>{aired}</aired>"));
+        lines.push(format!("  <premiered>{aired}</premiered>"));
+    }
+    lines.push(format!(
+        "  <uniqueid type=\"youtube\" default=\"true\">{}</uniqueid>",
+        escape(&video.id)
+    ));
+    lines.push("</episodedetails>".to_string());
+    lines.join("\n")
+}
+
+fn tvshow_nfo(channel: &str, channel_id: Option<&str>) -> String {
+    let mut lines = vec![XML_HEADER.to_string(), "<tvshow>".to_string()];
+    lines.push(format!("  <title>{}</title>", escape(channel)));
+    if let Some(id) = channel_id {
+        lines.push(format!(
+            "  <uniqueid type=\"youtube\" default=\"true\">{}</uniqueid>",
+            escape(id)
+        ));
+    }
+    lines.push("</tvshow>".to_string());
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn saved(filepath: PathBuf) -> SavedVideo {
+        SavedVideo {
+            filepath,
+            id: "abc123".to_string(),
+            title: Some("Fish & Chips".to_string()),
+            channel: Some("Cooking <Live>".to_string()),
+            channel_id: Some("UC123".to_string()),
+            upload_date: Some("20240120".to_string()),
+            description: Some("How to fry.".to_string()),
+        }
+    }
+
+    #[test]
+    fn episode_nfo_maps_the_video_to_an_episode() {
+        let nfo = episode_nfo(&saved(PathBuf::from("/videos/x.webm")));
+
+        assert_eq!(
+            nfo,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+             <episodedetails>\n  \
+             <title>Fish &amp; Chips</title>\n  \
+             <showtitle>Cooking &lt;Live&gt;</showtitle>\n  \
+             <plot>How to fry.</plot>\n  \
+             <aired>2024-01-20</aired>\n  \
+             <premiered>2024-01-20</premiered>\n  \
+             <uniqueid type=\"youtube\" default=\"true\">abc123</uniqueid>\n\
+             </episodedetails>"
+        );
+    }
+
+    #[test]
+    fn writes_sidecars_next_to_the_video() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let folder = root.join("Cooking");
+        fs::create_dir_all(&folder).unwrap();
+        let video = saved(folder.join("2024-01-20 Fish [abc123].webm"));
+        fs::write(folder.join("2024-01-20 Fish [abc123].jpg"), b"jpeg").unwrap();
+
+        write_sidecars(&video, root).unwrap();
+
+        assert!(folder.join("2024-01-20 Fish [abc123].nfo").exists());
+        assert!(folder.join("2024-01-20 Fish [abc123]-thumb.jpg").exists());
+        assert!(!folder.join("2024-01-20 Fish [abc123].jpg").exists());
+        let show = fs::read_to_string(folder.join("tvshow.nfo")).unwrap();
+        assert!(show.contains("<title>Cooking &lt;Live&gt;</title>"));
+        assert!(!root.join("tvshow.nfo").exists());
+    }
+}
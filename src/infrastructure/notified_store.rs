@@ -0,0 +1,32 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::json_file::{read_json, write_json};
+use crate::application::ports::{NotifiedStore, StoreError};
+use crate::domain::video::VideoId;
+
+/// Videos `blepo notify` has announced, in `<data_dir>/notified.json`.
+pub struct JsonNotifiedStore {
+    path: PathBuf,
+}
+
+impl JsonNotifiedStore {
+    pub fn new(data_dir: &Path) -> Result<Self, StoreError> {
+        fs::create_dir_all(data_dir)
+            .map_err(|e| StoreError::Write(format!("cannot create data dir: {e}")))?;
+        Ok(Self {
+            path: data_dir.join("notified.json"),
+        })
+    }
+}
+
+impl NotifiedStore for JsonNotifiedStore {
+    fn load_notified(&self) -> Result<Option<HashSet<VideoId>>, StoreError> {
+        read_json(&self.path, "notified videos")
+    }
+
+    fn save_notified(&self, video_ids: &HashSet<VideoId>) -> Result<(), StoreError> {
+        write_json(&self.path, video_ids, "notified videos")
+    }
+}
@@ -0,0 +1,118 @@
+use std::process::{Command, Output, Stdio};
+
+use crate::application::ports::{PlayError, ProcessControl};
+
+/// Checks processes with `kill` and `ps`, and stops them with `kill`. Where
+/// those don't exist (Windows), `tasklist`, PowerShell and `taskkill` stand
+/// in.
+pub struct SystemProcesses;
+
+impl SystemProcesses {
+    fn run(program: &str, args: &[&str]) -> std::io::Result<Output> {
+        Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+    }
+
+    /// Trimmed standard output, if the command succeeded and printed any.
+    fn stdout(program: &str, args: &[&str]) -> Option<String> {
+        let output = Self::run(program, args).ok()?;
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !stdout.is_empty()).then_some(stdout)
+    }
+
+    fn stop(pid: u32, program: &str, args: &[&str]) -> Result<(), PlayError> {
+        match Self::run(program, args) {
+            Ok(output) if output.status.success() => Ok(()),
+            Ok(output) => Err(PlayError::PlayerFailed(format!(
+                "cannot stop process {pid}: {program} exited with {}",
+                output.status
+            ))),
+            Err(e) => Err(PlayError::PlayerFailed(format!(
+                "cannot stop process {pid}: {e}"
+            ))),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl ProcessControl for SystemProcesses {
+    /// Signal 0 only checks that the process exists.
+    fn is_running(&self, pid: u32) -> bool {
+        Self::run("kill", &["-0", &pid.to_string()]).is_ok_and(|output| output.status.success())
+    }
+
+    /// The start time and command name `ps` reports, e.g.
+    /// `Tue Jun  3 18:30:00 2025 mpv`.
+    fn identity(&self, pid: u32) -> Option<String> {
+        Self::stdout("ps", &["-o", "lstart=,comm=", "-p", &pid.to_string()])
+    }
+
+    fn terminate(&self, pid: u32) -> Result<(), PlayError> {
+        Self::stop(pid, "kill", &[&pid.to_string()])
+    }
+}
+
+#[cfg(not(unix))]
+impl ProcessControl for SystemProcesses {
+    /// `tasklist` exits 0 either way, so its output is checked for the ID.
+    fn is_running(&self, pid: u32) -> bool {
+        let filter = format!("PID eq {pid}");
+        Self::stdout("tasklist", &["/FI", &filter, "/FO", "CSV", "/NH"])
+            .is_some_and(|listed| listed.contains(&format!("\"{pid}\"")))
+    }
+
+    /// The start time and process name PowerShell reports.
+    fn identity(&self, pid: u32) -> Option<String> {
+        let script = format!(
+            "$p = Get-Process -Id {pid} -ErrorAction Stop; '{{0:o}} {{1}}' -f $p.StartTime, $p.ProcessName"
+        );
+        Self::stdout("powershell", &["-NoProfile", "-Command", &script])
+    }
+
+    fn terminate(&self, pid: u32) -> Result<(), PlayError> {
+        Self::stop(pid, "taskkill", &["/PID", &pid.to_string()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sees_own_process_running() {
+        assert!(SystemProcesses.is_running(std::process::id()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn identifies_a_process_until_it_exits() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+
+        assert!(SystemProcesses.identity(child.id()).is_some());
+        SystemProcesses.terminate(child.id()).unwrap();
+        child.wait().unwrap();
+
+        assert_eq!(SystemProcesses.identity(child.id()), None);
+    }
+
+    #[test]
+    fn identity_is_stable_for_one_process() {
+        let own = SystemProcesses.identity(std::process::id());
+
+        assert!(own.is_some());
+        assert_eq!(SystemProcesses.identity(std::process::id()), own);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stops_a_running_process() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+
+        SystemProcesses.terminate(child.id()).unwrap();
+
+        assert!(!child.wait().unwrap().success());
+    }
+}
@@ -0,0 +1,78 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::json_file::{read_json, write_json};
+use crate::application::ports::{QueueStore, StoreError};
+use crate::domain::video::Video;
+
+/// The watch-later queue, in play order, in `<data_dir>/queue.json`.
+pub struct JsonQueueStore {
+    path: PathBuf,
+}
+
+impl JsonQueueStore {
+    pub fn new(data_dir: &Path) -> Result<Self, StoreError> {
+        fs::create_dir_all(data_dir)
+            .map_err(|e| StoreError::Write(format!("cannot create data dir: {e}")))?;
+        Ok(Self {
+            path: data_dir.join("queue.json"),
+        })
+    }
+}
+
+impl QueueStore for JsonQueueStore {
+    fn load_queue(&self) -> Result<Vec<Video>, StoreError> {
+        read_json(&self.path, "queue").map(Option::unwrap_or_default)
+    }
+
+    fn save_queue(&self, videos: &[Video]) -> Result<(), StoreError> {
+        write_json(&self.path, &videos, "queue")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::channel::ChannelId;
+    use crate::domain::video::{VideoId, VideoKind};
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    fn video(id: &str) -> Video {
+        Video {
+            id: VideoId::parse(id).unwrap(),
+            title: format!("Title {id}"),
+            url: format!("https://www.youtube.com/watch?v={id}"),
+            published: Utc::now(),
+            channel_name: "Chan".to_string(),
+            channel_id: ChannelId::parse("UCtest").unwrap(),
+            kind: VideoKind::Upload,
+            duration: None,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn empty_until_something_is_queued() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonQueueStore::new(dir.path()).unwrap();
+
+        assert!(store.load_queue().unwrap().is_empty());
+    }
+
+    #[test]
+    fn keeps_queue_order() {
+        let dir = TempDir::new().unwrap();
+        let store = JsonQueueStore::new(dir.path()).unwrap();
+
+        store.save_queue(&[video("b"), video("a")]).unwrap();
+        let ids: Vec<String> = store
+            .load_queue()
+            .unwrap()
+            .iter()
+            .map(|v| v.id.to_string())
+            .collect();
+
+        assert_eq!(ids, ["b", "a"]);
+    }
+}
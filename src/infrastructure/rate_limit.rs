@@ -0,0 +1,166 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+
+use crate::application::ports::FetchError;
+
+/// Spaces out requests to youtube.com across every thread that shares it.
+pub struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Blocks until this caller's slot comes up.
+    pub fn wait(&self) {
+        let slot = self.reserve(Instant::now());
+        let now = Instant::now();
+        if slot > now {
+            std::thread::sleep(slot - now);
+        }
+    }
+
+    /// Like `wait`, but yields to the other fetches instead of blocking the
+    /// thread they run on.
+    pub async fn wait_async(&self) {
+        let slot = self.reserve(Instant::now());
+        tokio::time::sleep_until(slot.into()).await;
+    }
+
+    /// Pauses every caller until `delay` has passed, e.g. after a 429.
+    pub fn back_off(&self, delay: Duration) {
+        let mut next = self.next_slot.lock().unwrap();
+        *next = (*next).max(Instant::now() + delay);
+    }
+
+    /// Builds the error for a 429 response with these `headers`, pausing all
+    /// callers for as long as its `Retry-After` header asks.
+    pub fn rate_limited(&self, headers: &HeaderMap) -> FetchError {
+        let retry_after = headers
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| parse_retry_after(v, Utc::now()));
+        if let Some(delay) = retry_after {
+            self.back_off(delay);
+        }
+        FetchError::RateLimited(retry_after)
+    }
+
+    fn reserve(&self, now: Instant) -> Instant {
+        let mut next = self.next_slot.lock().unwrap();
+        let slot = (*next).max(now);
+        *next = slot + self.interval;
+        slot
+    }
+}
+
+/// Parses a `Retry-After` value, either delay-seconds or an HTTP date.
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((at.with_timezone(&Utc) - now).to_std().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn reserves_consecutive_slots_one_interval_apart() {
+        let limiter = RateLimiter::new(Duration::from_millis(100));
+        let now = Instant::now();
+
+        let first = limiter.reserve(now);
+        let second = limiter.reserve(now);
+
+        assert_eq!(second - first, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn idle_limiter_does_not_delay() {
+        let limiter = RateLimiter::new(Duration::from_millis(100));
+        let later = Instant::now() + Duration::from_secs(5);
+
+        assert_eq!(limiter.reserve(later), later);
+    }
+
+    #[test]
+    fn back_off_pushes_next_slot_out() {
+        let limiter = RateLimiter::new(Duration::ZERO);
+        let now = Instant::now();
+
+        limiter.back_off(Duration::from_secs(30));
+
+        assert!(limiter.reserve(now) >= now + Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn waits_for_the_reserved_slot_without_blocking() {
+        let limiter = RateLimiter::new(Duration::from_millis(30));
+        let started = Instant::now();
+
+        limiter.wait_async().await;
+        limiter.wait_async().await;
+
+        assert!(started.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[test]
+    fn pauses_callers_for_retry_after() {
+        let limiter = RateLimiter::new(Duration::ZERO);
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "30".parse().unwrap());
+        let now = Instant::now();
+
+        let error = limiter.rate_limited(&headers);
+
+        assert!(matches!(error, FetchError::RateLimited(Some(d)) if d == Duration::from_secs(30)));
+        assert!(limiter.reserve(now) >= now + Duration::from_secs(30));
+    }
+
+    #[test]
+    fn parses_retry_after_seconds() {
+        assert_eq!(
+            parse_retry_after("120", Utc::now()),
+            Some(Duration::from_secs(120))
+        );
+    }
+
+    #[test]
+    fn parses_retry_after_http_date() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 20, 15, 0, 0).unwrap();
+
+        assert_eq!(
+            parse_retry_after("Sat, 20 Jan 2024 15:00:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+    }
+
+    #[test]
+    fn past_retry_after_date_means_no_wait() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 20, 15, 0, 0).unwrap();
+
+        assert_eq!(
+            parse_retry_after("Sat, 20 Jan 2024 14:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn ignores_garbage_retry_after() {
+        assert_eq!(parse_retry_after("soon", Utc::now()), None);
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use tracing::debug;
+
+use crate::application::ports::{FeedFetcher, FetchError, LocalFuture};
+use crate::domain::channel::Channel;
+use crate::domain::video::Video;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub retries: u32,
+    pub base_delay: Duration,
+    pub jitter: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 2,
+            base_delay: Duration::from_millis(500),
+            jitter: Duration::from_millis(250),
+        }
+    }
+}
+
+/// Upper bound on how long a single `Retry-After` can stall a channel.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+impl RetryPolicy {
+    /// Delay before retry number `attempt` (0-based): `base * 2^attempt` plus
+    /// up to `jitter`, where `jitter_fraction` is in `[0, 1)`.
+    pub fn delay_for(&self, attempt: u32, jitter_fraction: f64) -> Duration {
+        let backoff = self.base_delay.saturating_mul(2u32.saturating_pow(attempt));
+        backoff + self.jitter.mul_f64(jitter_fraction)
+    }
+
+    /// Like `delay_for`, but a server-provided `Retry-After` wins when present.
+    pub fn delay_after(&self, error: &FetchError, attempt: u32, jitter_fraction: f64) -> Duration {
+        match error {
+            FetchError::RateLimited(Some(retry_after)) => (*retry_after).min(MAX_RETRY_AFTER),
+            _ => self.delay_for(attempt, jitter_fraction),
+        }
+    }
+}
+
+pub fn is_transient(error: &FetchError) -> bool {
+    match error {
+        FetchError::Network(_) => true,
+        FetchError::HttpError(status) => *status == 429 || *status >= 500,
+        FetchError::RateLimited(_) => true,
+        FetchError::Parse(_) | FetchError::YtDlp(_) => false,
+    }
+}
+
+pub struct RetryingFetcher<F> {
+    inner: F,
+    policy: RetryPolicy,
+    sleep: fn(Duration) -> LocalFuture<'static, ()>,
+}
+
+impl<F: FeedFetcher> RetryingFetcher<F> {
+    pub fn new(inner: F, policy: RetryPolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            sleep: |delay| Box::pin(tokio::time::sleep(delay)),
+        }
+    }
+}
+
+impl<F: FeedFetcher> FeedFetcher for RetryingFetcher<F> {
+    fn fetch<'a>(
+        &'a self,
+        channel: &'a Channel,
+    ) -> LocalFuture<'a, Result<Vec<Video>, FetchError>> {
+        Box::pin(async move {
+            let mut attempt = 0;
+            loop {
+                match self.inner.fetch(channel).await {
+                    Err(e) if attempt < self.policy.retries && is_transient(&e) => {
+                        let delay = self.policy.delay_after(&e, attempt, random_fraction());
+                        debug!(error = %e, ?delay, "retrying");
+                        (self.sleep)(delay).await;
+                        attempt += 1;
+                    }
+                    result => return result,
+                }
+            }
+        })
+    }
+}
+
+fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::channel::ChannelId;
+    use std::sync::Mutex;
+
+    struct FlakyFetcher {
+        failures: Mutex<Vec<FetchError>>,
+        calls: Mutex<usize>,
+    }
+
+    impl FlakyFetcher {
+        fn failing_with(failures: Vec<FetchError>) -> Self {
+            Self {
+                failures: Mutex::new(failures),
+                calls: Mutex::new(0),
+            }
+        }
+
+        fn calls(&self) -> usize {
+            *self.calls.lock().unwrap()
+        }
+    }
+
+    impl FeedFetcher for FlakyFetcher {
+        fn fetch<'a>(
+            &'a self,
+            _channel: &'a Channel,
+        ) -> LocalFuture<'a, Result<Vec<Video>, FetchError>> {
+            *self.calls.lock().unwrap() += 1;
+            let mut failures = self.failures.lock().unwrap();
+            let result = if failures.is_empty() {
+                Ok(Vec::new())
+            } else {
+                Err(failures.remove(0))
+            };
+            Box::pin(std::future::ready(result))
+        }
+    }
+
+    fn retrying(inner: FlakyFetcher, retries: u32) -> RetryingFetcher<FlakyFetcher> {
+        RetryingFetcher {
+            inner,
+            policy: RetryPolicy {
+                retries,
+                ..RetryPolicy::default()
+            },
+            sleep: |_| Box::pin(std::future::ready(())),
+        }
+    }
+
+    fn test_channel() -> Channel {
+        Channel {
+            name: "Test".to_string(),
+            id: ChannelId::parse("UC123").unwrap(),
+            include_streams: false,
+            title_filter: Default::default(),
+            duration_range: Default::default(),
+            tags: Vec::new(),
+            name_from_feed: false,
+            alias: None,
+            snoozed_until: None,
+            player: None,
+            auto_download: false,
+        }
+    }
+
+    fn network() -> FetchError {
+        FetchError::Network("timeout".to_string())
+    }
+
+    #[tokio::test]
+    async fn succeeds_after_transient_failures() {
+        let fetcher = retrying(FlakyFetcher::failing_with(vec![network(), network()]), 2);
+
+        assert!(fetcher.fetch(&test_channel()).await.is_ok());
+        assert_eq!(fetcher.inner.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn surfaces_error_once_retries_are_exhausted() {
+        let fetcher = retrying(
+            FlakyFetcher::failing_with(vec![network(), network(), network()]),
+            2,
+        );
+
+        assert!(matches!(
+            fetcher.fetch(&test_channel()).await,
+            Err(FetchError::Network(_))
+        ));
+        assert_eq!(fetcher.inner.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_permanent_errors() {
+        let fetcher = retrying(
+            FlakyFetcher::failing_with(vec![FetchError::HttpError(404)]),
+            3,
+        );
+
+        assert!(matches!(
+            fetcher.fetch(&test_channel()).await,
+            Err(FetchError::HttpError(404))
+        ));
+        assert_eq!(fetcher.inner.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn fails_at_once_without_yt_dlp() {
+        let missing = FetchError::YtDlp("yt-dlp is not installed".to_string());
+        let fetcher = retrying(FlakyFetcher::failing_with(vec![missing]), 3);
+
+        assert!(matches!(
+            fetcher.fetch(&test_channel()).await,
+            Err(FetchError::YtDlp(_))
+        ));
+        assert_eq!(fetcher.inner.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn zero_retries_makes_a_single_attempt() {
+        let fetcher = retrying(FlakyFetcher::failing_with(vec![network()]), 0);
+
+        assert!(fetcher.fetch(&test_channel()).await.is_err());
+        assert_eq!(fetcher.inner.calls(), 1);
+    }
+
+    #[test]
+    fn classifies_transient_errors() {
+        assert!(is_transient(&network()));
+        assert!(is_transient(&FetchError::HttpError(503)));
+        assert!(is_transient(&FetchError::HttpError(429)));
+        assert!(is_transient(&FetchError::RateLimited(None)));
+        assert!(!is_transient(&FetchError::HttpError(404)));
+        assert!(!is_transient(&FetchError::Parse("bad".to_string())));
+        assert!(!is_transient(&FetchError::YtDlp(
+            "yt-dlp failed: ERROR".to_string()
+        )));
+    }
+
+    #[test]
+    fn delay_doubles_each_attempt_plus_jitter() {
+        let policy = RetryPolicy {
+            retries: 3,
+            base_delay: Duration::from_millis(100),
+            jitter: Duration::from_millis(50),
+        };
+
+        assert_eq!(policy.delay_for(0, 0.0), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(2, 0.0), Duration::from_millis(400));
+        assert_eq!(policy.delay_for(1, 0.5), Duration::from_millis(225));
+    }
+
+    #[tokio::test]
+    async fn retries_rate_limited_fetches() {
+        let fetcher = retrying(
+            FlakyFetcher::failing_with(vec![FetchError::RateLimited(None)]),
+            1,
+        );
+
+        assert!(fetcher.fetch(&test_channel()).await.is_ok());
+        assert_eq!(fetcher.inner.calls(), 2);
+    }
+
+    #[test]
+    fn retry_after_overrides_backoff_but_is_capped() {
+        let policy = RetryPolicy::default();
+        let asked = |secs| FetchError::RateLimited(Some(Duration::from_secs(secs)));
+
+        assert_eq!(
+            policy.delay_after(&asked(7), 0, 0.0),
+            Duration::from_secs(7)
+        );
+        assert_eq!(policy.delay_after(&asked(3600), 0, 0.0), MAX_RETRY_AFTER);
+        assert_eq!(
+            policy.delay_after(&FetchError::RateLimited(None), 1, 0.0),
+            policy.delay_for(1, 0.0)
+        );
+    }
+
+    #[test]
+    fn random_fraction_is_in_unit_interval() {
+        for _ in 0..100 {
+            assert!((0.0..1.0).contains(&random_fraction()));
+        }
+    }
+}
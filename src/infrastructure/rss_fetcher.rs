@@ -1,26 +1,38 @@
 use chrono::{DateTime, Utc};
 use quick_xml::de::from_str;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
 use serde::Deserialize;
-
-use crate::application::ports::{FeedFetcher, FetchError};
+use std::io;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use super::feed_cache::{CachedFeed, FeedCache};
+use super::fixtures::Fixtures;
+use super::http::{send_past_consent_async, HTTP_LOG_TARGET};
+use super::rate_limit::RateLimiter;
+use crate::application::ports::{FeedFetcher, FeedProbe, FeedSummary, FetchError, LocalFuture};
 use crate::domain::channel::Channel;
-use crate::domain::video::{Video, VideoId};
+use crate::domain::video::{IdRules, Video, VideoId, VideoKind};
 
 const RSS_URL_TEMPLATE: &str = "https://www.youtube.com/feeds/videos.xml?channel_id=";
 
 #[derive(Debug, Deserialize)]
 struct Feed {
+    title: Option<String>,
     #[serde(default)]
     entry: Vec<Entry>,
 }
 
+/// Every field is optional so that one incomplete entry is skipped rather
+/// than failing the whole feed.
 #[derive(Debug, Deserialize)]
 struct Entry {
     #[serde(rename = "videoId")]
-    video_id: String,
-    title: String,
-    published: String,
-    link: Link,
+    video_id: Option<String>,
+    title: Option<String>,
+    published: Option<String>,
+    link: Option<Link>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -30,70 +42,252 @@ struct Link {
 }
 
 pub struct RssFeedFetcher {
-    client: reqwest::blocking::Client,
+    client: reqwest::Client,
+    limiter: Arc<RateLimiter>,
+    cache: Option<FeedCache>,
+    fixtures: Option<Fixtures>,
 }
 
-impl Default for RssFeedFetcher {
-    fn default() -> Self {
+impl RssFeedFetcher {
+    pub fn new(client: reqwest::Client, limiter: Arc<RateLimiter>) -> Self {
         Self {
-            client: reqwest::blocking::Client::new(),
+            client,
+            limiter,
+            cache: None,
+            fixtures: None,
         }
     }
-}
 
-impl RssFeedFetcher {
-    pub fn new() -> Self {
-        Self::default()
+    /// Sends conditional requests and reuses the cached body on 304 Not Modified.
+    pub fn with_cache(self, cache: FeedCache) -> Self {
+        Self {
+            cache: Some(cache),
+            ..self
+        }
+    }
+
+    /// Saves each feed as `<id>.xml`, or reads it from there instead of
+    /// YouTube.
+    pub fn with_fixtures(self, fixtures: Option<Fixtures>) -> Self {
+        Self { fixtures, ..self }
     }
 }
 
 impl FeedFetcher for RssFeedFetcher {
-    fn fetch(&self, channel: &Channel) -> Result<Vec<Video>, FetchError> {
+    fn fetch<'a>(
+        &'a self,
+        channel: &'a Channel,
+    ) -> LocalFuture<'a, Result<Vec<Video>, FetchError>> {
+        Box::pin(self.fetch_feed(channel))
+    }
+}
+
+impl FeedProbe for RssFeedFetcher {
+    fn probe<'a>(
+        &'a self,
+        channel: &'a Channel,
+    ) -> LocalFuture<'a, Result<FeedSummary, FetchError>> {
+        Box::pin(async move {
+            self.limiter.wait_async().await;
+            let response = send_past_consent_async(
+                self.client.get(format!("{RSS_URL_TEMPLATE}{}", channel.id)),
+            )
+            .await?;
+            if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                return Err(self.limiter.rate_limited(response.headers()));
+            }
+            if !response.status().is_success() {
+                return Err(FetchError::HttpError(response.status().as_u16()));
+            }
+            let body = response
+                .text()
+                .await
+                .map_err(|e| FetchError::Network(e.to_string()))?;
+            summarize_feed(&body)
+        })
+    }
+}
+
+impl RssFeedFetcher {
+    async fn fetch_feed(&self, channel: &Channel) -> Result<Vec<Video>, FetchError> {
+        if let Some(replayed) = self
+            .fixtures
+            .as_ref()
+            .and_then(|f| f.replayed(&channel.id, "xml"))
+        {
+            let xml = replayed.map_err(|e| match e.kind() {
+                // Nothing recorded: the feed was missing, so fall back as then.
+                io::ErrorKind::NotFound => FetchError::HttpError(404),
+                _ => FetchError::Network(e.to_string()),
+            })?;
+            return parse_feed(&xml, channel, IdRules::YouTube).map(|feed| feed.videos_of(channel));
+        }
+
         let url = format!("{RSS_URL_TEMPLATE}{}", channel.id);
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .map_err(|e| FetchError::Network(e.to_string()))?;
+        let cached = self.cache.as_ref().and_then(|c| c.load(&channel.id));
+
+        let mut request = self.client.get(&url);
+        if let Some(feed) = &cached {
+            if let Some(etag) = &feed.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &feed.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        self.limiter.wait_async().await;
+        let response = send_past_consent_async(request).await?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(feed) = cached {
+                debug!(target: HTTP_LOG_TARGET, "feed cache hit: not modified");
+                self.record(channel, &feed.body);
+                return parse_feed(&feed.body, channel, IdRules::YouTube)
+                    .map(|feed| feed.videos_of(channel));
+            }
+        }
+
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err(self.limiter.rate_limited(response.headers()));
+        }
 
         let status = response.status().as_u16();
         if !response.status().is_success() {
             return Err(FetchError::HttpError(status));
         }
 
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+
         let body = response
             .text()
+            .await
             .map_err(|e| FetchError::Network(e.to_string()))?;
+        debug!(
+            target: HTTP_LOG_TARGET,
+            bytes = body.len(),
+            "feed cache miss: {}",
+            if cached.is_some() { "changed" } else { "not cached" }
+        );
 
-        parse_feed(&body, channel)
+        self.record(channel, &body);
+        let videos = parse_feed(&body, channel, IdRules::YouTube)?.videos_of(channel);
+        self.remember(
+            channel,
+            CachedFeed {
+                etag,
+                last_modified,
+                body,
+            },
+        );
+        Ok(videos)
+    }
+
+    fn record(&self, channel: &Channel, xml: &str) {
+        if let Some(fixtures) = &self.fixtures {
+            fixtures.record(&channel.id, "xml", xml);
+        }
+    }
+
+    fn remember(&self, channel: &Channel, feed: CachedFeed) {
+        let Some(cache) = &self.cache else {
+            return;
+        };
+        if !feed.has_validators() {
+            return;
+        }
+        if let Err(e) = cache.store(&channel.id, &feed) {
+            warn!("cannot cache feed for {}: {e}", channel.name);
+        }
     }
 }
 
-pub fn parse_feed(xml: &str, channel: &Channel) -> Result<Vec<Video>, FetchError> {
-    let feed: Feed = from_str(xml).map_err(|e| FetchError::Parse(e.to_string()))?;
+/// The videos of a feed, and why any of its entries were left out.
+#[derive(Debug)]
+pub struct ParsedFeed {
+    pub videos: Vec<Video>,
+    pub skipped: Vec<String>,
+}
 
-    feed.entry
-        .into_iter()
-        .map(|entry| parse_entry(entry, channel))
-        .collect()
+impl ParsedFeed {
+    /// The videos, after warning about the skipped entries.
+    fn videos_of(self, channel: &Channel) -> Vec<Video> {
+        for reason in &self.skipped {
+            warn!("skipped a feed entry of {}: {reason}", channel.name);
+        }
+        self.videos
+    }
 }
 
-fn parse_entry(entry: Entry, channel: &Channel) -> Result<Video, FetchError> {
-    let id = VideoId::parse(entry.video_id)
-        .map_err(|e| FetchError::Parse(format!("invalid video ID: {e}")))?;
+/// Fails only when the feed itself can't be read; entries with a missing
+/// field, a video ID `rules` reject or a bad date are skipped and reported
+/// in `skipped`.
+pub fn parse_feed(xml: &str, channel: &Channel, rules: IdRules) -> Result<ParsedFeed, FetchError> {
+    let feed: Feed = from_str(xml).map_err(|e| FetchError::Parse(e.to_string()))?;
+    let channel_name = feed
+        .title
+        .filter(|title| channel.name_from_feed && !title.trim().is_empty())
+        .unwrap_or_else(|| channel.name.clone());
+
+    let mut parsed = ParsedFeed {
+        videos: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for (index, entry) in feed.entry.into_iter().enumerate() {
+        let label = match &entry.video_id {
+            Some(id) => format!("entry {} ({id})", index + 1),
+            None => format!("entry {}", index + 1),
+        };
+        match parse_entry(entry, channel, &channel_name, rules) {
+            Ok(video) => parsed.videos.push(video),
+            Err(reason) => parsed.skipped.push(format!("{label}: {reason}")),
+        }
+    }
+    Ok(parsed)
+}
 
-    let published: DateTime<Utc> = entry
-        .published
+pub fn summarize_feed(xml: &str) -> Result<FeedSummary, FetchError> {
+    let feed: Feed = from_str(xml).map_err(|e| FetchError::Parse(e.to_string()))?;
+    Ok(FeedSummary {
+        title: feed.title,
+        entries: feed.entry.len(),
+    })
+}
+
+fn parse_entry(
+    entry: Entry,
+    channel: &Channel,
+    channel_name: &str,
+    rules: IdRules,
+) -> Result<Video, String> {
+    let missing = |field: &str| format!("missing {field}");
+    let id = VideoId::parse_with(entry.video_id.ok_or_else(|| missing("video ID"))?, rules)
+        .map_err(|e| format!("invalid video ID: {e}"))?;
+    let title = entry.title.ok_or_else(|| missing("title"))?;
+    let url = entry.link.ok_or_else(|| missing("link"))?.href;
+    let published = entry.published.ok_or_else(|| missing("date"))?;
+    let published: DateTime<Utc> = published
         .parse()
-        .map_err(|e| FetchError::Parse(format!("invalid date '{}': {e}", entry.published)))?;
+        .map_err(|e| format!("invalid date '{published}': {e}"))?;
 
     Ok(Video {
         id,
-        title: entry.title,
-        url: entry.link.href,
+        title,
+        url,
         published,
-        channel_name: channel.name.clone(),
+        channel_name: channel_name.to_string(),
         channel_id: channel.id.clone(),
+        kind: VideoKind::Upload,
+        duration: None,
+        tags: Vec::new(),
     })
 }
 
@@ -106,6 +300,15 @@ mod tests {
         Channel {
             name: "Test Channel".to_string(),
             id: ChannelId::parse("UC_x5XG1OV2P6uZZ5FSM9Ttw").unwrap(),
+            include_streams: false,
+            title_filter: Default::default(),
+            duration_range: Default::default(),
+            tags: Vec::new(),
+            name_from_feed: false,
+            alias: None,
+            snoozed_until: None,
+            player: None,
+            auto_download: false,
         }
     }
 
@@ -129,7 +332,9 @@ mod tests {
     #[test]
     fn parses_youtube_rss_feed() {
         let channel = test_channel();
-        let videos = parse_feed(SAMPLE_FEED, &channel).unwrap();
+        let videos = parse_feed(SAMPLE_FEED, &channel, IdRules::YouTube)
+            .unwrap()
+            .videos;
 
         assert_eq!(videos.len(), 2);
         assert_eq!(videos[0].id.to_string(), "dQw4w9WgXcQ");
@@ -138,10 +343,61 @@ mod tests {
         assert_eq!(videos[0].channel_name, "Test Channel");
     }
 
+    #[test]
+    fn names_videos_after_the_feed_for_unnamed_channels() {
+        let channel = Channel {
+            name: "UC_x5XG1OV2P6uZZ5FSM9Ttw".to_string(),
+            name_from_feed: true,
+            ..test_channel()
+        };
+        let videos = parse_feed(
+            &SAMPLE_FEED.replace("<title>Test Channel", "<title>Renamed"),
+            &channel,
+            IdRules::YouTube,
+        )
+        .unwrap()
+        .videos;
+
+        assert_eq!(videos[0].channel_name, "Renamed");
+        let named = parse_feed(
+            &SAMPLE_FEED.replace("<title>Test Channel", "<title>Renamed"),
+            &test_channel(),
+            IdRules::YouTube,
+        )
+        .unwrap()
+        .videos;
+        assert_eq!(named[0].channel_name, "Test Channel");
+    }
+
+    #[test]
+    fn skips_ids_the_rules_reject() {
+        let xml = SAMPLE_FEED.replace("abc123def45", "item-42");
+
+        let feed = parse_feed(&xml, &test_channel(), IdRules::YouTube).unwrap();
+        assert_eq!(feed.videos.len(), 1);
+        assert!(feed.skipped[0].contains("'item-42' is not a YouTube video ID"));
+
+        let feed = parse_feed(&xml, &test_channel(), IdRules::Relaxed).unwrap();
+        assert_eq!(feed.videos.len(), 2);
+    }
+
+    #[test]
+    fn summarizes_feeds() {
+        assert_eq!(
+            summarize_feed(SAMPLE_FEED).unwrap(),
+            FeedSummary {
+                title: Some("Test Channel".to_string()),
+                entries: 2,
+            }
+        );
+    }
+
     #[test]
     fn parses_published_dates() {
         let channel = test_channel();
-        let videos = parse_feed(SAMPLE_FEED, &channel).unwrap();
+        let videos = parse_feed(SAMPLE_FEED, &channel, IdRules::YouTube)
+            .unwrap()
+            .videos;
 
         assert_eq!(
             videos[0].published,
@@ -159,32 +415,127 @@ mod tests {
 </feed>"#;
 
         let channel = test_channel();
-        let videos = parse_feed(xml, &channel).unwrap();
+        let feed = parse_feed(xml, &channel, IdRules::YouTube).unwrap();
 
-        assert!(videos.is_empty());
+        assert!(feed.videos.is_empty());
+        assert!(feed.skipped.is_empty());
     }
 
     #[test]
     fn returns_error_for_invalid_xml() {
-        let result = parse_feed("not xml at all", &test_channel());
+        let result = parse_feed("not xml at all", &test_channel(), IdRules::YouTube);
 
         assert!(result.is_err());
     }
 
     #[test]
-    fn returns_error_for_invalid_date() {
+    fn skips_malformed_entries_and_keeps_the_rest() {
         let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
 <feed xmlns:yt="http://www.youtube.com/xml/schemas/2015" xmlns="http://www.w3.org/2005/Atom">
   <entry>
-    <yt:videoId>vid1</yt:videoId>
+    <yt:videoId>vid10000000</yt:videoId>
     <title>Bad Date Video</title>
-    <link rel="alternate" href="https://www.youtube.com/watch?v=vid1"/>
+    <link rel="alternate" href="https://www.youtube.com/watch?v=vid10000000"/>
     <published>not-a-date</published>
   </entry>
+  <entry>
+    <title>No ID Video</title>
+    <link rel="alternate" href="https://www.youtube.com/watch?v=vid20000000"/>
+    <published>2024-01-15T10:00:00+00:00</published>
+  </entry>
+  <entry>
+    <yt:videoId>vid30000000</yt:videoId>
+    <title>Good Video</title>
+    <link rel="alternate" href="https://www.youtube.com/watch?v=vid30000000"/>
+    <published>2024-01-14T08:30:00+00:00</published>
+  </entry>
+  <entry>
+    <yt:videoId>item-42</yt:videoId>
+    <title>Not From YouTube</title>
+    <link rel="alternate" href="https://example.com/item-42"/>
+    <published>2024-01-13T08:30:00+00:00</published>
+  </entry>
 </feed>"#;
 
-        let result = parse_feed(xml, &test_channel());
+        let feed = parse_feed(xml, &test_channel(), IdRules::YouTube).unwrap();
+
+        assert_eq!(feed.videos.len(), 1);
+        assert_eq!(feed.videos[0].title, "Good Video");
+        assert_eq!(feed.skipped.len(), 3);
+        assert!(feed.skipped[0].starts_with("entry 1 (vid10000000): invalid date 'not-a-date'"));
+        assert_eq!(feed.skipped[1], "entry 2: missing video ID");
+        assert!(feed.skipped[2].starts_with("entry 4 (item-42): invalid video ID"));
+
+        let feed = parse_feed(xml, &test_channel(), IdRules::Relaxed).unwrap();
+        assert_eq!(feed.videos.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_feeds_and_falls_back_without_one() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let fetcher = |fixtures: Fixtures| {
+            RssFeedFetcher::new(
+                reqwest::Client::new(),
+                Arc::new(RateLimiter::new(std::time::Duration::ZERO)),
+            )
+            .with_fixtures(Some(fixtures))
+        };
+        let replayer = fetcher(Fixtures::Replay(dir.path().to_path_buf()));
+
+        assert!(matches!(
+            replayer.fetch(&test_channel()).await,
+            Err(FetchError::HttpError(404))
+        ));
+
+        Fixtures::Record(dir.path().to_path_buf()).record(&test_channel().id, "xml", SAMPLE_FEED);
+        let videos = replayer.fetch(&test_channel()).await.unwrap();
+        assert_eq!(videos[0].id.to_string(), "dQw4w9WgXcQ");
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct ExpectedVideo {
+        id: String,
+        title: String,
+        url: String,
+        published: DateTime<Utc>,
+        channel_name: String,
+        channel_id: String,
+    }
+
+    fn fixture_channel() -> Channel {
+        Channel {
+            name: "Google for Developers".to_string(),
+            ..test_channel()
+        }
+    }
+
+    fn assert_matches_expected(videos: &[Video], expected_path: &str) {
+        let expected: Vec<ExpectedVideo> =
+            serde_json::from_str(&std::fs::read_to_string(expected_path).unwrap()).unwrap();
+
+        assert_eq!(videos.len(), expected.len());
+        for (actual, exp) in videos.iter().zip(expected.iter()) {
+            assert_eq!(actual.id.to_string(), exp.id);
+            assert_eq!(actual.title, exp.title);
+            assert_eq!(actual.url, exp.url);
+            assert_eq!(actual.published, exp.published);
+            assert_eq!(actual.channel_name, exp.channel_name);
+            assert_eq!(actual.channel_id.to_string(), exp.channel_id);
+        }
+    }
+
+    /// The fixtures are immutable and serve as the contract. Their IDs are
+    /// placeholders, so only the test-only relaxed rules accept them.
+    #[test]
+    fn parses_fixture_rss_feed_to_expected_videos() {
+        let xml = std::fs::read_to_string("tests/fixtures/input/sample_feed.xml").unwrap();
+
+        let feed = parse_feed(&xml, &fixture_channel(), IdRules::Relaxed).unwrap();
+        assert_matches_expected(&feed.videos, "tests/fixtures/expected/parsed_videos.json");
 
-        assert!(matches!(result, Err(FetchError::Parse(msg)) if msg.contains("invalid date")));
+        let feed = parse_feed(&xml, &fixture_channel(), IdRules::YouTube).unwrap();
+        assert!(feed.videos.is_empty());
+        assert_eq!(feed.skipped.len(), 3);
+        assert!(feed.skipped[0].contains("'video1' is not a YouTube video ID"));
     }
 }
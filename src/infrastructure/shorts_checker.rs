@@ -1,35 +1,206 @@
-use reqwest::blocking::Client;
-use reqwest::redirect::Policy;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
-use crate::application::ports::ShortsChecker;
+use reqwest::{Client, StatusCode};
+use tracing::{debug, warn};
+
+use super::http::{send_past_consent_async, HTTP_LOG_TARGET};
+use super::json_file::{read_json, write_json};
+use super::rate_limit::RateLimiter;
+use crate::application::ports::{FetchError, LocalFuture, ShortsChecker, StoreError};
 use crate::domain::video::VideoId;
 
 pub struct HttpShortsChecker {
     client: Client,
+    limiter: Arc<RateLimiter>,
 }
 
-impl Default for HttpShortsChecker {
-    fn default() -> Self {
-        Self::new()
+impl HttpShortsChecker {
+    /// `client` must come from `http::build_async_client`, which does not
+    /// follow the redirect YouTube sends for regular videos.
+    pub fn new(client: Client, limiter: Arc<RateLimiter>) -> Self {
+        Self { client, limiter }
     }
-}
 
-impl HttpShortsChecker {
-    pub fn new() -> Self {
-        let client = Client::builder()
-            .redirect(Policy::none())
-            .build()
-            .expect("failed to build HTTP client");
-        Self { client }
+    async fn head(&self, url: &str) -> Result<StatusCode, FetchError> {
+        self.limiter.wait_async().await;
+        let response = send_past_consent_async(self.client.head(url)).await?;
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err(self.limiter.rate_limited(response.headers()));
+        }
+        Ok(response.status())
     }
 }
 
 impl ShortsChecker for HttpShortsChecker {
-    fn is_short(&self, video_id: &VideoId) -> bool {
-        let url = format!("https://www.youtube.com/shorts/{video_id}");
-        match self.client.head(&url).send() {
-            Ok(response) => response.status().as_u16() == 200,
-            Err(_) => false,
+    fn is_short<'a>(&'a self, video_id: &'a VideoId) -> LocalFuture<'a, Result<bool, FetchError>> {
+        Box::pin(async move {
+            let url = format!("https://www.youtube.com/shorts/{video_id}");
+            let status = match self.head(&url).await {
+                // The limiter has backed off by now, so one more try is polite.
+                Err(FetchError::RateLimited(_)) => self.head(&url).await?,
+                other => other?,
+            };
+            shortness(status)
+        })
+    }
+}
+
+/// 200 means a Short; a redirect or 404 means a regular video. Anything
+/// else says nothing about the video.
+fn shortness(status: StatusCode) -> Result<bool, FetchError> {
+    if status == StatusCode::OK {
+        Ok(true)
+    } else if status.is_redirection() || status == StatusCode::NOT_FOUND {
+        Ok(false)
+    } else {
+        Err(FetchError::HttpError(status.as_u16()))
+    }
+}
+
+/// Never answers, so a `CachedShortsChecker` around it only knows what
+/// earlier runs found; unchecked videos then count as regular ones.
+pub struct NoShortsLookup;
+
+impl ShortsChecker for NoShortsLookup {
+    fn is_short<'a>(&'a self, _video_id: &'a VideoId) -> LocalFuture<'a, Result<bool, FetchError>> {
+        Box::pin(async move {
+            Err(FetchError::Network(
+                "not checking Shorts offline".to_string(),
+            ))
+        })
+    }
+}
+
+/// Remembers answers in the data dir: whether a video is a Short never changes.
+/// Unknown answers (errors) are not cached.
+pub struct CachedShortsChecker<C> {
+    inner: C,
+    cache_path: PathBuf,
+    cache: Mutex<HashMap<VideoId, bool>>,
+}
+
+impl<C: ShortsChecker> CachedShortsChecker<C> {
+    pub fn new(inner: C, data_dir: &Path) -> Result<Self, StoreError> {
+        let cache_path = data_dir.join("shorts.json");
+        let cache = read_json(&cache_path, "shorts cache")?.unwrap_or_default();
+        Ok(Self {
+            inner,
+            cache_path,
+            cache: Mutex::new(cache),
+        })
+    }
+}
+
+impl<C: ShortsChecker> ShortsChecker for CachedShortsChecker<C> {
+    fn is_short<'a>(&'a self, video_id: &'a VideoId) -> LocalFuture<'a, Result<bool, FetchError>> {
+        Box::pin(async move {
+            if let Some(short) = self.cache.lock().unwrap().get(video_id) {
+                debug!(target: HTTP_LOG_TARGET, %video_id, short, "Shorts cache hit");
+                return Ok(*short);
+            }
+            debug!(target: HTTP_LOG_TARGET, %video_id, "Shorts cache miss");
+
+            let short = self.inner.is_short(video_id).await?;
+            let mut cache = self.cache.lock().unwrap();
+            cache.insert(video_id.clone(), short);
+            if let Err(e) = write_json(&self.cache_path, &*cache, "shorts cache") {
+                warn!("{e}");
+            }
+            Ok(short)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    struct CountingChecker {
+        answer: fn() -> Result<bool, FetchError>,
+        calls: Mutex<usize>,
+    }
+
+    impl CountingChecker {
+        fn answering(answer: fn() -> Result<bool, FetchError>) -> Self {
+            Self {
+                answer,
+                calls: Mutex::new(0),
+            }
+        }
+
+        fn calls(&self) -> usize {
+            *self.calls.lock().unwrap()
         }
     }
+
+    impl ShortsChecker for CountingChecker {
+        fn is_short<'a>(
+            &'a self,
+            _video_id: &'a VideoId,
+        ) -> LocalFuture<'a, Result<bool, FetchError>> {
+            Box::pin(async move {
+                *self.calls.lock().unwrap() += 1;
+                (self.answer)()
+            })
+        }
+    }
+
+    fn video_id() -> VideoId {
+        VideoId::parse("abc123").unwrap()
+    }
+
+    #[test]
+    fn classifies_statuses() {
+        assert!(shortness(StatusCode::OK).unwrap());
+        assert!(!shortness(StatusCode::SEE_OTHER).unwrap());
+        assert!(!shortness(StatusCode::NOT_FOUND).unwrap());
+        assert!(matches!(
+            shortness(StatusCode::SERVICE_UNAVAILABLE),
+            Err(FetchError::HttpError(503))
+        ));
+    }
+
+    #[tokio::test]
+    async fn cache_checks_each_video_once() {
+        let dir = TempDir::new().unwrap();
+        let checker =
+            CachedShortsChecker::new(CountingChecker::answering(|| Ok(true)), dir.path()).unwrap();
+
+        assert!(checker.is_short(&video_id()).await.unwrap());
+        assert!(checker.is_short(&video_id()).await.unwrap());
+        assert_eq!(checker.inner.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn cache_persists_across_instances() {
+        let dir = TempDir::new().unwrap();
+        CachedShortsChecker::new(CountingChecker::answering(|| Ok(false)), dir.path())
+            .unwrap()
+            .is_short(&video_id())
+            .await
+            .unwrap();
+
+        let second =
+            CachedShortsChecker::new(CountingChecker::answering(|| Ok(true)), dir.path()).unwrap();
+
+        assert!(!second.is_short(&video_id()).await.unwrap());
+        assert_eq!(second.inner.calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn errors_are_not_cached() {
+        let dir = TempDir::new().unwrap();
+        let checker = CachedShortsChecker::new(
+            CountingChecker::answering(|| Err(FetchError::Network("timeout".to_string()))),
+            dir.path(),
+        )
+        .unwrap();
+
+        assert!(checker.is_short(&video_id()).await.is_err());
+        assert!(checker.is_short(&video_id()).await.is_err());
+        assert_eq!(checker.inner.calls(), 2);
+    }
 }
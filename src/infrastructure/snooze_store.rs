@@ -0,0 +1,34 @@
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::json_file::{read_json, write_json};
+use crate::application::ports::{SnoozeStore, StoreError};
+use crate::domain::channel::ChannelId;
+
+/// Channels snoozed with `blepo snooze`, each with the day it is listed
+/// again, in `<data_dir>/snoozed.json`.
+pub struct JsonSnoozeStore {
+    path: PathBuf,
+}
+
+impl JsonSnoozeStore {
+    pub fn new(data_dir: &Path) -> Result<Self, StoreError> {
+        fs::create_dir_all(data_dir)
+            .map_err(|e| StoreError::Write(format!("cannot create data dir: {e}")))?;
+        Ok(Self {
+            path: data_dir.join("snoozed.json"),
+        })
+    }
+}
+
+impl SnoozeStore for JsonSnoozeStore {
+    fn load_snoozes(&self) -> Result<HashMap<ChannelId, NaiveDate>, StoreError> {
+        read_json(&self.path, "snoozes").map(Option::unwrap_or_default)
+    }
+
+    fn save_snoozes(&self, snoozes: &HashMap<ChannelId, NaiveDate>) -> Result<(), StoreError> {
+        write_json(&self.path, snoozes, "snoozes")
+    }
+}
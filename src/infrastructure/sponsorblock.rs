@@ -0,0 +1,145 @@
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::application::ports::{FetchError, SkipSegmentFetcher, StoreError};
+use crate::domain::sponsor::SkipSegment;
+use crate::domain::video::VideoId;
+
+const SKIP_SEGMENTS_URL: &str = "https://sponsor.ajay.app/api/skipSegments";
+/// Paid promotions, self-promotion and "like and subscribe" reminders.
+const CATEGORIES: &str = r#"["sponsor","selfpromo","interaction"]"#;
+
+/// mpv script that seeks past the segments handed to it in the
+/// `blepo_sponsorblock-segments` script option (`start-end|start-end`).
+const SKIP_SCRIPT: &str = r#"local raw = mp.get_opt("blepo_sponsorblock-segments") or ""
+local segments = {}
+for s, e in raw:gmatch("([%d.]+)-([%d.]+)") do
+    segments[#segments + 1] = { tonumber(s), tonumber(e) }
+end
+
+mp.observe_property("time-pos", "number", function(_, pos)
+    if not pos then
+        return
+    end
+    for _, segment in ipairs(segments) do
+        if pos >= segment[1] and pos < segment[2] - 0.5 then
+            mp.set_property_number("time-pos", segment[2])
+            mp.osd_message("Skipped sponsor segment")
+            return
+        end
+    end
+end)
+"#;
+
+/// Looks up skip segments in the public SponsorBlock database.
+pub struct SponsorBlockClient {
+    client: reqwest::blocking::Client,
+}
+
+impl SponsorBlockClient {
+    pub fn new(client: reqwest::blocking::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl SkipSegmentFetcher for SponsorBlockClient {
+    fn fetch_segments(&self, video_id: &VideoId) -> Result<Vec<SkipSegment>, FetchError> {
+        let response = self
+            .client
+            .get(SKIP_SEGMENTS_URL)
+            .query(&[
+                ("videoID", video_id.to_string().as_str()),
+                ("categories", CATEGORIES),
+            ])
+            .send()
+            .map_err(|e| FetchError::Network(e.to_string()))?;
+
+        // SponsorBlock answers 404 for videos nobody has submitted segments for.
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+        if !response.status().is_success() {
+            return Err(FetchError::HttpError(response.status().as_u16()));
+        }
+        let body = response
+            .text()
+            .map_err(|e| FetchError::Network(e.to_string()))?;
+        parse_segments(&body)
+    }
+}
+
+#[derive(Deserialize)]
+struct ApiSegment {
+    segment: (f64, f64),
+}
+
+fn parse_segments(json: &str) -> Result<Vec<SkipSegment>, FetchError> {
+    let segments: Vec<ApiSegment> =
+        serde_json::from_str(json).map_err(|e| FetchError::Parse(e.to_string()))?;
+    Ok(segments
+        .into_iter()
+        .map(|s| SkipSegment {
+            start: s.segment.0,
+            end: s.segment.1,
+        })
+        .filter(|s| s.end > s.start)
+        .collect())
+}
+
+/// Writes the skip script to `<data_dir>/blepo_sponsorblock.lua`, where mpv
+/// loads it from; rewritten on every call so upgrades take effect.
+pub fn install_skip_script(data_dir: &Path) -> Result<PathBuf, StoreError> {
+    let path = data_dir.join("blepo_sponsorblock.lua");
+    fs::write(&path, SKIP_SCRIPT)
+        .map_err(|e| StoreError::Write(format!("cannot write {}: {e}", path.display())))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn parses_api_response() {
+        let json = r#"[
+            {"category": "sponsor", "actionType": "skip", "segment": [12.5, 45.0], "UUID": "a"},
+            {"category": "selfpromo", "actionType": "skip", "segment": [300, 330.25], "UUID": "b"}
+        ]"#;
+
+        assert_eq!(
+            parse_segments(json).unwrap(),
+            [
+                SkipSegment {
+                    start: 12.5,
+                    end: 45.0
+                },
+                SkipSegment {
+                    start: 300.0,
+                    end: 330.25
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn drops_empty_segments() {
+        assert!(parse_segments(r#"[{"segment": [10, 10]}]"#)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn installs_skip_script_in_data_dir() {
+        let dir = TempDir::new().unwrap();
+
+        let path = install_skip_script(dir.path()).unwrap();
+
+        assert_eq!(path, dir.path().join("blepo_sponsorblock.lua"));
+        assert!(fs::read_to_string(path)
+            .unwrap()
+            .contains("blepo_sponsorblock-segments"));
+    }
+}
@@ -0,0 +1,181 @@
+use tracing::warn;
+
+use crate::application::ports::{FeedFetcher, FetchError, LocalFuture};
+use crate::domain::channel::Channel;
+use crate::domain::video::{Video, VideoKind};
+
+pub struct StreamsFetcher<U, S> {
+    uploads: U,
+    streams: S,
+}
+
+impl<U: FeedFetcher, S: FeedFetcher> StreamsFetcher<U, S> {
+    pub fn new(uploads: U, streams: S) -> Self {
+        Self { uploads, streams }
+    }
+}
+
+impl<U: FeedFetcher, S: FeedFetcher> FeedFetcher for StreamsFetcher<U, S> {
+    fn fetch<'a>(
+        &'a self,
+        channel: &'a Channel,
+    ) -> LocalFuture<'a, Result<Vec<Video>, FetchError>> {
+        Box::pin(async move {
+            let mut videos = self.uploads.fetch(channel).await?;
+            if !channel.include_streams {
+                return Ok(videos);
+            }
+
+            match self.streams.fetch(channel).await {
+                Ok(streams) => merge_streams(&mut videos, streams),
+                Err(e) => warn!("failed to fetch streams for {}: {e}", channel.name),
+            }
+            Ok(videos)
+        })
+    }
+}
+
+/// Adds the streams tab's entries to the uploads. Past livestreams are in
+/// the uploads feed too, so a stream already there only marks that upload
+/// as a livestream VOD instead of being listed twice.
+fn merge_streams(videos: &mut Vec<Video>, streams: Vec<Video>) {
+    for stream in streams {
+        match videos.iter_mut().find(|video| video.id == stream.id) {
+            Some(upload) => upload.kind = VideoKind::LivestreamVod,
+            None => videos.push(stream),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::channel::ChannelId;
+    use crate::domain::video::VideoId;
+    use chrono::Utc;
+
+    struct MockFetcher {
+        result: Result<Vec<Video>, String>,
+    }
+
+    impl MockFetcher {
+        fn ok(ids: &[&str], kind: VideoKind) -> Self {
+            Self {
+                result: Ok(ids.iter().map(|id| make_video(id, kind)).collect()),
+            }
+        }
+
+        fn err() -> Self {
+            Self {
+                result: Err("yt-dlp failed".to_string()),
+            }
+        }
+    }
+
+    impl FeedFetcher for MockFetcher {
+        fn fetch<'a>(
+            &'a self,
+            _channel: &'a Channel,
+        ) -> LocalFuture<'a, Result<Vec<Video>, FetchError>> {
+            Box::pin(async move { self.result.clone().map_err(FetchError::Network) })
+        }
+    }
+
+    fn make_video(id: &str, kind: VideoKind) -> Video {
+        Video {
+            id: VideoId::parse(id).unwrap(),
+            title: id.to_string(),
+            url: format!("https://youtube.com/watch?v={id}"),
+            published: Utc::now(),
+            channel_name: "Test".to_string(),
+            channel_id: ChannelId::parse("UC123").unwrap(),
+            kind,
+            duration: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn channel(include_streams: bool) -> Channel {
+        Channel {
+            name: "Test".to_string(),
+            id: ChannelId::parse("UC123").unwrap(),
+            include_streams,
+            title_filter: Default::default(),
+            duration_range: Default::default(),
+            tags: Vec::new(),
+            name_from_feed: false,
+            alias: None,
+            snoozed_until: None,
+            player: None,
+            auto_download: false,
+        }
+    }
+
+    fn ids(videos: &[Video]) -> Vec<String> {
+        videos.iter().map(|v| v.id.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn skips_streams_when_channel_does_not_include_them() {
+        let fetcher = StreamsFetcher::new(
+            MockFetcher::ok(&["v1"], VideoKind::Upload),
+            MockFetcher::ok(&["s1"], VideoKind::LivestreamVod),
+        );
+
+        let videos = fetcher.fetch(&channel(false)).await.unwrap();
+
+        assert_eq!(ids(&videos), ["v1"]);
+    }
+
+    #[tokio::test]
+    async fn appends_streams_when_channel_includes_them() {
+        let fetcher = StreamsFetcher::new(
+            MockFetcher::ok(&["v1"], VideoKind::Upload),
+            MockFetcher::ok(&["s1"], VideoKind::LivestreamVod),
+        );
+
+        let videos = fetcher.fetch(&channel(true)).await.unwrap();
+
+        assert_eq!(ids(&videos), ["v1", "s1"]);
+        assert_eq!(videos[1].kind, VideoKind::LivestreamVod);
+    }
+
+    #[tokio::test]
+    async fn marks_uploads_that_are_also_streams() {
+        let fetcher = StreamsFetcher::new(
+            MockFetcher::ok(&["v1", "s1"], VideoKind::Upload),
+            MockFetcher::ok(&["s1", "s2"], VideoKind::LivestreamVod),
+        );
+
+        let videos = fetcher.fetch(&channel(true)).await.unwrap();
+
+        assert_eq!(ids(&videos), ["v1", "s1", "s2"]);
+        assert_eq!(videos[0].kind, VideoKind::Upload);
+        assert_eq!(videos[1].kind, VideoKind::LivestreamVod);
+        assert_eq!(videos[2].kind, VideoKind::LivestreamVod);
+    }
+
+    #[tokio::test]
+    async fn keeps_uploads_when_streams_fail() {
+        let fetcher = StreamsFetcher::new(
+            MockFetcher::ok(&["v1"], VideoKind::Upload),
+            MockFetcher::err(),
+        );
+
+        let videos = fetcher.fetch(&channel(true)).await.unwrap();
+
+        assert_eq!(ids(&videos), ["v1"]);
+    }
+
+    #[tokio::test]
+    async fn propagates_uploads_error() {
+        let fetcher = StreamsFetcher::new(
+            MockFetcher::err(),
+            MockFetcher::ok(&["s1"], VideoKind::LivestreamVod),
+        );
+
+        let result = fetcher.fetch(&channel(true)).await;
+
+        assert!(matches!(result, Err(FetchError::Network(_))));
+    }
+}
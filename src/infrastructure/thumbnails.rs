@@ -0,0 +1,63 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::application::ports::StoreError;
+use crate::domain::video::VideoId;
+
+const THUMBNAIL_URL_TEMPLATE: &str = "https://i.ytimg.com/vi/";
+
+/// Downloads the small (120x90) JPEG thumbnail of a video and keeps it under
+/// `<data_dir>/thumbnails/`; thumbnails never change once published.
+pub struct ThumbnailCache {
+    client: reqwest::blocking::Client,
+    dir: PathBuf,
+}
+
+impl ThumbnailCache {
+    pub fn new(client: reqwest::blocking::Client, data_dir: &Path) -> Result<Self, StoreError> {
+        let dir = data_dir.join("thumbnails");
+        fs::create_dir_all(&dir)
+            .map_err(|e| StoreError::Write(format!("cannot create thumbnails dir: {e}")))?;
+        Ok(Self { client, dir })
+    }
+
+    /// `None` when the thumbnail cannot be had; thumbnails are decoration, so
+    /// failures are not reported.
+    pub fn load(&self, video_id: &VideoId) -> Option<Vec<u8>> {
+        let path = self.dir.join(format!("{video_id}.jpg"));
+        if let Ok(bytes) = fs::read(&path) {
+            return Some(bytes);
+        }
+
+        let response = self
+            .client
+            .get(format!("{THUMBNAIL_URL_TEMPLATE}{video_id}/default.jpg"))
+            .send()
+            .ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        let bytes = response.bytes().ok()?.to_vec();
+
+        let tmp_path = path.with_extension("jpg.tmp");
+        let _ = fs::write(&tmp_path, &bytes).and_then(|()| fs::rename(&tmp_path, &path));
+        Some(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn serves_downloaded_thumbnail_from_disk() {
+        let dir = TempDir::new().unwrap();
+        let cache = ThumbnailCache::new(reqwest::blocking::Client::new(), dir.path()).unwrap();
+        fs::write(dir.path().join("thumbnails/abc123.jpg"), b"jpeg bytes").unwrap();
+
+        let bytes = cache.load(&VideoId::parse("abc123").unwrap());
+
+        assert_eq!(bytes.as_deref(), Some(&b"jpeg bytes"[..]));
+    }
+}
@@ -0,0 +1,275 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use super::http::HTTP_LOG_TARGET;
+use super::json_file::{read_json, write_json};
+use crate::application::ports::{FeedFetcher, FetchError, LocalFuture, StoreError, UploadCatalog};
+use crate::domain::channel::{Channel, ChannelId};
+use crate::domain::video::Video;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FetchedChannel {
+    fetched_at: DateTime<Utc>,
+    include_streams: bool,
+    videos: Vec<Video>,
+}
+
+/// Reuses a channel's last fetch result while it is younger than `ttl`.
+/// One JSON file per channel under `<data_dir>/channel_cache/`.
+pub struct TtlFetcher<F> {
+    inner: F,
+    dir: PathBuf,
+    ttl: Duration,
+    force: bool,
+    now: fn() -> DateTime<Utc>,
+}
+
+impl<F: FeedFetcher> TtlFetcher<F> {
+    pub fn new(inner: F, data_dir: &Path, ttl: Duration) -> Result<Self, StoreError> {
+        let dir = data_dir.join("channel_cache");
+        fs::create_dir_all(&dir)
+            .map_err(|e| StoreError::Write(format!("cannot create channel cache dir: {e}")))?;
+        Ok(Self {
+            inner,
+            dir,
+            ttl,
+            force: false,
+            now: Utc::now,
+        })
+    }
+
+    /// Always fetches, but still records the result for later runs.
+    pub fn forced(self, force: bool) -> Self {
+        Self { force, ..self }
+    }
+
+    fn path(&self, channel_id: &ChannelId) -> PathBuf {
+        cache_path(&self.dir, channel_id)
+    }
+
+    fn fresh(&self, channel: &Channel) -> Option<Vec<Video>> {
+        if self.force {
+            return None;
+        }
+        let cached = read_cached(&self.path(&channel.id))?;
+        let age = ((self.now)() - cached.fetched_at).to_std().ok()?;
+        (age < self.ttl && cached.include_streams == channel.include_streams)
+            .then_some(cached.videos)
+    }
+}
+
+impl<F: FeedFetcher> FeedFetcher for TtlFetcher<F> {
+    fn fetch<'a>(
+        &'a self,
+        channel: &'a Channel,
+    ) -> LocalFuture<'a, Result<Vec<Video>, FetchError>> {
+        Box::pin(async move {
+            if let Some(videos) = self.fresh(channel) {
+                debug!(target: HTTP_LOG_TARGET, "channel cache hit: fetched recently");
+                return Ok(videos);
+            }
+            debug!(target: HTTP_LOG_TARGET, "channel cache miss");
+
+            let videos = self.inner.fetch(channel).await?;
+            let entry = FetchedChannel {
+                fetched_at: (self.now)(),
+                include_streams: channel.include_streams,
+                videos,
+            };
+            if let Err(e) = write_json(&self.path(&channel.id), &entry, "channel cache") {
+                warn!("{e}");
+            }
+            Ok(entry.videos)
+        })
+    }
+}
+
+/// What `TtlFetcher` last recorded for each channel, however old.
+pub struct CachedUploads {
+    dir: PathBuf,
+}
+
+impl CachedUploads {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            dir: data_dir.join("channel_cache"),
+        }
+    }
+}
+
+/// Serves each channel's last fetch result, however old, and nothing for
+/// channels never fetched, so listings can be rebuilt without the network.
+impl FeedFetcher for CachedUploads {
+    fn fetch<'a>(
+        &'a self,
+        channel: &'a Channel,
+    ) -> LocalFuture<'a, Result<Vec<Video>, FetchError>> {
+        Box::pin(async move { Ok(self.known_uploads(channel).unwrap_or_default()) })
+    }
+}
+
+impl UploadCatalog for CachedUploads {
+    fn known_uploads(&self, channel: &Channel) -> Option<Vec<Video>> {
+        read_cached(&cache_path(&self.dir, &channel.id)).map(|cached| cached.videos)
+    }
+}
+
+fn cache_path(dir: &Path, channel_id: &ChannelId) -> PathBuf {
+    dir.join(format!("{channel_id}.json"))
+}
+
+fn read_cached(path: &Path) -> Option<FetchedChannel> {
+    read_json(path, "channel cache").ok().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::video::{VideoId, VideoKind};
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    struct CountingFetcher {
+        calls: Mutex<usize>,
+    }
+
+    impl CountingFetcher {
+        fn new() -> Self {
+            Self {
+                calls: Mutex::new(0),
+            }
+        }
+
+        fn calls(&self) -> usize {
+            *self.calls.lock().unwrap()
+        }
+    }
+
+    impl FeedFetcher for CountingFetcher {
+        fn fetch<'a>(
+            &'a self,
+            channel: &'a Channel,
+        ) -> LocalFuture<'a, Result<Vec<Video>, FetchError>> {
+            Box::pin(async move {
+                *self.calls.lock().unwrap() += 1;
+                Ok(vec![Video {
+                    id: VideoId::parse("v1").unwrap(),
+                    title: "Video".to_string(),
+                    url: "https://www.youtube.com/watch?v=v1".to_string(),
+                    published: Utc::now(),
+                    channel_name: channel.name.clone(),
+                    channel_id: channel.id.clone(),
+                    kind: VideoKind::Upload,
+                    duration: None,
+                    tags: Vec::new(),
+                }])
+            })
+        }
+    }
+
+    fn test_channel() -> Channel {
+        Channel {
+            name: "Test".to_string(),
+            id: ChannelId::parse("UC123").unwrap(),
+            include_streams: false,
+            title_filter: Default::default(),
+            duration_range: Default::default(),
+            tags: Vec::new(),
+            name_from_feed: false,
+            alias: None,
+            snoozed_until: None,
+            player: None,
+            auto_download: false,
+        }
+    }
+
+    fn minutes(n: u64) -> Duration {
+        Duration::from_secs(n * 60)
+    }
+
+    fn ttl_fetcher(dir: &TempDir, ttl: Duration) -> TtlFetcher<CountingFetcher> {
+        TtlFetcher::new(CountingFetcher::new(), dir.path(), ttl).unwrap()
+    }
+
+    #[tokio::test]
+    async fn reuses_result_within_ttl() {
+        let dir = TempDir::new().unwrap();
+        let fetcher = ttl_fetcher(&dir, minutes(10));
+
+        fetcher.fetch(&test_channel()).await.unwrap();
+        let videos = fetcher.fetch(&test_channel()).await.unwrap();
+
+        assert_eq!(videos.len(), 1);
+        assert_eq!(fetcher.inner.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn cached_uploads_outlive_the_ttl() {
+        let dir = TempDir::new().unwrap();
+        let uploads = CachedUploads::new(dir.path());
+        assert!(uploads.known_uploads(&test_channel()).is_none());
+
+        ttl_fetcher(&dir, Duration::ZERO)
+            .fetch(&test_channel())
+            .await
+            .unwrap();
+
+        assert_eq!(uploads.known_uploads(&test_channel()).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn refetches_once_ttl_has_passed() {
+        let dir = TempDir::new().unwrap();
+        let mut fetcher = ttl_fetcher(&dir, minutes(10));
+        fetcher.fetch(&test_channel()).await.unwrap();
+
+        fetcher.now = || Utc::now() + chrono::Duration::minutes(11);
+        fetcher.fetch(&test_channel()).await.unwrap();
+
+        assert_eq!(fetcher.inner.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn force_bypasses_fresh_result() {
+        let dir = TempDir::new().unwrap();
+        ttl_fetcher(&dir, minutes(10))
+            .fetch(&test_channel())
+            .await
+            .unwrap();
+
+        let forced = ttl_fetcher(&dir, minutes(10)).forced(true);
+        forced.fetch(&test_channel()).await.unwrap();
+
+        assert_eq!(forced.inner.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn zero_ttl_always_fetches() {
+        let dir = TempDir::new().unwrap();
+        let fetcher = ttl_fetcher(&dir, Duration::ZERO);
+
+        fetcher.fetch(&test_channel()).await.unwrap();
+        fetcher.fetch(&test_channel()).await.unwrap();
+
+        assert_eq!(fetcher.inner.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn toggling_streams_invalidates_cached_result() {
+        let dir = TempDir::new().unwrap();
+        let fetcher = ttl_fetcher(&dir, minutes(10));
+        fetcher.fetch(&test_channel()).await.unwrap();
+
+        let with_streams = Channel {
+            include_streams: true,
+            ..test_channel()
+        };
+        fetcher.fetch(&with_streams).await.unwrap();
+
+        assert_eq!(fetcher.inner.calls(), 2);
+    }
+}
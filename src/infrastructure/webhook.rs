@@ -0,0 +1,182 @@
+use reqwest::header::CONTENT_TYPE;
+use serde_json::json;
+
+use crate::application::ports::{FetchError, Notifier};
+use crate::domain::video::Video;
+
+const PLACEHOLDERS: &[&str] = &["{title}", "{channel}", "{url}"];
+
+/// Where new videos are announced. `{title}`, `{channel}` and `{url}` in it
+/// are filled in, URL-encoded, for each video.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookUrl(String);
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct WebhookUrlError(String);
+
+impl std::fmt::Display for WebhookUrlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for WebhookUrlError {}
+
+/// The body a webhook expects.
+#[derive(Debug, PartialEq, Eq)]
+enum WebhookKind {
+    /// JSON with the message in `content`.
+    Discord,
+    /// JSON with the message in `text`.
+    Slack,
+    /// The video title as plain text, with `title` and `click` query
+    /// parameters, as ntfy takes them.
+    Plain,
+}
+
+impl WebhookUrl {
+    pub fn parse(url: impl Into<String>) -> Result<Self, WebhookUrlError> {
+        let url = url.into();
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            return Err(WebhookUrlError(format!(
+                "'{url}' must start with https:// or http://"
+            )));
+        }
+        let bare = PLACEHOLDERS.iter().fold(url.clone(), |bare, placeholder| {
+            bare.replace(placeholder, "x")
+        });
+        if let Some(start) = bare.find('{') {
+            let unknown = bare[start..].split_inclusive('}').next().unwrap_or("{");
+            return Err(WebhookUrlError(format!(
+                "unknown placeholder {unknown} (expected {})",
+                PLACEHOLDERS.join(", ")
+            )));
+        }
+        reqwest::Url::parse(&bare).map_err(|e| WebhookUrlError(format!("'{url}': {e}")))?;
+        Ok(Self(url))
+    }
+
+    fn kind(&self) -> WebhookKind {
+        let host = self.0.split('/').nth(2).unwrap_or_default();
+        match host {
+            "discord.com" | "discordapp.com" => WebhookKind::Discord,
+            "hooks.slack.com" => WebhookKind::Slack,
+            _ => WebhookKind::Plain,
+        }
+    }
+
+    fn for_video(&self, video: &Video) -> String {
+        self.0
+            .replace("{title}", &percent_encode(&video.title))
+            .replace("{channel}", &percent_encode(&video.channel_name))
+            .replace("{url}", &percent_encode(&video.url))
+    }
+}
+
+/// Posts each new video to a webhook: an ntfy topic, a Discord or Slack
+/// webhook, or any URL taking a plain-text body.
+pub struct WebhookNotifier {
+    client: reqwest::blocking::Client,
+    url: WebhookUrl,
+}
+
+impl WebhookNotifier {
+    pub fn new(client: reqwest::blocking::Client, url: WebhookUrl) -> Self {
+        Self { client, url }
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify(&self, video: &Video) -> Result<(), FetchError> {
+        let request = self.client.post(self.url.for_video(video));
+        let message = format!("{}: {}\n{}", video.channel_name, video.title, video.url);
+        let request = match self.url.kind() {
+            WebhookKind::Discord => request
+                .header(CONTENT_TYPE, "application/json")
+                .body(json!({ "content": message }).to_string()),
+            WebhookKind::Slack => request
+                .header(CONTENT_TYPE, "application/json")
+                .body(json!({ "text": message }).to_string()),
+            WebhookKind::Plain => request
+                .query(&[("title", &video.channel_name), ("click", &video.url)])
+                .body(video.title.clone()),
+        };
+        let response = request
+            .send()
+            .map_err(|e| FetchError::Network(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(FetchError::HttpError(response.status().as_u16()));
+        }
+        Ok(())
+    }
+}
+
+/// Escapes everything but unreserved characters, so a value can't break
+/// out of the part of the URL it is placed in.
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::channel::ChannelId;
+    use crate::domain::video::{VideoId, VideoKind};
+    use chrono::Utc;
+
+    #[test]
+    fn tells_webhooks_apart_by_host() {
+        let kind = |url: &str| WebhookUrl::parse(url).unwrap().kind();
+
+        assert_eq!(
+            kind("https://discord.com/api/webhooks/1/abc"),
+            WebhookKind::Discord
+        );
+        assert_eq!(
+            kind("https://hooks.slack.com/services/T/B/x"),
+            WebhookKind::Slack
+        );
+        assert_eq!(kind("https://ntfy.sh/my-videos"), WebhookKind::Plain);
+    }
+
+    #[test]
+    fn rejects_urls_it_cannot_post_to() {
+        assert!(WebhookUrl::parse("ntfy.sh/my-videos").is_err());
+        assert!(WebhookUrl::parse("https://").is_err());
+        assert_eq!(
+            WebhookUrl::parse("https://example.com/?v={id}")
+                .unwrap_err()
+                .to_string(),
+            "unknown placeholder {id} (expected {title}, {channel}, {url})"
+        );
+    }
+
+    #[test]
+    fn fills_in_placeholders_encoded() {
+        let url = WebhookUrl::parse("https://example.com/hook?t={title}&c={channel}").unwrap();
+        let video = Video {
+            id: VideoId::parse("v1").unwrap(),
+            title: "Rust & you?".to_string(),
+            url: "https://www.youtube.com/watch?v=v1".to_string(),
+            published: Utc::now(),
+            channel_name: "Café".to_string(),
+            channel_id: ChannelId::parse("UC123").unwrap(),
+            kind: VideoKind::Upload,
+            duration: None,
+            tags: Vec::new(),
+        };
+
+        assert_eq!(
+            url.for_video(&video),
+            "https://example.com/hook?t=Rust%20%26%20you%3F&c=Caf%C3%A9"
+        );
+    }
+}
@@ -0,0 +1,398 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use tracing::warn;
+
+use super::cookies::Cookies;
+use super::dependency::YT_DLP;
+use super::hook::run_download_hook;
+use super::http::ProxyUrl;
+use super::nfo::{write_sidecars, SavedVideo};
+use crate::application::ports::{Downloader, FetchError};
+use crate::domain::channel::ChannelId;
+use crate::domain::playback::Subtitles;
+use crate::domain::video::VideoId;
+
+const WATCH_URL_TEMPLATE: &str = "https://www.youtube.com/watch?v=";
+/// What yt-dlp prints once a video is saved, as one JSON object.
+const SAVED_FIELDS: &str =
+    "after_move:%(.{filepath,id,title,channel,channel_id,upload_date,description})j";
+/// The `{placeholders}` a download template may use, as yt-dlp fields.
+const PLACEHOLDERS: [(&str, &str); 6] = [
+    ("channel", "%(channel)s"),
+    ("channel_id", "%(channel_id)s"),
+    ("title", "%(title)s"),
+    ("date", "%(upload_date>%Y-%m-%d)s"),
+    ("id", "%(id)s"),
+    ("ext", "%(ext)s"),
+];
+
+/// How videos are saved: where and under what name, and what is fetched
+/// along with them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DownloadOptions {
+    /// yt-dlp output template (see `output_template`).
+    pub template: String,
+    /// Subtitle files to save next to the video.
+    pub subtitles: Option<Subtitles>,
+    /// Save the thumbnail next to the video as a `.jpg`.
+    pub thumbnail: bool,
+}
+
+/// Saves videos with yt-dlp under `dir`, as the default options say or their
+/// channel's own ones. yt-dlp's progress goes straight to stderr.
+pub struct YtDlpDownloader {
+    dir: PathBuf,
+    options: DownloadOptions,
+    channel_options: HashMap<ChannelId, DownloadOptions>,
+    proxy: Option<ProxyUrl>,
+    cookies: Option<Cookies>,
+    quiet: bool,
+    sidecars: bool,
+    hook: Option<String>,
+    rate_limit: Option<u64>,
+}
+
+impl YtDlpDownloader {
+    pub fn new(dir: &Path, options: DownloadOptions) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            options,
+            channel_options: HashMap::new(),
+            proxy: None,
+            cookies: None,
+            quiet: false,
+            sidecars: false,
+            hook: None,
+            rate_limit: None,
+        }
+    }
+
+    pub fn with_proxy(self, proxy: Option<ProxyUrl>) -> Self {
+        Self { proxy, ..self }
+    }
+
+    /// Sends the user's cookies, for age-restricted and members-only videos.
+    pub fn with_cookies(self, cookies: Option<Cookies>) -> Self {
+        Self { cookies, ..self }
+    }
+
+    /// Options used instead of the default ones for these channels.
+    pub fn with_channel_options(
+        self,
+        channel_options: HashMap<ChannelId, DownloadOptions>,
+    ) -> Self {
+        Self {
+            channel_options,
+            ..self
+        }
+    }
+
+    /// Subtitles and thumbnails asked for on the command line, whatever the
+    /// video's channel says.
+    pub fn with_overrides(mut self, subtitles: Option<Subtitles>, thumbnail: bool) -> Self {
+        for options in std::iter::once(&mut self.options).chain(self.channel_options.values_mut()) {
+            if subtitles.is_some() {
+                options.subtitles.clone_from(&subtitles);
+            }
+            options.thumbnail |= thumbnail;
+        }
+        self
+    }
+
+    /// No progress bar, for `--quiet`.
+    pub fn quiet(self, quiet: bool) -> Self {
+        Self { quiet, ..self }
+    }
+
+    /// Also writes `.nfo` files and thumbnails for media centers (see
+    /// `write_sidecars`).
+    pub fn with_sidecars(self, sidecars: bool) -> Self {
+        Self { sidecars, ..self }
+    }
+
+    /// Bytes per second each download may use.
+    pub fn with_rate_limit(self, rate_limit: Option<u64>) -> Self {
+        Self { rate_limit, ..self }
+    }
+
+    /// A shell command run after each download (see `run_download_hook`).
+    pub fn with_hook(self, hook: Option<String>) -> Self {
+        Self { hook, ..self }
+    }
+
+    fn args(&self, video_id: &VideoId, channel_id: Option<&ChannelId>) -> Vec<String> {
+        let options = channel_id
+            .and_then(|id| self.channel_options.get(id))
+            .unwrap_or(&self.options);
+        let mut args: Vec<String> = vec![
+            "--no-playlist".to_string(),
+            // Picks up the .part file of an interrupted download.
+            "--continue".to_string(),
+            "--paths".to_string(),
+            self.dir.display().to_string(),
+            "--output".to_string(),
+            options.template.clone(),
+            // Names that are valid on any filesystem, e.g. a NAS share.
+            "--windows-filenames".to_string(),
+            // Prints where the file ended up, which also silences yt-dlp.
+            "--print".to_string(),
+            SAVED_FIELDS.to_string(),
+        ];
+        if self.sidecars || options.thumbnail {
+            args.extend(["--write-thumbnail", "--convert-thumbnails", "jpg"].map(str::to_string));
+        }
+        if let Some(subtitles) = &options.subtitles {
+            args.extend([
+                "--write-subs".to_string(),
+                "--sub-langs".to_string(),
+                subtitles.languages().join(","),
+            ]);
+            if subtitles.auto_generated() {
+                args.push("--write-auto-subs".to_string());
+            }
+        }
+        if !self.quiet {
+            args.push("--progress".to_string());
+        }
+        if let Some(rate) = self.rate_limit {
+            args.extend(["--limit-rate".to_string(), rate.to_string()]);
+        }
+        if let Some(proxy) = &self.proxy {
+            args.extend(["--proxy".to_string(), proxy.as_str().to_string()]);
+        }
+        if let Some(cookies) = &self.cookies {
+            args.extend(cookies.ytdlp_args());
+        }
+        args.push(format!("{WATCH_URL_TEMPLATE}{video_id}"));
+        args
+    }
+}
+
+impl Downloader for YtDlpDownloader {
+    fn download(
+        &self,
+        video_id: &VideoId,
+        channel_id: Option<&ChannelId>,
+    ) -> Result<PathBuf, FetchError> {
+        YT_DLP
+            .require()
+            .map_err(|e| FetchError::YtDlp(e.to_string()))?;
+        let output = Command::new("yt-dlp")
+            .args(self.args(video_id, channel_id))
+            .stdin(Stdio::null())
+            .stderr(Stdio::inherit())
+            .output()
+            .map_err(|e| FetchError::YtDlp(format!("failed to run yt-dlp: {e}")))?;
+        if !output.status.success() {
+            return Err(FetchError::YtDlp(format!(
+                "yt-dlp failed ({})",
+                output.status
+            )));
+        }
+        let stdout =
+            String::from_utf8(output.stdout).map_err(|e| FetchError::Parse(e.to_string()))?;
+        let saved = saved_video(&stdout)?;
+        if self.sidecars {
+            if let Err(e) = write_sidecars(&saved, &self.dir) {
+                warn!(
+                    "cannot write metadata for {}: {e}",
+                    saved.filepath.display()
+                );
+            }
+        }
+        if let Some(hook) = &self.hook {
+            if let Err(e) = run_download_hook(hook, &saved) {
+                warn!("download_hook failed: {e}");
+            }
+        }
+        Ok(saved.filepath)
+    }
+}
+
+/// Turns the `{channel}`, `{channel_id}`, `{title}`, `{date}`, `{id}` and
+/// `{ext}` placeholders of a download template into yt-dlp fields, which
+/// yt-dlp fills in with `/` and other characters unsafe in file names
+/// replaced. yt-dlp's own `%(field)s` syntax is left as is.
+pub fn output_template(template: &str) -> Result<String, String> {
+    let mut translated = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        translated.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let name = after.find('}').map(|end| &after[..end]).filter(|name| {
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_lowercase() || c == '_')
+        });
+        match name {
+            Some(name) => {
+                let (_, field) = PLACEHOLDERS
+                    .iter()
+                    .find(|(placeholder, _)| *placeholder == name)
+                    .ok_or_else(|| {
+                        format!(
+                            "unknown placeholder {{{name}}}; use {{channel}}, {{channel_id}}, \
+                             {{title}}, {{date}}, {{id}} or {{ext}}"
+                        )
+                    })?;
+                translated.push_str(field);
+                rest = &after[name.len() + 1..];
+            }
+            None => {
+                translated.push('{');
+                rest = after;
+            }
+        }
+    }
+    translated.push_str(rest);
+    Ok(translated)
+}
+
+/// The video yt-dlp reported last; earlier lines may be warnings.
+fn saved_video(stdout: &str) -> Result<SavedVideo, FetchError> {
+    let line = stdout
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())
+        .ok_or_else(|| {
+            FetchError::Parse("yt-dlp did not say where it saved the video".to_string())
+        })?;
+    serde_json::from_str(line).map_err(|e| FetchError::Parse(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(template: &str) -> DownloadOptions {
+        DownloadOptions {
+            template: template.to_string(),
+            subtitles: None,
+            thumbnail: false,
+        }
+    }
+
+    #[test]
+    fn builds_args_with_proxy_and_rate_limit() {
+        let proxy = ProxyUrl::parse("socks5://127.0.0.1:1080").unwrap();
+        let args = YtDlpDownloader::new(Path::new("/videos"), options("%(title)s.%(ext)s"))
+            .with_proxy(Some(proxy))
+            .with_rate_limit(Some(512 * 1024))
+            .quiet(true)
+            .args(&VideoId::parse("abc123").unwrap(), None);
+
+        assert_eq!(
+            args,
+            [
+                "--no-playlist",
+                "--continue",
+                "--paths",
+                "/videos",
+                "--output",
+                "%(title)s.%(ext)s",
+                "--windows-filenames",
+                "--print",
+                SAVED_FIELDS,
+                "--limit-rate",
+                "524288",
+                "--proxy",
+                "socks5://127.0.0.1:1080",
+                "https://www.youtube.com/watch?v=abc123",
+            ]
+        );
+    }
+
+    #[test]
+    fn asks_for_thumbnails_with_sidecars() {
+        let args = YtDlpDownloader::new(Path::new("/videos"), options("%(title)s.%(ext)s"))
+            .with_sidecars(true)
+            .args(&VideoId::parse("abc123").unwrap(), None);
+
+        assert!(args
+            .windows(3)
+            .any(|w| w == ["--write-thumbnail", "--convert-thumbnails", "jpg"]));
+    }
+
+    #[test]
+    fn uses_the_channel_template_when_it_has_one() {
+        let archived = ChannelId::parse("UC123").unwrap();
+        let downloader = YtDlpDownloader::new(Path::new("/videos"), options("%(title)s.%(ext)s"))
+            .with_channel_options(HashMap::from([(
+                archived.clone(),
+                options("/archive/%(title)s.%(ext)s"),
+            )]));
+        let video = VideoId::parse("abc123").unwrap();
+        let template = |channel: Option<&ChannelId>| {
+            let args = downloader.args(&video, channel);
+            let at = args.iter().position(|a| a == "--output").unwrap();
+            args[at + 1].clone()
+        };
+
+        assert_eq!(template(Some(&archived)), "/archive/%(title)s.%(ext)s");
+        assert_eq!(
+            template(Some(&ChannelId::parse("UC456").unwrap())),
+            "%(title)s.%(ext)s"
+        );
+        assert_eq!(template(None), "%(title)s.%(ext)s");
+    }
+
+    #[test]
+    fn asks_for_subtitles_and_thumbnails() {
+        let subtitles = Subtitles::parse("en,de,auto").unwrap();
+        let archived = ChannelId::parse("UC123").unwrap();
+        let downloader = YtDlpDownloader::new(Path::new("/videos"), options("%(title)s.%(ext)s"))
+            .with_channel_options(HashMap::from([(
+                archived.clone(),
+                DownloadOptions {
+                    thumbnail: true,
+                    ..options("%(title)s.%(ext)s")
+                },
+            )]));
+        let video = VideoId::parse("abc123").unwrap();
+
+        let args = downloader.args(&video, Some(&archived));
+        assert!(args.contains(&"--write-thumbnail".to_string()));
+        assert!(!args.contains(&"--write-subs".to_string()));
+
+        let args = downloader
+            .with_overrides(Some(subtitles), false)
+            .args(&video, None);
+        assert!(args
+            .windows(3)
+            .any(|w| w == ["--write-subs", "--sub-langs", "en,de"]));
+        assert!(args.contains(&"--write-auto-subs".to_string()));
+        assert!(!args.contains(&"--write-thumbnail".to_string()));
+    }
+
+    #[test]
+    fn translates_template_placeholders() {
+        assert_eq!(
+            output_template("{channel}/{date}-{title} [{id}].{ext}").unwrap(),
+            "%(channel)s/%(upload_date>%Y-%m-%d)s-%(title)s [%(id)s].%(ext)s"
+        );
+        assert_eq!(
+            output_template("%(uploader)s/{title}.%(ext)s {x,y} {").unwrap(),
+            "%(uploader)s/%(title)s.%(ext)s {x,y} {"
+        );
+        assert!(output_template("{name}.{ext}")
+            .unwrap_err()
+            .starts_with("unknown placeholder {name}"));
+    }
+
+    #[test]
+    fn reads_the_saved_video() {
+        let saved = saved_video(
+            "{\"filepath\": \"/videos/Chan/Title [abc123].webm\", \"id\": \"abc123\", \
+             \"title\": \"Title\", \"channel\": \"Chan\", \"channel_id\": null, \
+             \"upload_date\": \"20240120\", \"description\": null}\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            saved.filepath,
+            PathBuf::from("/videos/Chan/Title [abc123].webm")
+        );
+        assert_eq!(saved.channel.as_deref(), Some("Chan"));
+        assert!(saved_video("\n").is_err());
+    }
+}
@@ -1,12 +1,20 @@
 use chrono::{DateTime, NaiveDate, TimeZone, Utc};
 use serde::Deserialize;
-use std::process::Command;
-
-use crate::application::ports::{FeedFetcher, FetchError};
+use std::time::Duration;
+use tokio::process::Command;
+use tracing::warn;
+
+use super::cookies::Cookies;
+use super::dependency::YT_DLP;
+use super::fixtures::Fixtures;
+use super::http::ProxyUrl;
+use crate::application::ports::{FeedFetcher, FetchError, LocalFuture};
 use crate::domain::channel::Channel;
-use crate::domain::video::{Video, VideoId};
+use crate::domain::video::{FetchWindow, IdRules, Video, VideoId, VideoKind};
 
 const CHANNEL_URL_TEMPLATE: &str = "https://www.youtube.com/channel/";
+/// Safety net for `--playlist-end` in case dates are missing from the listing.
+const MAX_ENTRIES_PER_DAY: i64 = 20;
 
 #[derive(Debug, Deserialize)]
 struct YtDlpEntry {
@@ -15,13 +23,115 @@ struct YtDlpEntry {
     url: Option<String>,
     timestamp: Option<i64>,
     upload_date: Option<String>,
+    duration: Option<f64>,
+    live_status: Option<String>,
+    release_timestamp: Option<i64>,
+    /// The channel title; flat playlist entries may only carry the
+    /// playlist's.
+    channel: Option<String>,
+    playlist_channel: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChannelTab {
+    Videos,
+    Streams,
 }
 
-pub struct YtDlpFetcher;
+impl ChannelTab {
+    fn path(self) -> &'static str {
+        match self {
+            ChannelTab::Videos => "videos",
+            ChannelTab::Streams => "streams",
+        }
+    }
+
+    fn video_kind(self) -> VideoKind {
+        match self {
+            ChannelTab::Videos => VideoKind::Upload,
+            ChannelTab::Streams => VideoKind::LivestreamVod,
+        }
+    }
+}
+
+pub struct YtDlpFetcher {
+    tab: ChannelTab,
+    proxy: Option<ProxyUrl>,
+    cookies: Option<Cookies>,
+    window: Option<FetchWindow>,
+    fixtures: Option<Fixtures>,
+}
 
 impl YtDlpFetcher {
     pub fn new() -> Self {
-        Self
+        Self {
+            tab: ChannelTab::Videos,
+            proxy: None,
+            cookies: None,
+            window: None,
+            fixtures: None,
+        }
+    }
+
+    pub fn streams() -> Self {
+        Self {
+            tab: ChannelTab::Streams,
+            ..Self::new()
+        }
+    }
+
+    pub fn with_proxy(self, proxy: Option<ProxyUrl>) -> Self {
+        Self { proxy, ..self }
+    }
+
+    /// Sends the user's cookies, for age-restricted and members-only videos.
+    pub fn with_cookies(self, cookies: Option<Cookies>) -> Self {
+        Self { cookies, ..self }
+    }
+
+    /// Stops listing once entries fall outside the window instead of dumping
+    /// the whole channel; playlists are newest first.
+    pub fn with_window(self, window: FetchWindow) -> Self {
+        Self {
+            window: Some(window),
+            ..self
+        }
+    }
+
+    /// Saves each listing as `<id>.videos.jsonl` or `<id>.streams.jsonl`, or
+    /// reads it from there instead of running yt-dlp.
+    pub fn with_fixtures(self, fixtures: Option<Fixtures>) -> Self {
+        Self { fixtures, ..self }
+    }
+
+    fn args(&self, channel: &Channel, today: NaiveDate) -> Vec<String> {
+        let url = format!("{CHANNEL_URL_TEMPLATE}{}/{}", channel.id, self.tab.path());
+        let mut args: Vec<String> = [
+            "--flat-playlist",
+            "--dump-json",
+            "--extractor-args",
+            "youtubetab:approximate_date",
+        ]
+        .map(String::from)
+        .to_vec();
+        if let Some(proxy) = &self.proxy {
+            args.extend(["--proxy".to_string(), proxy.as_str().to_string()]);
+        }
+        if let Some(cookies) = &self.cookies {
+            args.extend(cookies.ytdlp_args());
+        }
+        if let Some(window) = self.window {
+            let days = window.days_covered();
+            let since = today - chrono::Duration::days(days);
+            args.extend([
+                "--break-match-filters".to_string(),
+                format!("upload_date>=?{}", since.format("%Y%m%d")),
+                "--playlist-end".to_string(),
+                days.saturating_mul(MAX_ENTRIES_PER_DAY).to_string(),
+            ]);
+        }
+        args.push(url);
+        args
     }
 }
 
@@ -31,48 +141,136 @@ impl Default for YtDlpFetcher {
     }
 }
 
-impl FeedFetcher for YtDlpFetcher {
-    fn fetch(&self, channel: &Channel) -> Result<Vec<Video>, FetchError> {
-        let url = format!("{CHANNEL_URL_TEMPLATE}{}/videos", channel.id);
+impl YtDlpFetcher {
+    async fn run(&self, channel: &Channel) -> Result<String, FetchError> {
+        YT_DLP
+            .require()
+            .map_err(|e| FetchError::YtDlp(e.to_string()))?;
         let output = Command::new("yt-dlp")
-            .args([
-                "--flat-playlist",
-                "--dump-json",
-                "--extractor-args",
-                "youtubetab:approximate_date",
-                &url,
-            ])
+            .args(self.args(channel, Utc::now().date_naive()))
             .output()
-            .map_err(|e| FetchError::Network(format!("failed to run yt-dlp: {e}")))?;
+            .await
+            .map_err(|e| FetchError::YtDlp(format!("failed to run yt-dlp: {e}")))?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(FetchError::Network(format!("yt-dlp failed: {stderr}")));
+            return Err(FetchError::YtDlp(format!("yt-dlp failed: {stderr}")));
         }
 
-        let stdout =
-            String::from_utf8(output.stdout).map_err(|e| FetchError::Parse(e.to_string()))?;
+        String::from_utf8(output.stdout).map_err(|e| FetchError::Parse(e.to_string()))
+    }
+}
+
+impl FeedFetcher for YtDlpFetcher {
+    fn fetch<'a>(
+        &'a self,
+        channel: &'a Channel,
+    ) -> LocalFuture<'a, Result<Vec<Video>, FetchError>> {
+        Box::pin(self.fetch_tab(channel))
+    }
+}
 
-        parse_ytdlp_output(&stdout, channel)
+impl YtDlpFetcher {
+    async fn fetch_tab(&self, channel: &Channel) -> Result<Vec<Video>, FetchError> {
+        let suffix = format!("{}.jsonl", self.tab.path());
+        let replayed = self
+            .fixtures
+            .as_ref()
+            .and_then(|f| f.replayed(&channel.id, &suffix));
+        let stdout = match replayed {
+            Some(replayed) => replayed
+                .map_err(|e| FetchError::Network(format!("cannot replay yt-dlp output: {e}")))?,
+            None => {
+                let stdout = self.run(channel).await?;
+                if let Some(fixtures) = &self.fixtures {
+                    fixtures.record(&channel.id, &suffix, &stdout);
+                }
+                stdout
+            }
+        };
+
+        let tab_kind = self.tab.video_kind();
+        let output = parse_ytdlp_output(&stdout, channel, IdRules::YouTube)?;
+        if let Some(first) = output.skipped.first() {
+            let count = output.skipped.len();
+            let lines = if count == 1 { "line" } else { "lines" };
+            warn!(
+                "skipped {count} unreadable {lines} of yt-dlp output for {} ({first})",
+                channel.name
+            );
+        }
+        Ok(output
+            .videos
+            .into_iter()
+            .map(|v| match v.kind {
+                VideoKind::Upload => Video {
+                    kind: tab_kind,
+                    ..v
+                },
+                _ => v,
+            })
+            .collect())
     }
 }
 
-pub fn parse_ytdlp_output(jsonl: &str, channel: &Channel) -> Result<Vec<Video>, FetchError> {
-    jsonl
-        .lines()
-        .filter(|line| !line.trim().is_empty())
-        .map(|line| parse_entry(line, channel))
-        .collect()
+/// The videos yt-dlp listed, and why any of its lines were left out.
+#[derive(Debug)]
+pub struct ParsedOutput {
+    pub videos: Vec<Video>,
+    pub skipped: Vec<String>,
 }
 
-fn parse_entry(json_line: &str, channel: &Channel) -> Result<Video, FetchError> {
+/// Skips lines that aren't a video, such as warnings or partial objects
+/// yt-dlp interleaves with its JSON, or whose video ID `rules` reject; fails
+/// only when no line is a video.
+pub fn parse_ytdlp_output(
+    jsonl: &str,
+    channel: &Channel,
+    rules: IdRules,
+) -> Result<ParsedOutput, FetchError> {
+    let mut parsed = ParsedOutput {
+        videos: Vec::new(),
+        skipped: Vec::new(),
+    };
+    let mut first_error = None;
+    for (index, line) in jsonl.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_entry(line, channel, rules) {
+            Ok(video) => parsed.videos.push(video),
+            Err(e) => {
+                parsed.skipped.push(format!("line {}: {e}", index + 1));
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    match first_error {
+        Some(e) if parsed.videos.is_empty() => Err(e),
+        _ => Ok(parsed),
+    }
+}
+
+fn parse_entry(json_line: &str, channel: &Channel, rules: IdRules) -> Result<Video, FetchError> {
     let entry: YtDlpEntry =
         serde_json::from_str(json_line).map_err(|e| FetchError::Parse(e.to_string()))?;
 
-    let id = VideoId::parse(&entry.id)
+    let id = VideoId::parse_with(entry.id.as_str(), rules)
         .map_err(|e| FetchError::Parse(format!("invalid video ID: {e}")))?;
 
-    let published = if let Some(ts) = entry.timestamp {
+    let kind = match entry.live_status.as_deref() {
+        Some("is_upcoming") => VideoKind::Upcoming,
+        Some("is_live") => VideoKind::Live,
+        _ => VideoKind::Upload,
+    };
+
+    // Upcoming entries carry their scheduled start, not an upload time.
+    let timestamp = match kind {
+        VideoKind::Upcoming => entry.release_timestamp.or(entry.timestamp),
+        _ => entry.timestamp,
+    };
+
+    let published = if let Some(ts) = timestamp {
         Utc.timestamp_opt(ts, 0)
             .single()
             .ok_or_else(|| FetchError::Parse(format!("invalid timestamp: {ts}")))?
@@ -82,6 +280,11 @@ fn parse_entry(json_line: &str, channel: &Channel) -> Result<Video, FetchError>
         Utc::now()
     };
 
+    let channel_name = entry
+        .channel
+        .or(entry.playlist_channel)
+        .filter(|name| channel.name_from_feed && !name.trim().is_empty())
+        .unwrap_or_else(|| channel.name.clone());
     let title = entry.title.unwrap_or_default();
     let url = entry
         .url
@@ -92,8 +295,14 @@ fn parse_entry(json_line: &str, channel: &Channel) -> Result<Video, FetchError>
         title,
         url,
         published,
-        channel_name: channel.name.clone(),
+        channel_name,
         channel_id: channel.id.clone(),
+        kind,
+        duration: entry
+            .duration
+            .filter(|secs| secs.is_finite() && *secs >= 0.0)
+            .map(Duration::from_secs_f64),
+        tags: Vec::new(),
     })
 }
 
@@ -111,34 +320,162 @@ fn parse_upload_date(date_str: &str) -> Result<DateTime<Utc>, FetchError> {
 mod tests {
     use super::*;
     use crate::domain::channel::ChannelId;
+    use std::path::PathBuf;
 
     fn test_channel() -> Channel {
         Channel {
             name: "Test Channel".to_string(),
             id: ChannelId::parse("UC_x5XG1OV2P6uZZ5FSM9Ttw").unwrap(),
+            include_streams: false,
+            title_filter: Default::default(),
+            duration_range: Default::default(),
+            tags: Vec::new(),
+            name_from_feed: false,
+            alias: None,
+            snoozed_until: None,
+            player: None,
+            auto_download: false,
         }
     }
 
+    #[test]
+    fn streams_tab_yields_livestream_vods() {
+        assert_eq!(ChannelTab::Streams.path(), "streams");
+        assert_eq!(ChannelTab::Streams.video_kind(), VideoKind::LivestreamVod);
+        assert_eq!(ChannelTab::Videos.video_kind(), VideoKind::Upload);
+    }
+
+    #[test]
+    fn builds_args_for_channel_tab() {
+        let args = YtDlpFetcher::streams().args(&test_channel(), today());
+
+        assert_eq!(
+            args,
+            [
+                "--flat-playlist",
+                "--dump-json",
+                "--extractor-args",
+                "youtubetab:approximate_date",
+                "https://www.youtube.com/channel/UC_x5XG1OV2P6uZZ5FSM9Ttw/streams",
+            ]
+        );
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, 20).unwrap()
+    }
+
+    #[test]
+    fn limits_listing_to_fetch_window() {
+        let args = YtDlpFetcher::new()
+            .with_window(FetchWindow::from_days(7).unwrap())
+            .args(&test_channel(), today());
+
+        let filter = args
+            .iter()
+            .position(|a| a == "--break-match-filters")
+            .unwrap();
+        assert_eq!(args[filter + 1], "upload_date>=?20240113");
+        let end = args.iter().position(|a| a == "--playlist-end").unwrap();
+        assert_eq!(args[end + 1], "140");
+        assert!(args.last().unwrap().ends_with("/videos"));
+    }
+
+    #[test]
+    fn passes_cookies_to_ytdlp() {
+        let args = YtDlpFetcher::new()
+            .with_cookies(Some(Cookies::File(PathBuf::from("/home/me/cookies.txt"))))
+            .args(&test_channel(), today());
+
+        let at = args.iter().position(|a| a == "--cookies").unwrap();
+        assert_eq!(args[at + 1], "/home/me/cookies.txt");
+    }
+
+    #[test]
+    fn passes_proxy_to_ytdlp() {
+        let proxy = ProxyUrl::parse("socks5://127.0.0.1:1080").unwrap();
+        let args = YtDlpFetcher::new()
+            .with_proxy(Some(proxy))
+            .args(&test_channel(), today());
+
+        let at = args.iter().position(|a| a == "--proxy").unwrap();
+        assert_eq!(args[at + 1], "socks5://127.0.0.1:1080");
+        assert!(args.last().unwrap().ends_with("/videos"));
+    }
+
     #[test]
     fn parses_valid_jsonl() {
-        let jsonl = r#"{"id": "abc123", "title": "My Video", "url": "https://www.youtube.com/watch?v=abc123", "upload_date": "20240120"}
-{"id": "def456", "title": "Another Video", "url": "https://www.youtube.com/watch?v=def456", "upload_date": "20240118"}"#;
+        let jsonl = r#"{"id": "abc12300000", "title": "My Video", "url": "https://www.youtube.com/watch?v=abc12300000", "upload_date": "20240120"}
+{"id": "def45600000", "title": "Another Video", "url": "https://www.youtube.com/watch?v=def45600000", "upload_date": "20240118"}"#;
 
-        let videos = parse_ytdlp_output(jsonl, &test_channel()).unwrap();
+        let videos = parse_ytdlp_output(jsonl, &test_channel(), IdRules::YouTube)
+            .unwrap()
+            .videos;
 
         assert_eq!(videos.len(), 2);
-        assert_eq!(videos[0].id.to_string(), "abc123");
+        assert_eq!(videos[0].id.to_string(), "abc12300000");
         assert_eq!(videos[0].title, "My Video");
-        assert_eq!(videos[0].url, "https://www.youtube.com/watch?v=abc123");
+        assert_eq!(videos[0].url, "https://www.youtube.com/watch?v=abc12300000");
+        assert_eq!(videos[0].channel_name, "Test Channel");
+        assert_eq!(videos[1].id.to_string(), "def45600000");
+    }
+
+    #[test]
+    fn names_videos_after_the_channel_for_unnamed_channels() {
+        let jsonl =
+            r#"{"id": "abc12300000", "upload_date": "20240120", "playlist_channel": "Renamed"}"#;
+        let channel = Channel {
+            name_from_feed: true,
+            ..test_channel()
+        };
+
+        let videos = parse_ytdlp_output(jsonl, &channel, IdRules::YouTube)
+            .unwrap()
+            .videos;
+        assert_eq!(videos[0].channel_name, "Renamed");
+        let videos = parse_ytdlp_output(jsonl, &test_channel(), IdRules::YouTube)
+            .unwrap()
+            .videos;
         assert_eq!(videos[0].channel_name, "Test Channel");
-        assert_eq!(videos[1].id.to_string(), "def456");
+    }
+
+    #[test]
+    fn reads_duration_when_listed() {
+        let jsonl = r#"{"id": "vid10000000", "title": "Test", "duration": 754.0}
+{"id": "vid20000000", "title": "Live"}"#;
+
+        let videos = parse_ytdlp_output(jsonl, &test_channel(), IdRules::YouTube)
+            .unwrap()
+            .videos;
+
+        assert_eq!(videos[0].duration, Some(Duration::from_secs(754)));
+        assert_eq!(videos[1].duration, None);
+    }
+
+    #[test]
+    fn reads_live_status() {
+        let jsonl = r#"{"id": "up000000000", "title": "Premiere", "live_status": "is_upcoming", "release_timestamp": 1705334400}
+{"id": "live0000000", "title": "Live now", "live_status": "is_live"}
+{"id": "vod00000000", "title": "Yesterday's stream", "live_status": "was_live", "timestamp": 1705248000}"#;
+
+        let videos = parse_ytdlp_output(jsonl, &test_channel(), IdRules::YouTube)
+            .unwrap()
+            .videos;
+
+        assert_eq!(videos[0].kind, VideoKind::Upcoming);
+        let scheduled: DateTime<Utc> = "2024-01-15T16:00:00Z".parse().unwrap();
+        assert_eq!(videos[0].published, scheduled);
+        assert_eq!(videos[1].kind, VideoKind::Live);
+        assert_eq!(videos[2].kind, VideoKind::Upload);
     }
 
     #[test]
     fn prefers_timestamp_over_upload_date() {
-        let jsonl = r#"{"id": "vid1", "title": "Test", "url": "https://www.youtube.com/watch?v=vid1", "timestamp": 1705334400, "upload_date": "20240115"}"#;
+        let jsonl = r#"{"id": "vid10000000", "title": "Test", "url": "https://www.youtube.com/watch?v=vid10000000", "timestamp": 1705334400, "upload_date": "20240115"}"#;
 
-        let videos = parse_ytdlp_output(jsonl, &test_channel()).unwrap();
+        let videos = parse_ytdlp_output(jsonl, &test_channel(), IdRules::YouTube)
+            .unwrap()
+            .videos;
 
         let expected: DateTime<Utc> = "2024-01-15T16:00:00Z".parse().unwrap();
         assert_eq!(videos[0].published, expected);
@@ -146,9 +483,11 @@ mod tests {
 
     #[test]
     fn falls_back_to_upload_date_when_no_timestamp() {
-        let jsonl = r#"{"id": "vid1", "title": "Test", "url": "https://www.youtube.com/watch?v=vid1", "upload_date": "20240115"}"#;
+        let jsonl = r#"{"id": "vid10000000", "title": "Test", "url": "https://www.youtube.com/watch?v=vid10000000", "upload_date": "20240115"}"#;
 
-        let videos = parse_ytdlp_output(jsonl, &test_channel()).unwrap();
+        let videos = parse_ytdlp_output(jsonl, &test_channel(), IdRules::YouTube)
+            .unwrap()
+            .videos;
 
         let expected: DateTime<Utc> = "2024-01-15T00:00:00Z".parse().unwrap();
         assert_eq!(videos[0].published, expected);
@@ -156,10 +495,12 @@ mod tests {
 
     #[test]
     fn defaults_to_now_when_upload_date_missing() {
-        let jsonl = r#"{"id": "live1", "title": "Live Stream", "url": "https://www.youtube.com/watch?v=live1"}"#;
+        let jsonl = r#"{"id": "live1000000", "title": "Live Stream", "url": "https://www.youtube.com/watch?v=live1000000"}"#;
 
         let before = Utc::now();
-        let videos = parse_ytdlp_output(jsonl, &test_channel()).unwrap();
+        let videos = parse_ytdlp_output(jsonl, &test_channel(), IdRules::YouTube)
+            .unwrap()
+            .videos;
         let after = Utc::now();
 
         assert!(videos[0].published >= before);
@@ -168,21 +509,54 @@ mod tests {
 
     #[test]
     fn handles_empty_output() {
-        let videos = parse_ytdlp_output("", &test_channel()).unwrap();
-        assert!(videos.is_empty());
+        let output = parse_ytdlp_output("", &test_channel(), IdRules::YouTube).unwrap();
+        assert!(output.videos.is_empty());
+        assert!(output.skipped.is_empty());
+    }
+
+    #[test]
+    fn skips_unreadable_lines_and_keeps_the_rest() {
+        let jsonl = r#"WARNING: [youtube] Falling back to generic n function search
+{"id": "vid10000000", "title": "Test", "upload_date": "20240115"}
+{"id": "vid20000000", "title": "Trunc
+{"id": "vid30000000", "title": "Bad date", "upload_date": "2024-01-15"}"#;
+
+        let output = parse_ytdlp_output(jsonl, &test_channel(), IdRules::YouTube).unwrap();
+
+        assert_eq!(output.videos.len(), 1);
+        assert_eq!(output.videos[0].id.to_string(), "vid10000000");
+        assert_eq!(output.skipped.len(), 3);
+        assert!(output.skipped[0].starts_with("line 1: "));
+        assert!(
+            output.skipped[2].starts_with("line 4: ")
+                && output.skipped[2].contains("invalid upload_date")
+        );
+    }
+
+    #[test]
+    fn skips_ids_the_rules_reject() {
+        let jsonl = r#"{"id": "vid10000000", "title": "Test", "upload_date": "20240115"}
+{"id": "item-42", "title": "Not YouTube", "upload_date": "20240115"}"#;
+
+        let output = parse_ytdlp_output(jsonl, &test_channel(), IdRules::YouTube).unwrap();
+        assert_eq!(output.videos.len(), 1);
+        assert!(output.skipped[0].contains("'item-42' is not a YouTube video ID"));
+
+        let output = parse_ytdlp_output(jsonl, &test_channel(), IdRules::Relaxed).unwrap();
+        assert_eq!(output.videos.len(), 2);
     }
 
     #[test]
     fn returns_error_for_invalid_json() {
-        let result = parse_ytdlp_output("not json", &test_channel());
+        let result = parse_ytdlp_output("not json", &test_channel(), IdRules::YouTube);
         assert!(matches!(result, Err(FetchError::Parse(_))));
     }
 
     #[test]
     fn returns_error_for_invalid_date_format() {
-        let jsonl = r#"{"id": "vid1", "title": "Test", "url": "https://www.youtube.com/watch?v=vid1", "upload_date": "2024-01-15"}"#;
+        let jsonl = r#"{"id": "vid10000000", "title": "Test", "url": "https://www.youtube.com/watch?v=vid10000000", "upload_date": "2024-01-15"}"#;
 
-        let result = parse_ytdlp_output(jsonl, &test_channel());
+        let result = parse_ytdlp_output(jsonl, &test_channel(), IdRules::YouTube);
         assert!(
             matches!(result, Err(FetchError::Parse(msg)) if msg.contains("invalid upload_date"))
         );
@@ -190,19 +564,72 @@ mod tests {
 
     #[test]
     fn generates_url_when_missing() {
-        let jsonl = r#"{"id": "vid1", "title": "Test", "upload_date": "20240115"}"#;
+        let jsonl = r#"{"id": "vid10000000", "title": "Test", "upload_date": "20240115"}"#;
 
-        let videos = parse_ytdlp_output(jsonl, &test_channel()).unwrap();
-        assert_eq!(videos[0].url, "https://www.youtube.com/watch?v=vid1");
+        let videos = parse_ytdlp_output(jsonl, &test_channel(), IdRules::YouTube)
+            .unwrap()
+            .videos;
+        assert_eq!(videos[0].url, "https://www.youtube.com/watch?v=vid10000000");
     }
 
     #[test]
     fn skips_blank_lines() {
-        let jsonl = r#"{"id": "vid1", "title": "Test", "url": "https://www.youtube.com/watch?v=vid1", "upload_date": "20240115"}
+        let jsonl = r#"{"id": "vid10000000", "title": "Test", "url": "https://www.youtube.com/watch?v=vid10000000", "upload_date": "20240115"}
 
-{"id": "vid2", "title": "Test 2", "url": "https://www.youtube.com/watch?v=vid2", "upload_date": "20240116"}"#;
+{"id": "vid20000000", "title": "Test 2", "url": "https://www.youtube.com/watch?v=vid20000000", "upload_date": "20240116"}"#;
 
-        let videos = parse_ytdlp_output(jsonl, &test_channel()).unwrap();
+        let videos = parse_ytdlp_output(jsonl, &test_channel(), IdRules::YouTube)
+            .unwrap()
+            .videos;
         assert_eq!(videos.len(), 2);
     }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct ExpectedVideo {
+        id: String,
+        title: String,
+        url: String,
+        published: DateTime<Utc>,
+        channel_name: String,
+        channel_id: String,
+    }
+
+    fn fixture_channel() -> Channel {
+        Channel {
+            name: "Google for Developers".to_string(),
+            ..test_channel()
+        }
+    }
+
+    fn assert_matches_expected(videos: &[Video], expected_path: &str) {
+        let expected: Vec<ExpectedVideo> =
+            serde_json::from_str(&std::fs::read_to_string(expected_path).unwrap()).unwrap();
+
+        assert_eq!(videos.len(), expected.len());
+        for (actual, exp) in videos.iter().zip(expected.iter()) {
+            assert_eq!(actual.id.to_string(), exp.id);
+            assert_eq!(actual.title, exp.title);
+            assert_eq!(actual.url, exp.url);
+            assert_eq!(actual.published, exp.published);
+            assert_eq!(actual.channel_name, exp.channel_name);
+            assert_eq!(actual.channel_id.to_string(), exp.channel_id);
+        }
+    }
+
+    /// Mirrors the RSS fixture test: the placeholder IDs only pass the
+    /// test-only relaxed rules, and under YouTube's no line is a video.
+    #[test]
+    fn parses_fixture_ytdlp_output_to_expected_videos() {
+        let jsonl =
+            std::fs::read_to_string("tests/fixtures/input/ytdlp_channel_output.jsonl").unwrap();
+
+        let output = parse_ytdlp_output(&jsonl, &fixture_channel(), IdRules::Relaxed).unwrap();
+        assert_matches_expected(
+            &output.videos,
+            "tests/fixtures/expected/ytdlp_parsed_videos.json",
+        );
+
+        let err = parse_ytdlp_output(&jsonl, &fixture_channel(), IdRules::YouTube).unwrap_err();
+        assert!(err.to_string().contains("is not a YouTube video ID"));
+    }
 }
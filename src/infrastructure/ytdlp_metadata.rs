@@ -0,0 +1,192 @@
+use serde::Deserialize;
+use std::process::Command;
+
+use super::cookies::Cookies;
+use super::dependency::YT_DLP;
+use super::http::ProxyUrl;
+use crate::application::ports::{FetchError, MetadataFetcher, Uploader, UploaderLookup};
+use crate::domain::channel::ChannelId;
+use crate::domain::metadata::VideoMetadata;
+use crate::domain::video::{IdRules, VideoId};
+
+const WATCH_URL_TEMPLATE: &str = "https://www.youtube.com/watch?v=";
+
+#[derive(Debug, Deserialize)]
+struct YtDlpVideo {
+    title: Option<String>,
+    channel: Option<String>,
+    channel_id: Option<String>,
+    view_count: Option<u64>,
+    like_count: Option<u64>,
+    description: Option<String>,
+}
+
+/// Reads a single video's full page through yt-dlp, which is slow (a second
+/// or more per video); only used by the opt-in enrichment pass.
+#[derive(Default)]
+pub struct YtDlpMetadataFetcher {
+    proxy: Option<ProxyUrl>,
+    cookies: Option<Cookies>,
+}
+
+impl YtDlpMetadataFetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_proxy(self, proxy: Option<ProxyUrl>) -> Self {
+        Self { proxy, ..self }
+    }
+
+    /// Sends the user's cookies, for age-restricted and members-only videos.
+    pub fn with_cookies(self, cookies: Option<Cookies>) -> Self {
+        Self { cookies, ..self }
+    }
+
+    fn args(&self, video_id: &VideoId) -> Vec<String> {
+        let mut args: Vec<String> = ["--skip-download", "--dump-json", "--no-playlist"]
+            .map(String::from)
+            .to_vec();
+        if let Some(proxy) = &self.proxy {
+            args.extend(["--proxy".to_string(), proxy.as_str().to_string()]);
+        }
+        if let Some(cookies) = &self.cookies {
+            args.extend(cookies.ytdlp_args());
+        }
+        args.push(format!("{WATCH_URL_TEMPLATE}{video_id}"));
+        args
+    }
+
+    /// The `--dump-json` of one video.
+    fn dump(&self, video_id: &VideoId) -> Result<String, FetchError> {
+        YT_DLP
+            .require()
+            .map_err(|e| FetchError::YtDlp(e.to_string()))?;
+        let output = Command::new("yt-dlp")
+            .args(self.args(video_id))
+            .output()
+            .map_err(|e| FetchError::YtDlp(format!("failed to run yt-dlp: {e}")))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(FetchError::YtDlp(format!("yt-dlp failed: {stderr}")));
+        }
+
+        String::from_utf8(output.stdout).map_err(|e| FetchError::Parse(e.to_string()))
+    }
+}
+
+impl MetadataFetcher for YtDlpMetadataFetcher {
+    fn fetch_metadata(&self, video_id: &VideoId) -> Result<VideoMetadata, FetchError> {
+        parse_metadata(&self.dump(video_id)?)
+    }
+}
+
+impl UploaderLookup for YtDlpMetadataFetcher {
+    fn uploader(&self, video_id: &VideoId) -> Result<Uploader, FetchError> {
+        parse_uploader(&self.dump(video_id)?)
+    }
+}
+
+fn parse_video(json: &str) -> Result<YtDlpVideo, FetchError> {
+    serde_json::from_str(json).map_err(|e| FetchError::Parse(e.to_string()))
+}
+
+fn parse_uploader(json: &str) -> Result<Uploader, FetchError> {
+    let video = parse_video(json)?;
+    let id = video
+        .channel_id
+        .ok_or_else(|| FetchError::Parse("yt-dlp gave no channel ID".to_string()))
+        .and_then(|id| {
+            ChannelId::parse_with(id, IdRules::YouTube)
+                .map_err(|e| FetchError::Parse(format!("invalid channel ID: {e}")))
+        })?;
+    Ok(Uploader {
+        name: video
+            .channel
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| id.to_string()),
+        id,
+    })
+}
+
+fn parse_metadata(json: &str) -> Result<VideoMetadata, FetchError> {
+    let video = parse_video(json)?;
+    Ok(VideoMetadata {
+        title: video.title.unwrap_or_default(),
+        channel_name: video.channel,
+        view_count: video.view_count,
+        like_count: video.like_count,
+        description: video.description.filter(|d| !d.trim().is_empty()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_video_json() {
+        let json = r#"{"id": "abc123", "title": "My Video", "channel": "Chan", "view_count": 1234, "like_count": 56, "description": "Links below", "formats": []}"#;
+
+        let metadata = parse_metadata(json).unwrap();
+
+        assert_eq!(
+            metadata,
+            VideoMetadata {
+                title: "My Video".to_string(),
+                channel_name: Some("Chan".to_string()),
+                view_count: Some(1234),
+                like_count: Some(56),
+                description: Some("Links below".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn reads_the_uploader() {
+        let json =
+            r#"{"id": "abc123", "channel": "Chan", "channel_id": "UC_x5XG1OV2P6uZZ5FSM9Ttw"}"#;
+
+        assert_eq!(
+            parse_uploader(json).unwrap(),
+            Uploader {
+                id: ChannelId::parse("UC_x5XG1OV2P6uZZ5FSM9Ttw").unwrap(),
+                name: "Chan".to_string(),
+            }
+        );
+        assert!(parse_uploader(r#"{"id": "abc123", "channel": "Chan"}"#).is_err());
+        assert!(
+            parse_uploader(r#"{"id": "abc123", "channel": "Chan", "channel_id": "UC123"}"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn hidden_counts_and_blank_description_are_unknown() {
+        let metadata = parse_metadata(r#"{"title": "T", "description": "  "}"#).unwrap();
+
+        assert_eq!(metadata.like_count, None);
+        assert_eq!(metadata.description, None);
+    }
+
+    #[test]
+    fn builds_args_with_proxy() {
+        let proxy = ProxyUrl::parse("socks5://127.0.0.1:1080").unwrap();
+        let args = YtDlpMetadataFetcher::new()
+            .with_proxy(Some(proxy))
+            .args(&VideoId::parse("abc123").unwrap());
+
+        assert_eq!(
+            args,
+            [
+                "--skip-download",
+                "--dump-json",
+                "--no-playlist",
+                "--proxy",
+                "socks5://127.0.0.1:1080",
+                "https://www.youtube.com/watch?v=abc123",
+            ]
+        );
+    }
+}
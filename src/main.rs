@@ -3,11 +3,33 @@ mod domain;
 mod infrastructure;
 mod presentation;
 
+use presentation::args::parse_args;
 use presentation::cli::run;
+use presentation::exit::{exit_code, is_silent};
+use presentation::json::error_json;
+use presentation::logging;
 
 fn main() {
-    if let Err(e) = run() {
-        eprintln!("Error: {e}");
-        std::process::exit(1);
+    let mut json_errors = false;
+    let result = parse_args(std::env::args().skip(1))
+        .map_err(Into::into)
+        .and_then(|invocation| {
+            json_errors = invocation.json_errors();
+            logging::init(
+                invocation.log_level,
+                invocation.log_format,
+                invocation.debug_http,
+            );
+            run(invocation)
+        });
+    if let Err(e) = result {
+        if !is_silent(e.as_ref()) {
+            if json_errors {
+                eprintln!("{}", error_json(e.as_ref()));
+            } else {
+                eprintln!("Error: {e}");
+            }
+        }
+        std::process::exit(exit_code(e.as_ref()));
     }
 }
@@ -0,0 +1,1559 @@
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+
+use crate::domain::channel::{SnoozeLength, SnoozeLengthError};
+use crate::domain::playback::Subtitles;
+use crate::domain::video::{IdRules, VideoId, VideoNumber};
+use crate::presentation::logging::{LogFormat, LogLevel};
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BrowseFlags {
+    pub force: bool,
+    pub show_muted: bool,
+    pub enrich: bool,
+    pub group_by_channel: bool,
+    pub qr: bool,
+    /// Chromecast or DLNA device to play on instead of mpv.
+    pub cast: Option<String>,
+    /// Only channels carrying this tag.
+    pub tag: Option<String>,
+    /// Print the listing in this format instead of prompting.
+    pub output: Option<ListingFormat>,
+    /// `--quiet`: no progress on stderr, and the listing is printed without
+    /// a prompt.
+    pub quiet: bool,
+    /// Print how long each channel took to fetch once the run is over.
+    pub timing: bool,
+    /// Hidden `--record-fixtures <dir>`: save what each channel's fetch read.
+    pub record_fixtures: Option<PathBuf>,
+    /// Hidden `--replay <dir>`: fetch from what was recorded there instead.
+    pub replay: Option<PathBuf>,
+}
+
+/// Machine-readable listings, for `--json` and `--format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListingFormat {
+    Json,
+    /// Tab-separated, one video per line, no header.
+    Tsv,
+    /// Comma-separated with a header line, quoted where needed.
+    Csv,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PlaySelection {
+    /// Every unwatched video, oldest first.
+    All,
+    /// One unwatched video, picked at random.
+    Random,
+    /// The most recent unwatched video.
+    Latest,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PlayFlags {
+    pub selection: PlaySelection,
+    /// Only this configured channel, by name.
+    pub channel: Option<String>,
+    pub browse: BrowseFlags,
+}
+
+/// How `blepo status` prints the unwatched count.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StatusFormat {
+    #[default]
+    Plain,
+    /// A JSON object for a Waybar custom module.
+    Waybar,
+    /// Plain text, but nothing at all when there is nothing to watch, which
+    /// hides a Polybar script module.
+    Polybar,
+    Json,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct StatusFlags {
+    pub format: StatusFormat,
+    /// Also count the videos of each channel tag.
+    pub by_tag: bool,
+    /// Only channels carrying this tag.
+    pub tag: Option<String>,
+}
+
+/// What to run, and with which config file.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Invocation {
+    /// `--config <path>`, accepted anywhere on the command line.
+    pub config: Option<PathBuf>,
+    /// `--strict-config`: unknown config keys are errors, not warnings.
+    pub strict_config: bool,
+    /// `--quiet`: only requested output on stdout and warnings on stderr.
+    pub quiet: bool,
+    /// `--wait`: wait for another running blepo instead of failing.
+    pub wait: bool,
+    /// `-v`, `-vv`, `-vvv` or `--log-level <level>`.
+    pub log_level: LogLevel,
+    /// `--log-format text|json`.
+    pub log_format: LogFormat,
+    /// `--debug-http`: log requests and cache lookups whatever the level.
+    pub debug_http: bool,
+    pub command: Command,
+}
+
+impl Invocation {
+    /// Whether the command prints JSON, so errors go to stderr as JSON too.
+    pub fn json_errors(&self) -> bool {
+        match &self.command {
+            Command::Browse(flags) => flags.output == Some(ListingFormat::Json),
+            Command::Status(flags) => flags.format == StatusFormat::Json,
+            Command::Channels { json, .. } => *json,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Browse(BrowseFlags),
+    Undo(NonZeroUsize),
+    Info(VideoId),
+    /// Open a video from the last listing in the browser.
+    Open(VideoNumber),
+    Queue(QueueCommand),
+    Play(PlayFlags),
+    /// List players running in the background.
+    Jobs,
+    /// Announce new videos at `notify_url`.
+    Notify,
+    /// List the videos first seen since the previous digest.
+    Digest,
+    /// Print the unwatched count from cached fetches, for status bars.
+    Status(StatusFlags),
+    /// Stop one background player, by its number in `jobs`, or all of them.
+    Stop(Option<VideoNumber>),
+    Config(ConfigCommand),
+    Snooze(SnoozeCommand),
+    Channels {
+        command: ChannelsCommand,
+        json: bool,
+    },
+    /// Print the unwatched videos for another program to read.
+    Export {
+        command: ExportCommand,
+        /// Only channels carrying this tag.
+        tag: Option<String>,
+    },
+    /// Save a video with yt-dlp for watching offline.
+    Download {
+        target: DownloadTarget,
+        mark_watched: bool,
+        /// Subtitles to save, instead of what the config says.
+        subtitles: Option<Subtitles>,
+        /// Save the thumbnail too, whatever the config says.
+        thumbnail: bool,
+    },
+    Downloads(DownloadsCommand),
+    /// Add the channel that uploaded a video to the config.
+    Add {
+        video: VideoId,
+        mark_watched: bool,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DownloadsCommand {
+    /// Show the download queue and the latest saved videos.
+    List,
+    /// Run queued and interrupted downloads, retrying failed ones.
+    Resume,
+    /// Delete downloads watched more than this many days ago, or than
+    /// `prune_downloads_after_days`.
+    Prune(Option<NonZeroUsize>),
+}
+
+/// The video `blepo download` saves.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DownloadTarget {
+    /// By its number in the last listing.
+    Listed(VideoNumber),
+    /// By ID or link, listed or not.
+    Video(VideoId),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ChannelsCommand {
+    /// Probe every configured channel's feed for dead or renamed channels.
+    Check,
+    /// Upload rate, last upload and share watched of every channel.
+    Stats,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ExportCommand {
+    /// One Atom feed of every unwatched video, for a feed reader.
+    Feed,
+    /// Scheduled premieres and streams as an iCalendar file.
+    Calendar,
+    /// The unwatched videos as a Markdown document, for notes.
+    Markdown(MarkdownGrouping),
+    /// The unwatched videos as one HTML page with thumbnails, written to
+    /// `out` or printed.
+    Html { out: Option<PathBuf> },
+}
+
+/// The sections of `export markdown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkdownGrouping {
+    /// One section per publish day, newest first.
+    Day,
+    Channel,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SnoozeCommand {
+    /// List the channels that are snoozed.
+    List,
+    /// Hide a channel, by name, from listings for a while.
+    Snooze(String, SnoozeLength),
+    /// List a snoozed channel again.
+    Wake(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConfigCommand {
+    /// Print where the config file is.
+    Path,
+    Edit,
+    /// Report every problem in the config.
+    Check,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum QueueCommand {
+    List,
+    Play,
+    Remove(VideoNumber),
+    Move(VideoNumber, VideoNumber),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ArgsError {
+    UnknownCommand(String),
+    UnknownFlag(String),
+    InvalidCount(String),
+    MissingVideoId,
+    InvalidVideoNumber(Option<String>),
+    MissingPlaySelection,
+    MissingChannel,
+    MissingCastDevice,
+    MissingTag,
+    MissingConfigPath,
+    MissingOutPath,
+    MissingSnoozeLength,
+    MissingVideoUrl,
+    MissingFixtureDir(String),
+    RecordAndReplay,
+    InvalidDownloadTarget(Option<String>),
+    InvalidSubtitles(Option<String>),
+    InvalidVideoUrl(String),
+    InvalidSnoozeLength(SnoozeLengthError),
+    InvalidGroupBy(Option<String>),
+    InvalidMarkdownGroupBy(Option<String>),
+    InvalidStatusFormat(Option<String>),
+    InvalidListingFormat(Option<String>),
+    InvalidLogLevel(Option<String>),
+    InvalidLogFormat(Option<String>),
+    UnexpectedArgument(String),
+}
+
+impl std::fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgsError::UnknownCommand(cmd) => write!(f, "unknown command: {cmd}\n\n{USAGE}"),
+            ArgsError::UnknownFlag(flag) => write!(f, "unknown flag: {flag}\n\n{USAGE}"),
+            ArgsError::InvalidCount(raw) => {
+                write!(f, "invalid count: {raw} (expected a positive number)")
+            }
+            ArgsError::InvalidGroupBy(Some(raw)) => {
+                write!(f, "invalid --group-by: {raw} (expected channel)")
+            }
+            ArgsError::InvalidGroupBy(None) => write!(f, "--group-by needs a value (channel)"),
+            ArgsError::InvalidMarkdownGroupBy(Some(raw)) => {
+                write!(f, "invalid --group-by: {raw} (expected day or channel)")
+            }
+            ArgsError::InvalidMarkdownGroupBy(None) => {
+                write!(f, "--group-by needs a value (day or channel)")
+            }
+            ArgsError::InvalidStatusFormat(Some(raw)) => {
+                write!(
+                    f,
+                    "invalid --format: {raw} (expected plain, waybar, polybar or json)"
+                )
+            }
+            ArgsError::InvalidStatusFormat(None) => {
+                write!(f, "--format needs a value (plain, waybar, polybar or json)")
+            }
+            ArgsError::InvalidListingFormat(Some(raw)) => {
+                write!(f, "invalid --format: {raw} (expected json, tsv or csv)")
+            }
+            ArgsError::InvalidListingFormat(None) => {
+                write!(f, "--format needs a value (json, tsv or csv)")
+            }
+            ArgsError::InvalidLogLevel(Some(raw)) => write!(
+                f,
+                "invalid --log-level: {raw} (expected error, warn, info, debug or trace)"
+            ),
+            ArgsError::InvalidLogLevel(None) => write!(
+                f,
+                "--log-level needs a value (error, warn, info, debug or trace)"
+            ),
+            ArgsError::InvalidLogFormat(Some(raw)) => {
+                write!(f, "invalid --log-format: {raw} (expected text or json)")
+            }
+            ArgsError::InvalidLogFormat(None) => {
+                write!(f, "--log-format needs a value (text or json)")
+            }
+            ArgsError::MissingVideoId => write!(f, "missing video ID\n\n{USAGE}"),
+            ArgsError::InvalidVideoNumber(Some(raw)) => {
+                write!(
+                    f,
+                    "invalid video number: {raw} (expected a number from the listing)"
+                )
+            }
+            ArgsError::MissingPlaySelection => {
+                write!(f, "play needs --all, --random or --latest\n\n{USAGE}")
+            }
+            ArgsError::MissingChannel => write!(f, "--channel needs a channel name"),
+            ArgsError::MissingCastDevice => write!(f, "--cast needs a device name"),
+            ArgsError::MissingTag => write!(f, "--tag needs a tag name"),
+            ArgsError::MissingConfigPath => write!(f, "--config needs a file path"),
+            ArgsError::MissingOutPath => write!(f, "--out needs a file path"),
+            ArgsError::MissingFixtureDir(flag) => write!(f, "{flag} needs a directory"),
+            ArgsError::RecordAndReplay => {
+                write!(f, "--record-fixtures and --replay can't be combined")
+            }
+            ArgsError::MissingSnoozeLength => {
+                write!(f, "snooze needs a length, e.g. 30d, or off\n\n{USAGE}")
+            }
+            ArgsError::InvalidSnoozeLength(e) => write!(f, "{e}"),
+            ArgsError::MissingVideoUrl => {
+                write!(f, "add needs --from-video <url>\n\n{USAGE}")
+            }
+            ArgsError::InvalidVideoUrl(url) => write!(f, "not a YouTube video link: {url}"),
+            ArgsError::InvalidDownloadTarget(Some(raw)) => write!(
+                f,
+                "invalid video: {raw} (expected a number from the listing, a video ID or a link)"
+            ),
+            ArgsError::InvalidDownloadTarget(None) => {
+                write!(f, "download needs a video number, ID or link\n\n{USAGE}")
+            }
+            ArgsError::InvalidSubtitles(Some(reason)) => write!(f, "invalid --subs: {reason}"),
+            ArgsError::InvalidSubtitles(None) => {
+                write!(f, "--subs needs languages, e.g. en,de")
+            }
+            ArgsError::InvalidVideoNumber(None) => {
+                write!(f, "missing video number\n\n{USAGE}")
+            }
+            ArgsError::UnexpectedArgument(arg) => {
+                write!(f, "unexpected argument: {arg}\n\n{USAGE}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArgsError {}
+
+const USAGE: &str = "Usage:\n  \
+    blepo [flags]     Fetch videos, show list, pick one to play\n  \
+    blepo list [flags]  Same as blepo [flags]\n  \
+    blepo undo [n]    Undo the last n state changes (default 1)\n  \
+    blepo info <id>   Show views, likes and description of an enriched video\n  \
+    blepo open <n>    Open video number n of the last listing in the browser\n  \
+    blepo queue       List the watch-later queue\n  \
+    blepo queue play  Play the queue as one playlist\n  \
+    blepo queue rm <n>          Remove video n from the queue\n  \
+    blepo queue mv <from> <to>  Move a queued video to another position\n  \
+    blepo play --all [--channel <name>]  Play every unwatched video, oldest first\n  \
+    blepo play --random [--channel <name>]  Play one unwatched video at random\n  \
+    blepo next        Play the newest unwatched video (same as play --latest)\n  \
+    blepo jobs        List videos playing in the background\n  \
+    blepo notify      Announce new videos at notify_url (for cron)\n  \
+    blepo digest      List the videos first seen since the last digest\n  \
+    blepo status [--format plain|waybar|polybar|json] [--by-tag]  Print the unwatched count for a status bar\n  \
+    blepo stop [n]    Stop background player n, or all of them\n  \
+    blepo config      Print the path of the config file\n  \
+    blepo config edit   Open the config in $VISUAL or $EDITOR, then check it\n  \
+    blepo config check  Report every problem in the config\n  \
+    blepo snooze      List snoozed channels\n  \
+    blepo snooze <name> <30d|2w|date>  Leave a channel out of listings for a while\n  \
+    blepo snooze <name> off  List a snoozed channel again\n  \
+    blepo add --from-video <url> [--mark-watched]  Add the channel of a video to the config\n  \
+    blepo download <n|id|url> [--mark-watched] [--subs <langs>] [--thumbnail]  Save a video with yt-dlp to download_dir\n  \
+    blepo downloads   Show queued, running, failed and recently saved downloads\n  \
+    blepo downloads resume  Run interrupted downloads and retry failed ones\n  \
+    blepo downloads prune [days]  Delete downloads watched more than days ago\n  \
+    blepo export feed [--tag <name>]  Print the unwatched videos as an Atom feed\n  \
+    blepo export calendar [--tag <name>]  Print scheduled premieres and streams as iCalendar\n  \
+    blepo export markdown [--group-by day|channel] [--tag <name>]  Print the unwatched videos as Markdown\n  \
+    blepo export html [--out <file>] [--tag <name>]  Write the unwatched videos as an HTML page\n  \
+    blepo channels check [--json]  Look for deleted, empty or renamed channels\n  \
+    blepo channels stats [--json]  Show how often channels upload and how much of them you watch\n\n\
+    Flags:\n  \
+    --force           Refetch channels fetched within fetch_ttl_minutes\n  \
+    --show-muted      Also list videos hidden by muted_words\n  \
+    --enrich          Fetch views, likes and description of listed videos\n  \
+    --group-by channel  List videos under channel headers\n  \
+    --qr              Also show a QR code of URLs copied with y<number>\n  \
+    --timing          Show how long each channel and the Shorts check took\n  \
+    --cast <device>   Play on a Chromecast or DLNA device (needs catt) instead of mpv\n  \
+    --tag <name>      Only channels tagged <name> in the config\n  \
+    --json            Print the listing as JSON and exit (blepo and blepo list only)\n  \
+    --format tsv|csv  Print the listing as tab- or comma-separated columns and exit\n  \
+    --config <path>   Read this config file instead of the default (or $BLEPO_CONFIG)\n  \
+    --strict-config   Fail on unknown config keys instead of warning\n  \
+    --quiet           No progress messages and no prompt: print the listing and exit\n  \
+    --wait            Wait for another running blepo to finish instead of failing\n  \
+    -v, -vv, -vvv     Log more on stderr: what is fetched, then request details\n  \
+    --log-level <level>  error, warn (default), info, debug or trace\n  \
+    --log-format text|json  Log as readable lines (default) or JSON objects\n  \
+    --debug-http      Log feed and Shorts requests, their status and size, and cache hits";
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Invocation, ArgsError> {
+    let mut config = None;
+    let mut strict_config = false;
+    let mut quiet = false;
+    let mut wait = false;
+    let mut verbosity = 0;
+    let mut log_level = None;
+    let mut log_format = LogFormat::default();
+    let mut debug_http = false;
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            config = Some(PathBuf::from(
+                args.next().ok_or(ArgsError::MissingConfigPath)?,
+            ));
+        } else if arg == "--strict-config" {
+            strict_config = true;
+        } else if arg == "--quiet" {
+            quiet = true;
+        } else if arg == "--wait" {
+            wait = true;
+        } else if arg.len() > 1
+            && arg
+                .strip_prefix('-')
+                .is_some_and(|v| v.bytes().all(|b| b == b'v'))
+        {
+            verbosity += arg.len() - 1;
+        } else if arg == "--log-level" {
+            let raw = args.next().ok_or(ArgsError::InvalidLogLevel(None))?;
+            log_level = Some(LogLevel::parse(&raw).ok_or(ArgsError::InvalidLogLevel(Some(raw)))?);
+        } else if arg == "--log-format" {
+            let raw = args.next().ok_or(ArgsError::InvalidLogFormat(None))?;
+            log_format = LogFormat::parse(&raw).ok_or(ArgsError::InvalidLogFormat(Some(raw)))?;
+        } else if arg == "--debug-http" {
+            debug_http = true;
+        } else {
+            rest.push(arg);
+        }
+    }
+    Ok(Invocation {
+        config,
+        strict_config,
+        quiet,
+        wait,
+        log_level: log_level.unwrap_or(LogLevel::verbose(verbosity)),
+        log_format,
+        debug_http,
+        command: parse_command(rest)?,
+    })
+}
+
+fn parse_command(args: Vec<String>) -> Result<Command, ArgsError> {
+    let mut args = args.into_iter().peekable();
+    let command = match args.peek().map(String::as_str) {
+        None => Command::Browse(BrowseFlags::default()),
+        Some(flag) if flag.starts_with("--") => {
+            return parse_browse_flags(args).map(Command::Browse);
+        }
+        Some("list") => {
+            args.next();
+            return parse_browse_flags(args).map(Command::Browse);
+        }
+        Some("undo") => {
+            args.next();
+            Command::Undo(parse_count(args.next())?)
+        }
+        Some("info") => {
+            args.next();
+            let id = args
+                .next()
+                .and_then(|raw| VideoId::parse(raw).ok())
+                .ok_or(ArgsError::MissingVideoId)?;
+            Command::Info(id)
+        }
+        Some("open") => {
+            args.next();
+            Command::Open(parse_video_number(args.next())?)
+        }
+        Some("queue") => {
+            args.next();
+            Command::Queue(match args.next().as_deref() {
+                None => QueueCommand::List,
+                Some("play") => QueueCommand::Play,
+                Some("rm") => QueueCommand::Remove(parse_video_number(args.next())?),
+                Some("mv") => QueueCommand::Move(
+                    parse_video_number(args.next())?,
+                    parse_video_number(args.next())?,
+                ),
+                Some(other) => return Err(ArgsError::UnknownCommand(format!("queue {other}"))),
+            })
+        }
+        Some("jobs") => {
+            args.next();
+            Command::Jobs
+        }
+        Some("notify") => {
+            args.next();
+            Command::Notify
+        }
+        Some("digest") => {
+            args.next();
+            Command::Digest
+        }
+        Some("status") => {
+            args.next();
+            return parse_status_flags(args).map(Command::Status);
+        }
+        Some("config") => {
+            args.next();
+            Command::Config(match args.next().as_deref() {
+                None => ConfigCommand::Path,
+                Some("edit") => ConfigCommand::Edit,
+                Some("check") => ConfigCommand::Check,
+                Some(other) => return Err(ArgsError::UnknownCommand(format!("config {other}"))),
+            })
+        }
+        Some("snooze") => {
+            args.next();
+            Command::Snooze(match args.next() {
+                None => SnoozeCommand::List,
+                Some(channel) => match args.next().as_deref() {
+                    None => return Err(ArgsError::MissingSnoozeLength),
+                    Some("off") => SnoozeCommand::Wake(channel),
+                    Some(raw) => SnoozeCommand::Snooze(
+                        channel,
+                        SnoozeLength::parse(raw).map_err(ArgsError::InvalidSnoozeLength)?,
+                    ),
+                },
+            })
+        }
+        Some("channels") => {
+            args.next();
+            let command = match args.next().as_deref() {
+                Some("check") => ChannelsCommand::Check,
+                Some("stats") => ChannelsCommand::Stats,
+                Some(other) => return Err(ArgsError::UnknownCommand(format!("channels {other}"))),
+                None => return Err(ArgsError::UnknownCommand("channels".to_string())),
+            };
+            let json = args.next_if(|arg| arg == "--json").is_some();
+            Command::Channels { command, json }
+        }
+        Some("export") => {
+            args.next();
+            let mut command = match args.next().as_deref() {
+                Some("feed") => ExportCommand::Feed,
+                Some("calendar") => ExportCommand::Calendar,
+                Some("markdown") => ExportCommand::Markdown(MarkdownGrouping::Day),
+                Some("html") => ExportCommand::Html { out: None },
+                Some(other) => return Err(ArgsError::UnknownCommand(format!("export {other}"))),
+                None => return Err(ArgsError::UnknownCommand("export".to_string())),
+            };
+            let mut tag = None;
+            loop {
+                if args.next_if(|arg| arg == "--tag").is_some() {
+                    tag = Some(args.next().ok_or(ArgsError::MissingTag)?);
+                } else if matches!(command, ExportCommand::Markdown(_))
+                    && args.next_if(|arg| arg == "--group-by").is_some()
+                {
+                    command = ExportCommand::Markdown(match args.next().as_deref() {
+                        Some("day") => MarkdownGrouping::Day,
+                        Some("channel") => MarkdownGrouping::Channel,
+                        other => {
+                            return Err(ArgsError::InvalidMarkdownGroupBy(
+                                other.map(str::to_string),
+                            ))
+                        }
+                    });
+                } else if matches!(command, ExportCommand::Html { .. })
+                    && args.next_if(|arg| arg == "--out").is_some()
+                {
+                    let out = args.next().ok_or(ArgsError::MissingOutPath)?;
+                    command = ExportCommand::Html {
+                        out: Some(PathBuf::from(out)),
+                    };
+                } else {
+                    break;
+                }
+            }
+            Command::Export { command, tag }
+        }
+        Some("add") => {
+            args.next();
+            let mut video = None;
+            let mut mark_watched = false;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--from-video" => {
+                        let url = args.next().ok_or(ArgsError::MissingVideoUrl)?;
+                        video =
+                            Some(VideoId::from_url(&url).ok_or(ArgsError::InvalidVideoUrl(url))?);
+                    }
+                    "--mark-watched" => mark_watched = true,
+                    _ if arg.starts_with("--") => return Err(ArgsError::UnknownFlag(arg)),
+                    _ => return Err(ArgsError::UnexpectedArgument(arg)),
+                }
+            }
+            Command::Add {
+                video: video.ok_or(ArgsError::MissingVideoUrl)?,
+                mark_watched,
+            }
+        }
+        Some("download") => {
+            args.next();
+            let mut target = None;
+            let mut mark_watched = false;
+            let mut subtitles = None;
+            let mut thumbnail = false;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--mark-watched" => mark_watched = true,
+                    "--subs" => {
+                        let raw = args.next().ok_or(ArgsError::InvalidSubtitles(None))?;
+                        subtitles = Some(
+                            Subtitles::parse(&raw)
+                                .map_err(|e| ArgsError::InvalidSubtitles(Some(e.to_string())))?,
+                        );
+                    }
+                    "--thumbnail" => thumbnail = true,
+                    _ if arg.starts_with("--") => return Err(ArgsError::UnknownFlag(arg)),
+                    _ if target.is_none() => target = Some(parse_download_target(arg)?),
+                    _ => return Err(ArgsError::UnexpectedArgument(arg)),
+                }
+            }
+            Command::Download {
+                target: target.ok_or(ArgsError::InvalidDownloadTarget(None))?,
+                mark_watched,
+                subtitles,
+                thumbnail,
+            }
+        }
+        Some("downloads") => {
+            args.next();
+            Command::Downloads(match args.next().as_deref() {
+                None => DownloadsCommand::List,
+                Some("resume") => DownloadsCommand::Resume,
+                Some("prune") => DownloadsCommand::Prune(
+                    args.next().map(|raw| parse_count(Some(raw))).transpose()?,
+                ),
+                Some(other) => return Err(ArgsError::UnknownCommand(format!("downloads {other}"))),
+            })
+        }
+        Some("stop") => {
+            args.next();
+            match args.next() {
+                None => Command::Stop(None),
+                number => Command::Stop(Some(parse_video_number(number)?)),
+            }
+        }
+        Some("play") => {
+            args.next();
+            return parse_play_flags(args, None).map(Command::Play);
+        }
+        Some("next") => {
+            args.next();
+            return parse_play_flags(args, Some(PlaySelection::Latest)).map(Command::Play);
+        }
+        Some(other) => return Err(ArgsError::UnknownCommand(other.to_string())),
+    };
+
+    match args.next() {
+        Some(extra) => Err(ArgsError::UnexpectedArgument(extra)),
+        None => Ok(command),
+    }
+}
+
+fn parse_browse_flags(mut args: impl Iterator<Item = String>) -> Result<BrowseFlags, ArgsError> {
+    let mut flags = BrowseFlags::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--force" => flags.force = true,
+            "--show-muted" => flags.show_muted = true,
+            "--enrich" => flags.enrich = true,
+            "--qr" => flags.qr = true,
+            "--timing" => flags.timing = true,
+            "--record-fixtures" => {
+                flags.record_fixtures = Some(PathBuf::from(
+                    args.next().ok_or(ArgsError::MissingFixtureDir(arg))?,
+                ))
+            }
+            "--replay" => {
+                flags.replay = Some(PathBuf::from(
+                    args.next().ok_or(ArgsError::MissingFixtureDir(arg))?,
+                ))
+            }
+            "--json" => flags.output = Some(ListingFormat::Json),
+            "--format" => {
+                flags.output = Some(match args.next().as_deref() {
+                    Some("json") => ListingFormat::Json,
+                    Some("tsv") => ListingFormat::Tsv,
+                    Some("csv") => ListingFormat::Csv,
+                    other => {
+                        return Err(ArgsError::InvalidListingFormat(other.map(str::to_string)))
+                    }
+                })
+            }
+            "--cast" => flags.cast = Some(args.next().ok_or(ArgsError::MissingCastDevice)?),
+            "--tag" => flags.tag = Some(args.next().ok_or(ArgsError::MissingTag)?),
+            "--group-by" => match args.next().as_deref() {
+                Some("channel") => flags.group_by_channel = true,
+                other => return Err(ArgsError::InvalidGroupBy(other.map(str::to_string))),
+            },
+            _ if arg.starts_with("--") => return Err(ArgsError::UnknownFlag(arg)),
+            _ => return Err(ArgsError::UnexpectedArgument(arg)),
+        }
+    }
+    if flags.record_fixtures.is_some() && flags.replay.is_some() {
+        return Err(ArgsError::RecordAndReplay);
+    }
+    Ok(flags)
+}
+
+fn parse_status_flags(mut args: impl Iterator<Item = String>) -> Result<StatusFlags, ArgsError> {
+    let mut flags = StatusFlags::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                flags.format = match args.next().as_deref() {
+                    Some("plain") => StatusFormat::Plain,
+                    Some("waybar") => StatusFormat::Waybar,
+                    Some("polybar") => StatusFormat::Polybar,
+                    Some("json") => StatusFormat::Json,
+                    other => return Err(ArgsError::InvalidStatusFormat(other.map(str::to_string))),
+                }
+            }
+            "--json" => flags.format = StatusFormat::Json,
+            "--by-tag" => flags.by_tag = true,
+            "--tag" => flags.tag = Some(args.next().ok_or(ArgsError::MissingTag)?),
+            _ if arg.starts_with("--") => return Err(ArgsError::UnknownFlag(arg)),
+            _ => return Err(ArgsError::UnexpectedArgument(arg)),
+        }
+    }
+    Ok(flags)
+}
+
+/// `selection` is what `next` implies; `play` has to be told.
+fn parse_play_flags(
+    mut args: impl Iterator<Item = String>,
+    mut selection: Option<PlaySelection>,
+) -> Result<PlayFlags, ArgsError> {
+    let mut channel = None;
+    let mut browse_args = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--all" => selection = Some(PlaySelection::All),
+            "--random" => selection = Some(PlaySelection::Random),
+            "--latest" => selection = Some(PlaySelection::Latest),
+            "--channel" => channel = Some(args.next().ok_or(ArgsError::MissingChannel)?),
+            "--json" | "--format" => return Err(ArgsError::UnknownFlag(arg)),
+            _ => browse_args.push(arg),
+        }
+    }
+    Ok(PlayFlags {
+        selection: selection.ok_or(ArgsError::MissingPlaySelection)?,
+        channel,
+        browse: parse_browse_flags(browse_args.into_iter())?,
+    })
+}
+
+fn parse_video_number(raw: Option<String>) -> Result<VideoNumber, ArgsError> {
+    let raw = raw.ok_or(ArgsError::InvalidVideoNumber(None))?;
+    raw.parse::<usize>()
+        .ok()
+        .and_then(|n| VideoNumber::parse(n).ok())
+        .ok_or(ArgsError::InvalidVideoNumber(Some(raw)))
+}
+
+/// A number from the listing, else a video link, else a bare video ID.
+fn parse_download_target(raw: String) -> Result<DownloadTarget, ArgsError> {
+    if raw.chars().all(|c| c.is_ascii_digit()) {
+        return parse_video_number(Some(raw.clone()))
+            .map(DownloadTarget::Listed)
+            .map_err(|_| ArgsError::InvalidDownloadTarget(Some(raw)));
+    }
+    VideoId::from_url(&raw)
+        .or_else(|| VideoId::parse_with(raw.clone(), IdRules::YouTube).ok())
+        .map(DownloadTarget::Video)
+        .ok_or(ArgsError::InvalidDownloadTarget(Some(raw)))
+}
+
+fn parse_count(raw: Option<String>) -> Result<NonZeroUsize, ArgsError> {
+    match raw {
+        None => Ok(NonZeroUsize::MIN),
+        Some(raw) => raw
+            .parse::<NonZeroUsize>()
+            .map_err(|_| ArgsError::InvalidCount(raw)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, ArgsError> {
+        parse_args(args.iter().map(|a| a.to_string())).map(|invocation| invocation.command)
+    }
+
+    #[test]
+    fn no_arguments_browses() {
+        assert_eq!(parse(&[]), Ok(Command::Browse(BrowseFlags::default())));
+    }
+
+    #[test]
+    fn force_flag_browses_without_ttl() {
+        assert_eq!(
+            parse(&["--force"]),
+            Ok(Command::Browse(BrowseFlags {
+                force: true,
+                ..BrowseFlags::default()
+            }))
+        );
+    }
+
+    #[test]
+    fn browse_flags_combine() {
+        assert_eq!(
+            parse(&["--show-muted", "--force"]),
+            Ok(Command::Browse(BrowseFlags {
+                force: true,
+                show_muted: true,
+                ..BrowseFlags::default()
+            }))
+        );
+    }
+
+    #[test]
+    fn timing_applies_to_play_too() {
+        assert_eq!(
+            parse(&["next", "--timing"]),
+            Ok(Command::Play(PlayFlags {
+                selection: PlaySelection::Latest,
+                channel: None,
+                browse: BrowseFlags {
+                    timing: true,
+                    ..BrowseFlags::default()
+                },
+            }))
+        );
+    }
+
+    #[test]
+    fn records_or_replays_fixtures() {
+        assert_eq!(
+            parse(&["--record-fixtures", "fixtures"]),
+            Ok(Command::Browse(BrowseFlags {
+                record_fixtures: Some(PathBuf::from("fixtures")),
+                ..BrowseFlags::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["list", "--replay", "fixtures"]),
+            Ok(Command::Browse(BrowseFlags {
+                replay: Some(PathBuf::from("fixtures")),
+                ..BrowseFlags::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["--replay"]),
+            Err(ArgsError::MissingFixtureDir("--replay".to_string()))
+        );
+        assert_eq!(
+            parse(&["--replay", "a", "--record-fixtures", "b"]),
+            Err(ArgsError::RecordAndReplay)
+        );
+    }
+
+    #[test]
+    fn json_prints_the_listing_but_not_for_play() {
+        assert_eq!(
+            parse(&["list", "--json"]),
+            Ok(Command::Browse(BrowseFlags {
+                output: Some(ListingFormat::Json),
+                ..BrowseFlags::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["play", "--all", "--json"]),
+            Err(ArgsError::UnknownFlag("--json".to_string()))
+        );
+    }
+
+    #[test]
+    fn json_output_reports_errors_as_json() {
+        let json_errors = |args: &[&str]| {
+            parse_args(args.iter().map(|a| a.to_string()))
+                .unwrap()
+                .json_errors()
+        };
+
+        assert!(json_errors(&["--json"]));
+        assert!(json_errors(&["status", "--format", "json"]));
+        assert!(json_errors(&["channels", "check", "--json"]));
+        assert!(!json_errors(&["--format", "tsv"]));
+        assert!(!json_errors(&["notify"]));
+    }
+
+    #[test]
+    fn parses_listing_formats() {
+        assert_eq!(
+            parse(&["--format", "tsv"]),
+            Ok(Command::Browse(BrowseFlags {
+                output: Some(ListingFormat::Tsv),
+                ..BrowseFlags::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["list", "--format", "csv", "--tag", "music"]),
+            Ok(Command::Browse(BrowseFlags {
+                output: Some(ListingFormat::Csv),
+                tag: Some("music".to_string()),
+                ..BrowseFlags::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["--format", "xml"]),
+            Err(ArgsError::InvalidListingFormat(Some("xml".to_string())))
+        );
+        assert_eq!(
+            parse(&["list", "--format"]),
+            Err(ArgsError::InvalidListingFormat(None))
+        );
+        assert_eq!(
+            parse(&["next", "--format", "tsv"]),
+            Err(ArgsError::UnknownFlag("--format".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_flag() {
+        assert!(matches!(
+            parse(&["--verbose"]),
+            Err(ArgsError::UnknownFlag(_))
+        ));
+    }
+
+    #[test]
+    fn undo_defaults_to_one() {
+        assert_eq!(parse(&["undo"]), Ok(Command::Undo(NonZeroUsize::MIN)));
+    }
+
+    #[test]
+    fn undo_accepts_count() {
+        assert_eq!(
+            parse(&["undo", "3"]),
+            Ok(Command::Undo(NonZeroUsize::new(3).unwrap()))
+        );
+    }
+
+    #[test]
+    fn undo_rejects_zero_and_garbage() {
+        assert!(matches!(
+            parse(&["undo", "0"]),
+            Err(ArgsError::InvalidCount(_))
+        ));
+        assert!(matches!(
+            parse(&["undo", "x"]),
+            Err(ArgsError::InvalidCount(_))
+        ));
+    }
+
+    #[test]
+    fn enrich_flag_browses_with_enrichment() {
+        assert_eq!(
+            parse(&["--enrich"]),
+            Ok(Command::Browse(BrowseFlags {
+                enrich: true,
+                ..BrowseFlags::default()
+            }))
+        );
+    }
+
+    #[test]
+    fn qr_flag_combines_with_others() {
+        assert_eq!(
+            parse(&["--qr", "--force"]),
+            Ok(Command::Browse(BrowseFlags {
+                qr: true,
+                force: true,
+                ..BrowseFlags::default()
+            }))
+        );
+    }
+
+    #[test]
+    fn group_by_takes_channel() {
+        assert_eq!(
+            parse(&["--group-by", "channel"]),
+            Ok(Command::Browse(BrowseFlags {
+                group_by_channel: true,
+                ..BrowseFlags::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["--group-by", "day"]),
+            Err(ArgsError::InvalidGroupBy(Some("day".to_string())))
+        );
+        assert_eq!(parse(&["--group-by"]), Err(ArgsError::InvalidGroupBy(None)));
+    }
+
+    #[test]
+    fn cast_takes_device_name() {
+        assert_eq!(
+            parse(&["--cast", "Living Room"]),
+            Ok(Command::Browse(BrowseFlags {
+                cast: Some("Living Room".to_string()),
+                ..BrowseFlags::default()
+            }))
+        );
+        assert!(matches!(
+            parse(&["next", "--cast", "tv"]),
+            Ok(Command::Play(PlayFlags {
+                browse: BrowseFlags { cast: Some(_), .. },
+                ..
+            }))
+        ));
+        assert_eq!(parse(&["--cast"]), Err(ArgsError::MissingCastDevice));
+    }
+
+    #[test]
+    fn list_takes_a_tag() {
+        assert_eq!(
+            parse(&["list", "--tag", "music"]),
+            Ok(Command::Browse(BrowseFlags {
+                tag: Some("music".to_string()),
+                ..BrowseFlags::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["list"]),
+            Ok(Command::Browse(BrowseFlags::default()))
+        );
+        assert_eq!(parse(&["--tag"]), Err(ArgsError::MissingTag));
+    }
+
+    #[test]
+    fn info_takes_video_id() {
+        assert_eq!(
+            parse(&["info", "dQw4w9WgXcQ"]),
+            Ok(Command::Info(VideoId::parse("dQw4w9WgXcQ").unwrap()))
+        );
+        assert_eq!(parse(&["info"]), Err(ArgsError::MissingVideoId));
+    }
+
+    #[test]
+    fn open_takes_listing_number() {
+        assert_eq!(
+            parse(&["open", "3"]),
+            Ok(Command::Open(VideoNumber::parse(3).unwrap()))
+        );
+        assert_eq!(
+            parse(&["open", "0"]),
+            Err(ArgsError::InvalidVideoNumber(Some("0".to_string())))
+        );
+        assert_eq!(parse(&["open"]), Err(ArgsError::InvalidVideoNumber(None)));
+    }
+
+    #[test]
+    fn parses_queue_subcommands() {
+        let number = |n| VideoNumber::parse(n).unwrap();
+
+        assert_eq!(parse(&["queue"]), Ok(Command::Queue(QueueCommand::List)));
+        assert_eq!(
+            parse(&["queue", "play"]),
+            Ok(Command::Queue(QueueCommand::Play))
+        );
+        assert_eq!(
+            parse(&["queue", "rm", "2"]),
+            Ok(Command::Queue(QueueCommand::Remove(number(2))))
+        );
+        assert_eq!(
+            parse(&["queue", "mv", "3", "1"]),
+            Ok(Command::Queue(QueueCommand::Move(number(3), number(1))))
+        );
+        assert_eq!(
+            parse(&["queue", "mv", "3"]),
+            Err(ArgsError::InvalidVideoNumber(None))
+        );
+        assert_eq!(
+            parse(&["queue", "shuffle"]),
+            Err(ArgsError::UnknownCommand("queue shuffle".to_string()))
+        );
+    }
+
+    #[test]
+    fn play_all_takes_channel_and_browse_flags() {
+        assert_eq!(
+            parse(&["play", "--all", "--channel", "Fireship", "--force"]),
+            Ok(Command::Play(PlayFlags {
+                selection: PlaySelection::All,
+                channel: Some("Fireship".to_string()),
+                browse: BrowseFlags {
+                    force: true,
+                    ..BrowseFlags::default()
+                },
+            }))
+        );
+        assert_eq!(parse(&["play"]), Err(ArgsError::MissingPlaySelection));
+        assert!(matches!(
+            parse(&["play", "--random"]),
+            Ok(Command::Play(PlayFlags {
+                selection: PlaySelection::Random,
+                channel: None,
+                ..
+            }))
+        ));
+        assert_eq!(
+            parse(&["play", "--all", "--channel"]),
+            Err(ArgsError::MissingChannel)
+        );
+    }
+
+    #[test]
+    fn next_plays_latest() {
+        assert_eq!(
+            parse(&["next", "--channel", "Fireship"]),
+            Ok(Command::Play(PlayFlags {
+                selection: PlaySelection::Latest,
+                channel: Some("Fireship".to_string()),
+                browse: BrowseFlags::default(),
+            }))
+        );
+        assert_eq!(parse(&["next"]), parse(&["play", "--latest"]));
+    }
+
+    #[test]
+    fn stop_takes_optional_job_number() {
+        assert_eq!(parse(&["jobs"]), Ok(Command::Jobs));
+        assert_eq!(parse(&["notify"]), Ok(Command::Notify));
+        assert_eq!(parse(&["digest"]), Ok(Command::Digest));
+        assert_eq!(parse(&["stop"]), Ok(Command::Stop(None)));
+        assert_eq!(
+            parse(&["stop", "2"]),
+            Ok(Command::Stop(Some(VideoNumber::parse(2).unwrap())))
+        );
+        assert_eq!(
+            parse(&["stop", "x"]),
+            Err(ArgsError::InvalidVideoNumber(Some("x".to_string())))
+        );
+    }
+
+    #[test]
+    fn config_path_is_taken_anywhere() {
+        let invocation = |args: &[&str]| parse_args(args.iter().map(|a| a.to_string()));
+
+        assert_eq!(
+            invocation(&["--config", "test.toml", "undo"]),
+            Ok(Invocation {
+                config: Some(PathBuf::from("test.toml")),
+                strict_config: false,
+                quiet: false,
+                wait: false,
+                log_level: LogLevel::Warn,
+                log_format: LogFormat::Text,
+                debug_http: false,
+                command: Command::Undo(NonZeroUsize::MIN),
+            })
+        );
+        assert_eq!(
+            invocation(&["--force", "--config", "test.toml"]).map(|i| i.config),
+            Ok(Some(PathBuf::from("test.toml")))
+        );
+        assert_eq!(parse(&[]), invocation(&[]).map(|i| i.command));
+        assert_eq!(invocation(&["--config"]), Err(ArgsError::MissingConfigPath));
+    }
+
+    #[test]
+    fn parses_config_subcommands() {
+        assert_eq!(parse(&["config"]), Ok(Command::Config(ConfigCommand::Path)));
+        assert_eq!(
+            parse(&["config", "edit"]),
+            Ok(Command::Config(ConfigCommand::Edit))
+        );
+        assert_eq!(
+            parse(&["config", "check"]),
+            Ok(Command::Config(ConfigCommand::Check))
+        );
+        assert!(matches!(
+            parse(&["config", "fix"]),
+            Err(ArgsError::UnknownCommand(_))
+        ));
+    }
+
+    #[test]
+    fn parses_snooze_commands() {
+        assert_eq!(parse(&["snooze"]), Ok(Command::Snooze(SnoozeCommand::List)));
+        assert_eq!(
+            parse(&["snooze", "Spoilers", "30d"]),
+            Ok(Command::Snooze(SnoozeCommand::Snooze(
+                "Spoilers".to_string(),
+                SnoozeLength::Days(30)
+            )))
+        );
+        assert_eq!(
+            parse(&["snooze", "Spoilers", "off"]),
+            Ok(Command::Snooze(SnoozeCommand::Wake("Spoilers".to_string())))
+        );
+        assert_eq!(
+            parse(&["snooze", "Spoilers"]),
+            Err(ArgsError::MissingSnoozeLength)
+        );
+        assert!(matches!(
+            parse(&["snooze", "Spoilers", "soon"]),
+            Err(ArgsError::InvalidSnoozeLength(_))
+        ));
+    }
+
+    #[test]
+    fn parses_status_flags() {
+        assert_eq!(
+            parse(&["status"]),
+            Ok(Command::Status(StatusFlags::default()))
+        );
+        assert_eq!(
+            parse(&["status", "--format", "waybar", "--by-tag", "--tag", "music"]),
+            Ok(Command::Status(StatusFlags {
+                format: StatusFormat::Waybar,
+                by_tag: true,
+                tag: Some("music".to_string()),
+            }))
+        );
+        assert_eq!(
+            parse(&["status", "--json"]),
+            Ok(Command::Status(StatusFlags {
+                format: StatusFormat::Json,
+                ..StatusFlags::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["status", "--format", "i3"]),
+            Err(ArgsError::InvalidStatusFormat(Some("i3".to_string())))
+        );
+        assert_eq!(
+            parse(&["status", "--format"]),
+            Err(ArgsError::InvalidStatusFormat(None))
+        );
+    }
+
+    #[test]
+    fn parses_channels_check() {
+        assert_eq!(
+            parse(&["channels", "check"]),
+            Ok(Command::Channels {
+                command: ChannelsCommand::Check,
+                json: false
+            })
+        );
+        assert_eq!(
+            parse(&["channels", "stats", "--json"]),
+            Ok(Command::Channels {
+                command: ChannelsCommand::Stats,
+                json: true
+            })
+        );
+        assert!(matches!(
+            parse(&["channels"]),
+            Err(ArgsError::UnknownCommand(_))
+        ));
+    }
+
+    #[test]
+    fn parses_export() {
+        assert_eq!(
+            parse(&["export", "feed"]),
+            Ok(Command::Export {
+                command: ExportCommand::Feed,
+                tag: None
+            })
+        );
+        assert_eq!(
+            parse(&["export", "feed", "--tag", "music"]),
+            Ok(Command::Export {
+                command: ExportCommand::Feed,
+                tag: Some("music".to_string())
+            })
+        );
+        assert_eq!(
+            parse(&["export", "calendar"]),
+            Ok(Command::Export {
+                command: ExportCommand::Calendar,
+                tag: None
+            })
+        );
+        assert_eq!(
+            parse(&[
+                "export",
+                "markdown",
+                "--tag",
+                "music",
+                "--group-by",
+                "channel"
+            ]),
+            Ok(Command::Export {
+                command: ExportCommand::Markdown(MarkdownGrouping::Channel),
+                tag: Some("music".to_string())
+            })
+        );
+        assert_eq!(
+            parse(&["export", "markdown"]),
+            Ok(Command::Export {
+                command: ExportCommand::Markdown(MarkdownGrouping::Day),
+                tag: None
+            })
+        );
+        assert_eq!(
+            parse(&["export", "markdown", "--group-by", "tag"]),
+            Err(ArgsError::InvalidMarkdownGroupBy(Some("tag".to_string())))
+        );
+        assert_eq!(
+            parse(&["export", "html", "--out", "index.html"]),
+            Ok(Command::Export {
+                command: ExportCommand::Html {
+                    out: Some(PathBuf::from("index.html"))
+                },
+                tag: None
+            })
+        );
+        assert_eq!(
+            parse(&["export", "html", "--out"]),
+            Err(ArgsError::MissingOutPath)
+        );
+        assert_eq!(
+            parse(&["export", "feed", "--group-by", "day"]),
+            Err(ArgsError::UnexpectedArgument("--group-by".to_string()))
+        );
+        assert!(matches!(
+            parse(&["export", "opml"]),
+            Err(ArgsError::UnknownCommand(_))
+        ));
+    }
+
+    #[test]
+    fn parses_add_from_video() {
+        let video = VideoId::parse("dQw4w9WgXcQ").unwrap();
+
+        assert_eq!(
+            parse(&["add", "--from-video", "https://youtu.be/dQw4w9WgXcQ"]),
+            Ok(Command::Add {
+                video: video.clone(),
+                mark_watched: false,
+            })
+        );
+        assert_eq!(
+            parse(&[
+                "add",
+                "--mark-watched",
+                "--from-video",
+                "youtube.com/watch?v=dQw4w9WgXcQ"
+            ]),
+            Ok(Command::Add {
+                video,
+                mark_watched: true,
+            })
+        );
+        assert_eq!(parse(&["add"]), Err(ArgsError::MissingVideoUrl));
+        assert!(matches!(
+            parse(&["add", "--from-video", "https://example.com"]),
+            Err(ArgsError::InvalidVideoUrl(_))
+        ));
+    }
+
+    #[test]
+    fn parses_download() {
+        let video = VideoId::parse("dQw4w9WgXcQ").unwrap();
+
+        assert_eq!(
+            parse(&["download", "3", "--mark-watched"]),
+            Ok(Command::Download {
+                target: DownloadTarget::Listed(VideoNumber::parse(3).unwrap()),
+                mark_watched: true,
+                subtitles: None,
+                thumbnail: false,
+            })
+        );
+        assert_eq!(
+            parse(&["download", "3", "--subs", "en,de", "--thumbnail"]),
+            Ok(Command::Download {
+                target: DownloadTarget::Listed(VideoNumber::parse(3).unwrap()),
+                mark_watched: false,
+                subtitles: Some(Subtitles::parse("en,de").unwrap()),
+                thumbnail: true,
+            })
+        );
+        assert_eq!(
+            parse(&["download", "3", "--subs"]),
+            Err(ArgsError::InvalidSubtitles(None))
+        );
+        assert!(matches!(
+            parse(&["download", "3", "--subs", "en;de"]),
+            Err(ArgsError::InvalidSubtitles(Some(_)))
+        ));
+        for raw in ["dQw4w9WgXcQ", "https://youtu.be/dQw4w9WgXcQ"] {
+            assert_eq!(
+                parse(&["download", raw]),
+                Ok(Command::Download {
+                    target: DownloadTarget::Video(video.clone()),
+                    mark_watched: false,
+                    subtitles: None,
+                    thumbnail: false,
+                })
+            );
+        }
+        assert_eq!(
+            parse(&["download"]),
+            Err(ArgsError::InvalidDownloadTarget(None))
+        );
+        assert_eq!(
+            parse(&["download", "0"]),
+            Err(ArgsError::InvalidDownloadTarget(Some("0".to_string())))
+        );
+        assert!(matches!(
+            parse(&["download", "https://example.com/x"]),
+            Err(ArgsError::InvalidDownloadTarget(_))
+        ));
+        assert!(matches!(
+            parse(&["download", "dQw4w9WgXc"]),
+            Err(ArgsError::InvalidDownloadTarget(_))
+        ));
+    }
+
+    #[test]
+    fn parses_downloads() {
+        assert_eq!(
+            parse(&["downloads"]),
+            Ok(Command::Downloads(DownloadsCommand::List))
+        );
+        assert_eq!(
+            parse(&["downloads", "resume"]),
+            Ok(Command::Downloads(DownloadsCommand::Resume))
+        );
+        assert_eq!(
+            parse(&["downloads", "prune"]),
+            Ok(Command::Downloads(DownloadsCommand::Prune(None)))
+        );
+        assert_eq!(
+            parse(&["downloads", "prune", "30"]),
+            Ok(Command::Downloads(DownloadsCommand::Prune(
+                NonZeroUsize::new(30)
+            )))
+        );
+        assert_eq!(
+            parse(&["downloads", "prune", "0"]),
+            Err(ArgsError::InvalidCount("0".to_string()))
+        );
+        assert_eq!(
+            parse(&["downloads", "clear"]),
+            Err(ArgsError::UnknownCommand("downloads clear".to_string()))
+        );
+    }
+
+    #[test]
+    fn strict_config_is_accepted_anywhere() {
+        assert_eq!(
+            parse_args(["jobs", "--strict-config"].map(String::from)),
+            Ok(Invocation {
+                config: None,
+                strict_config: true,
+                quiet: false,
+                wait: false,
+                log_level: LogLevel::Warn,
+                log_format: LogFormat::Text,
+                debug_http: false,
+                command: Command::Jobs,
+            })
+        );
+    }
+
+    #[test]
+    fn quiet_is_accepted_anywhere() {
+        let quiet = |args: &[&str]| parse_args(args.iter().map(|a| a.to_string())).map(|i| i.quiet);
+
+        assert_eq!(quiet(&["--quiet"]), Ok(true));
+        assert_eq!(quiet(&["play", "--all", "--quiet"]), Ok(true));
+        assert_eq!(quiet(&["list"]), Ok(false));
+    }
+
+    #[test]
+    fn wait_is_accepted_anywhere() {
+        let wait = |args: &[&str]| parse_args(args.iter().map(|a| a.to_string())).map(|i| i.wait);
+
+        assert_eq!(wait(&["--wait", "notify"]), Ok(true));
+        assert_eq!(wait(&["downloads", "resume", "--wait"]), Ok(true));
+        assert_eq!(wait(&["notify"]), Ok(false));
+    }
+
+    #[test]
+    fn debug_http_is_accepted_anywhere() {
+        let debug_http =
+            |args: &[&str]| parse_args(args.iter().map(|a| a.to_string())).map(|i| i.debug_http);
+
+        assert_eq!(debug_http(&["--debug-http", "--force"]), Ok(true));
+        assert_eq!(debug_http(&["channels", "check", "--debug-http"]), Ok(true));
+        assert_eq!(debug_http(&["-vv"]), Ok(false));
+    }
+
+    #[test]
+    fn log_flags_are_accepted_anywhere() {
+        let log = |args: &[&str]| {
+            parse_args(args.iter().map(|a| a.to_string())).map(|i| (i.log_level, i.log_format))
+        };
+
+        assert_eq!(log(&["list"]), Ok((LogLevel::Warn, LogFormat::Text)));
+        assert_eq!(
+            log(&["-v", "notify"]),
+            Ok((LogLevel::Info, LogFormat::Text))
+        );
+        assert_eq!(
+            log(&["notify", "-vv"]),
+            Ok((LogLevel::Debug, LogFormat::Text))
+        );
+        assert_eq!(
+            log(&["--log-level", "error", "-v", "--log-format", "json"]),
+            Ok((LogLevel::Error, LogFormat::Json))
+        );
+        assert_eq!(
+            log(&["--log-level", "loud"]),
+            Err(ArgsError::InvalidLogLevel(Some("loud".to_string())))
+        );
+        assert_eq!(
+            log(&["--log-format"]),
+            Err(ArgsError::InvalidLogFormat(None))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_command() {
+        assert!(matches!(
+            parse(&["frobnicate"]),
+            Err(ArgsError::UnknownCommand(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_trailing_arguments() {
+        assert!(matches!(
+            parse(&["undo", "1", "2"]),
+            Err(ArgsError::UnexpectedArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,135 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use quick_xml::escape::escape;
+use std::collections::HashMap;
+
+use crate::domain::channel::ChannelId;
+use crate::domain::video::{Video, VideoKind};
+
+/// The unwatched videos as one Atom feed, newest first. Entry IDs are the
+/// ones YouTube's own feeds use, so a reader also subscribed to a channel
+/// directly sees each video once. Scheduled premieres and streams are left
+/// out: there is nothing to watch yet.
+pub fn videos_atom(
+    videos: &[Video],
+    aliases: &HashMap<ChannelId, String>,
+    now: DateTime<Utc>,
+) -> String {
+    let videos: Vec<&Video> = videos
+        .iter()
+        .filter(|video| video.kind != VideoKind::Upcoming)
+        .collect();
+    let updated = videos
+        .iter()
+        .map(|video| video.published)
+        .max()
+        .unwrap_or(now);
+
+    let mut lines = vec![
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>".to_string(),
+        "<feed xmlns=\"http://www.w3.org/2005/Atom\">".to_string(),
+        "  <title>blepo: unwatched videos</title>".to_string(),
+        "  <id>urn:blepo:unwatched</id>".to_string(),
+        format!("  <updated>{}</updated>", timestamp(updated)),
+    ];
+    for video in videos {
+        let channel = aliases
+            .get(&video.channel_id)
+            .unwrap_or(&video.channel_name);
+        lines.push("  <entry>".to_string());
+        lines.push(format!(
+            "    <id>yt:video:{}</id>",
+            escape(&video.id.to_string())
+        ));
+        lines.push(format!("    <title>{}</title>", escape(&video.title)));
+        lines.push(format!(
+            "    <link rel=\"alternate\" href=\"{}\"/>",
+            escape(&video.url)
+        ));
+        lines.push(format!(
+            "    <author><name>{}</name><uri>https://www.youtube.com/channel/{}</uri></author>",
+            escape(channel),
+            escape(&video.channel_id.to_string())
+        ));
+        lines.push(format!(
+            "    <published>{}</published>",
+            timestamp(video.published)
+        ));
+        lines.push(format!(
+            "    <updated>{}</updated>",
+            timestamp(video.published)
+        ));
+        for tag in &video.tags {
+            lines.push(format!(
+                "    <category term=\"{}\"/>",
+                escape(&tag.to_string())
+            ));
+        }
+        lines.push("  </entry>".to_string());
+    }
+    lines.push("</feed>".to_string());
+    lines.join("\n")
+}
+
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::channel::ChannelTag;
+    use crate::domain::video::VideoId;
+    use chrono::TimeZone;
+
+    fn video(id: &str, kind: VideoKind) -> Video {
+        Video {
+            id: VideoId::parse(id).unwrap(),
+            title: "Fish & <Chips>".to_string(),
+            url: format!("https://www.youtube.com/watch?v={id}"),
+            published: Utc.with_ymd_and_hms(2024, 1, 20, 15, 0, 0).unwrap(),
+            channel_name: "Channel".to_string(),
+            channel_id: ChannelId::parse("UC123").unwrap(),
+            kind,
+            duration: None,
+            tags: vec![ChannelTag::parse("food").unwrap()],
+        }
+    }
+
+    #[test]
+    fn writes_an_escaped_entry_per_watchable_video() {
+        let videos = [
+            video("v1", VideoKind::Upload),
+            video("v2", VideoKind::Upcoming),
+        ];
+        let aliases = HashMap::from([(videos[0].channel_id.clone(), "Ch".to_string())]);
+
+        let feed = videos_atom(&videos, &aliases, Utc::now());
+
+        assert_eq!(
+            feed,
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+             <feed xmlns=\"http://www.w3.org/2005/Atom\">\n  \
+             <title>blepo: unwatched videos</title>\n  \
+             <id>urn:blepo:unwatched</id>\n  \
+             <updated>2024-01-20T15:00:00Z</updated>\n  \
+             <entry>\n    \
+             <id>yt:video:v1</id>\n    \
+             <title>Fish &amp; &lt;Chips&gt;</title>\n    \
+             <link rel=\"alternate\" href=\"https://www.youtube.com/watch?v=v1\"/>\n    \
+             <author><name>Ch</name><uri>https://www.youtube.com/channel/UC123</uri></author>\n    \
+             <published>2024-01-20T15:00:00Z</published>\n    \
+             <updated>2024-01-20T15:00:00Z</updated>\n    \
+             <category term=\"food\"/>\n  \
+             </entry>\n\
+             </feed>"
+        );
+    }
+
+    #[test]
+    fn an_empty_feed_is_updated_now() {
+        let now = Utc.with_ymd_and_hms(2024, 2, 1, 8, 0, 0).unwrap();
+
+        assert!(videos_atom(&[], &HashMap::new(), now)
+            .contains("<updated>2024-02-01T08:00:00Z</updated>"));
+    }
+}
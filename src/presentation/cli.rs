@@ -1,45 +1,1373 @@
-use std::io::{self, Write};
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::io::{self, IsTerminal, Write};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
 
-use crate::application::use_cases;
-use crate::domain::video::VideoNumber;
+use super::args::{
+    BrowseFlags, ChannelsCommand, Command, ConfigCommand, DownloadTarget, DownloadsCommand,
+    ExportCommand, Invocation, ListingFormat, PlayFlags, PlaySelection, QueueCommand,
+    SnoozeCommand, StatusFormat,
+};
+use super::atom::videos_atom;
+use super::delimited::{videos_csv, videos_tsv};
+use super::exit::{fetch_outcome, Exit};
+use super::graphics::GraphicsProtocol;
+use super::html::videos_html;
+use super::ical::upcoming_ical;
+use super::json::{channel_failure_json, channel_problems_json, channel_stats_json, videos_json};
+use super::layout::{display_width, pad, terminal_width, truncate};
+use super::markdown::videos_markdown;
+use super::qr::render_qr;
+use crate::application::parallel::map_bounded;
+use crate::application::ports::{
+    ChannelNameStore, ChannelResolver, DownloadStore, FeedFetcher, FetchError, PlayError,
+    ProcessControl, ShortsChecker, VideoPlayer,
+};
+use crate::application::use_cases::{
+    self, DownloadRun, FetchReport, FetchTiming, FetchedVideos, PROGRESS_LOG_TARGET,
+};
+use crate::domain::channel::{match_channels, Channel, ChannelId};
+use crate::domain::display::{relative_age, Color, DateFormat, DateStyle, ReadyAlert, Theme};
+use crate::domain::download::{Download, DownloadState};
+use crate::domain::metadata::VideoMetadata;
+use crate::domain::playback::{ChannelPlayer, PlaybackSpeed, Subtitles};
+use crate::domain::video::{
+    catch_up_order, channel_grouped_order, newest_playable, random_pick, LiveHandling,
+    PlaybackMode, Video, VideoId, VideoKind, VideoNumber, WatchProgress, WatchThreshold,
+};
 use crate::infrastructure::{
-    config::load_config, fallback_fetcher::FallbackFetcher, json_store::JsonVideoStore,
-    mpv_player::MpvPlayer, rss_fetcher::RssFeedFetcher, shorts_checker::HttpShortsChecker,
+    browser::SystemBrowser,
+    catt_player::CattPlayer,
+    channel_names::JsonChannelNameStore,
+    channel_resolver::{CachedChannelResolver, HttpChannelResolver, NoChannelLookup},
+    clipboard::SystemClipboard,
+    config::{check_config, config_file, load_config, AppConfig, ConfigError},
+    config_channels::ConfigChannelList,
+    cookies::Cookies,
+    desktop_notifier::DesktopNotifier,
+    digest_store::JsonDigestStore,
+    download_store::JsonDownloadStore,
+    editor::edit_file,
+    fallback_fetcher::FallbackFetcher,
+    feed_cache::FeedCache,
+    fixtures::Fixtures,
+    http::{build_async_client, build_client, ProxyUrl},
+    instance_lock::{InstanceLock, LockError},
+    job_store::JsonJobStore,
+    json_store::JsonVideoStore,
+    launcher_player::LauncherPlayer,
+    listing_store::JsonListingStore,
+    local_player::LocalFirstPlayer,
+    metadata_store::JsonMetadataStore,
+    mpv_player::MpvPlayer,
+    notified_store::JsonNotifiedStore,
+    processes::SystemProcesses,
+    queue_store::JsonQueueStore,
+    rate_limit::RateLimiter,
+    retry::RetryingFetcher,
+    rss_fetcher::RssFeedFetcher,
+    shorts_checker::{CachedShortsChecker, HttpShortsChecker, NoShortsLookup},
+    snooze_store::JsonSnoozeStore,
+    sponsorblock::{install_skip_script, SponsorBlockClient},
+    streams_fetcher::StreamsFetcher,
+    thumbnails::ThumbnailCache,
+    ttl_fetcher::{CachedUploads, TtlFetcher},
+    webhook::WebhookNotifier,
+    ytdlp_downloader::YtDlpDownloader,
     ytdlp_fetcher::YtDlpFetcher,
+    ytdlp_metadata::YtDlpMetadataFetcher,
 };
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let config = load_config()?;
+pub fn run(invocation: Invocation) -> Result<(), Box<dyn std::error::Error>> {
+    // These work on a config that doesn't load.
+    let command = match invocation.command {
+        Command::Config(command) => return config(command, invocation.config),
+        command => command,
+    };
+    let config_path = invocation.config.clone();
+    let quiet = invocation.quiet;
+    let config = load_config(invocation.config, invocation.strict_config)?;
+    let _lock = lock_data_dir(&config.data_dir, &command, invocation.wait, quiet)?;
     let store = JsonVideoStore::new(&config.data_dir)?;
-    let fetcher = FallbackFetcher::new(RssFeedFetcher::new(), YtDlpFetcher::new());
-    let shorts_checker = HttpShortsChecker::new();
-
-    let videos = use_cases::fetch_videos(
-        &config.channels,
-        &fetcher,
-        &store,
-        &shorts_checker,
-        config.fetch_window_days,
+
+    match command {
+        Command::Browse(flags) => browse(config, &store, BrowseFlags { quiet, ..flags }),
+        Command::Undo(count) => Ok(use_cases::undo(&store, count)?),
+        Command::Info(video_id) => {
+            let metadata_store = JsonMetadataStore::new(&config.data_dir)?;
+            match use_cases::video_info(&video_id, &metadata_store)? {
+                Some(metadata) => println!("{}", format_info(&metadata)),
+                None => println!("No details for {video_id}; run `blepo --enrich` first."),
+            }
+            Ok(())
+        }
+        Command::Open(number) => {
+            let listing = JsonListingStore::new(&config.data_dir)?;
+            match use_cases::listed_video(number, &listing)? {
+                Some(video) => use_cases::open_in_browser(
+                    &video,
+                    &store,
+                    &SystemBrowser,
+                    config.open_marks_watched,
+                )?,
+                None => println!("No video #{number} in the last listing; run `blepo` first."),
+            }
+            Ok(())
+        }
+        Command::Queue(command) => queue(command, &config, &store),
+        Command::Play(mut flags) => {
+            flags.browse.quiet = quiet;
+            play(config, &store, flags)
+        }
+        Command::Jobs => {
+            let jobs = JsonJobStore::new(&config.data_dir)?;
+            let running = use_cases::running_jobs(&jobs, &SystemProcesses)?;
+            if running.is_empty() {
+                println!("Nothing is playing.");
+            }
+            let now = Utc::now();
+            for (n, job) in running.iter().enumerate() {
+                println!(
+                    "{:>3}. {} [{}], started {} (pid {})",
+                    n + 1,
+                    job.video.title,
+                    job.video.channel_name,
+                    relative_age(job.started, now),
+                    job.pid
+                );
+            }
+            Ok(())
+        }
+        Command::Stop(number) => {
+            let jobs = JsonJobStore::new(&config.data_dir)?;
+            Ok(use_cases::stop_jobs(number, &jobs, &SystemProcesses)?)
+        }
+        Command::Notify => notify(config, &store, quiet),
+        Command::Digest => digest(config, &store, quiet),
+        Command::Status(flags) => {
+            let client = build_client(config.proxy.as_ref());
+            let browse = BrowseFlags {
+                tag: flags.tag,
+                // Only for warnings: JSON status output reports them as JSON.
+                output: (flags.format == StatusFormat::Json).then_some(ListingFormat::Json),
+                ..BrowseFlags::default()
+            };
+            let Unwatched {
+                videos, failures, ..
+            } = fetch_unwatched(config, &store, &client, &browse, None, true)?;
+            println!("{}", format_status(&videos, flags.format, flags.by_tag));
+            failures.print();
+            Ok(())
+        }
+        Command::Export { command, tag } => {
+            let mut config = config;
+            if command == ExportCommand::Calendar {
+                // live_handling = "hide" is about listings; the calendar is all upcoming videos.
+                config.live_handling = LiveHandling::Section;
+            }
+            let client = build_client(config.proxy.as_ref());
+            let thumbnails = ThumbnailCache::new(client.clone(), &config.data_dir)?;
+            let fetch_concurrency = config.fetch_concurrency;
+            let browse = BrowseFlags {
+                tag,
+                quiet,
+                ..BrowseFlags::default()
+            };
+            let Unwatched {
+                videos,
+                aliases,
+                failures,
+                ..
+            } = fetch_unwatched(config, &store, &client, &browse, None, false)?;
+            match command {
+                ExportCommand::Feed => println!("{}", videos_atom(&videos, &aliases, Utc::now())),
+                ExportCommand::Calendar => {
+                    print!("{}", upcoming_ical(&videos, &aliases, Utc::now()))
+                }
+                ExportCommand::Markdown(grouping) => println!(
+                    "{}",
+                    videos_markdown(&videos, &aliases, grouping, local_offset)
+                ),
+                ExportCommand::Html { out } => {
+                    let images = map_bounded(&videos, fetch_concurrency, |video| {
+                        thumbnails.load(&video.id)
+                    });
+                    let page = videos_html(&videos, &images, &aliases, Utc::now(), local_offset);
+                    match out {
+                        Some(path) => std::fs::write(&path, page + "\n")
+                            .map_err(|e| format!("cannot write {}: {e}", path.display()))?,
+                        None => println!("{page}"),
+                    }
+                }
+            }
+            failures.outcome(false)
+        }
+        Command::Snooze(command) => snooze(command, config),
+        Command::Channels {
+            command: ChannelsCommand::Check,
+            json,
+        } => check_channels(config, json),
+        Command::Channels {
+            command: ChannelsCommand::Stats,
+            json,
+        } => channel_stats(config, &store, json),
+        Command::Download {
+            target,
+            mark_watched,
+            subtitles,
+            thumbnail,
+        } => download(
+            &config,
+            &store,
+            target,
+            mark_watched,
+            subtitles,
+            thumbnail,
+            quiet,
+        ),
+        Command::Downloads(command) => downloads(&config, &store, command, quiet),
+        Command::Add {
+            video,
+            mark_watched,
+        } => {
+            let channels = ConfigChannelList::new(config_file(config_path)?, &config.channels);
+            let lookup = YtDlpMetadataFetcher::new()
+                .with_proxy(config.proxy.clone())
+                .with_cookies(config.cookies.clone());
+            let (uploader, added) = use_cases::add_channel_from_video(
+                &video,
+                &lookup,
+                &channels,
+                &store,
+                mark_watched,
+            )?;
+            if added {
+                println!("Added {} ({}) to the config.", uploader.name, uploader.id);
+            } else {
+                println!(
+                    "{} ({}) is already in the config.",
+                    uploader.name, uploader.id
+                );
+            }
+            if mark_watched {
+                println!("Marked as watched.");
+            }
+            Ok(())
+        }
+        Command::Config(_) => unreachable!("handled before loading the config"),
+    }
+}
+
+/// Keeps other blepo processes out of the data dir for the run, except for
+/// `status`, `jobs` and `info`, which only read, so status bars keep working
+/// during a long interactive session. With `wait`, waits for the running
+/// instance to finish instead of failing.
+fn lock_data_dir(
+    data_dir: &std::path::Path,
+    command: &Command,
+    wait: bool,
+    quiet: bool,
+) -> Result<Option<InstanceLock>, LockError> {
+    if matches!(
+        command,
+        Command::Status(_) | Command::Jobs | Command::Info(_)
+    ) {
+        return Ok(None);
+    }
+    match InstanceLock::try_acquire(data_dir) {
+        Err(LockError::Busy(pid)) if wait => {
+            if !quiet {
+                let pid = pid.map(|pid| format!(" (pid {pid})")).unwrap_or_default();
+                info!(target: PROGRESS_LOG_TARGET, "Waiting for another blepo instance{pid} to finish...");
+            }
+            InstanceLock::wait(data_dir).map(Some)
+        }
+        result => result.map(Some),
+    }
+}
+
+fn config(command: ConfigCommand, path: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    let path = config_file(path)?;
+    match command {
+        ConfigCommand::Path => println!("{}", path.display()),
+        ConfigCommand::Edit => {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            edit_file(&path)?;
+            if path.exists() {
+                print_config_problems(&path, &check_config(Some(path.clone()))?);
+            }
+        }
+        ConfigCommand::Check => {
+            let problems = check_config(Some(path.clone()))?;
+            print_config_problems(&path, &problems);
+            if !problems.is_empty() {
+                return Err(
+                    Exit::ConfigProblems(problems.len(), path.display().to_string()).into(),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_config_problems(path: &std::path::Path, problems: &[ConfigError]) {
+    if problems.is_empty() {
+        println!("{} is valid.", path.display());
+    }
+    for problem in problems {
+        for line in problem.to_string().lines() {
+            println!("- {line}");
+        }
+    }
+}
+
+fn play(
+    config: AppConfig,
+    store: &JsonVideoStore,
+    flags: PlayFlags,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let client = build_client(config.proxy.as_ref());
+    let mut playback = Playback::new(&config, &client, flags.browse.cast.clone())?;
+    let Unwatched {
+        videos,
+        players,
+        failures,
+        ..
+    } = fetch_unwatched(
+        config,
+        store,
+        &client,
+        &flags.browse,
+        flags.channel.as_deref(),
+        false,
     )?;
+    playback.channel_players = players;
+    let picked = match flags.selection {
+        PlaySelection::All => {
+            use_cases::play_all(&videos, store, playback.player()?.as_ref())?;
+            return failures.outcome(catch_up_order(&videos).is_empty());
+        }
+        PlaySelection::Random => random_pick(&videos, random_seed()),
+        PlaySelection::Latest => newest_playable(&videos),
+    };
+    match picked {
+        Some(video) => playback.play(video, store)?,
+        None => println!("No unwatched videos."),
+    }
+    failures.outcome(picked.is_none())
+}
 
-    if videos.is_empty() {
-        println!("No unwatched videos.");
+fn queue(
+    command: QueueCommand,
+    config: &AppConfig,
+    store: &JsonVideoStore,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let queue_store = JsonQueueStore::new(&config.data_dir)?;
+    match command {
+        QueueCommand::List => {
+            let pending = use_cases::pending_queue(&queue_store, store)?;
+            if pending.is_empty() {
+                println!("The queue is empty; add videos with q<number> while browsing.");
+            }
+            for (n, video) in pending.iter().enumerate() {
+                println!("{:>3}. {} [{}]", n + 1, video.title, video.channel_name);
+            }
+        }
+        QueueCommand::Play => {
+            let player =
+                Playback::new(config, &build_client(config.proxy.as_ref()), None)?.player()?;
+            use_cases::play_queue(&queue_store, store, player.as_ref())?;
+        }
+        QueueCommand::Remove(number) => {
+            use_cases::remove_from_queue(number, &queue_store, store)?;
+        }
+        QueueCommand::Move(from, to) => use_cases::move_in_queue(from, to, &queue_store, store)?,
+    }
+    Ok(())
+}
+
+/// Unwatched videos, with the aliases their channels are listed under and
+/// the players they chose.
+struct Unwatched {
+    videos: Vec<Video>,
+    aliases: HashMap<ChannelId, String>,
+    players: HashMap<ChannelId, ChannelPlayer>,
+    /// Channels that failed to fetch and were left out.
+    failures: FetchFailures,
+}
+
+/// The channels a fetch left out, reported at the end of the run so the
+/// listing doesn't push them out of sight, along with `--timing`'s table.
+struct FetchFailures {
+    report: FetchReport,
+    /// As JSON lines, for `--output json`, instead of a table.
+    json: bool,
+    timing: Option<FetchTiming>,
+}
+
+impl FetchFailures {
+    /// Prints the timing table if asked for, then the report if anything
+    /// failed, on stderr.
+    fn print(&self) {
+        if let Some(timing) = &self.timing {
+            eprint!("{}", fetch_timing_table(timing));
+        }
+        if self.json {
+            for failure in &self.report.failures {
+                eprintln!("{}", channel_failure_json(failure));
+            }
+        } else if !self.report.is_empty() {
+            eprint!("{}", fetch_report_table(&self.report));
+        }
+    }
+
+    /// Prints the report, then ends the run as `fetch_outcome` says.
+    fn outcome(self, nothing_unwatched: bool) -> Result<(), Box<dyn std::error::Error>> {
+        self.print();
+        fetch_outcome(self.report.len(), nothing_unwatched)
+    }
+}
+
+/// One line per failed channel under a header: its name, what went wrong
+/// and what to do about it.
+fn fetch_report_table(report: &FetchReport) -> String {
+    let rows: Vec<[String; 3]> = report
+        .failures
+        .iter()
+        .map(|failure| {
+            [
+                failure.channel_name.clone(),
+                fetch_error_kind(&failure.error),
+                failure.suggestion().to_string(),
+            ]
+        })
+        .collect();
+    let header = ["Channel", "Error", "Suggestion"].map(str::to_string);
+    let width = |column: usize| {
+        std::iter::once(&header)
+            .chain(&rows)
+            .map(|row| display_width(&row[column]))
+            .max()
+            .unwrap_or(0)
+    };
+    let (channel_width, kind_width) = (width(0), width(1));
+    let count = report.len();
+    let channels = if count == 1 { "channel" } else { "channels" };
+    let mut table = format!("Could not fetch {count} {channels}:\n");
+    for [channel, kind, suggestion] in std::iter::once(header).chain(rows) {
+        table.push_str(&format!(
+            "  {}  {}  {suggestion}\n",
+            pad(&channel, channel_width),
+            pad(&kind, kind_width)
+        ));
+    }
+    table
+}
+
+/// One line per channel, slowest first, then the time the whole fetch and
+/// the Shorts check took.
+fn fetch_timing_table(timing: &FetchTiming) -> String {
+    let rows: Vec<[String; 3]> = timing
+        .channels
+        .iter()
+        .map(|channel| {
+            [
+                channel.channel_name.clone(),
+                seconds(channel.elapsed),
+                channel
+                    .videos
+                    .map_or_else(|| "failed".to_string(), |n| n.to_string()),
+            ]
+        })
+        .collect();
+    let header = ["Channel", "Time", "Videos"].map(str::to_string);
+    let width = |column: usize| {
+        std::iter::once(&header)
+            .chain(&rows)
+            .map(|row| display_width(&row[column]))
+            .max()
+            .unwrap_or(0)
+    };
+    let (channel_width, time_width) = (width(0), width(1));
+    let mut table = "Fetch timing, slowest first:\n".to_string();
+    for [channel, time, videos] in std::iter::once(header).chain(rows) {
+        table.push_str(&format!(
+            "  {}  {time:>time_width$}  {videos}\n",
+            pad(&channel, channel_width)
+        ));
+    }
+    let count = timing.channels.len();
+    let channels = if count == 1 { "channel" } else { "channels" };
+    table.push_str(&format!(
+        "Fetching {count} {channels} took {}; checking {} videos for Shorts took {}.\n",
+        seconds(timing.fetching),
+        timing.shorts_checked,
+        seconds(timing.shorts_checking)
+    ));
+    table
+}
+
+fn seconds(duration: std::time::Duration) -> String {
+    format!("{:.1}s", duration.as_secs_f64())
+}
+
+fn fetch_error_kind(error: &FetchError) -> String {
+    match error {
+        FetchError::Network(_) => "network".to_string(),
+        FetchError::HttpError(status) => format!("HTTP {status}"),
+        FetchError::RateLimited(_) => "rate limited".to_string(),
+        FetchError::Parse(_) => "parse".to_string(),
+        FetchError::YtDlp(_) => "yt-dlp".to_string(),
+    }
+}
+
+/// Fetches the unwatched videos of every configured channel, or only of
+/// `channel` (see `match_channels`) and of channels carrying
+/// `--tag` when given. `offline` rebuilds the list from what earlier runs
+/// fetched instead, quietly, leaving unchecked videos in as regular ones.
+/// Progress goes to stderr unless `offline` or `--quiet`; warnings always do.
+fn fetch_unwatched(
+    config: AppConfig,
+    store: &JsonVideoStore,
+    client: &reqwest::blocking::Client,
+    flags: &BrowseFlags,
+    channel: Option<&str>,
+    offline: bool,
+) -> Result<Unwatched, Box<dyn std::error::Error>> {
+    let options = use_cases::FetchOptions {
+        fetch_window: config.fetch_window,
+        fetch_concurrency: config.fetch_concurrency,
+        shorts_concurrency: config.shorts_concurrency,
+        include_shorts: config.include_shorts,
+        title_filter: config.title_filter.clone(),
+        muted_words: config.muted_words.clone(),
+        duration_range: config.duration_range,
+        live_handling: config.live_handling,
+        show_muted: flags.show_muted,
+        quiet: offline || flags.quiet,
+    };
+    let limiter = Arc::new(RateLimiter::new(config.request_interval));
+    let fetch_client = build_async_client(config.proxy.as_ref());
+    let fixtures = fixtures(flags);
+    let replaying = fixtures.as_ref().is_some_and(Fixtures::is_replay);
+    let (fetcher, shorts_checker): (Box<dyn FeedFetcher>, Box<dyn ShortsChecker>) = if offline {
+        (
+            Box::new(CachedUploads::new(&config.data_dir)),
+            Box::new(CachedShortsChecker::new(NoShortsLookup, &config.data_dir)?),
+        )
+    } else if let Some(fixtures) = fixtures.clone().filter(Fixtures::is_replay) {
+        (
+            Box::new(replay_fetcher(
+                &fetch_client,
+                Arc::clone(&limiter),
+                fixtures,
+            )),
+            Box::new(CachedShortsChecker::new(NoShortsLookup, &config.data_dir)?),
+        )
+    } else {
+        (
+            // Recording refetches, so every channel gets its files.
+            Box::new(network_fetcher(
+                &config,
+                &fetch_client,
+                Arc::clone(&limiter),
+                flags.force || fixtures.is_some(),
+                fixtures,
+            )?),
+            Box::new(CachedShortsChecker::new(
+                HttpShortsChecker::new(fetch_client.clone(), Arc::clone(&limiter)),
+                &config.data_dir,
+            )?),
+        )
+    };
+    // Offline only reads: handles resolve from the cache or not at all.
+    let resolver: Box<dyn ChannelResolver> = if offline {
+        Box::new(CachedChannelResolver::new(
+            NoChannelLookup,
+            &config.data_dir,
+        )?)
+    } else {
+        Box::new(CachedChannelResolver::new(
+            HttpChannelResolver::new(client.clone(), limiter),
+            &config.data_dir,
+        )?)
+    };
+    let names = JsonChannelNameStore::new(&config.data_dir)?;
+    let snoozes = JsonSnoozeStore::new(&config.data_dir)?;
+    let downloads = JsonDownloadStore::new(&config.data_dir)?;
+    let archiver = downloader(&config, resolver.as_ref(), flags.quiet);
+    let download_run = download_run(&archiver, &downloads, config.max_concurrent_downloads);
+    let prune = match config.prune_downloads_after_days {
+        Some(days) if !(offline || replaying) => {
+            Some((days, config.kept_channels(resolver.as_ref())))
+        }
+        _ => None,
+    };
+    let learned = names.load_channel_names()?;
+    let mut channels = if offline {
+        config.resolve_known_channels(resolver.as_ref(), &learned)
+    } else {
+        config.resolve_channels(resolver.as_ref(), &learned)
+    };
+    use_cases::apply_snoozes(&mut channels, &snoozes)?;
+    // Filtered once resolved, so unnamed channels match their learned name.
+    if let Some(name) = channel {
+        channels = match_channels(&channels, name)?
+            .into_iter()
+            .cloned()
+            .collect();
+    } else {
+        let today = Local::now().date_naive();
+        let before = channels.len();
+        channels.retain(|c| !c.is_snoozed(today));
+        let snoozed = before - channels.len();
+        if snoozed > 0 && !(offline || flags.quiet) {
+            info!(target: PROGRESS_LOG_TARGET, "Skipped {snoozed} snoozed channels (blepo snooze to list them)");
+        }
+    }
+    if let Some(tag) = &flags.tag {
+        channels.retain(|c| c.tags.iter().any(|t| t.matches(tag)));
+        if channels.is_empty() {
+            return Err(format!("no channel tagged '{tag}' in the config").into());
+        }
+    }
+
+    let FetchedVideos {
+        videos,
+        report,
+        timing,
+    } = block_on(use_cases::fetch_videos(
+        &channels,
+        fetcher.as_ref(),
+        store,
+        shorts_checker.as_ref(),
+        options,
+    ))??;
+    let failures = FetchFailures {
+        report,
+        json: flags.output == Some(ListingFormat::Json),
+        timing: flags.timing.then_some(timing),
+    };
+    if !failures.report.is_empty() && failures.report.len() == channels.len() {
+        failures.print();
+        return Err(Exit::AllChannelsFailed(failures.report.len()).into());
+    }
+    if !offline {
+        use_cases::learn_channel_names(&channels, &videos, &names)?;
+    }
+    if !offline && !replaying && channels.iter().any(|c| c.auto_download) {
+        use_cases::queue_auto_downloads(&videos, &channels, &downloads)?;
+        run_downloads(&download_run, flags.quiet)?;
+    }
+    if let Some((days, kept)) = &prune {
+        prune_downloads(&downloads, store, kept, *days, flags.quiet)?;
+    }
+    let aliases = channels
+        .iter()
+        .filter_map(|c| Some((c.id.clone(), c.alias.clone()?)))
+        .collect();
+    let players = channels
+        .into_iter()
+        .filter_map(|c| Some((c.id, c.player?)))
+        .collect();
+    Ok(Unwatched {
+        videos,
+        aliases,
+        players,
+        failures,
+    })
+}
+
+/// Runs the async fetch pipeline to completion on the calling thread, so
+/// fetches wait on the network together rather than on a thread each.
+fn block_on<F: Future>(future: F) -> io::Result<F::Output> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    Ok(runtime.block_on(future))
+}
+
+/// Deletes downloads watched more than `days` ago, except those of `kept`
+/// channels, saying which on stderr unless `quiet`; returns how many.
+fn prune_downloads(
+    downloads: &JsonDownloadStore,
+    store: &JsonVideoStore,
+    kept: &HashSet<ChannelId>,
+    days: NonZeroUsize,
+    quiet: bool,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let older_than = chrono::Duration::days(days.get() as i64);
+    let outcomes = use_cases::prune_downloads(downloads, store, kept, older_than, Utc::now())?;
+    let mut deleted = 0;
+    for (download, result) in outcomes {
+        match result {
+            Ok(()) => {
+                deleted += 1;
+                if !quiet {
+                    info!(target: PROGRESS_LOG_TARGET, "Deleted: {}", download.label());
+                }
+            }
+            Err(e) => warn!("cannot delete {}: {e}", download.label()),
+        }
+    }
+    Ok(deleted)
+}
+
+/// Runs the download queue, showing progress on stderr unless `quiet` and
+/// warning about each download that failed.
+fn run_downloads(run: &DownloadRun, quiet: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let outcomes = use_cases::run_downloads(run, &|download| report_download(download, quiet))?;
+    for (id, result) in outcomes {
+        match result {
+            Ok(path) if !quiet => info!(target: PROGRESS_LOG_TARGET, "Saved: {}", path.display()),
+            Ok(_) => {}
+            Err(e) => warn!("failed to download {id}: {e}"),
+        }
+    }
+    Ok(())
+}
+
+fn report_download(download: &Download, quiet: bool) {
+    if matches!(download.state, DownloadState::Downloading { .. }) && !quiet {
+        info!(target: PROGRESS_LOG_TARGET, "Downloading: {}", download.label());
+    }
+}
+
+/// The unwatched count for a status bar, leaving out videos that haven't
+/// started yet; `by_tag` adds the count of each channel tag, e.g.
+/// `12 (music 3, tech 9)`.
+fn format_status(videos: &[Video], format: StatusFormat, by_tag: bool) -> String {
+    let videos: Vec<&Video> = videos
+        .iter()
+        .filter(|v| v.kind != VideoKind::Upcoming)
+        .collect();
+    let mut tags: BTreeMap<String, usize> = BTreeMap::new();
+    for tag in videos.iter().flat_map(|v| &v.tags) {
+        *tags.entry(tag.to_string().to_lowercase()).or_default() += 1;
+    }
+    let split: Vec<String> = tags.iter().map(|(tag, n)| format!("{tag} {n}")).collect();
+    let text = if by_tag && !split.is_empty() {
+        format!("{} ({})", videos.len(), split.join(", "))
+    } else {
+        videos.len().to_string()
+    };
+    match format {
+        StatusFormat::Plain => text,
+        StatusFormat::Polybar if videos.is_empty() => String::new(),
+        StatusFormat::Polybar => text,
+        StatusFormat::Json => {
+            serde_json::json!({ "unwatched": videos.len(), "tags": tags }).to_string()
+        }
+        StatusFormat::Waybar => {
+            let tooltip = std::iter::once(format!("{} unwatched videos", videos.len()))
+                .chain(tags.iter().map(|(tag, n)| format!("{tag}: {n}")))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let class = if videos.is_empty() {
+                "empty"
+            } else {
+                "unwatched"
+            };
+            serde_json::json!({ "text": text, "tooltip": tooltip, "class": class }).to_string()
+        }
+    }
+}
+
+/// `--record-fixtures` or `--replay`, if given.
+fn fixtures(flags: &BrowseFlags) -> Option<Fixtures> {
+    match (&flags.record_fixtures, &flags.replay) {
+        (Some(dir), _) => Some(Fixtures::Record(dir.clone())),
+        (None, Some(dir)) => Some(Fixtures::Replay(dir.clone())),
+        (None, None) => None,
+    }
+}
+
+/// RSS with a yt-dlp fallback, plus streams where asked, each retried, and
+/// reused within `fetch_ttl_minutes` unless `force`d. Both save what they
+/// read to `fixtures` when recording.
+fn network_fetcher(
+    config: &AppConfig,
+    client: &reqwest::Client,
+    limiter: Arc<RateLimiter>,
+    force: bool,
+    fixtures: Option<Fixtures>,
+) -> Result<impl FeedFetcher, Box<dyn std::error::Error>> {
+    let retry = config.retry;
+    let proxy = config.proxy.clone();
+    Ok(TtlFetcher::new(
+        StreamsFetcher::new(
+            FallbackFetcher::new(
+                RetryingFetcher::new(
+                    RssFeedFetcher::new(client.clone(), limiter)
+                        .with_cache(FeedCache::new(&config.data_dir)?)
+                        .with_fixtures(fixtures.clone()),
+                    retry,
+                ),
+                RetryingFetcher::new(
+                    YtDlpFetcher::new()
+                        .with_proxy(proxy.clone())
+                        .with_cookies(config.cookies.clone())
+                        .with_window(config.fetch_window)
+                        .with_fixtures(fixtures.clone()),
+                    retry,
+                ),
+            ),
+            RetryingFetcher::new(
+                YtDlpFetcher::streams()
+                    .with_proxy(proxy)
+                    .with_cookies(config.cookies.clone())
+                    .with_window(config.fetch_window)
+                    .with_fixtures(fixtures),
+                retry,
+            ),
+        ),
+        &config.data_dir,
+        config.fetch_ttl,
+    )?
+    .forced(force))
+}
+
+/// `network_fetcher` reading what `--record-fixtures` saved instead of the
+/// network, through the same fallback and streams but without retries or
+/// caches, so replaying leaves the data dir as it was.
+fn replay_fetcher(
+    client: &reqwest::Client,
+    limiter: Arc<RateLimiter>,
+    fixtures: Fixtures,
+) -> impl FeedFetcher {
+    StreamsFetcher::new(
+        FallbackFetcher::new(
+            RssFeedFetcher::new(client.clone(), limiter).with_fixtures(Some(fixtures.clone())),
+            YtDlpFetcher::new().with_fixtures(Some(fixtures.clone())),
+        ),
+        YtDlpFetcher::streams().with_fixtures(Some(fixtures)),
+    )
+}
+
+fn notify(
+    config: AppConfig,
+    store: &JsonVideoStore,
+    quiet: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let url = config
+        .notify_url
+        .clone()
+        .ok_or("set notify_url in the config to announce new videos")?;
+    let client = build_client(config.proxy.as_ref());
+    let notified = JsonNotifiedStore::new(&config.data_dir)?;
+    let flags = BrowseFlags {
+        quiet,
+        ..BrowseFlags::default()
+    };
+    let Unwatched {
+        videos, failures, ..
+    } = fetch_unwatched(config, store, &client, &flags, None, false)?;
+    let notifier = WebhookNotifier::new(client, url);
+    let outcome = use_cases::notify_new_videos(&videos, &notifier, &notified)?;
+    if outcome.first_run {
+        println!(
+            "Remembered {} videos; videos published from now on will be announced.",
+            videos.len()
+        );
+    } else {
+        println!("Announced {} new videos.", outcome.sent);
+    }
+    if outcome.failed > 0 {
+        return Err(format!(
+            "{} announcements failed; they are tried again next time",
+            outcome.failed
+        )
+        .into());
+    }
+    failures.outcome(false)
+}
+
+/// Lists the videos no earlier digest has seen and remembers them as the
+/// last listing, so `blepo open <n>` works on them.
+fn digest(
+    config: AppConfig,
+    store: &JsonVideoStore,
+    quiet: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let client = build_client(config.proxy.as_ref());
+    let digests = JsonDigestStore::new(&config.data_dir)?;
+    let listing_store = JsonListingStore::new(&config.data_dir)?;
+    let date_style = config.date_style;
+    let date_format = config.date_format.clone();
+    let theme = config.theme;
+    let flags = BrowseFlags {
+        quiet,
+        ..BrowseFlags::default()
+    };
+    let Unwatched {
+        videos,
+        aliases,
+        failures,
+        ..
+    } = fetch_unwatched(config, store, &client, &flags, None, false)?;
+    let now = Utc::now();
+    let digest = use_cases::digest(&videos, &digests, now)?;
+    if digest.videos.is_empty() {
+        match digest.since {
+            Some(since) => println!(
+                "Nothing new since the last digest, {}.",
+                relative_age(since, now)
+            ),
+            None => println!("No unwatched videos."),
+        }
+        return failures.outcome(false);
+    }
+    let style = LineStyle {
+        date_style,
+        date_format,
+        now,
+        utc_offset: local_offset,
+        theme: colors_enabled().then_some(theme),
+        aliases,
+        date_width: 0,
+        channel_width: 0,
+        line_width: None,
+    }
+    .fit(&digest.videos, terminal_width());
+    for (n, video) in digest.videos.iter().enumerate() {
+        println!("{}", format_video_line(n + 1, video, None, &style));
+    }
+    use_cases::remember_listing(&digest.videos, &listing_store)?;
+    failures.outcome(false)
+}
+
+/// `subtitles` and `thumbnail` come from `--subs` and `--thumbnail`, and
+/// apply to every download this runs.
+fn download(
+    config: &AppConfig,
+    store: &JsonVideoStore,
+    target: DownloadTarget,
+    mark_watched: bool,
+    subtitles: Option<Subtitles>,
+    thumbnail: bool,
+    quiet: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (video_id, video) = match target {
+        DownloadTarget::Listed(number) => {
+            let listing = JsonListingStore::new(&config.data_dir)?;
+            let Some(video) = use_cases::listed_video(number, &listing)? else {
+                println!("No video #{number} in the last listing; run `blepo` first.");
+                return Ok(());
+            };
+            (video.id.clone(), Some(video))
+        }
+        DownloadTarget::Video(id) => (id, None),
+    };
+    let resolver = CachedChannelResolver::new(
+        HttpChannelResolver::new(
+            build_client(config.proxy.as_ref()),
+            Arc::new(RateLimiter::new(config.request_interval)),
+        ),
+        &config.data_dir,
+    )?;
+    let downloader = downloader(config, &resolver, quiet).with_overrides(subtitles, thumbnail);
+    let downloads = JsonDownloadStore::new(&config.data_dir)?;
+    let mark_watched = mark_watched || config.download_marks_watched;
+    let Some(path) = use_cases::download(
+        &video_id,
+        video.as_ref(),
+        &download_run(&downloader, &downloads, config.max_concurrent_downloads),
+        store,
+        mark_watched,
+        &|download| report_download(download, quiet),
+    )?
+    else {
+        println!("Another blepo is downloading it; see `blepo downloads`.");
         return Ok(());
+    };
+    println!("Saved: {}", path.display());
+    if mark_watched {
+        println!("Marked as watched.");
     }
+    Ok(())
+}
 
-    for (i, video) in videos.iter().enumerate() {
-        let date = video.published.format("%Y-%m-%d");
+/// Lists queued, running, interrupted and failed downloads, then the most
+/// recently saved ones; `resume` runs the queue first.
+fn downloads(
+    config: &AppConfig,
+    store: &JsonVideoStore,
+    command: DownloadsCommand,
+    quiet: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let downloads = JsonDownloadStore::new(&config.data_dir)?;
+    let resolver = CachedChannelResolver::new(
+        HttpChannelResolver::new(
+            build_client(config.proxy.as_ref()),
+            Arc::new(RateLimiter::new(config.request_interval)),
+        ),
+        &config.data_dir,
+    )?;
+    match command {
+        DownloadsCommand::List => {}
+        DownloadsCommand::Resume => {
+            use_cases::retry_failed_downloads(&downloads)?;
+            let downloader = downloader(config, &resolver, quiet);
+            run_downloads(
+                &download_run(&downloader, &downloads, config.max_concurrent_downloads),
+                quiet,
+            )?;
+        }
+        DownloadsCommand::Prune(days) => {
+            let Some(days) = days.or(config.prune_downloads_after_days) else {
+                return Err("downloads prune needs a number of days, e.g. \
+                            `blepo downloads prune 30`, or prune_downloads_after_days \
+                            in the config"
+                    .into());
+            };
+            let kept = config.kept_channels(&resolver);
+            match prune_downloads(&downloads, store, &kept, days, quiet)? {
+                0 => println!("No downloads watched more than {days} days ago."),
+                1 => println!("Deleted 1 download."),
+                deleted => println!("Deleted {deleted} downloads."),
+            }
+            return Ok(());
+        }
+    }
+    let queue = downloads.load_downloads()?;
+    if queue.is_empty() {
+        println!("No downloads yet.");
+        return Ok(());
+    }
+    let now = Utc::now();
+    let mut saved = Vec::new();
+    for download in &queue {
+        let status = match &download.state {
+            DownloadState::Saved { path, at } => {
+                saved.push((*at, download.label(), path));
+                continue;
+            }
+            DownloadState::Queued => "queued".to_string(),
+            DownloadState::Downloading { pid } if SystemProcesses.is_running(*pid) => {
+                format!("downloading (pid {pid})")
+            }
+            DownloadState::Downloading { .. } => "interrupted".to_string(),
+            DownloadState::Failed { error } => format!("failed: {error}"),
+        };
+        println!("{} — {status}", download.label());
+    }
+    saved.sort_by_key(|(at, ..)| std::cmp::Reverse(*at));
+    for (at, label, path) in saved.iter().take(RECENT_DOWNLOADS) {
         println!(
-            "{:>3}. [{}] {} — {}",
-            i + 1,
-            date,
-            video.channel_name,
-            video.title
+            "{label} — saved {} to {}",
+            relative_age(*at, now),
+            path.display()
         );
     }
+    if saved.len() > RECENT_DOWNLOADS {
+        println!("...and {} saved earlier.", saved.len() - RECENT_DOWNLOADS);
+    }
+    Ok(())
+}
+
+/// Resolves the channels with their own download options through
+/// `resolver`.
+/// `download_rate_limit` is shared out between the downloads that may run at
+/// once, whose progress bars would garble each other, so only a lone
+/// download shows one (unless `quiet`).
+fn downloader(config: &AppConfig, resolver: &dyn ChannelResolver, quiet: bool) -> YtDlpDownloader {
+    let concurrency = config.max_concurrent_downloads.get() as u64;
+    YtDlpDownloader::new(&config.download_dir, config.download_options())
+        .with_channel_options(config.channel_download_options(resolver))
+        .with_proxy(config.proxy.clone())
+        .with_cookies(config.cookies.clone())
+        .with_rate_limit(
+            config
+                .download_rate_limit
+                .map(|total| (total / concurrency).max(1)),
+        )
+        .with_sidecars(config.download_nfo)
+        .with_hook(config.download_hook.clone())
+        .quiet(quiet || concurrency > 1)
+}
+
+/// Downloads run `concurrency` at a time, recorded against this process.
+fn download_run<'a>(
+    downloader: &'a YtDlpDownloader,
+    downloads: &'a JsonDownloadStore,
+    concurrency: NonZeroUsize,
+) -> DownloadRun<'a> {
+    DownloadRun {
+        downloader,
+        downloads,
+        processes: &SystemProcesses,
+        pid: std::process::id(),
+        concurrency,
+    }
+}
+
+fn check_channels(config: AppConfig, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let client = build_client(config.proxy.as_ref());
+    let limiter = Arc::new(RateLimiter::new(config.request_interval));
+    let resolver = CachedChannelResolver::new(
+        HttpChannelResolver::new(client.clone(), Arc::clone(&limiter)),
+        &config.data_dir,
+    )?;
+    let names = JsonChannelNameStore::new(&config.data_dir)?;
+    let concurrency = config.fetch_concurrency;
+    let probe = RssFeedFetcher::new(build_async_client(config.proxy.as_ref()), limiter);
+    let channels = config.resolve_channels(&resolver, &names.load_channel_names()?);
+    let problems = block_on(use_cases::check_channels(&channels, &probe, concurrency))?;
+    if json {
+        println!("{}", channel_problems_json(&problems));
+    } else if problems.is_empty() {
+        println!("All {} channels look fine.", channels.len());
+    } else {
+        for (channel, problem) in &problems {
+            println!("- {} ({}): {problem}", channel.name, channel.id);
+        }
+    }
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "{} of {} channels need a look",
+        problems.len(),
+        channels.len()
+    )
+    .into())
+}
+
+fn channel_stats(
+    config: AppConfig,
+    store: &JsonVideoStore,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let resolver = CachedChannelResolver::new(
+        HttpChannelResolver::new(
+            build_client(config.proxy.as_ref()),
+            Arc::new(RateLimiter::new(config.request_interval)),
+        ),
+        &config.data_dir,
+    )?;
+    let names = JsonChannelNameStore::new(&config.data_dir)?;
+    let catalog = CachedUploads::new(&config.data_dir);
+    let channels = config.resolve_channels(&resolver, &names.load_channel_names()?);
+    let now = Utc::now();
+    let stats = use_cases::channel_stats(&channels, &catalog, store, now)?;
+    if json {
+        println!("{}", channel_stats_json(&stats));
+        return Ok(());
+    }
+    let width = channels
+        .iter()
+        .map(|c| c.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (channel, stats) in stats {
+        let name = &channel.name;
+        match stats {
+            Some(s) => println!(
+                "{name:<width$}  {:>5.1}/week  last upload {:<10}  watched {} of {} ({:.0}%)",
+                s.per_week,
+                relative_age(s.last_upload, now),
+                s.watched,
+                s.uploads,
+                s.watched_share() * 100.0
+            ),
+            None => println!("{name:<width$}  no uploads known yet (run blepo to fetch them)"),
+        }
+    }
+    Ok(())
+}
+
+fn snooze(command: SnoozeCommand, config: AppConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let snoozes = JsonSnoozeStore::new(&config.data_dir)?;
+    let resolver = CachedChannelResolver::new(
+        HttpChannelResolver::new(
+            build_client(config.proxy.as_ref()),
+            Arc::new(RateLimiter::new(config.request_interval)),
+        ),
+        &config.data_dir,
+    )?;
+    let names = JsonChannelNameStore::new(&config.data_dir)?;
+    // Snoozed only as far as the config says, until the list applies the rest.
+    let mut channels = config.resolve_channels(&resolver, &names.load_channel_names()?);
+    let today = Local::now().date_naive();
+    let named = |name: &str| match_channels(&channels, name).map(|matched| matched[0]);
+    match command {
+        SnoozeCommand::List => {
+            use_cases::apply_snoozes(&mut channels, &snoozes)?;
+            let snoozed: Vec<&Channel> = channels.iter().filter(|c| c.is_snoozed(today)).collect();
+            if snoozed.is_empty() {
+                println!("No channel is snoozed.");
+            }
+            for channel in snoozed {
+                if let Some(until) = channel.snoozed_until {
+                    println!("{}: back on {until}", channel.name);
+                }
+            }
+        }
+        SnoozeCommand::Snooze(name, length) => {
+            let channel = named(&name)?;
+            let until = length
+                .until(today)
+                .ok_or("snooze until a day after today")?;
+            use_cases::snooze_channel(channel, Some(until), today, &snoozes)?;
+            match channel
+                .snoozed_until
+                .filter(|&configured| configured > until)
+            {
+                Some(configured) => println!(
+                    "Snoozed {}, but the config keeps it snoozed until {configured}.",
+                    channel.name
+                ),
+                None => println!("Snoozed {}; back on {until}.", channel.name),
+            }
+        }
+        SnoozeCommand::Wake(name) => {
+            let channel = named(&name)?;
+            use_cases::snooze_channel(channel, None, today, &snoozes)?;
+            match channel.snoozed_until.filter(|_| channel.is_snoozed(today)) {
+                Some(configured) => println!(
+                    "{} stays snoozed until {configured} by the config.",
+                    channel.name
+                ),
+                None => println!("{} is listed again.", channel.name),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn browse(
+    config: AppConfig,
+    store: &JsonVideoStore,
+    flags: BrowseFlags,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let started = std::time::Instant::now();
+    let enrich = config.enrich || flags.enrich;
+    let fetch_concurrency = config.fetch_concurrency;
+    let metadata_store = JsonMetadataStore::new(&config.data_dir)?;
+    let live_handling = config.live_handling;
+    let date_style = config.date_style;
+    let date_format = config.date_format.clone();
+    let group_by_channel = flags.group_by_channel;
+    let show_qr = flags.qr;
+    let mut clipboard = SystemClipboard::default();
+    let theme = config.theme;
+    let ready_alert = config.ready_alert;
+    let graphics = GraphicsProtocol::detect().filter(|_| config.thumbnails);
+    let proxy = config.proxy.clone();
+    let cookies = config.cookies.clone();
+    let client = build_client(proxy.as_ref());
+    let thumbnails = ThumbnailCache::new(client.clone(), &config.data_dir)?;
+    let mut playback = Playback::new(&config, &client, flags.cast.clone())?;
+    let listing_store = JsonListingStore::new(&config.data_dir)?;
+    let queue_store = JsonQueueStore::new(&config.data_dir)?;
+    let open_marks_watched = config.open_marks_watched;
+    let resolver = CachedChannelResolver::new(
+        HttpChannelResolver::new(
+            client.clone(),
+            Arc::new(RateLimiter::new(config.request_interval)),
+        ),
+        &config.data_dir,
+    )?;
+    let downloader = downloader(&config, &resolver, false);
+    let downloads = JsonDownloadStore::new(&config.data_dir)?;
+    let download_marks_watched = config.download_marks_watched;
+    let download_concurrency = config.max_concurrent_downloads;
+    let Unwatched {
+        videos,
+        aliases,
+        players,
+        failures,
+    } = fetch_unwatched(config, store, &client, &flags, None, false)?;
+    playback.channel_players = players;
+    let progress = use_cases::watch_progress(store)?;
+
+    if let Some(format) = flags.output {
+        let listing = match format {
+            ListingFormat::Json => videos_json(&videos, &aliases, &progress),
+            ListingFormat::Tsv => videos_tsv(&videos, &aliases, &progress),
+            ListingFormat::Csv => videos_csv(&videos, &aliases, &progress),
+        };
+        if !listing.is_empty() {
+            println!("{listing}");
+        }
+        use_cases::remember_listing(&videos, &listing_store)?;
+        return failures.outcome(videos.is_empty());
+    }
+
+    if videos.is_empty() {
+        if !flags.quiet {
+            println!("No unwatched videos.");
+            alert_ready(ready_alert, started.elapsed(), "No unwatched videos.");
+        }
+        return failures.outcome(true);
+    }
+
+    if enrich {
+        let fetcher = YtDlpMetadataFetcher::new()
+            .with_proxy(proxy.clone())
+            .with_cookies(cookies.clone());
+        use_cases::enrich(
+            &videos,
+            &fetcher,
+            &metadata_store,
+            fetch_concurrency,
+            flags.quiet,
+        )?;
+    }
+
+    let images = match graphics {
+        Some(protocol) => map_bounded(&videos, fetch_concurrency, |video| {
+            thumbnails
+                .load(&video.id)
+                .and_then(|jpeg| protocol.render(&jpeg))
+        }),
+        None => vec![None; videos.len()],
+    };
+
+    let style = LineStyle {
+        date_style,
+        date_format,
+        now: Utc::now(),
+        utc_offset: local_offset,
+        theme: colors_enabled().then_some(theme),
+        aliases,
+        date_width: 0,
+        channel_width: 0,
+        line_width: None,
+    }
+    .fit(&videos, terminal_width());
+    let live_section_starts = match live_handling {
+        LiveHandling::Section => videos.iter().position(|v| v.kind.is_live_or_upcoming()),
+        LiveHandling::Hide | LiveHandling::Include => None,
+    };
+    let mut listing = Listing {
+        videos: &videos,
+        images: &images,
+        progress: &progress,
+        live_section_starts,
+        order: Vec::new(),
+        grouped: false,
+    };
+    listing.group(group_by_channel);
+    listing.print(&style);
+    use_cases::remember_listing(&listing.numbered(), &listing_store)?;
+    if flags.quiet {
+        return failures.outcome(false);
+    }
+    alert_ready(
+        ready_alert,
+        started.elapsed(),
+        &unwatched_count(videos.len()),
+    );
 
     loop {
-        print!("\nEnter number to play, w<number> to mark watched, wa to mark all watched, q to quit: ");
+        print!("\nEnter number to play (several separated by spaces for a playlist), w<number> to mark watched, i<number> for details, o<number> to open in browser, d<number> to download, y<number> to copy URL, q<number> to queue, wa to mark all watched, g to toggle grouping by channel, q to quit: ");
         io::stdout().flush()?;
 
         let mut input = String::new();
@@ -47,18 +1375,62 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
         let input = input.trim();
 
         if input.is_empty() || input == "q" {
-            return Ok(());
+            return failures.outcome(false);
         }
 
         if input == "wa" {
-            use_cases::mark_all_as_watched(&videos, &store)?;
-            return Ok(());
+            use_cases::mark_all_as_watched(&videos, store)?;
+            return failures.outcome(false);
+        }
+
+        if input == "g" {
+            listing.group(!listing.grouped);
+            listing.print(&style);
+            use_cases::remember_listing(&listing.numbered(), &listing_store)?;
+            continue;
         }
 
-        let (mark_only, num_str) = if let Some(rest) = input.strip_prefix('w') {
-            (true, rest)
+        if input.contains(char::is_whitespace) {
+            let picked = input
+                .split_whitespace()
+                .map(|raw| -> Result<&Video, Box<dyn std::error::Error>> {
+                    let number: usize =
+                        raw.parse().map_err(|_| format!("invalid number: {raw}"))?;
+                    let number = VideoNumber::parse(number)?;
+                    let video = listing.get(number).ok_or_else(|| {
+                        format!(
+                            "video #{raw} not found (have {} unwatched videos)",
+                            videos.len()
+                        )
+                    })?;
+                    Ok(video)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let played = use_cases::play_playlist(&picked, store, playback.player()?.as_ref());
+            if !playback.attached {
+                played?;
+                break;
+            }
+            if let Err(e) = played {
+                warn!("playback failed: {e}");
+            }
+            continue;
+        }
+
+        let (pick, num_str) = if let Some(rest) = input.strip_prefix('w') {
+            (Pick::MarkWatched, rest)
+        } else if let Some(rest) = input.strip_prefix('i') {
+            (Pick::Info, rest)
+        } else if let Some(rest) = input.strip_prefix('o') {
+            (Pick::Open, rest)
+        } else if let Some(rest) = input.strip_prefix('d') {
+            (Pick::Download, rest)
+        } else if let Some(rest) = input.strip_prefix('y') {
+            (Pick::Copy, rest)
+        } else if let Some(rest) = input.strip_prefix('q') {
+            (Pick::Enqueue, rest)
         } else {
-            (false, input)
+            (Pick::Play, input)
         };
 
         let number: usize = num_str
@@ -66,7 +1438,7 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
             .map_err(|_| format!("invalid number: {input}"))?;
         let number = VideoNumber::parse(number)?;
 
-        let video = videos.get(number.to_index()).ok_or_else(|| {
+        let video = listing.get(number).ok_or_else(|| {
             format!(
                 "video #{} not found (have {} unwatched videos)",
                 input,
@@ -74,18 +1446,864 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
             )
         })?;
 
-        if mark_only {
-            use_cases::mark_as_watched(video, &store)?;
-            println!(
-                "Marked as watched: {} [{}]",
-                video.title, video.channel_name
-            );
+        match pick {
+            Pick::MarkWatched => {
+                use_cases::mark_as_watched(video, store)?;
+                println!(
+                    "Marked as watched: {} [{}]",
+                    video.title, video.channel_name
+                );
+            }
+            Pick::Info => match use_cases::video_info(&video.id, &metadata_store)? {
+                Some(metadata) => println!("\n{}", format_info(&metadata)),
+                None => println!("No details for this video; run with --enrich to fetch them."),
+            },
+            Pick::Open => {
+                use_cases::open_in_browser(video, store, &SystemBrowser, open_marks_watched)?
+            }
+            Pick::Download => match use_cases::download(
+                &video.id,
+                Some(video),
+                &download_run(&downloader, &downloads, download_concurrency),
+                store,
+                download_marks_watched,
+                &|download| report_download(download, false),
+            ) {
+                Ok(Some(path)) => println!("Saved: {}", path.display()),
+                Ok(None) => println!("Another blepo is downloading it; see `blepo downloads`."),
+                Err(e) => warn!("download failed: {e}"),
+            },
+            Pick::Copy => {
+                match clipboard.copy(&video.url) {
+                    Ok(()) => println!("Copied: {}", video.url),
+                    Err(e) => warn!("cannot copy {}: {e}", video.url),
+                }
+                if let Some(qr) = show_qr.then(|| render_qr(&video.url)).flatten() {
+                    println!("\n{qr}");
+                }
+            }
+            Pick::Enqueue => use_cases::enqueue(video, &queue_store)?,
+            Pick::Play => {
+                let played = playback.play(video, store);
+                if !playback.attached {
+                    played?;
+                    break;
+                }
+                if let Err(e) = played {
+                    warn!("playback failed, not marked as watched: {e}");
+                }
+            }
+        }
+    }
+
+    failures.outcome(false)
+}
+
+/// Playback settings from the config, shared by every way of playing.
+struct Playback {
+    proxy: Option<ProxyUrl>,
+    cookies: Option<Cookies>,
+    mpv_args: Vec<String>,
+    playback_speed: Option<PlaybackSpeed>,
+    subtitles: Option<Subtitles>,
+    sponsorblock: Option<SponsorBlockClient>,
+    data_dir: PathBuf,
+    watch_threshold: Option<WatchThreshold>,
+    /// Wait for mpv and prompt again instead of exiting.
+    attached: bool,
+    /// Device to cast to instead of playing locally.
+    cast: Option<String>,
+    /// Channels' own players, for single videos played locally.
+    channel_players: HashMap<ChannelId, ChannelPlayer>,
+    /// Downloaded videos, played from disk instead of streamed.
+    local_files: HashMap<VideoId, PathBuf>,
+}
+
+impl Playback {
+    fn new(
+        config: &AppConfig,
+        client: &reqwest::blocking::Client,
+        cast: Option<String>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let downloads = JsonDownloadStore::new(&config.data_dir)?;
+        Ok(Self {
+            proxy: config.proxy.clone(),
+            cookies: config.cookies.clone(),
+            mpv_args: config.mpv_args.clone(),
+            playback_speed: config.playback_speed,
+            subtitles: config.subtitles.clone(),
+            sponsorblock: config
+                .sponsorblock
+                .then(|| SponsorBlockClient::new(client.clone())),
+            data_dir: config.data_dir.clone(),
+            watch_threshold: config.watch_threshold,
+            attached: config.playback_mode == PlaybackMode::Attached,
+            cast,
+            channel_players: HashMap::new(),
+            local_files: use_cases::downloaded_files(&downloads)?,
+        })
+    }
+
+    /// catt when casting, else mpv, else the first other player found.
+    fn player(&self) -> Result<Box<dyn VideoPlayer>, PlayError> {
+        if let Some(device) = &self.cast {
+            return Ok(self.local_first(Box::new(CattPlayer::new(device.clone())?)));
+        }
+        match self.mpv(&[]) {
+            Ok(mpv) => Ok(self.local_first(Box::new(mpv))),
+            Err(PlayError::PlayerFailed(missing)) => {
+                let fallback = LauncherPlayer::detect();
+                warn!("{missing}; playing with {} instead", fallback.name());
+                Ok(self.local_first(Box::new(fallback)))
+            }
+        }
+    }
+
+    /// Plays downloaded videos from disk.
+    fn local_first(&self, player: Box<dyn VideoPlayer>) -> Box<dyn VideoPlayer> {
+        Box::new(LocalFirstPlayer::new(player, self.local_files.clone()))
+    }
+
+    /// mpv with `mpv_args`, then `extra_args`.
+    fn mpv(&self, extra_args: &[String]) -> Result<MpvPlayer, PlayError> {
+        Ok(MpvPlayer::new()?
+            .with_proxy(self.proxy.clone())
+            .with_cookies(self.cookies.clone())
+            .with_preferences(self.playback_speed, self.subtitles.as_ref())
+            .with_args([&self.mpv_args[..], extra_args].concat()))
+    }
+
+    /// Plays one video, marking it watched as `playback_mode` and
+    /// `watched_threshold_percent` say. Only mpv can be tracked, so the
+    /// threshold and SponsorBlock don't apply to casting or other players.
+    /// Unless casting, the video's channel may pick the browser or add mpv
+    /// options.
+    fn play(
+        &self,
+        video: &Video,
+        store: &JsonVideoStore,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let channel_player = match self.cast {
+            Some(_) => None,
+            None => use_cases::channel_player(video, &self.channel_players),
+        };
+        let channel_args = match channel_player {
+            Some(ChannelPlayer::Browser) => {
+                use_cases::open_in_browser(video, store, &SystemBrowser, true)?;
+                return Ok(());
+            }
+            Some(ChannelPlayer::Mpv(args)) => args.as_slice(),
+            None => &[],
+        };
+        let mpv = match self.cast {
+            Some(_) => None,
+            None => self.mpv(channel_args).ok(),
+        };
+        let jobs = JsonJobStore::new(&self.data_dir)?;
+        let Some(mut player) = mpv else {
+            let player = self.player()?;
+            if self.attached {
+                use_cases::play_attached(video, store, player.as_ref())?;
+            } else {
+                use_cases::mark_and_play(video, store, player.as_ref(), &jobs, &SystemProcesses)?;
+            }
+            return Ok(());
+        };
+        if let Some(sponsorblock) = &self.sponsorblock {
+            let segments = use_cases::skip_segments(video, sponsorblock);
+            player = player.with_skip_segments(&install_skip_script(&self.data_dir)?, &segments);
+        }
+        let player = self.local_first(Box::new(player));
+        match (self.watch_threshold, self.attached) {
+            (Some(threshold), _) => {
+                use_cases::play_and_mark_if_watched(video, store, player.as_ref(), threshold)?
+            }
+            (None, true) => use_cases::play_attached(video, store, player.as_ref())?,
+            (None, false) => {
+                use_cases::mark_and_play(video, store, player.as_ref(), &jobs, &SystemProcesses)?
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A different number on every run; good enough to pick a video.
+fn random_seed() -> u64 {
+    use std::hash::{BuildHasher, Hasher};
+    std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish()
+}
+
+/// The listing as displayed; numbers the user types index into `order`.
+struct Listing<'a> {
+    videos: &'a [Video],
+    images: &'a [Option<String>],
+    progress: &'a HashMap<VideoId, WatchProgress>,
+    /// Where the upcoming & live section begins; it is never regrouped.
+    live_section_starts: Option<usize>,
+    order: Vec<usize>,
+    grouped: bool,
+}
+
+impl Listing<'_> {
+    fn group(&mut self, by_channel: bool) {
+        let regular = self.live_section_starts.unwrap_or(self.videos.len());
+        self.order = if by_channel {
+            channel_grouped_order(&self.videos[..regular])
         } else {
-            let player = MpvPlayer::new()?;
-            use_cases::mark_and_play(video, &store, &player)?;
-            break;
+            (0..regular).collect()
+        };
+        self.order.extend(regular..self.videos.len());
+        self.grouped = by_channel;
+    }
+
+    /// Videos in the order they are numbered.
+    fn numbered(&self) -> Vec<Video> {
+        self.order.iter().map(|&i| self.videos[i].clone()).collect()
+    }
+
+    fn get(&self, number: VideoNumber) -> Option<&Video> {
+        self.order.get(number.to_index()).map(|&i| &self.videos[i])
+    }
+
+    fn print(&self, style: &LineStyle) {
+        let mut channel = None;
+        for (n, &i) in self.order.iter().enumerate() {
+            let video = &self.videos[i];
+            if self.live_section_starts == Some(i) {
+                println!("\nUpcoming & live:");
+            } else if self.grouped
+                && self.live_section_starts.is_none_or(|start| i < start)
+                && channel != Some(&video.channel_id)
+            {
+                println!("\n{}:", style.channel_name(video));
+                channel = Some(&video.channel_id);
+            }
+            if let Some(image) = &self.images[i] {
+                print!("{image}");
+            }
+            let progress = self.progress.get(&video.id).copied();
+            println!("{}", format_video_line(n + 1, video, progress, style));
         }
     }
+}
 
-    Ok(())
+enum Pick {
+    Play,
+    MarkWatched,
+    Info,
+    Open,
+    Download,
+    Copy,
+    Enqueue,
+}
+
+/// Listing settings shared by every line.
+struct LineStyle {
+    date_style: DateStyle,
+    date_format: DateFormat,
+    now: DateTime<Utc>,
+    /// The viewer's UTC offset at a given instant.
+    utc_offset: fn(DateTime<Utc>) -> FixedOffset,
+    /// `None` when output is plain.
+    theme: Option<Theme>,
+    /// Shown instead of the channel names of these channels.
+    aliases: HashMap<ChannelId, String>,
+    /// Dates and channel names are padded to these widths so titles line up.
+    date_width: usize,
+    channel_width: usize,
+    /// Titles are cut to keep lines within this many columns.
+    line_width: Option<usize>,
+}
+
+impl LineStyle {
+    /// Sizes the date and channel columns for `videos`.
+    fn fit(self, videos: &[Video], line_width: Option<usize>) -> Self {
+        let date_width = videos
+            .iter()
+            .map(|v| display_width(&format_date(v, &self)) + 2)
+            .max()
+            .unwrap_or(0);
+        let channel_width = videos
+            .iter()
+            .map(|v| display_width(self.channel_name(v)).min(MAX_CHANNEL_WIDTH))
+            .max()
+            .unwrap_or(0);
+        Self {
+            date_width,
+            channel_width,
+            line_width,
+            ..self
+        }
+    }
+
+    /// The channel's alias, if it has one.
+    fn channel_name<'a>(&'a self, video: &'a Video) -> &'a str {
+        self.aliases
+            .get(&video.channel_id)
+            .unwrap_or(&video.channel_name)
+    }
+
+    fn paint(&self, part: fn(&Theme) -> Option<Color>, text: &str) -> String {
+        match self.theme.as_ref().and_then(part) {
+            Some(color) => color.paint(text),
+            None => text.to_string(),
+        }
+    }
+}
+
+/// Saved downloads `blepo downloads` lists, newest first.
+const RECENT_DOWNLOADS: usize = 10;
+
+/// Longer channel names are cut short.
+const MAX_CHANNEL_WIDTH: usize = 24;
+/// Titles keep at least this many columns, wrapping in very narrow terminals.
+const MIN_TITLE_WIDTH: usize = 20;
+
+/// Videos published this recently are tagged `[new]`.
+const NEW_VIDEO_AGE: chrono::Duration = chrono::Duration::hours(24);
+
+/// Honours https://no-color.org and keeps escapes out of pipes and files.
+fn colors_enabled() -> bool {
+    io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+}
+
+fn local_offset(at: DateTime<Utc>) -> FixedOffset {
+    *at.with_timezone(&Local).offset()
+}
+
+fn format_date(video: &Video, style: &LineStyle) -> String {
+    let absolute = video
+        .published
+        .with_timezone(&(style.utc_offset)(video.published))
+        .format(style.date_format.as_str());
+    match style.date_style {
+        DateStyle::Absolute => absolute.to_string(),
+        DateStyle::Relative => relative_age(video.published, style.now),
+        DateStyle::Both => format!("{absolute}, {}", relative_age(video.published, style.now)),
+    }
+}
+
+fn format_video_line(
+    number: usize,
+    video: &Video,
+    progress: Option<WatchProgress>,
+    style: &LineStyle,
+) -> String {
+    let prefix = format!("{number:>3}. ");
+    let date = pad(
+        &format!("[{}]", format_date(video, style)),
+        style.date_width,
+    );
+    let channel = pad(
+        &truncate(style.channel_name(video), MAX_CHANNEL_WIDTH),
+        style.channel_width,
+    );
+    let tag = match video.kind {
+        VideoKind::Upload => "",
+        VideoKind::LivestreamVod => " [stream]",
+        VideoKind::Short => " [short]",
+        VideoKind::Upcoming => " [upcoming]",
+        VideoKind::Live => " [live]",
+    };
+    let duration = video
+        .duration
+        .map(|d| format!(" [{}]", format_duration(d)))
+        .unwrap_or_default();
+    let progress = progress
+        .map(|p| format!(" [{}% watched]", p.percent()))
+        .unwrap_or_default();
+    let age = style.now - video.published;
+    let new =
+        !video.kind.is_live_or_upcoming() && age >= chrono::Duration::zero() && age < NEW_VIDEO_AGE;
+    let new_marker = if new { " [new]" } else { "" };
+    let tags: String = video.tags.iter().map(|t| format!(" #{t}")).collect();
+
+    let title = match style.line_width {
+        Some(width) => {
+            let taken = [
+                &prefix, &date, " ", &channel, " — ", &duration, &progress, tag, new_marker, &tags,
+            ]
+            .iter()
+            .map(|part| display_width(part))
+            .sum::<usize>();
+            truncate(
+                &video.title,
+                width.saturating_sub(taken).max(MIN_TITLE_WIDTH),
+            )
+        }
+        None => video.title.clone(),
+    };
+    let new_marker = if new {
+        format!(" {}", style.paint(|t| t.new, "[new]"))
+    } else {
+        String::new()
+    };
+    format!(
+        "{prefix}{} {} — {title}{duration}{progress}{tag}{new_marker}{tags}",
+        style.paint(|t| t.date, &date),
+        style.paint(|t| t.channel, &channel),
+    )
+}
+
+/// A listing ready this quickly is on screen before the user could have
+/// looked away, so it doesn't get a `ready_alert`.
+const READY_ALERT_AFTER: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Rings the terminal bell or shows `message` as a desktop notification, as
+/// `ready_alert` says, if the listing took `took` to be ready.
+fn alert_ready(alert: ReadyAlert, took: std::time::Duration, message: &str) {
+    if took < READY_ALERT_AFTER {
+        return;
+    }
+    match alert {
+        ReadyAlert::Off => {}
+        ReadyAlert::Bell => {
+            print!("\x07");
+            let _ = io::stdout().flush();
+        }
+        ReadyAlert::Desktop => {
+            if let Err(e) = DesktopNotifier.notify("blepo", message) {
+                warn!("cannot show a desktop notification: {e}");
+            }
+        }
+    }
+}
+
+fn unwatched_count(count: usize) -> String {
+    let videos = if count == 1 { "video" } else { "videos" };
+    format!("{count} unwatched {videos}")
+}
+
+/// `12:34`, or `1:02:03` from an hour up.
+fn format_duration(duration: std::time::Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+fn format_info(metadata: &VideoMetadata) -> String {
+    let mut lines = vec![metadata.title.clone()];
+    lines.extend(metadata.channel_name.clone());
+    let counts: Vec<String> = [
+        (metadata.view_count, "views"),
+        (metadata.like_count, "likes"),
+    ]
+    .into_iter()
+    .filter_map(|(count, what)| count.map(|n| format!("{} {what}", group_thousands(n))))
+    .collect();
+    if !counts.is_empty() {
+        lines.push(counts.join(" · "));
+    }
+    if let Some(description) = &metadata.description {
+        lines.push(String::new());
+        lines.push(description.trim_end().to_string());
+    }
+    lines.join("\n")
+}
+
+/// `1234567` → `1,234,567`.
+fn group_thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::channel::ChannelTag;
+    use crate::domain::video::VideoId;
+    use chrono::TimeZone;
+
+    fn make_video(kind: VideoKind) -> Video {
+        Video {
+            id: VideoId::parse("v1").unwrap(),
+            title: "Title".to_string(),
+            url: "https://youtube.com/watch?v=v1".to_string(),
+            published: chrono::Utc.with_ymd_and_hms(2024, 1, 20, 15, 0, 0).unwrap(),
+            channel_name: "Channel".to_string(),
+            channel_id: ChannelId::parse("UC123").unwrap(),
+            kind,
+            duration: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn style() -> LineStyle {
+        LineStyle {
+            date_style: DateStyle::Absolute,
+            date_format: DateFormat::default(),
+            now: chrono::Utc.with_ymd_and_hms(2024, 1, 22, 17, 0, 0).unwrap(),
+            utc_offset: |_| FixedOffset::east_opt(0).unwrap(),
+            theme: None,
+            aliases: HashMap::new(),
+            date_width: 0,
+            channel_width: 0,
+            line_width: None,
+        }
+    }
+
+    #[test]
+    fn formats_upload_line() {
+        assert_eq!(
+            format_video_line(1, &make_video(VideoKind::Upload), None, &style()),
+            "  1. [2024-01-20] Channel — Title"
+        );
+    }
+
+    #[test]
+    fn formats_status_for_bars() {
+        let tagged = |tags: &[&str]| Video {
+            tags: tags
+                .iter()
+                .map(|t| ChannelTag::parse(*t).unwrap())
+                .collect(),
+            ..make_video(VideoKind::Upload)
+        };
+        let videos = [
+            tagged(&["music"]),
+            tagged(&["Music", "live"]),
+            tagged(&[]),
+            make_video(VideoKind::Upcoming),
+        ];
+
+        assert_eq!(format_status(&videos, StatusFormat::Plain, false), "3");
+        assert_eq!(
+            format_status(&videos, StatusFormat::Polybar, true),
+            "3 (live 1, music 2)"
+        );
+        assert_eq!(format_status(&[], StatusFormat::Polybar, true), "");
+        assert_eq!(
+            format_status(&videos, StatusFormat::Waybar, false),
+            r#"{"class":"unwatched","text":"3","tooltip":"3 unwatched videos\nlive: 1\nmusic: 2"}"#
+        );
+    }
+
+    #[test]
+    fn lists_channels_under_their_alias() {
+        let video = make_video(VideoKind::Upload);
+        let style = LineStyle {
+            aliases: HashMap::from([(video.channel_id.clone(), "Ch".to_string())]),
+            ..style()
+        }
+        .fit(std::slice::from_ref(&video), None);
+
+        assert_eq!(
+            format_video_line(1, &video, None, &style),
+            "  1. [2024-01-20] Ch — Title"
+        );
+    }
+
+    #[test]
+    fn shows_relative_dates() {
+        let video = make_video(VideoKind::Upload);
+        let relative = LineStyle {
+            date_style: DateStyle::Relative,
+            ..style()
+        };
+        let both = LineStyle {
+            date_style: DateStyle::Both,
+            ..style()
+        };
+
+        assert_eq!(
+            format_video_line(1, &video, None, &relative),
+            "  1. [2 days ago] Channel — Title"
+        );
+        assert_eq!(
+            format_video_line(1, &video, None, &both),
+            "  1. [2024-01-20, 2 days ago] Channel — Title"
+        );
+    }
+
+    #[test]
+    fn shows_dates_in_local_time_and_format() {
+        let style = LineStyle {
+            date_format: DateFormat::parse("%a %d %b %H:%M").unwrap(),
+            utc_offset: |_| FixedOffset::east_opt(10 * 3600).unwrap(),
+            ..style()
+        };
+
+        assert_eq!(
+            format_video_line(1, &make_video(VideoKind::Upload), None, &style),
+            "  1. [Sun 21 Jan 01:00] Channel — Title"
+        );
+    }
+
+    #[test]
+    fn tags_videos_from_the_last_day_as_new() {
+        let style = LineStyle {
+            now: chrono::Utc.with_ymd_and_hms(2024, 1, 21, 9, 0, 0).unwrap(),
+            ..style()
+        };
+
+        assert_eq!(
+            format_video_line(1, &make_video(VideoKind::Upload), None, &style),
+            "  1. [2024-01-20] Channel — Title [new]"
+        );
+        assert!(
+            !format_video_line(1, &make_video(VideoKind::Live), None, &style).ends_with("[new]")
+        );
+    }
+
+    #[test]
+    fn colors_date_channel_and_new_marker() {
+        let style = LineStyle {
+            now: chrono::Utc.with_ymd_and_hms(2024, 1, 21, 9, 0, 0).unwrap(),
+            theme: Some(Theme {
+                date: None,
+                ..Theme::default()
+            }),
+            ..style()
+        };
+
+        assert_eq!(
+            format_video_line(1, &make_video(VideoKind::Upload), None, &style),
+            "  1. [2024-01-20] \x1b[36mChannel\x1b[0m — Title \x1b[32m[new]\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn tags_livestream_vods() {
+        assert_eq!(
+            format_video_line(12, &make_video(VideoKind::LivestreamVod), None, &style()),
+            " 12. [2024-01-20] Channel — Title [stream]"
+        );
+    }
+
+    #[test]
+    fn tags_upcoming_and_live() {
+        assert!(
+            format_video_line(1, &make_video(VideoKind::Upcoming), None, &style())
+                .ends_with(" [upcoming]")
+        );
+        assert!(
+            format_video_line(1, &make_video(VideoKind::Live), None, &style()).ends_with(" [live]")
+        );
+    }
+
+    #[test]
+    fn shows_known_duration() {
+        let video = Video {
+            duration: Some(std::time::Duration::from_secs(754)),
+            ..make_video(VideoKind::LivestreamVod)
+        };
+
+        assert_eq!(
+            format_video_line(1, &video, None, &style()),
+            "  1. [2024-01-20] Channel — Title [12:34] [stream]"
+        );
+    }
+
+    #[test]
+    fn formats_durations() {
+        let secs = std::time::Duration::from_secs;
+        assert_eq!(format_duration(secs(7)), "0:07");
+        assert_eq!(format_duration(secs(599)), "9:59");
+        assert_eq!(format_duration(secs(3723)), "1:02:03");
+    }
+
+    #[test]
+    fn shows_progress_of_started_videos() {
+        let video = Video {
+            duration: Some(std::time::Duration::from_secs(754)),
+            ..make_video(VideoKind::Upload)
+        };
+        assert_eq!(
+            format_video_line(1, &video, WatchProgress::from_percent(45.0), &style()),
+            "  1. [2024-01-20] Channel — Title [12:34] [45% watched]"
+        );
+    }
+
+    #[test]
+    fn shows_channel_tags_last() {
+        let video = Video {
+            tags: vec![
+                ChannelTag::parse("music").unwrap(),
+                ChannelTag::parse("live").unwrap(),
+            ],
+            ..make_video(VideoKind::LivestreamVod)
+        };
+
+        assert_eq!(
+            format_video_line(1, &video, None, &style()),
+            "  1. [2024-01-20] Channel — Title [stream] #music #live"
+        );
+    }
+
+    #[test]
+    fn tags_shorts() {
+        assert_eq!(
+            format_video_line(3, &make_video(VideoKind::Short), None, &style()),
+            "  3. [2024-01-20] Channel — Title [short]"
+        );
+    }
+
+    #[test]
+    fn groups_thousands() {
+        assert_eq!(group_thousands(0), "0");
+        assert_eq!(group_thousands(999), "999");
+        assert_eq!(group_thousands(1_234_567), "1,234,567");
+    }
+
+    #[test]
+    fn formats_info() {
+        let metadata = VideoMetadata {
+            title: "Title".to_string(),
+            channel_name: Some("Channel".to_string()),
+            view_count: Some(12_345),
+            like_count: None,
+            description: Some("Links below\n".to_string()),
+        };
+
+        assert_eq!(
+            format_info(&metadata),
+            "Title\nChannel\n12,345 views\n\nLinks below"
+        );
+    }
+
+    #[test]
+    fn grouping_keeps_live_section_last() {
+        let from = |channel: &str, title: &str, kind| Video {
+            channel_id: ChannelId::parse(channel).unwrap(),
+            title: title.to_string(),
+            ..make_video(kind)
+        };
+        let videos = vec![
+            from("UCa", "a1", VideoKind::Upload),
+            from("UCb", "b1", VideoKind::Upload),
+            from("UCa", "a2", VideoKind::Upload),
+            from("UCa", "live", VideoKind::Live),
+        ];
+        let images = vec![None; videos.len()];
+        let progress = HashMap::new();
+        let mut listing = Listing {
+            videos: &videos,
+            images: &images,
+            progress: &progress,
+            live_section_starts: Some(3),
+            order: Vec::new(),
+            grouped: false,
+        };
+
+        listing.group(true);
+
+        let titles: Vec<&str> = (1..=4)
+            .map(|n| {
+                listing
+                    .get(VideoNumber::parse(n).unwrap())
+                    .unwrap()
+                    .title
+                    .as_str()
+            })
+            .collect();
+        assert_eq!(titles, ["a1", "a2", "b1", "live"]);
+    }
+
+    #[test]
+    fn aligns_columns_across_lines() {
+        let videos = vec![
+            Video {
+                channel_name: "日本語チャンネル".to_string(),
+                ..make_video(VideoKind::Upload)
+            },
+            make_video(VideoKind::Upload),
+        ];
+        let style = LineStyle {
+            date_style: DateStyle::Relative,
+            ..style()
+        }
+        .fit(&videos, None);
+
+        assert_eq!(
+            format_video_line(1, &videos[0], None, &style),
+            "  1. [2 days ago] 日本語チャンネル — Title"
+        );
+        assert_eq!(
+            format_video_line(2, &videos[1], None, &style),
+            "  2. [2 days ago] Channel          — Title"
+        );
+    }
+
+    #[test]
+    fn truncates_titles_to_terminal_width() {
+        let video = Video {
+            title: "A very long title that cannot possibly fit on one line".to_string(),
+            duration: Some(std::time::Duration::from_secs(754)),
+            ..make_video(VideoKind::Upload)
+        };
+        let style = style().fit(std::slice::from_ref(&video), Some(60));
+
+        let line = format_video_line(1, &video, None, &style);
+
+        assert_eq!(
+            line,
+            "  1. [2024-01-20] Channel — A very long title that… [12:34]"
+        );
+        assert!(display_width(&line) <= 60);
+    }
+
+    #[test]
+    fn reports_failed_channels_as_a_table() {
+        let failure = |name: &str, error| use_cases::ChannelFailure {
+            channel_id: ChannelId::parse("UC123").unwrap(),
+            channel_name: name.to_string(),
+            error,
+        };
+        let report = FetchReport {
+            failures: vec![
+                failure("Gone", FetchError::HttpError(404)),
+                failure(
+                    "Offline Channel",
+                    FetchError::Network("timeout".to_string()),
+                ),
+            ],
+        };
+
+        assert_eq!(
+            fetch_report_table(&report),
+            "Could not fetch 2 channels:\n\
+             \x20 Channel          Error     Suggestion\n\
+             \x20 Gone             HTTP 404  check the channel ID in the config\n\
+             \x20 Offline Channel  network   check the connection and the proxy setting\n"
+        );
+    }
+    #[test]
+    fn prints_fetch_timing_as_a_table() {
+        let channel = |name: &str, millis, videos| use_cases::ChannelTiming {
+            channel_name: name.to_string(),
+            elapsed: std::time::Duration::from_millis(millis),
+            videos,
+        };
+        let timing = FetchTiming {
+            channels: vec![
+                channel("Slow Channel", 12_340, Some(15)),
+                channel("Gone", 800, None),
+            ],
+            fetching: std::time::Duration::from_millis(12_500),
+            shorts_checked: 40,
+            shorts_checking: std::time::Duration::from_millis(2_100),
+        };
+
+        assert_eq!(
+            fetch_timing_table(&timing),
+            "Fetch timing, slowest first:\n\
+             \x20 Channel        Time  Videos\n\
+             \x20 Slow Channel  12.3s  15\n\
+             \x20 Gone           0.8s  failed\n\
+             Fetching 2 channels took 12.5s; checking 40 videos for Shorts took 2.1s.\n"
+        );
+    }
 }
@@ -0,0 +1,157 @@
+use chrono::SecondsFormat;
+use std::collections::HashMap;
+
+use crate::domain::channel::ChannelId;
+use crate::domain::video::{Video, VideoId, WatchProgress};
+use crate::presentation::json::kind_name;
+
+/// Column order of `--format tsv` and `csv`. Scripts pick columns by
+/// position, so add new ones at the end and never reorder these.
+const COLUMNS: &[&str] = &[
+    "number",
+    "id",
+    "published",
+    "channel",
+    "title",
+    "url",
+    "kind",
+    "duration_seconds",
+    "watched_percent",
+    "tags",
+];
+
+/// The listing, one line per video in `COLUMNS` order and no header, so
+/// lines can go straight to awk or dmenu.
+pub fn videos_tsv(
+    videos: &[Video],
+    aliases: &HashMap<ChannelId, String>,
+    progress: &HashMap<VideoId, WatchProgress>,
+) -> String {
+    rows(videos, aliases, progress)
+        .map(|row| {
+            row.iter()
+                .map(|v| tsv_field(v))
+                .collect::<Vec<_>>()
+                .join("\t")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The listing after a header line of `COLUMNS`, as spreadsheets expect.
+pub fn videos_csv(
+    videos: &[Video],
+    aliases: &HashMap<ChannelId, String>,
+    progress: &HashMap<VideoId, WatchProgress>,
+) -> String {
+    let rows = rows(videos, aliases, progress).map(|row| {
+        row.iter()
+            .map(|v| csv_field(v))
+            .collect::<Vec<_>>()
+            .join(",")
+    });
+    std::iter::once(COLUMNS.join(","))
+        .chain(rows)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Each video's values in `COLUMNS` order. The channel is its alias if it
+/// has one, tags are comma-separated and unknown values are empty.
+fn rows<'a>(
+    videos: &'a [Video],
+    aliases: &'a HashMap<ChannelId, String>,
+    progress: &'a HashMap<VideoId, WatchProgress>,
+) -> impl Iterator<Item = [String; 10]> + 'a {
+    videos.iter().enumerate().map(|(i, video)| {
+        let channel = aliases
+            .get(&video.channel_id)
+            .unwrap_or(&video.channel_name);
+        [
+            (i + 1).to_string(),
+            video.id.to_string(),
+            video.published.to_rfc3339_opts(SecondsFormat::Secs, true),
+            channel.clone(),
+            video.title.clone(),
+            video.url.clone(),
+            kind_name(video.kind).to_string(),
+            video
+                .duration
+                .map(|d| d.as_secs().to_string())
+                .unwrap_or_default(),
+            progress
+                .get(&video.id)
+                .map(|p| p.percent().to_string())
+                .unwrap_or_default(),
+            video
+                .tags
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(","),
+        ]
+    })
+}
+
+/// TSV has no quoting, so tabs and line breaks in a title become spaces.
+fn tsv_field(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+/// Quotes a value holding a comma, quote or line break, doubling its quotes.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::channel::ChannelTag;
+    use crate::domain::video::VideoKind;
+    use chrono::{TimeZone, Utc};
+    use std::time::Duration;
+
+    fn video() -> Video {
+        Video {
+            id: VideoId::parse("v1").unwrap(),
+            title: "Tabs\tand \"quotes\", too".to_string(),
+            url: "https://www.youtube.com/watch?v=v1".to_string(),
+            published: Utc.with_ymd_and_hms(2024, 1, 20, 15, 0, 0).unwrap(),
+            channel_name: "Channel".to_string(),
+            channel_id: ChannelId::parse("UC123").unwrap(),
+            kind: VideoKind::Upload,
+            duration: Some(Duration::from_secs(754)),
+            tags: vec![
+                ChannelTag::parse("music").unwrap(),
+                ChannelTag::parse("live").unwrap(),
+            ],
+        }
+    }
+
+    #[test]
+    fn prints_tsv_without_a_header() {
+        let aliases = HashMap::from([(video().channel_id, "Ch".to_string())]);
+
+        assert_eq!(
+            videos_tsv(&[video()], &aliases, &HashMap::new()),
+            "1\tv1\t2024-01-20T15:00:00Z\tCh\tTabs and \"quotes\", too\t\
+             https://www.youtube.com/watch?v=v1\tupload\t754\t\tmusic,live"
+        );
+    }
+
+    #[test]
+    fn prints_csv_with_a_header_and_quotes() {
+        let progress = HashMap::from([(video().id, WatchProgress::from_percent(45.0).unwrap())]);
+
+        assert_eq!(
+            videos_csv(&[video()], &HashMap::new(), &progress),
+            "number,id,published,channel,title,url,kind,duration_seconds,watched_percent,tags\n\
+             1,v1,2024-01-20T15:00:00Z,Channel,\"Tabs\tand \"\"quotes\"\", too\",\
+             https://www.youtube.com/watch?v=v1,upload,754,45,\"music,live\""
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use crate::application::ports::PlayError;
+use crate::application::use_cases::AppError;
+use crate::infrastructure::config::ConfigError;
+use crate::infrastructure::instance_lock::LockError;
+use crate::presentation::args::ArgsError;
+
+/// Exit codes scripts can tell apart. Any other failure exits with 1.
+pub const USAGE_ERROR: i32 = 2;
+pub const CONFIG_ERROR: i32 = 3;
+pub const ALL_CHANNELS_FAILED: i32 = 4;
+pub const SOME_CHANNELS_FAILED: i32 = 5;
+pub const NOTHING_UNWATCHED: i32 = 6;
+pub const PLAYER_FAILED: i32 = 7;
+pub const ALREADY_RUNNING: i32 = 8;
+
+/// Ends a run with one of the exit codes above rather than 1.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Exit {
+    /// `blepo config check` found this many problems in the config file.
+    ConfigProblems(usize, String),
+    /// Every one of this many channels failed to fetch.
+    AllChannelsFailed(usize),
+    /// This many channels failed to fetch; the rest were shown. They were
+    /// already reported after the listing, so nothing more is printed.
+    SomeChannelsFailed(usize),
+    /// The listing was empty; "No unwatched videos." was already printed, or
+    /// nothing was asked to be.
+    NothingUnwatched,
+}
+
+impl std::fmt::Display for Exit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Exit::ConfigProblems(count, path) => write!(f, "{count} problem(s) in {path}"),
+            Exit::AllChannelsFailed(count) => {
+                write!(f, "none of the {count} channels could be fetched")
+            }
+            Exit::SomeChannelsFailed(count) => write!(f, "{count} channels could not be fetched"),
+            Exit::NothingUnwatched => write!(f, "no unwatched videos"),
+        }
+    }
+}
+
+impl std::error::Error for Exit {}
+
+/// `SomeChannelsFailed` when a fetch lost channels, else `NothingUnwatched`
+/// when it found nothing, else success.
+pub fn fetch_outcome(
+    failed_channels: usize,
+    nothing_unwatched: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if failed_channels > 0 {
+        Err(Exit::SomeChannelsFailed(failed_channels).into())
+    } else if nothing_unwatched {
+        Err(Exit::NothingUnwatched.into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Whether `error` was already reported, so printing it would repeat it.
+pub fn is_silent(error: &(dyn std::error::Error + 'static)) -> bool {
+    matches!(
+        error.downcast_ref::<Exit>(),
+        Some(Exit::SomeChannelsFailed(_) | Exit::NothingUnwatched)
+    )
+}
+
+pub fn exit_code(error: &(dyn std::error::Error + 'static)) -> i32 {
+    if let Some(exit) = error.downcast_ref::<Exit>() {
+        return match exit {
+            Exit::ConfigProblems(..) => CONFIG_ERROR,
+            Exit::AllChannelsFailed(_) => ALL_CHANNELS_FAILED,
+            Exit::SomeChannelsFailed(_) => SOME_CHANNELS_FAILED,
+            Exit::NothingUnwatched => NOTHING_UNWATCHED,
+        };
+    }
+    if error.is::<ArgsError>() {
+        USAGE_ERROR
+    } else if error.is::<ConfigError>() {
+        CONFIG_ERROR
+    } else if matches!(error.downcast_ref(), Some(LockError::Busy(_))) {
+        ALREADY_RUNNING
+    } else if error.is::<PlayError>() || matches!(error.downcast_ref(), Some(AppError::Play(_))) {
+        PLAYER_FAILED
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(error: impl std::error::Error + 'static) -> i32 {
+        exit_code(&error)
+    }
+
+    #[test]
+    fn tells_failures_apart() {
+        assert_eq!(code(ArgsError::MissingTag), USAGE_ERROR);
+        assert_eq!(code(ConfigError::Read("denied".to_string())), CONFIG_ERROR);
+        assert_eq!(
+            code(AppError::Play(PlayError::PlayerFailed("mpv".to_string()))),
+            PLAYER_FAILED
+        );
+        assert_eq!(code(Exit::AllChannelsFailed(3)), ALL_CHANNELS_FAILED);
+        assert_eq!(code(LockError::Busy(Some(42))), ALREADY_RUNNING);
+        assert_eq!(code(std::io::Error::other("disk full")), 1);
+    }
+
+    #[test]
+    fn partial_failure_outranks_an_empty_listing() {
+        let code = |result: Result<(), Box<dyn std::error::Error>>| {
+            result.map_err(|e| (exit_code(e.as_ref()), is_silent(e.as_ref())))
+        };
+
+        assert_eq!(code(fetch_outcome(0, false)), Ok(()));
+        assert_eq!(code(fetch_outcome(0, true)), Err((NOTHING_UNWATCHED, true)));
+        assert_eq!(
+            code(fetch_outcome(2, true)),
+            Err((SOME_CHANNELS_FAILED, true))
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::collections::BTreeSet;
+use std::io::IsTerminal;
+
+/// Height of a thumbnail in text rows, where the protocol lets us choose.
+const THUMBNAIL_ROWS: usize = 3;
+/// Kitty accepts the payload in chunks of at most this many base64 bytes.
+const KITTY_CHUNK: usize = 4096;
+
+/// Inline image protocols, picked from the environment. Terminals are not
+/// queried, so an unrecognised one simply gets no images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphicsProtocol {
+    Kitty,
+    Iterm2,
+    Sixel,
+}
+
+impl GraphicsProtocol {
+    pub fn detect() -> Option<Self> {
+        if !std::io::stdout().is_terminal() {
+            return None;
+        }
+        Self::from_env(|key| std::env::var(key).ok())
+    }
+
+    fn from_env(var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        // tmux swallows graphics escapes unless passthrough is configured.
+        if var("TMUX").is_some() {
+            return None;
+        }
+        let term = var("TERM").unwrap_or_default();
+        let program = var("TERM_PROGRAM").unwrap_or_default();
+        if term == "xterm-kitty" || var("KITTY_WINDOW_ID").is_some() || program == "ghostty" {
+            Some(GraphicsProtocol::Kitty)
+        } else if program == "iTerm.app"
+            || program == "WezTerm"
+            || var("LC_TERMINAL").as_deref() == Some("iTerm2")
+        {
+            Some(GraphicsProtocol::Iterm2)
+        } else if term.starts_with("foot") || term.starts_with("mlterm") || term.contains("sixel") {
+            Some(GraphicsProtocol::Sixel)
+        } else {
+            None
+        }
+    }
+
+    /// Escape sequence drawing the JPEG, leaving the cursor at the start of
+    /// the line below it. `None` if the image can't be decoded.
+    pub fn render(self, jpeg: &[u8]) -> Option<String> {
+        match self {
+            GraphicsProtocol::Iterm2 => Some(iterm2(jpeg)),
+            GraphicsProtocol::Kitty => decode(jpeg).map(|image| kitty(&image)),
+            GraphicsProtocol::Sixel => decode(jpeg).map(|image| sixel(&image.halved())),
+        }
+    }
+}
+
+/// Decoded 8-bit RGB pixels, row by row.
+struct Rgb {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Rgb {
+    fn at(&self, x: usize, y: usize) -> [u8; 3] {
+        let i = (y * self.width + x) * 3;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]]
+    }
+
+    /// Every other pixel in both directions.
+    fn halved(&self) -> Rgb {
+        let (width, height) = (self.width.div_ceil(2), self.height.div_ceil(2));
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).flat_map(move |x| self.at(x * 2, y * 2)))
+            .collect();
+        Rgb {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+fn decode(jpeg: &[u8]) -> Option<Rgb> {
+    let mut decoder = jpeg_decoder::Decoder::new(jpeg);
+    let pixels = decoder.decode().ok()?;
+    let info = decoder.info()?;
+    let pixels = match info.pixel_format {
+        jpeg_decoder::PixelFormat::RGB24 => pixels,
+        jpeg_decoder::PixelFormat::L8 => pixels.iter().flat_map(|&l| [l, l, l]).collect(),
+        _ => return None,
+    };
+    Some(Rgb {
+        width: usize::from(info.width),
+        height: usize::from(info.height),
+        pixels,
+    })
+}
+
+fn iterm2(jpeg: &[u8]) -> String {
+    format!(
+        "\x1b]1337;File=inline=1;size={};height={THUMBNAIL_ROWS};preserveAspectRatio=1:{}\x07\n",
+        jpeg.len(),
+        STANDARD.encode(jpeg)
+    )
+}
+
+fn kitty(image: &Rgb) -> String {
+    let payload = STANDARD.encode(&image.pixels);
+    let chunks: Vec<&[u8]> = payload.as_bytes().chunks(KITTY_CHUNK).collect();
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        let chunk = std::str::from_utf8(chunk).expect("base64 is ASCII");
+        if i == 0 {
+            // q=2 keeps kitty from answering on stdin, where the prompt reads.
+            out.push_str(&format!(
+                "\x1b_Ga=T,q=2,f=24,s={},v={},r={THUMBNAIL_ROWS},m={more};{chunk}\x1b\\",
+                image.width, image.height
+            ));
+        } else {
+            out.push_str(&format!("\x1b_Gm={more};{chunk}\x1b\\"));
+        }
+    }
+    out.push('\n');
+    out
+}
+
+/// Index into a 6x6x6 colour cube.
+fn palette_index([r, g, b]: [u8; 3]) -> usize {
+    let level = |c: u8| (usize::from(c) * 5 + 127) / 255;
+    level(r) * 36 + level(g) * 6 + level(b)
+}
+
+fn sixel(image: &Rgb) -> String {
+    let mut out = format!("\x1bPq\"1;1;{};{}", image.width, image.height);
+    for index in 0..216 {
+        let percent = |level: usize| level * 100 / 5;
+        out.push_str(&format!(
+            "#{index};2;{};{};{}",
+            percent(index / 36),
+            percent(index / 6 % 6),
+            percent(index % 6)
+        ));
+    }
+
+    for top in (0..image.height).step_by(6) {
+        let rows = top..(top + 6).min(image.height);
+        let colors: BTreeSet<usize> = rows
+            .clone()
+            .flat_map(|y| (0..image.width).map(move |x| palette_index(image.at(x, y))))
+            .collect();
+        for (n, color) in colors.into_iter().enumerate() {
+            if n > 0 {
+                out.push('$');
+            }
+            out.push_str(&format!("#{color}"));
+            let column = |x: usize| {
+                let bits = rows
+                    .clone()
+                    .filter(|&y| palette_index(image.at(x, y)) == color)
+                    .fold(0u8, |bits, y| bits | 1 << (y - top));
+                char::from(63 + bits)
+            };
+            push_runs(&mut out, (0..image.width).map(column));
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+/// Sixel run-length encoding: `!<count><char>` for runs longer than three.
+fn push_runs(out: &mut String, chars: impl Iterator<Item = char>) {
+    let mut run: Option<(char, usize)> = None;
+    let flush = |out: &mut String, (c, count): (char, usize)| {
+        if count > 3 {
+            out.push_str(&format!("!{count}{c}"));
+        } else {
+            out.extend(std::iter::repeat_n(c, count));
+        }
+    };
+    for c in chars {
+        run = match run {
+            Some((prev, count)) if prev == c => Some((prev, count + 1)),
+            Some(done) => {
+                flush(out, done);
+                Some((c, 1))
+            }
+            None => Some((c, 1)),
+        };
+    }
+    if let Some(done) = run {
+        flush(out, done);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn detect(vars: &[(&str, &str)]) -> Option<GraphicsProtocol> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        GraphicsProtocol::from_env(|key| vars.get(key).cloned())
+    }
+
+    fn solid(width: usize, height: usize, color: [u8; 3]) -> Rgb {
+        Rgb {
+            width,
+            height,
+            pixels: color.repeat(width * height),
+        }
+    }
+
+    #[test]
+    fn detects_protocol_from_environment() {
+        assert_eq!(
+            detect(&[("TERM", "xterm-kitty")]),
+            Some(GraphicsProtocol::Kitty)
+        );
+        assert_eq!(
+            detect(&[("TERM_PROGRAM", "iTerm.app")]),
+            Some(GraphicsProtocol::Iterm2)
+        );
+        assert_eq!(detect(&[("TERM", "foot")]), Some(GraphicsProtocol::Sixel));
+        assert_eq!(detect(&[("TERM", "xterm-256color")]), None);
+    }
+
+    #[test]
+    fn no_images_inside_tmux() {
+        assert_eq!(
+            detect(&[
+                ("TERM", "xterm-kitty"),
+                ("TMUX", "/tmp/tmux-1000/default,1,0")
+            ]),
+            None
+        );
+    }
+
+    #[test]
+    fn iterm2_embeds_file_as_is() {
+        assert_eq!(
+            iterm2(b"jpeg"),
+            "\x1b]1337;File=inline=1;size=4;height=3;preserveAspectRatio=1:anBlZw==\x07\n"
+        );
+    }
+
+    #[test]
+    fn kitty_splits_payload_into_chunks() {
+        let image = solid(40, 40, [255, 0, 0]);
+
+        let out = kitty(&image);
+
+        assert!(out.starts_with("\x1b_Ga=T,q=2,f=24,s=40,v=40,r=3,m=1;"));
+        assert_eq!(out.matches("\x1b_G").count(), 2);
+        assert!(out.contains("\x1b_Gm=0;"));
+    }
+
+    #[test]
+    fn quantizes_to_colour_cube() {
+        assert_eq!(palette_index([0, 0, 0]), 0);
+        assert_eq!(palette_index([255, 255, 255]), 215);
+        assert_eq!(palette_index([255, 0, 0]), 180);
+    }
+
+    #[test]
+    fn sixel_encodes_bands_with_runs() {
+        let out = sixel(&solid(8, 6, [255, 0, 0]));
+
+        assert!(out.starts_with("\x1bPq\"1;1;8;6"));
+        assert!(out.ends_with("#180!8~-\x1b\\"));
+    }
+
+    #[test]
+    fn halving_keeps_every_other_pixel() {
+        let image = solid(5, 3, [1, 2, 3]).halved();
+
+        assert_eq!((image.width, image.height), (3, 2));
+        assert_eq!(image.pixels.len(), 3 * 2 * 3);
+    }
+}
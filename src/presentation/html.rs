@@ -0,0 +1,130 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, FixedOffset, Utc};
+use quick_xml::escape::escape;
+use std::collections::HashMap;
+
+use crate::domain::channel::ChannelId;
+use crate::domain::video::{Video, VideoKind};
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;background:#111;color:#eee}\
+a{color:inherit;text-decoration:none}\
+ul{list-style:none;padding:0;display:grid;gap:1.5em;grid-template-columns:repeat(auto-fill,minmax(16em,1fr))}\
+img{width:100%;aspect-ratio:4/3;object-fit:cover;background:#333}\
+.title{display:block;font-weight:bold;margin:.3em 0}\
+.meta{color:#aaa;font-size:.9em}";
+
+/// The unwatched videos as one HTML page with no outside assets but the
+/// videos' links: styles are inline and `thumbnails[i]`, the JPEG of
+/// `videos[i]` when known, is embedded. Scheduled premieres and streams are
+/// left out: there is nothing to watch yet.
+pub fn videos_html(
+    videos: &[Video],
+    thumbnails: &[Option<Vec<u8>>],
+    aliases: &HashMap<ChannelId, String>,
+    now: DateTime<Utc>,
+    utc_offset: fn(DateTime<Utc>) -> FixedOffset,
+) -> String {
+    let local = |time: DateTime<Utc>| {
+        time.with_timezone(&utc_offset(time))
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    };
+    let mut lines = vec![
+        "<!DOCTYPE html>".to_string(),
+        "<html>".to_string(),
+        "<head>".to_string(),
+        "<meta charset=\"utf-8\">".to_string(),
+        "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">".to_string(),
+        "<title>blepo: unwatched videos</title>".to_string(),
+        format!("<style>{STYLE}</style>"),
+        "</head>".to_string(),
+        "<body>".to_string(),
+        "<h1>Unwatched videos</h1>".to_string(),
+        format!("<p class=\"meta\">Updated {}</p>", local(now)),
+        "<ul>".to_string(),
+    ];
+    for (video, thumbnail) in videos.iter().zip(thumbnails) {
+        if video.kind == VideoKind::Upcoming {
+            continue;
+        }
+        let channel = aliases
+            .get(&video.channel_id)
+            .unwrap_or(&video.channel_name);
+        let url = escape(&video.url);
+        lines.push("<li>".to_string());
+        match thumbnail {
+            Some(jpeg) => lines.push(format!(
+                "<a href=\"{url}\"><img src=\"data:image/jpeg;base64,{}\" alt=\"\"></a>",
+                STANDARD.encode(jpeg)
+            )),
+            None => lines.push(format!("<a href=\"{url}\"><img alt=\"\"></a>")),
+        }
+        lines.push(format!(
+            "<a class=\"title\" href=\"{url}\">{}</a>",
+            escape(&video.title)
+        ));
+        lines.push(format!(
+            "<span class=\"meta\">{} · {}</span>",
+            escape(channel),
+            local(video.published)
+        ));
+        lines.push("</li>".to_string());
+    }
+    lines.extend(["</ul>", "</body>", "</html>"].map(str::to_string));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::video::VideoId;
+    use chrono::TimeZone;
+
+    fn utc(_: DateTime<Utc>) -> FixedOffset {
+        FixedOffset::east_opt(0).unwrap()
+    }
+
+    fn video(id: &str, kind: VideoKind) -> Video {
+        Video {
+            id: VideoId::parse(id).unwrap(),
+            title: "Fish & <Chips>".to_string(),
+            url: format!("https://www.youtube.com/watch?v={id}&t=1"),
+            published: Utc.with_ymd_and_hms(2024, 1, 20, 15, 0, 0).unwrap(),
+            channel_name: "Channel".to_string(),
+            channel_id: ChannelId::parse("UC123").unwrap(),
+            kind,
+            duration: None,
+            tags: vec![],
+        }
+    }
+
+    #[test]
+    fn lists_watchable_videos_with_embedded_thumbnails() {
+        let videos = [
+            video("v1", VideoKind::Upload),
+            video("v2", VideoKind::Upcoming),
+            video("v3", VideoKind::Live),
+        ];
+        let thumbnails = [Some(b"jpeg".to_vec()), None, None];
+        let aliases = HashMap::from([(videos[0].channel_id.clone(), "Ch".to_string())]);
+        let now = Utc.with_ymd_and_hms(2024, 2, 1, 8, 0, 0).unwrap();
+
+        let page = videos_html(&videos, &thumbnails, &aliases, now, utc);
+
+        assert!(page.contains("<p class=\"meta\">Updated 2024-02-01 08:00</p>"));
+        assert!(page.contains(
+            "<li>\n\
+             <a href=\"https://www.youtube.com/watch?v=v1&amp;t=1\">\
+             <img src=\"data:image/jpeg;base64,anBlZw==\" alt=\"\"></a>\n\
+             <a class=\"title\" href=\"https://www.youtube.com/watch?v=v1&amp;t=1\">\
+             Fish &amp; &lt;Chips&gt;</a>\n\
+             <span class=\"meta\">Ch · 2024-01-20 15:00</span>\n\
+             </li>"
+        ));
+        assert!(!page.contains("v=v2"));
+        assert!(page
+            .contains("<a href=\"https://www.youtube.com/watch?v=v3&amp;t=1\"><img alt=\"\"></a>"));
+        assert!(page.ends_with("</ul>\n</body>\n</html>"));
+    }
+}
@@ -0,0 +1,141 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use crate::domain::channel::ChannelId;
+use crate::domain::video::{Video, VideoKind};
+
+/// How long an event lasts when the video's duration isn't known.
+const DEFAULT_EVENT_LENGTH: chrono::Duration = chrono::Duration::hours(1);
+
+/// Longest content line, in bytes, before it is folded (RFC 5545 3.1).
+const MAX_LINE_BYTES: usize = 75;
+
+/// Scheduled premieres and streams as an iCalendar file, one event each,
+/// starting when the video is scheduled to air. Other videos are left out.
+pub fn upcoming_ical(
+    videos: &[Video],
+    aliases: &HashMap<ChannelId, String>,
+    now: DateTime<Utc>,
+) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//blepo//upcoming videos//EN".to_string(),
+        "X-WR-CALNAME:blepo: upcoming videos".to_string(),
+    ];
+    for video in videos.iter().filter(|v| v.kind == VideoKind::Upcoming) {
+        let channel = aliases
+            .get(&video.channel_id)
+            .unwrap_or(&video.channel_name);
+        let length = video
+            .duration
+            .and_then(|d| chrono::Duration::from_std(d).ok())
+            .unwrap_or(DEFAULT_EVENT_LENGTH);
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}@youtube.com", video.id),
+            format!("DTSTAMP:{}", timestamp(now)),
+            format!("DTSTART:{}", timestamp(video.published)),
+            format!("DTEND:{}", timestamp(video.published + length)),
+            format!("SUMMARY:{}", text(&format!("{channel}: {}", video.title))),
+            format!("URL:{}", video.url),
+            format!("DESCRIPTION:{}", text(&video.url)),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines
+        .iter()
+        .map(|line| fold(line) + "\r\n")
+        .collect::<String>()
+}
+
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escapes a TEXT value: backslashes, semicolons, commas and line breaks.
+fn text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\n', '\r'], "\\n")
+}
+
+/// Splits a line longer than `MAX_LINE_BYTES` into continuation lines,
+/// each starting with a space, without cutting a character in two.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_BYTES {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::video::VideoId;
+    use chrono::TimeZone;
+
+    fn video(id: &str, kind: VideoKind) -> Video {
+        Video {
+            id: VideoId::parse(id).unwrap(),
+            title: "Q&A; part 2, live".to_string(),
+            url: format!("https://www.youtube.com/watch?v={id}"),
+            published: Utc.with_ymd_and_hms(2024, 1, 20, 15, 0, 0).unwrap(),
+            channel_name: "Channel".to_string(),
+            channel_id: ChannelId::parse("UC123").unwrap(),
+            kind,
+            duration: None,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn writes_an_event_per_upcoming_video() {
+        let videos = [
+            video("v1", VideoKind::Upload),
+            video("v2", VideoKind::Upcoming),
+        ];
+        let now = Utc.with_ymd_and_hms(2024, 1, 19, 8, 0, 0).unwrap();
+
+        assert_eq!(
+            upcoming_ical(&videos, &HashMap::new(), now),
+            "BEGIN:VCALENDAR\r\n\
+             VERSION:2.0\r\n\
+             PRODID:-//blepo//upcoming videos//EN\r\n\
+             X-WR-CALNAME:blepo: upcoming videos\r\n\
+             BEGIN:VEVENT\r\n\
+             UID:v2@youtube.com\r\n\
+             DTSTAMP:20240119T080000Z\r\n\
+             DTSTART:20240120T150000Z\r\n\
+             DTEND:20240120T160000Z\r\n\
+             SUMMARY:Channel: Q&A\\; part 2\\, live\r\n\
+             URL:https://www.youtube.com/watch?v=v2\r\n\
+             DESCRIPTION:https://www.youtube.com/watch?v=v2\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n"
+        );
+    }
+
+    #[test]
+    fn folds_long_lines_between_characters() {
+        let line = format!("SUMMARY:{}", "é".repeat(40));
+
+        let folded = fold(&line);
+
+        assert!(folded
+            .split("\r\n")
+            .all(|part| part.len() <= MAX_LINE_BYTES));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+}
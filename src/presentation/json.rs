@@ -0,0 +1,298 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::args::ArgsError;
+use super::exit::Exit;
+use crate::application::ports::{FetchError, PlayError, StoreError};
+use crate::application::use_cases::{AppError, ChannelFailure, ChannelProblem, ChannelStats};
+use crate::domain::channel::{Channel, ChannelId, ChannelTag};
+use crate::domain::video::{Video, VideoId, VideoKind, WatchProgress};
+use crate::infrastructure::config::ConfigError;
+use crate::infrastructure::instance_lock::LockError;
+
+/// A listed video as `--json` prints it. Field names are part of blepo's
+/// interface: add fields, but don't rename or remove them.
+#[derive(Serialize)]
+struct JsonVideo<'a> {
+    /// What `blepo open <n>` and the prompt call it.
+    number: usize,
+    id: &'a VideoId,
+    title: &'a str,
+    url: &'a str,
+    published: DateTime<Utc>,
+    channel: JsonChannel<'a>,
+    kind: &'static str,
+    duration_seconds: Option<u64>,
+    tags: &'a [ChannelTag],
+    watched_percent: Option<u8>,
+}
+
+#[derive(Serialize)]
+struct JsonChannel<'a> {
+    id: &'a ChannelId,
+    name: &'a str,
+    alias: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct JsonChannelStats<'a> {
+    channel: JsonChannel<'a>,
+    uploads: Option<usize>,
+    watched: Option<usize>,
+    uploads_per_week: Option<f64>,
+    last_upload: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize)]
+struct JsonChannelProblem<'a> {
+    channel: JsonChannel<'a>,
+    problem: &'static str,
+    message: String,
+}
+
+/// An error as `--json` reports it on stderr, one object per line. `kind`
+/// names are part of blepo's interface, like the listing's fields.
+#[derive(Serialize)]
+struct JsonError<'a> {
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    channel_id: Option<&'a ChannelId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    channel: Option<&'a str>,
+    message: String,
+}
+
+/// The listing, numbered in order, with each video's channel alias and how
+/// far it was played.
+pub fn videos_json(
+    videos: &[Video],
+    aliases: &HashMap<ChannelId, String>,
+    progress: &HashMap<VideoId, WatchProgress>,
+) -> String {
+    let videos: Vec<JsonVideo> = videos
+        .iter()
+        .enumerate()
+        .map(|(i, video)| JsonVideo {
+            number: i + 1,
+            id: &video.id,
+            title: &video.title,
+            url: &video.url,
+            published: video.published,
+            channel: JsonChannel {
+                id: &video.channel_id,
+                name: &video.channel_name,
+                alias: aliases.get(&video.channel_id).map(String::as_str),
+            },
+            kind: kind_name(video.kind),
+            duration_seconds: video.duration.map(|d| d.as_secs()),
+            tags: &video.tags,
+            watched_percent: progress.get(&video.id).map(|p| p.percent()),
+        })
+        .collect();
+    pretty(&videos)
+}
+
+/// `channel_stats` results; a channel with no known uploads has `null`
+/// figures.
+pub fn channel_stats_json(stats: &[(&Channel, Option<ChannelStats>)]) -> String {
+    let stats: Vec<JsonChannelStats> = stats
+        .iter()
+        .map(|(channel, stats)| JsonChannelStats {
+            channel: json_channel(channel),
+            uploads: stats.as_ref().map(|s| s.uploads),
+            watched: stats.as_ref().map(|s| s.watched),
+            uploads_per_week: stats.as_ref().map(|s| s.per_week),
+            last_upload: stats.as_ref().map(|s| s.last_upload),
+        })
+        .collect();
+    pretty(&stats)
+}
+
+/// `check_channels` results, each problem with a stable name and the
+/// sentence the table shows.
+pub fn channel_problems_json(problems: &[(&Channel, ChannelProblem)]) -> String {
+    let problems: Vec<JsonChannelProblem> = problems
+        .iter()
+        .map(|(channel, problem)| JsonChannelProblem {
+            channel: json_channel(channel),
+            problem: match problem {
+                ChannelProblem::NotFound => "not_found",
+                ChannelProblem::Empty => "empty",
+                ChannelProblem::Renamed(_) => "renamed",
+                ChannelProblem::Unreachable(_) => "unreachable",
+            },
+            message: problem.to_string(),
+        })
+        .collect();
+    pretty(&problems)
+}
+
+/// A channel left out of a fetch, for `--json` runs to report on stderr
+/// instead of a warning.
+pub fn channel_failure_json(failure: &ChannelFailure) -> String {
+    compact(&JsonError {
+        kind: fetch_error_kind(&failure.error),
+        channel_id: Some(&failure.channel_id),
+        channel: Some(&failure.channel_name),
+        message: failure.error.to_string(),
+    })
+}
+
+/// The error that ended a `--json` run, for stderr instead of `Error: ...`.
+pub fn error_json(error: &(dyn std::error::Error + 'static)) -> String {
+    compact(&JsonError {
+        kind: error_kind(error),
+        channel_id: None,
+        channel: None,
+        message: error.to_string(),
+    })
+}
+
+fn error_kind(error: &(dyn std::error::Error + 'static)) -> &'static str {
+    if error.is::<ArgsError>() {
+        return "usage";
+    }
+    if error.is::<ConfigError>() {
+        return "config";
+    }
+    if matches!(error.downcast_ref(), Some(LockError::Busy(_))) {
+        return "already_running";
+    }
+    if let Some(exit) = error.downcast_ref::<Exit>() {
+        return match exit {
+            Exit::ConfigProblems(..) => "config",
+            Exit::AllChannelsFailed(_) => "all_channels_failed",
+            Exit::SomeChannelsFailed(_) => "some_channels_failed",
+            Exit::NothingUnwatched => "nothing_unwatched",
+        };
+    }
+    match error.downcast_ref::<AppError>() {
+        Some(AppError::Store(_)) => return "store",
+        Some(AppError::Fetch(e)) => return fetch_error_kind(e),
+        Some(AppError::Play(_)) => return "player",
+        Some(AppError::NoSuchJob(_)) | None => {}
+    }
+    if error.is::<StoreError>() {
+        "store"
+    } else if let Some(e) = error.downcast_ref::<FetchError>() {
+        fetch_error_kind(e)
+    } else if error.is::<PlayError>() {
+        "player"
+    } else {
+        "other"
+    }
+}
+
+fn fetch_error_kind(error: &FetchError) -> &'static str {
+    match error {
+        FetchError::Network(_) => "network",
+        FetchError::HttpError(_) => "http",
+        FetchError::RateLimited(_) => "rate_limited",
+        FetchError::Parse(_) => "parse",
+        FetchError::YtDlp(_) => "yt_dlp",
+    }
+}
+
+fn json_channel(channel: &Channel) -> JsonChannel<'_> {
+    JsonChannel {
+        id: &channel.id,
+        name: &channel.name,
+        alias: channel.alias.as_deref(),
+    }
+}
+
+pub(super) fn kind_name(kind: VideoKind) -> &'static str {
+    match kind {
+        VideoKind::Upload => "upload",
+        VideoKind::LivestreamVod => "stream",
+        VideoKind::Short => "short",
+        VideoKind::Upcoming => "upcoming",
+        VideoKind::Live => "live",
+    }
+}
+
+fn pretty<T: Serialize>(value: &T) -> String {
+    serde_json::to_string_pretty(value).expect("JSON output only holds strings and numbers")
+}
+
+fn compact<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("JSON output only holds strings and numbers")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::time::Duration;
+
+    #[test]
+    fn prints_videos_with_their_channel() {
+        let video = Video {
+            id: VideoId::parse("v1").unwrap(),
+            title: "Title".to_string(),
+            url: "https://www.youtube.com/watch?v=v1".to_string(),
+            published: Utc.with_ymd_and_hms(2024, 1, 20, 15, 0, 0).unwrap(),
+            channel_name: "Channel".to_string(),
+            channel_id: ChannelId::parse("UC123").unwrap(),
+            kind: VideoKind::LivestreamVod,
+            duration: Some(Duration::from_secs(754)),
+            tags: vec![ChannelTag::parse("music").unwrap()],
+        };
+        let aliases = HashMap::from([(video.channel_id.clone(), "Ch".to_string())]);
+        let progress =
+            HashMap::from([(video.id.clone(), WatchProgress::from_percent(45.0).unwrap())]);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&videos_json(&[video], &aliases, &progress)).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "number": 1,
+                "id": "v1",
+                "title": "Title",
+                "url": "https://www.youtube.com/watch?v=v1",
+                "published": "2024-01-20T15:00:00Z",
+                "channel": {"id": "UC123", "name": "Channel", "alias": "Ch"},
+                "kind": "stream",
+                "duration_seconds": 754,
+                "tags": ["music"],
+                "watched_percent": 45
+            }])
+        );
+    }
+
+    #[test]
+    fn reports_a_failed_channel_with_its_id() {
+        let failure = ChannelFailure {
+            channel_id: ChannelId::parse("UC123").unwrap(),
+            channel_name: "Channel".to_string(),
+            error: FetchError::HttpError(500),
+        };
+
+        assert_eq!(
+            channel_failure_json(&failure),
+            r#"{"kind":"http","channel_id":"UC123","channel":"Channel","message":"HTTP 500 from YouTube"}"#
+        );
+    }
+
+    #[test]
+    fn names_the_kind_of_a_fatal_error() {
+        let store = AppError::Store(StoreError::Read("bad JSON".to_string()));
+
+        assert_eq!(
+            error_json(&store),
+            r#"{"kind":"store","message":"store read error: bad JSON"}"#
+        );
+        assert!(error_json(&Exit::AllChannelsFailed(2)).contains(r#""kind":"all_channels_failed""#));
+        assert!(error_json(&std::io::Error::other("disk full")).contains(r#""kind":"other""#));
+        let yt_dlp = AppError::Fetch(FetchError::YtDlp(
+            "yt-dlp failed (exit status: 1)".to_string(),
+        ));
+        assert_eq!(
+            error_json(&yt_dlp),
+            r#"{"kind":"yt_dlp","message":"yt-dlp failed (exit status: 1)"}"#
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+const ELLIPSIS: char = '…';
+
+/// Columns the text occupies in a terminal: CJK and emoji count double,
+/// combining marks count zero.
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// Cuts `text` to at most `max` columns, ending in `…` when anything was cut.
+/// Whitespace before the ellipsis is dropped.
+pub fn truncate(text: &str, max: usize) -> String {
+    if display_width(text) <= max {
+        return text.to_string();
+    }
+    let budget = max.saturating_sub(1);
+    let mut out = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let width = c.width().unwrap_or(0);
+        if used + width > budget {
+            break;
+        }
+        out.push(c);
+        used += width;
+    }
+    out.truncate(out.trim_end().len());
+    if max > 0 {
+        out.push(ELLIPSIS);
+    }
+    out
+}
+
+/// Pads `text` with spaces to `width` columns; longer text is left alone.
+pub fn pad(text: &str, width: usize) -> String {
+    let fill = width.saturating_sub(display_width(text));
+    format!("{text}{}", " ".repeat(fill))
+}
+
+/// Width of the terminal on stdout, or `None` when not writing to one.
+pub fn terminal_width() -> Option<usize> {
+    terminal_size::terminal_size().map(|(width, _)| usize::from(width.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_characters_take_two_columns() {
+        assert_eq!(display_width("abc"), 3);
+        assert_eq!(display_width("日本語"), 6);
+        assert_eq!(display_width("é"), 1);
+    }
+
+    #[test]
+    fn truncates_to_column_budget() {
+        assert_eq!(truncate("Short", 10), "Short");
+        assert_eq!(truncate("A long title", 6), "A lon…");
+        assert_eq!(truncate("A long title", 8), "A long…");
+        assert_eq!(truncate("日本語のタイトル", 7), "日本語…");
+    }
+
+    #[test]
+    fn pads_by_display_width() {
+        assert_eq!(pad("日本", 6), "日本  ");
+        assert_eq!(pad("toolong", 3), "toolong");
+    }
+}
@@ -0,0 +1,149 @@
+use std::io::IsTerminal;
+
+use tracing::level_filters::LevelFilter;
+use tracing::Metadata;
+use tracing_subscriber::filter::{filter_fn, Targets};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::application::use_cases::PROGRESS_LOG_TARGET;
+use crate::infrastructure::http::HTTP_LOG_TARGET;
+
+/// How much log output reaches stderr: warnings unless `-v`, `-vv`, `-vvv`
+/// or `--log-level` ask for more, or `--log-level error` for less.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    #[default]
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.to_ascii_lowercase().as_str() {
+            "error" => Some(Self::Error),
+            "warn" | "warning" => Some(Self::Warn),
+            "info" => Some(Self::Info),
+            "debug" => Some(Self::Debug),
+            "trace" => Some(Self::Trace),
+            _ => None,
+        }
+    }
+
+    /// One step past the default per `v`.
+    pub fn verbose(count: usize) -> Self {
+        match count {
+            0 => Self::Warn,
+            1 => Self::Info,
+            2 => Self::Debug,
+            _ => Self::Trace,
+        }
+    }
+
+    fn filter(self) -> LevelFilter {
+        match self {
+            Self::Error => LevelFilter::ERROR,
+            Self::Warn => LevelFilter::WARN,
+            Self::Info => LevelFilter::INFO,
+            Self::Debug => LevelFilter::DEBUG,
+            Self::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One line per event, level first, then the channel being fetched.
+    #[default]
+    Text,
+    /// One JSON object per line, for cron jobs whose output is collected.
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "text" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Sends log events to stderr, where they don't mix with listings and
+/// exports on stdout. `debug_http` lets request details through whatever
+/// the level.
+pub fn init(level: LogLevel, format: LogFormat, debug_http: bool) {
+    let targets = filter(level, debug_http);
+    // blepo's own spans always, so every event shows the channel it is about.
+    let filter = filter_fn(move |metadata: &Metadata<'_>| {
+        (metadata.is_span() && metadata.target().starts_with(env!("CARGO_CRATE_NAME")))
+            || targets.would_enable(metadata.target(), metadata.level())
+    });
+    let builder = tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(LevelFilter::TRACE)
+        .with_target(false);
+    match format {
+        LogFormat::Text => builder
+            .without_time()
+            .with_ansi(std::io::stderr().is_terminal())
+            .finish()
+            .with(filter)
+            .init(),
+        LogFormat::Json => builder.json().finish().with(filter).init(),
+    }
+}
+
+/// Progress shows at every level but `error`, which only wants errors.
+fn filter(level: LogLevel, debug_http: bool) -> Targets {
+    let progress = match level {
+        LogLevel::Error => LevelFilter::ERROR,
+        _ => level.filter().max(LevelFilter::INFO),
+    };
+    let filter = Targets::new()
+        .with_default(level.filter())
+        .with_target(PROGRESS_LOG_TARGET, progress);
+    if debug_http {
+        filter.with_target(HTTP_LOG_TARGET, level.filter().max(LevelFilter::DEBUG))
+    } else {
+        filter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_levels_and_counts_verbosity() {
+        assert_eq!(LogLevel::parse("DEBUG"), Some(LogLevel::Debug));
+        assert_eq!(LogLevel::parse("warning"), Some(LogLevel::Warn));
+        assert_eq!(LogLevel::parse("loud"), None);
+        assert_eq!(LogLevel::verbose(0), LogLevel::default());
+        assert_eq!(LogLevel::verbose(2), LogLevel::Debug);
+        assert_eq!(LogLevel::verbose(5), LogLevel::Trace);
+    }
+
+    #[test]
+    fn debug_http_lets_request_details_through() {
+        let debug = |filter: Targets, target| filter.would_enable(target, &tracing::Level::DEBUG);
+
+        assert!(!debug(filter(LogLevel::Warn, false), HTTP_LOG_TARGET));
+        assert!(debug(filter(LogLevel::Warn, true), HTTP_LOG_TARGET));
+        assert!(!debug(filter(LogLevel::Warn, true), "blepo::application"));
+        assert!(debug(filter(LogLevel::Debug, false), HTTP_LOG_TARGET));
+    }
+
+    #[test]
+    fn progress_shows_unless_only_errors_are_wanted() {
+        let info = |filter: Targets, target| filter.would_enable(target, &tracing::Level::INFO);
+
+        assert!(info(filter(LogLevel::Warn, false), PROGRESS_LOG_TARGET));
+        assert!(!info(filter(LogLevel::Warn, false), "blepo::application"));
+        assert!(!info(filter(LogLevel::Error, false), PROGRESS_LOG_TARGET));
+    }
+}
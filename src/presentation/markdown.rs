@@ -0,0 +1,163 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use std::collections::HashMap;
+
+use super::args::MarkdownGrouping;
+use crate::domain::channel::ChannelId;
+use crate::domain::video::{channel_grouped_order, Video, VideoKind};
+
+/// The unwatched videos as a Markdown document, one section per day
+/// (newest first, in the viewer's time zone at `utc_offset`) or per channel,
+/// each video a list item linking its title to its URL. Scheduled premieres
+/// and streams are left out: there is nothing to watch yet.
+pub fn videos_markdown(
+    videos: &[Video],
+    aliases: &HashMap<ChannelId, String>,
+    grouping: MarkdownGrouping,
+    utc_offset: fn(DateTime<Utc>) -> FixedOffset,
+) -> String {
+    let videos: Vec<Video> = videos
+        .iter()
+        .filter(|video| video.kind != VideoKind::Upcoming)
+        .cloned()
+        .collect();
+    let mut lines = vec!["# blepo: unwatched videos".to_string()];
+    if videos.is_empty() {
+        lines.extend([String::new(), "No unwatched videos.".to_string()]);
+    }
+    let channel_name = |video: &Video| {
+        aliases
+            .get(&video.channel_id)
+            .unwrap_or(&video.channel_name)
+            .clone()
+    };
+    let day = |video: &Video| {
+        video
+            .published
+            .with_timezone(&utc_offset(video.published))
+            .date_naive()
+    };
+    let order: Vec<usize> = match grouping {
+        MarkdownGrouping::Day => (0..videos.len()).collect(),
+        MarkdownGrouping::Channel => channel_grouped_order(&videos),
+    };
+    let mut section = None;
+    for video in order.into_iter().map(|i| &videos[i]) {
+        let (heading, detail) = match grouping {
+            MarkdownGrouping::Day => (
+                day(video).format("%A, %-d %B %Y").to_string(),
+                channel_name(video),
+            ),
+            MarkdownGrouping::Channel => (
+                channel_name(video),
+                day(video).format("%Y-%m-%d").to_string(),
+            ),
+        };
+        if section.as_ref() != Some(&heading) {
+            lines.extend([
+                String::new(),
+                format!("## {}", escape(&heading)),
+                String::new(),
+            ]);
+            section = Some(heading);
+        }
+        lines.push(format!(
+            "- [{}]({}) — {}",
+            escape(&video.title),
+            video.url,
+            escape(&detail)
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Backslash-escapes the characters that would otherwise format text or
+/// end a link early.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::video::VideoId;
+    use chrono::TimeZone;
+
+    fn utc(_: DateTime<Utc>) -> FixedOffset {
+        FixedOffset::east_opt(0).unwrap()
+    }
+
+    fn video(id: &str, channel: &str, day: u32, kind: VideoKind) -> Video {
+        Video {
+            id: VideoId::parse(id).unwrap(),
+            title: format!("Fish [{id}] *chips*"),
+            url: format!("https://www.youtube.com/watch?v={id}"),
+            published: Utc.with_ymd_and_hms(2024, 1, day, 15, 0, 0).unwrap(),
+            channel_name: channel.to_string(),
+            channel_id: ChannelId::parse(format!("UC{channel}")).unwrap(),
+            kind,
+            duration: None,
+            tags: vec![],
+        }
+    }
+
+    fn videos() -> [Video; 4] {
+        [
+            video("v1", "Food", 20, VideoKind::Upload),
+            video("v2", "Cars", 20, VideoKind::Upload),
+            video("v3", "Food", 19, VideoKind::Upload),
+            video("v4", "Cars", 25, VideoKind::Upcoming),
+        ]
+    }
+
+    #[test]
+    fn groups_watchable_videos_by_day() {
+        let videos = videos();
+        let aliases = HashMap::from([(videos[0].channel_id.clone(), "Fo_od".to_string())]);
+
+        assert_eq!(
+            videos_markdown(&videos, &aliases, MarkdownGrouping::Day, utc),
+            "# blepo: unwatched videos\n\
+             \n\
+             ## Saturday, 20 January 2024\n\
+             \n\
+             - [Fish \\[v1\\] \\*chips\\*](https://www.youtube.com/watch?v=v1) — Fo\\_od\n\
+             - [Fish \\[v2\\] \\*chips\\*](https://www.youtube.com/watch?v=v2) — Cars\n\
+             \n\
+             ## Friday, 19 January 2024\n\
+             \n\
+             - [Fish \\[v3\\] \\*chips\\*](https://www.youtube.com/watch?v=v3) — Fo\\_od"
+        );
+    }
+
+    #[test]
+    fn groups_watchable_videos_by_channel() {
+        assert_eq!(
+            videos_markdown(&videos(), &HashMap::new(), MarkdownGrouping::Channel, utc),
+            "# blepo: unwatched videos\n\
+             \n\
+             ## Food\n\
+             \n\
+             - [Fish \\[v1\\] \\*chips\\*](https://www.youtube.com/watch?v=v1) — 2024-01-20\n\
+             - [Fish \\[v3\\] \\*chips\\*](https://www.youtube.com/watch?v=v3) — 2024-01-19\n\
+             \n\
+             ## Cars\n\
+             \n\
+             - [Fish \\[v2\\] \\*chips\\*](https://www.youtube.com/watch?v=v2) — 2024-01-20"
+        );
+    }
+
+    #[test]
+    fn says_so_when_nothing_is_unwatched() {
+        assert_eq!(
+            videos_markdown(&[], &HashMap::new(), MarkdownGrouping::Day, utc),
+            "# blepo: unwatched videos\n\nNo unwatched videos."
+        );
+    }
+}
@@ -1 +1,13 @@
+pub mod args;
+pub mod atom;
 pub mod cli;
+pub mod delimited;
+pub mod exit;
+pub mod graphics;
+pub mod html;
+pub mod ical;
+pub mod json;
+pub mod layout;
+pub mod logging;
+pub mod markdown;
+pub mod qr;
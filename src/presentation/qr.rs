@@ -0,0 +1,32 @@
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
+
+/// QR code of `text` drawn with half blocks, two modules per character row.
+/// Colours are inverted for dark terminals: light modules are the drawn
+/// ones, so phones see dark-on-light. `None` if `text` is too long to encode.
+pub fn render_qr(text: &str) -> Option<String> {
+    let code = QrCode::new(text).ok()?;
+    Some(
+        code.render::<Dense1x2>()
+            .dark_color(Dense1x2::Light)
+            .light_color(Dense1x2::Dark)
+            .build(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presentation::layout::display_width;
+
+    #[test]
+    fn renders_square_block_grid() {
+        let qr = render_qr("https://www.youtube.com/watch?v=dQw4w9WgXcQ").unwrap();
+        let lines: Vec<&str> = qr.lines().collect();
+
+        let width = display_width(lines[0]);
+        assert!(lines.iter().all(|line| display_width(line) == width));
+        // Two modules per row, so about half as many rows as columns.
+        assert_eq!(lines.len(), width.div_ceil(2));
+    }
+}